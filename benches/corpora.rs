@@ -0,0 +1,109 @@
+/*!
+Compares the speed of splitting each of several kinds of corpus (lots of
+short lines, a few huge records, and a long stretch with no delimiter at
+all) at newlines with `ByteChunker`, with `BufRead::lines()` and
+`BufRead::split()`, and (with the `async` feature) with `tokio-util`'s
+`AnyDelimiterCodec`. The corpora come from the `fixtures` module, so the
+same data can be used to benchmark other patterns.
+
+    cargo bench --bench corpora --all-features
+*/
+use std::{
+    io::{BufRead, BufReader, Cursor},
+    time::{Duration, Instant},
+};
+
+use regex_chunker::{fixtures, ByteChunker};
+
+const BUFFER_SIZE: usize = 8 * 1024;
+const N_RUNS: usize = 5;
+
+/* Run `f` `N_RUNS` times, and return the fastest time. */
+fn time<F: FnMut() -> usize>(name: &str, expected: usize, mut f: F) -> Duration {
+    let mut best = Duration::MAX;
+    for _ in 0..N_RUNS {
+        let start = Instant::now();
+        let n = f();
+        best = best.min(start.elapsed());
+        assert_eq!(n, expected, "{} found the wrong number of chunks", name);
+    }
+    println!("{:>20}: {:?}", name, best);
+    best
+}
+
+/* Split `text` with `AnyDelimiterCodec`, feeding it the same size pieces
+a `FramedRead` would. */
+#[cfg(feature = "async")]
+fn any_delimiter(text: &[u8]) -> usize {
+    use bytes::BytesMut;
+    use tokio_util::codec::{AnyDelimiterCodec, Decoder};
+
+    let mut codec = AnyDelimiterCodec::new(b"\n".to_vec(), b"\n".to_vec());
+    let mut buff = BytesMut::with_capacity(BUFFER_SIZE);
+    let mut n = 0;
+    for piece in text.chunks(BUFFER_SIZE) {
+        buff.extend_from_slice(piece);
+        while codec.decode(&mut buff).unwrap().is_some() {
+            n += 1;
+        }
+    }
+    while codec.decode_eof(&mut buff).unwrap().is_some() {
+        n += 1;
+    }
+    n
+}
+
+fn bench(corpus: &str, text: &[u8], expected: usize) {
+    println!("\n{}: {} chunks, {} bytes", corpus, expected, text.len());
+
+    let chunker = time("ByteChunker (\\n)", expected, || {
+        ByteChunker::new(Cursor::new(text), r"\n")
+            .unwrap()
+            .with_buffer_size(BUFFER_SIZE)
+            .map(|res| res.unwrap().len())
+            .filter(|&n| n < usize::MAX)
+            .count()
+    });
+    time("ByteChunker::lines", expected, || {
+        ByteChunker::lines(Cursor::new(text))
+            .with_buffer_size(BUFFER_SIZE)
+            .map(|res| res.unwrap().len())
+            .filter(|&n| n < usize::MAX)
+            .count()
+    });
+    let lines = time("BufRead::lines", expected, || {
+        BufReader::with_capacity(BUFFER_SIZE, Cursor::new(text))
+            .lines()
+            .map(|res| res.unwrap().len())
+            .filter(|&n| n < usize::MAX)
+            .count()
+    });
+    time("BufRead::split", expected, || {
+        BufReader::with_capacity(BUFFER_SIZE, Cursor::new(text))
+            .split(b'\n')
+            .map(|res| res.unwrap().len())
+            .filter(|&n| n < usize::MAX)
+            .count()
+    });
+    #[cfg(feature = "async")]
+    time("AnyDelimiterCodec", expected, || any_delimiter(text));
+
+    println!(
+        "ByteChunker is {:.2}x the speed of BufRead::lines",
+        lines.as_secs_f64() / chunker.as_secs_f64()
+    );
+}
+
+fn main() {
+    bench(
+        "short lines",
+        &fixtures::short_lines(1_000_000, 2718),
+        1_000_000,
+    );
+    bench(
+        "huge records",
+        &fixtures::huge_records(20, 4_000_000, 2718),
+        20,
+    );
+    bench("no match", &fixtures::no_match(64_000_000, 2718), 1);
+}
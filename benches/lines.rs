@@ -0,0 +1,84 @@
+/*!
+Compares the speed of splitting a large source into lines with
+`ByteChunker::lines()`, with the equivalent regex, and with
+`BufRead::lines()`, and of `string_lines()` against `BufRead::lines()`,
+both of which yield `String`s.
+
+    cargo bench --bench lines
+*/
+use std::{
+    io::{BufRead, BufReader, Cursor},
+    time::{Duration, Instant},
+};
+
+use regex_chunker::{string_lines, ByteChunker};
+
+const N_LINES: usize = 2_000_000;
+const N_RUNS: usize = 5;
+
+fn make_source() -> Vec<u8> {
+    let mut rng = fastrand::Rng::with_seed(2718);
+    let mut text = Vec::new();
+    for n in 0..N_LINES {
+        let len = rng.usize(0..120);
+        text.extend((0..len).map(|_| rng.u8(b' '..=b'~')));
+        text.extend_from_slice(if n % 4 == 0 { b"\r\n" } else { b"\n" });
+    }
+    text
+}
+
+/* Run `f` `N_RUNS` times, and return the fastest time. */
+fn time<F: FnMut() -> usize>(name: &str, mut f: F) -> Duration {
+    let mut best = Duration::MAX;
+    for _ in 0..N_RUNS {
+        let start = Instant::now();
+        let n = f();
+        best = best.min(start.elapsed());
+        assert_eq!(n, N_LINES);
+    }
+    println!("{:>16}: {:?}", name, best);
+    best
+}
+
+fn main() {
+    let text = make_source();
+    println!("{} lines, {} bytes", N_LINES, text.len());
+
+    let lines = time("ByteChunker::lines", || {
+        ByteChunker::lines(Cursor::new(&text))
+            .with_buffer_size(8 * 1024)
+            .map(|res| res.unwrap().len())
+            .filter(|&n| n < usize::MAX)
+            .count()
+    });
+    time("regex \\r?\\n", || {
+        ByteChunker::new(Cursor::new(&text), r"\r?\n")
+            .unwrap()
+            .with_buffer_size(8 * 1024)
+            .map(|res| res.unwrap().len())
+            .filter(|&n| n < usize::MAX)
+            .count()
+    });
+    let strings = time("string_lines", || {
+        string_lines(Cursor::new(&text))
+            .map(|res| res.unwrap().len())
+            .filter(|&n| n < usize::MAX)
+            .count()
+    });
+    let buf_read = time("BufRead::lines", || {
+        BufReader::with_capacity(8 * 1024, Cursor::new(&text))
+            .lines()
+            .map(|res| res.unwrap().len())
+            .filter(|&n| n < usize::MAX)
+            .count()
+    });
+
+    println!(
+        "ByteChunker::lines is {:.2}x the speed of BufRead::lines",
+        buf_read.as_secs_f64() / lines.as_secs_f64()
+    );
+    println!(
+        "string_lines is {:.2}x the speed of BufRead::lines",
+        buf_read.as_secs_f64() / strings.as_secs_f64()
+    );
+}
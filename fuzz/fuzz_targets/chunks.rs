@@ -0,0 +1,18 @@
+#![no_main]
+
+use std::io::Cursor;
+
+use libfuzzer_sys::fuzz_target;
+use regex_chunker::{ByteChunker, ReplayReader};
+use regex_chunker_fuzz::{check, Input};
+
+fuzz_target!(|input: Input| {
+    let source = ReplayReader::new(Cursor::new(input.data.clone()), &input.read_sizes());
+    let chunks: Vec<Vec<u8>> = ByteChunker::new(source, input.pattern())
+        .unwrap()
+        .with_buffer_size(input.buffer_size())
+        .with_match(input.disposition())
+        .map(|res| res.unwrap())
+        .collect();
+    check(&input, &chunks);
+});
@@ -0,0 +1,25 @@
+#![no_main]
+
+use std::io::Cursor;
+
+use libfuzzer_sys::fuzz_target;
+use regex_chunker::{stream::ByteChunker, ReplayReader};
+use regex_chunker_fuzz::{check, Input};
+use tokio_stream::StreamExt;
+
+fuzz_target!(|input: Input| {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let source = ReplayReader::new(Cursor::new(input.data.clone()), &input.read_sizes());
+    let chunks: Vec<Vec<u8>> = runtime.block_on(async {
+        ByteChunker::new(source, input.pattern())
+            .unwrap()
+            .with_read_size(input.buffer_size())
+            .with_match(input.disposition())
+            .map(|res| res.unwrap())
+            .collect()
+            .await
+    });
+    check(&input, &chunks);
+});
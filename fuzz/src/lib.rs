@@ -0,0 +1,112 @@
+/*!
+Shared input format and invariant checks for the fuzz targets.
+
+Each target chunks `Input::data`, delivered in pieces of
+`Input::read_sizes` by a `ReplayReader`, and hands the chunks to
+[`check`], which panics if they aren't what chunking all of the data at
+once would produce.
+*/
+use libfuzzer_sys::arbitrary::{self, Arbitrary};
+use regex::bytes::Regex;
+use regex_chunker::MatchDisposition;
+
+/*
+The patterns the data is chunked on, and whether each one's matches are
+fixed once they've been found. A match for `[,;]\s*` at the end of the
+data read so far could grow when more arrives, so where the chunks
+between its matches fall can depend on how the data was split up.
+*/
+const PATTERNS: [(&str, bool); 5] = [
+    (r"\n", true),
+    ("ab", true),
+    (r"\r?\n", true),
+    (r"(?:\r\n)+", false),
+    (r"[,;]\s*", false),
+];
+
+#[derive(Arbitrary, Debug)]
+pub struct Input {
+    pattern: u8,
+    disposition: u8,
+    buffer_size: u16,
+    read_sizes: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
+impl Input {
+    pub fn pattern(&self) -> &'static str {
+        PATTERNS[self.pattern as usize % PATTERNS.len()].0
+    }
+
+    fn is_fixed(&self) -> bool {
+        PATTERNS[self.pattern as usize % PATTERNS.len()].1
+    }
+
+    pub fn disposition(&self) -> MatchDisposition {
+        match self.disposition % 3 {
+            0 => MatchDisposition::Drop,
+            1 => MatchDisposition::Append,
+            _ => MatchDisposition::Prepend,
+        }
+    }
+
+    /// The size of the sync chunker's read buffer.
+    pub fn buffer_size(&self) -> usize {
+        (self.buffer_size as usize % 4096) + 1
+    }
+
+    /// The sizes of the pieces the source delivers its data in.
+    pub fn read_sizes(&self) -> Vec<usize> {
+        self.read_sizes.iter().map(|&n| n as usize).collect()
+    }
+}
+
+/* What chunking all of `data` at once on `re` produces. */
+fn reference(re: &Regex, data: &[u8], dispo: &MatchDisposition) -> Vec<Vec<u8>> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut carry: &[u8] = &[];
+    for m in re.find_iter(data) {
+        let mut chunk = carry.to_vec();
+        chunk.extend_from_slice(&data[start..m.start()]);
+        match dispo {
+            MatchDisposition::Append => chunk.extend_from_slice(m.as_bytes()),
+            MatchDisposition::Prepend => carry = m.as_bytes(),
+            _ => {}
+        }
+        chunks.push(chunk);
+        start = m.end();
+    }
+    let mut tail = carry.to_vec();
+    tail.extend_from_slice(&data[start..]);
+    if !tail.is_empty() {
+        chunks.push(tail);
+    }
+    chunks
+}
+
+/**
+Panic if `chunks` isn't a valid chunking of `input`. However the data was
+split up by reads, no bytes may be lost or duplicated unless the
+disposition is `Drop`, and for patterns whose matches can't grow, the
+chunks must be exactly the ones chunking all of the data at once yields.
+*/
+pub fn check(input: &Input, chunks: &[Vec<u8>]) {
+    let dispo = input.disposition();
+    if !matches!(dispo, MatchDisposition::Drop) {
+        assert_eq!(
+            chunks.concat(),
+            input.data,
+            "chunks don't add up to the input"
+        );
+    }
+    if input.is_fixed() {
+        let re = Regex::new(input.pattern()).unwrap();
+        let expected = reference(&re, &input.data, &dispo);
+        assert_eq!(
+            chunks,
+            &expected[..],
+            "chunks differ from chunking all at once"
+        );
+    }
+}
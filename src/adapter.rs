@@ -1,6 +1,11 @@
 /*!
 The trait used for types that transform the output of a Chunker.
 */
+use std::{
+    any::Any,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
 use crate::{
     ctrl::Utf8FailureMode,
     RcErr,
@@ -73,6 +78,41 @@ pub trait Adapter {
     fn adapt(&mut self, v: Option<Result<Vec<u8>, RcErr>>) -> Option<Self::Item>;
 }
 
+/**
+A closure taking each of the `ByteChunker`'s results can be used as an
+[`Adapter`] directly. It isn't called once the `ByteChunker` is exhausted,
+and returning `None` from it ends the iteration, as with
+[`.map_while()`](https://doc.rust-lang.org/std/iter/trait.Iterator.html#method.map_while).
+
+The closure's argument type has to be written out, as the compiler
+can't infer it.
+
+```rust
+use regex_chunker::{ByteChunker, RcErr};
+use std::io::Cursor;
+
+let text = b"3 1 4 x 5";
+let numbers: Vec<u32> = ByteChunker::new(Cursor::new(text), " ")?
+    .with_adapter(|res: Result<Vec<u8>, RcErr>| {
+        String::from_utf8(res.ok()?).ok()?.parse().ok()
+    })
+    .collect();
+
+assert_eq!(numbers, [3, 1, 4]);
+# Ok::<(), RcErr>(())
+```
+*/
+impl<F, T> Adapter for F
+where
+    F: FnMut(Result<Vec<u8>, RcErr>) -> Option<T>,
+{
+    type Item = T;
+
+    fn adapt(&mut self, v: Option<Result<Vec<u8>, RcErr>>) -> Option<T> {
+        v.and_then(self)
+    }
+}
+
 /**
 Simpler, less flexible, version of the [`Adapter`] trait.
 
@@ -129,6 +169,285 @@ pub trait SimpleAdapter {
     fn adapt(&mut self, v: Vec<u8>) -> Self::Item;
 }
 
+/**
+A closure can be used as a [`SimpleAdapter`].
+
+```rust
+# use regex_chunker::RcErr;
+use regex_chunker::ByteChunker;
+use std::io::Cursor;
+
+let text = b"One, two, three four.";
+let lengths: Vec<usize> = ByteChunker::new(Cursor::new(text), "[ .,]+")?
+    .with_simple_adapter(|v: Vec<u8>| v.len())
+    .collect::<Result<_, RcErr>>()?;
+
+assert_eq!(lengths, [3, 3, 5, 4]);
+# Ok::<(), RcErr>(())
+```
+*/
+impl<F, T> SimpleAdapter for F
+where
+    F: FnMut(Vec<u8>) -> T,
+{
+    type Item = T;
+
+    fn adapt(&mut self, v: Vec<u8>) -> T {
+        self(v)
+    }
+}
+
+/**
+Version of the [`SimpleAdapter`] trait for conversions that can fail with
+an error type of their own, rather than [`RcErr`].
+
+A parsing adapter usually has a domain error type of its own; a
+`TryAdapter` lets it report that error directly in the chunker's items,
+without boxing it or squeezing it into an `RcErr`. The chunker's own
+errors are converted into the adapter's error type with [`From`], so the
+items are `Result<Self::Item, Self::Error>`. A `TryAdapter` is combined with
+a [`ByteChunker`](crate::ByteChunker) by
+[`ByteChunker::with_try_adapter`](crate::ByteChunker::with_try_adapter),
+which wraps it in a [`Fallible`] to make a [`CustomChunker`](crate::CustomChunker).
+
+A closure returning a `Result` can be used as a `TryAdapter`.
+
+```rust
+use regex_chunker::{ByteChunker, RcErr};
+use std::{io::Cursor, num::ParseIntError};
+
+#[derive(Debug)]
+enum RecordError {
+    Chunker(RcErr),
+    BadNumber(ParseIntError),
+}
+
+impl From<RcErr> for RecordError {
+    fn from(e: RcErr) -> Self {
+        RecordError::Chunker(e)
+    }
+}
+
+let text = b"12,x,34";
+let results: Vec<Result<u32, RecordError>> = ByteChunker::new(Cursor::new(text), ",")?
+    .with_try_adapter(|v: Vec<u8>| {
+        String::from_utf8_lossy(&v).parse().map_err(RecordError::BadNumber)
+    })
+    .collect();
+
+assert_eq!(results[0].as_ref().unwrap(), &12);
+assert!(matches!(results[1], Err(RecordError::BadNumber(_))));
+assert_eq!(results[2].as_ref().unwrap(), &34);
+# Ok::<(), RcErr>(())
+```
+*/
+pub trait TryAdapter {
+    /// The type into which it converts the `Vec<u8>`s successfully
+    /// produced by the underlying [`ByteChunker`](crate::ByteChunker).
+    type Item;
+    /// The error type of the items, into which the chunker's own errors
+    /// are also converted.
+    type Error: From<RcErr>;
+
+    /// Convert the `ByteChunker`'s output when _successful_.
+    fn try_adapt(&mut self, v: Vec<u8>) -> Result<Self::Item, Self::Error>;
+}
+
+impl<F, T, E> TryAdapter for F
+where
+    F: FnMut(Vec<u8>) -> Result<T, E>,
+    E: From<RcErr>,
+{
+    type Item = T;
+    type Error = E;
+
+    fn try_adapt(&mut self, v: Vec<u8>) -> Result<T, E> {
+        self(v)
+    }
+}
+
+/**
+An [`Adapter`] made from a [`TryAdapter`], passing the chunker's errors
+through (converted to the `TryAdapter`'s error type) and the chunks on to
+the `TryAdapter`. It's what
+[`ByteChunker::with_try_adapter`](crate::ByteChunker::with_try_adapter)
+wraps its argument in.
+*/
+#[derive(Debug)]
+pub struct Fallible<A>(pub A);
+
+impl<A: TryAdapter> Adapter for Fallible<A> {
+    type Item = Result<A::Item, A::Error>;
+
+    fn adapt(&mut self, v: Option<Result<Vec<u8>, RcErr>>) -> Option<Self::Item> {
+        Some(match v? {
+            Ok(v) => self.0.try_adapt(v),
+            Err(e) => Err(e.into()),
+        })
+    }
+}
+
+/**
+Version of the [`SimpleAdapter`] trait for transformations that can be
+done in place.
+
+Trimming, case folding, and many kinds of escaping or unescaping can
+rewrite a chunk in the buffer it already occupies; an `InPlaceAdapter`
+gets a mutable reference to each successfully produced chunk, so no new
+allocation is needed to transform it. As with `SimpleAdapter`, `None` and
+`Err()` values are passed through untouched.
+
+```rust
+# use regex_chunker::RcErr;
+use regex_chunker::{ByteChunker, InPlaceAdapter};
+use std::io::Cursor;
+
+struct Shouter {}
+
+impl InPlaceAdapter for Shouter {
+    fn adapt_in_place(&mut self, chunk: &mut Vec<u8>) {
+        chunk.make_ascii_uppercase();
+        chunk.push(b'!');
+    }
+}
+
+let text = b"One, two, three four.";
+let c = Cursor::new(text);
+
+let chunks: Vec<_> = ByteChunker::new(c, "[ .,?]+")?
+    .with_in_place_adapter(Shouter{})
+    .map(|res| res.unwrap())
+    .collect();
+
+assert_eq!(
+    &chunks,
+    &[b"ONE!".to_vec(), b"TWO!".to_vec(), b"THREE!".to_vec(), b"FOUR!".to_vec()]
+);
+# Ok::<(), RcErr>(())
+```
+*/
+pub trait InPlaceAdapter {
+    /// Transform the `ByteChunker`'s output when _successful_.
+    fn adapt_in_place(&mut self, chunk: &mut Vec<u8>);
+}
+
+/// A closure can be used as an [`InPlaceAdapter`].
+impl<F> InPlaceAdapter for F
+where
+    F: FnMut(&mut Vec<u8>),
+{
+    fn adapt_in_place(&mut self, chunk: &mut Vec<u8>) {
+        self(chunk)
+    }
+}
+
+/**
+An [`Adapter`] that runs the `Adapter` it wraps under
+[`catch_unwind`](std::panic::catch_unwind), so that a panic while adapting
+a chunk is yielded as an [`RcErr::Panic`] error instead of unwinding
+through the chunker. The chunker itself is unaffected, so iteration can
+carry on with the next chunk; this keeps one malformed record from taking
+down a long-running process when the adapter is third-party code.
+
+The wrapped adapter must yield `Result`s, with an error type that an
+[`RcErr`] can be converted into (as with a [`TryAdapter`]); a panic is
+yielded as an `RcErr::Panic` converted to that type. Whatever state it
+was left in by the panic is kept, so an adapter that panics partway
+through updating its own state may go on to produce odd results; it's on
+the adapter to tolerate that. (The panic hook still
+runs as usual, so the panic message is printed unless the hook has been
+replaced.)
+
+Wrap an adapter with [`CatchPanics::new`], or call
+[`CustomChunker::isolate_panics`](crate::CustomChunker::isolate_panics).
+
+```rust
+use regex_chunker::{ByteChunker, RcErr};
+use std::io::Cursor;
+
+let text = b"1 2 x 4";
+let results: Vec<Result<u32, RcErr>> = ByteChunker::new(Cursor::new(text), " ")?
+    .with_adapter(|res: Result<Vec<u8>, RcErr>| {
+        let s = String::from_utf8(res.ok()?).ok()?;
+        Some(Ok(s.parse().expect("not a number")))
+    })
+    .isolate_panics()
+    .collect();
+
+assert_eq!(results.len(), 4);
+assert!(matches!(&results[2], Err(RcErr::Panic(msg)) if msg.starts_with("not a number")));
+assert_eq!(results[3].as_ref().unwrap(), &4);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Debug)]
+pub struct CatchPanics<A> {
+    adapter: A,
+    /* Set if the wrapped adapter panicked once the chunker was exhausted,
+    so it isn't called (and doesn't panic) again. */
+    finished: bool,
+}
+
+impl<A> CatchPanics<A> {
+    /// Wrap `adapter` so that its panics are caught.
+    pub fn new(adapter: A) -> Self {
+        Self {
+            adapter,
+            finished: false,
+        }
+    }
+
+    /// Get a reference to the wrapped [`Adapter`].
+    pub fn get_ref(&self) -> &A {
+        &self.adapter
+    }
+
+    /// Get a mutable reference to the wrapped [`Adapter`].
+    pub fn get_mut(&mut self) -> &mut A {
+        &mut self.adapter
+    }
+
+    /// Consume the `CatchPanics` and return the wrapped [`Adapter`].
+    pub fn into_inner(self) -> A {
+        self.adapter
+    }
+}
+
+impl<A, T, E> Adapter for CatchPanics<A>
+where
+    A: Adapter<Item = Result<T, E>>,
+    E: From<RcErr>,
+{
+    type Item = Result<T, E>;
+
+    fn adapt(&mut self, v: Option<Result<Vec<u8>, RcErr>>) -> Option<Self::Item> {
+        let exhausted = v.is_none();
+        if exhausted && self.finished {
+            return None;
+        }
+        let adapter = &mut self.adapter;
+        match catch_unwind(AssertUnwindSafe(|| adapter.adapt(v))) {
+            Ok(item) => item,
+            Err(payload) => {
+                self.finished = exhausted;
+                Some(Err(RcErr::Panic(panic_message(payload)).into()))
+            }
+        }
+    }
+}
+
+/* Extract the message from a panic's payload, which is a `&str` or a
+`String` unless something other than `panic!()` raised it. */
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(s) => *s,
+        Err(payload) => match payload.downcast_ref::<&str>() {
+            Some(s) => s.to_string(),
+            None => "unknown panic payload".to_string(),
+        },
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 enum Utf8ErrorStatus {
     #[default]
@@ -136,6 +455,7 @@ enum Utf8ErrorStatus {
     Errored,
     Lossy,
     Continue,
+    Skip,
 }
 impl Eq for Utf8ErrorStatus {}
 
@@ -170,6 +490,9 @@ An example [`Adapter`] type for producing a chunker that yields `String`s.
 #[derive(Debug, Default)]
 pub struct StringAdapter {
     status: Utf8ErrorStatus,
+    collapse_whitespace: bool,
+    /* Invalid bytes dropped under `Utf8FailureMode::Skip`. */
+    dropped: u64,
 }
 
 impl StringAdapter {
@@ -178,16 +501,62 @@ impl StringAdapter {
             Utf8FailureMode::Fatal => Utf8ErrorStatus::Ok,
             Utf8FailureMode::Lossy => Utf8ErrorStatus::Lossy,
             Utf8FailureMode::Continue => Utf8ErrorStatus::Continue,
+            Utf8FailureMode::Skip => Utf8ErrorStatus::Skip,
         };
 
-        Self { status }
+        Self {
+            status,
+            collapse_whitespace: false,
+            dropped: 0,
+        }
     }
-}
 
-impl Adapter for StringAdapter {
-    type Item = Result<String, RcErr>;
+    /**
+    The number of invalid bytes dropped so far under
+    [`Utf8FailureMode::Skip`].
 
-    fn adapt(&mut self, v: Option<Result<Vec<u8>, RcErr>>) -> Option<Self::Item> {
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr, StringAdapter, Utf8FailureMode};
+    use std::io::Cursor;
+
+    let text = b"caf\xc3\xa9 \xff\xfeok\n\xe2\x82 fine\n\xf0\x9f\x98\x80";
+    let mut chunker = ByteChunker::new(Cursor::new(text), "\n")?
+        .with_adapter(StringAdapter::new(Utf8FailureMode::Skip));
+    let chunks: Vec<String> = chunker.by_ref().collect::<Result<_, RcErr>>()?;
+
+    assert_eq!(chunks, ["café ok", " fine", "😀"]);
+    assert_eq!(chunker.get_adapter().dropped_bytes(), 4);
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn dropped_bytes(&self) -> u64 {
+        self.dropped
+    }
+
+    /**
+    Builder-pattern method for replacing each run of whitespace within a
+    chunk with a single space. Default is `false`.
+
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr, StringAdapter};
+    use std::io::Cursor;
+
+    let text = b"a  loosely\tspaced\r\n  line;another\t\tone";
+    let chunks: Vec<String> = ByteChunker::new(Cursor::new(text), ";")?
+        .with_adapter(StringAdapter::default().with_collapsed_whitespace(true))
+        .collect::<Result<_, RcErr>>()?;
+
+    assert_eq!(chunks, ["a loosely spaced line", "another one"]);
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn with_collapsed_whitespace(mut self, collapse: bool) -> Self {
+        self.collapse_whitespace = collapse;
+        self
+    }
+
+    /* Convert the chunk to a `String` according to the failure mode. */
+    fn convert(&mut self, v: Option<Result<Vec<u8>, RcErr>>) -> Option<Result<String, RcErr>> {
         match (self.status, v) {
             (Utf8ErrorStatus::Errored, _) => None,
             (_, None) => None,
@@ -204,7 +573,77 @@ impl Adapter for StringAdapter {
             (Utf8ErrorStatus::Continue, Some(Ok(v))) => match String::from_utf8(v) {
                 Ok(s) => Some(Ok(s)),
                 Err(e) => Some(Err(e.into())),
+            },
+            (Utf8ErrorStatus::Skip, Some(Ok(v))) => match String::from_utf8(v) {
+                Ok(s) => Some(Ok(s)),
+                Err(e) => {
+                    let (s, dropped) = skip_invalid(&e.into_bytes());
+                    self.dropped += dropped as u64;
+                    Some(Ok(s))
+                }
+            },
+        }
+    }
+}
+
+/* Convert `v` to a `String`, dropping any invalid byte sequences. Also
+returns the number of bytes dropped. */
+fn skip_invalid(mut v: &[u8]) -> (String, usize) {
+    let mut s = String::with_capacity(v.len());
+    let mut dropped = 0;
+    loop {
+        match std::str::from_utf8(v) {
+            Ok(valid) => {
+                s.push_str(valid);
+                return (s, dropped);
             }
+            Err(e) => {
+                let (valid, rest) = v.split_at(e.valid_up_to());
+                // This can't fail; we've just been told it's valid.
+                s.push_str(std::str::from_utf8(valid).unwrap());
+                let bad = e.error_len().unwrap_or(rest.len());
+                dropped += bad;
+                v = &rest[bad..];
+            }
+        }
+    }
+}
+
+/* Replace each run of whitespace in `s` with a single space. */
+fn collapse_whitespace(s: String) -> String {
+    let mut prev_space = false;
+    let untouched = s.chars().all(|c| {
+        let ok = !c.is_whitespace() || (c == ' ' && !prev_space);
+        prev_space = c.is_whitespace();
+        ok
+    });
+    if untouched {
+        return s;
+    }
+
+    let mut collapsed = String::with_capacity(s.len());
+    let mut in_run = false;
+    for c in s.chars() {
+        if !c.is_whitespace() {
+            collapsed.push(c);
+            in_run = false;
+        } else if !in_run {
+            collapsed.push(' ');
+            in_run = true;
+        }
+    }
+    collapsed
+}
+
+impl Adapter for StringAdapter {
+    type Item = Result<String, RcErr>;
+
+    fn adapt(&mut self, v: Option<Result<Vec<u8>, RcErr>>) -> Option<Self::Item> {
+        let res = self.convert(v);
+        if self.collapse_whitespace {
+            res.map(|res| res.map(collapse_whitespace))
+        } else {
+            res
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,74 @@
+/*!
+Allocating chunks with a custom allocator.
+*/
+use std::io::Read;
+
+use allocator_api2::{alloc::Allocator, vec::Vec as AllocVec};
+
+use crate::{ByteChunker, RcErr};
+
+/**
+A [`ByteChunker`] that allocates each chunk it returns with a supplied
+allocator, rather than the global one, returned by
+[`ByteChunker::with_allocator`].
+
+The allocator API is still unstable in `std`, so this uses the
+[`allocator-api2`](https://docs.rs/allocator-api2) crate's mirror of it,
+which works on stable Rust (and just re-exports `std`'s on nightly with
+its `nightly` feature). Arena allocators like
+[`bumpalo`](https://docs.rs/bumpalo) implement its `Allocator` trait for
+references to their arenas, so a batch job can allocate every chunk of a
+file in one arena and free them all at once by dropping it, and threads
+in a pipeline can each use their own arena instead of contending for the
+global allocator.
+
+The chunker's internal buffers still come from the global allocator; only
+the returned chunks use the supplied one.
+
+```rust
+use allocator_api2::alloc::Global;
+use regex_chunker::{ByteChunker, RcErr};
+use std::io::Cursor;
+
+// With bumpalo's `allocator-api2` feature, this could be `&bump`.
+let mut chunker = ByteChunker::new(Cursor::new("a;b;c"), ";")?.with_allocator(Global);
+let first = chunker.next().unwrap()?;
+assert_eq!(&first[..], b"a");
+assert_eq!(chunker.count(), 2);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Debug)]
+pub struct AllocChunker<R, A> {
+    chunker: ByteChunker<R>,
+    alloc: A,
+}
+
+impl<R, A> AllocChunker<R, A> {
+    pub(crate) fn new(chunker: ByteChunker<R>, alloc: A) -> Self {
+        Self { chunker, alloc }
+    }
+
+    /// Get a reference to the allocator used for the chunks.
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    /// Consume the `AllocChunker` and return the wrapped [`ByteChunker`].
+    pub fn into_inner(self) -> ByteChunker<R> {
+        self.chunker
+    }
+}
+
+impl<R: Read, A: Allocator + Clone> Iterator for AllocChunker<R, A> {
+    type Item = Result<AllocVec<u8, A>, RcErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let alloc = &self.alloc;
+        self.chunker.next_with(|chunk| {
+            let mut v = AllocVec::with_capacity_in(chunk.len(), alloc.clone());
+            v.extend_from_slice(chunk);
+            v
+        })
+    }
+}
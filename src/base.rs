@@ -4,12 +4,28 @@ The base ByteChunker types.
 use std::{
     fmt::{Debug, Formatter},
     hint::spin_loop,
-    io::{ErrorKind, Read},
+    io::{ErrorKind, IoSliceMut, Read},
+    ops::Range,
+    time::Instant,
 };
 
 use regex::bytes::Regex;
 
-use crate::{ctrl::*, CustomChunker, RcErr, SimpleCustomChunker};
+use crate::{
+    bom::UTF8_BOM,
+    chunk::char_count,
+    ctrl::*,
+    engine::{ChunkEngine, Verdict},
+    err::ErrorTally,
+    timing::{ChunkTiming, Timer},
+    trace::{Decision, DecodeEvent, TraceFn},
+    trim::Trimmer,
+    BomReader, Boundary, BufferPolicy, ByteSetBoundary, Chunk, ChunkBuf, ChunkIndex,
+    ChunkInspector, CustomChunker, DelimitedChunker, Encoding, Fallible, FastqBoundary,
+    InPlaceChunker, InfallibleChunker, InfallibleRead, JsonBoundary, LendingChunker, LineBoundary,
+    LiteralBoundary, MarkdownBoundary, MetaChunker, MultiRegex, OutputAdapter, RcErr,
+    RecordStartBoundary, RegexCache, SimpleCustomChunker, SqlBoundary, TryAdapter, WarcBoundary,
+};
 
 // By default the `read_buffer` size is 1 KiB.
 const DEFAULT_BUFFER_SIZE: usize = 1024;
@@ -78,40 +94,404 @@ assert_eq!(
 */
 pub struct ByteChunker<R> {
     source: R,
-    fence: Regex,
+    /* Decides where each chunk ends. */
+    engine: ChunkEngine,
     read_buff: Vec<u8>,
     search_buff: Vec<u8>,
+    /* The most unreturned data the search buffer has ever held. */
+    peak_buffered: usize,
+    /* Bytes at the front of the search buffer that have already been
+    returned. Rather than shifting the rest of the buffer down every time a
+    chunk is returned, this is only done before the next read. */
+    buff_start: usize,
+    /* End of the data in the search buffer. Anything after it is spare
+    room left initialised by earlier reads, so that vectored reads can
+    reuse it without zeroing it first. */
+    buff_end: usize,
     error_status: ErrorStatus,
-    match_dispo: MatchDisposition,
     /* Whether the last search of the search buffer found a match. If it did,
     then the next call to `.next()` should start by searching the search
     buffer again; otherwise we should start by trying to pull more bytes
     from our source. */
     last_scan_matched: bool,
-    /* If the MatchDisposition is Prepend, we need to keep the match in the
+    /* Whether the source has been exhausted, so the fence should be
+    searched with `Boundary::find_at_end`. */
+    at_end: bool,
+    /* If the MatchDisposition is Prepend (or Wrap), we need to keep the match in the
     scan buffer so we can return it with the next chunk. This means we need
     to start our next scan of the buffer from _after_ the match, or we'll
     just match the very beginning of the scan buffer again. */
     scan_start_offset: usize,
+    /* Index of the pattern whose match ended the most recently returned
+    chunk, if the fence reports one. */
+    matched_pattern: Option<usize>,
+    /* Absolute offset in the source of the first byte of the search
+    buffer, for reporting chunk spans. */
+    offset: u64,
+    /* If set, the offset in characters of the first byte of the search
+    buffer; see `MetaChunker::with_char_offsets`. */
+    char_offset: Option<u64>,
+    /* Whether to copy the matched delimiter into each `Chunk`. This is only
+    worth doing when someone is going to look at it. */
+    keep_delimiters: bool,
+    /* If set, trims matching text from the ends of each chunk. */
+    trimmer: Option<Trimmer>,
+    /* Set until a leading UTF-8 BOM has been looked for (and removed). */
+    strip_bom: bool,
+    /* Whether data left at the end of the source without a delimiter
+    after it is an error rather than a final chunk. */
+    strict_tail: bool,
+    vectored_reads: bool,
+    /* If set, chooses the size of each read; see `with_fragmented_reads`. */
+    read_size: Option<ReadSizeFn>,
+    buffer_policy: Option<Box<dyn BufferPolicy>>,
+    timer: Option<Timer>,
+    tracer: Option<TraceFn>,
+    /* The number of chunks returned so far. */
+    seq: u64,
+    /* Set when an error is passed on or ignored, and cleared when the
+    next chunk is returned; see `Chunk::gap`. */
+    gap: bool,
 }
 
 impl<R> ByteChunker<R> {
     /**
-    Return a new [`ByteChunker`] wrapping the given writer that will chunk its
+    Return a new [`ByteChunker`] wrapping the given reader that will chunk its
     output by delimiting it with the supplied regex pattern.
     */
     pub fn new(source: R, delimiter: &str) -> Result<Self, RcErr> {
         let fence = Regex::new(delimiter)?;
-        Ok(Self {
+        Ok(Self::from_boundary(source, fence))
+    }
+
+    /**
+    Return a new [`ByteChunker`] wrapping the given reader that will chunk
+    its output at occurrences of the supplied literal string. Characters
+    that mean something in a regex, like `.`, `+`, or `(`, are escaped, so
+    they match only themselves.
+
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr};
+    use std::io::Cursor;
+
+    let text = b"a1.2.3b1x2x3c";
+    let chunks: Vec<Vec<u8>> = ByteChunker::new_literal(Cursor::new(text), "1.2.3")?
+        .collect::<Result<_, RcErr>>()?;
+
+    assert_eq!(chunks, [&b"a"[..], b"b1x2x3c"]);
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn new_literal(source: R, delimiter: &str) -> Result<Self, RcErr> {
+        Self::new(source, &regex::escape(delimiter))
+    }
+
+    /**
+    Like [`ByteChunker::new`], but preallocates room for `capacity` bytes in
+    the chunker's internal buffer, which otherwise starts out empty and
+    grows (reallocating as it goes) to fit the largest chunk. If you know
+    roughly how large your chunks are, this saves that work.
+    */
+    pub fn new_with_capacity(source: R, delimiter: &str, capacity: usize) -> Result<Self, RcErr> {
+        let mut chunker = Self::new(source, delimiter)?;
+        chunker.search_buff.reserve_exact(capacity);
+        Ok(chunker)
+    }
+
+    /**
+    Like [`ByteChunker::new`], but looks the compiled regex up in (or
+    adds it to) the process-wide [`RegexCache`], rather than compiling
+    it anew.
+    */
+    pub fn new_cached(source: R, delimiter: &str) -> Result<Self, RcErr> {
+        let fence = RegexCache::global().get(delimiter)?;
+        Ok(Self::from_boundary(source, fence))
+    }
+
+    /**
+    Return a new [`ByteChunker`] wrapping the given reader that will chunk
+    its output by delimiting it with the supplied Unicode regex pattern,
+    which is matched against the _decoded text_ of the source rather than
+    its raw bytes. See the [`Boundary`] implementation for
+    [`regex::Regex`](https://docs.rs/regex/latest/regex/struct.Regex.html).
+    */
+    pub fn new_text(source: R, delimiter: &str) -> Result<Self, RcErr> {
+        let fence = regex::Regex::new(delimiter)?;
+        Ok(Self::from_boundary(source, fence))
+    }
+
+    /**
+    Return a new [`ByteChunker`] wrapping the given reader that will chunk
+    its output at the delimiters found by the supplied [`Boundary`].
+    */
+    pub fn from_boundary<B>(source: R, boundary: B) -> Self
+    where
+        B: Boundary + Send + 'static,
+    {
+        Self {
             source,
-            fence,
+            engine: ChunkEngine::new(Box::new(boundary)),
             read_buff: vec![0u8; DEFAULT_BUFFER_SIZE],
             search_buff: Vec::new(),
+            peak_buffered: 0,
+            buff_start: 0,
+            buff_end: 0,
             error_status: ErrorStatus::Ok,
-            match_dispo: MatchDisposition::default(),
             last_scan_matched: false,
+            at_end: false,
             scan_start_offset: 0,
-        })
+            matched_pattern: None,
+            offset: 0,
+            char_offset: None,
+            keep_delimiters: false,
+            trimmer: None,
+            strip_bom: false,
+            strict_tail: false,
+            vectored_reads: false,
+            read_size: None,
+            buffer_policy: None,
+            timer: None,
+            tracer: None,
+            seq: 0,
+            gap: false,
+        }
+    }
+
+    /**
+    Return a new [`ByteChunker`] wrapping the given reader that will chunk
+    its output at occurrences of any of the supplied literal delimiters.
+    See [`LiteralBoundary`].
+    */
+    pub fn from_literals<I, P>(source: R, literals: I) -> Result<Self, RcErr>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<[u8]>,
+    {
+        let fence = LiteralBoundary::new(literals)?;
+        Ok(Self::from_boundary(source, fence))
+    }
+
+    /**
+    Return a new [`ByteChunker`] wrapping the given reader that will chunk
+    its output at matches of any of the supplied patterns, each of which
+    is paired with the [`MatchDisposition`] to apply to its matches. See
+    [`MultiRegex`].
+    */
+    pub fn from_patterns<I, P>(source: R, patterns: I) -> Result<Self, RcErr>
+    where
+        I: IntoIterator<Item = (P, MatchDisposition)>,
+        P: AsRef<str>,
+    {
+        let fence = MultiRegex::new(patterns)?;
+        Ok(Self::from_boundary(source, fence))
+    }
+
+    /**
+    Return a new [`ByteChunker`] wrapping the given reader that will chunk
+    its output at runs of any of the supplied delimiter bytes. See
+    [`ByteSetBoundary`].
+    */
+    pub fn from_byte_set(source: R, delimiters: &[u8]) -> Self {
+        Self::from_boundary(source, ByteSetBoundary::new(delimiters))
+    }
+
+    /**
+    Return a new [`ByteChunker`] wrapping the given reader that will chunk
+    its output into lines, delimited by `\r?\n`. This is equivalent to
+    using that pattern as a regex, but faster. See [`LineBoundary`].
+    */
+    pub fn lines(source: R) -> Self {
+        Self::from_boundary(source, LineBoundary::crlf())
+    }
+
+    /**
+    Return a new [`ByteChunker`] wrapping the given reader that will chunk
+    its output at each `\0`, like the `-z`/`-0` options of `find`,
+    `xargs`, and `grep` do, for safely handling lists of file names (which
+    can contain newlines). Use a [`JoinWriter`](crate::JoinWriter) to
+    NUL-terminate the chunks again on the way out. See
+    [`LineBoundary::null`].
+
+    ```rust
+    use regex_chunker::{ByteChunker, JoinWriter, RcErr};
+    use std::io::Cursor;
+
+    let names = b"a.txt\0odd\nname\0c.txt\0";
+    let chunker = ByteChunker::null_delimited(Cursor::new(names))
+        .filter(|res| !matches!(res, Ok(name) if name.ends_with(b".txt")));
+
+    let mut writer = JoinWriter::null(Vec::new());
+    assert_eq!(writer.write_all(chunker)?, 1);
+    assert_eq!(writer.into_inner(), b"odd\nname\0");
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn null_delimited(source: R) -> Self {
+        Self::from_boundary(source, LineBoundary::null())
+    }
+
+    /**
+    Return a new [`ByteChunker`] wrapping the given reader that will chunk
+    its output into whole JSON documents, even ones that are pretty-printed
+    across several lines. See [`JsonBoundary`].
+    */
+    pub fn json_documents(source: R) -> Self {
+        Self::from_boundary(source, JsonBoundary::new())
+    }
+
+    /**
+    Return a new [`ByteChunker`] wrapping the given reader that will chunk
+    a SQL script into statements at each `;`, except those inside string
+    literals, quoted identifiers, and comments. See [`SqlBoundary`].
+    */
+    pub fn sql_statements(source: R) -> Self {
+        Self::from_boundary(source, SqlBoundary::new())
+    }
+
+    /**
+    Return a new [`ByteChunker`] wrapping the given reader that will chunk
+    its output into multi-line records, each of which starts with a line
+    that begins with a match for `record_start` (like a timestamp), so that
+    continuation lines like stack traces stay with the line they belong to.
+    See [`RecordStartBoundary`].
+
+    Returns an error if `record_start` isn't a valid regex.
+    */
+    pub fn log_records(source: R, record_start: &str) -> Result<Self, RcErr> {
+        let fence = RecordStartBoundary::new(record_start)?;
+        Ok(Self::from_boundary(source, fence))
+    }
+
+    /**
+    Return a new [`ByteChunker`] wrapping the given reader that will chunk
+    a FASTA file into records, each made up of a `>` header line and the
+    lines of sequence that follow it, however many there are. See
+    [`RecordStartBoundary::fasta`].
+
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr};
+    use std::io::Cursor;
+
+    let text = b">seq1 first\nACGT\nGGCA\n>seq2\nTTAG\n";
+    let records: Vec<Vec<u8>> = ByteChunker::fasta_records(Cursor::new(text))
+        .collect::<Result<_, RcErr>>()?;
+
+    assert_eq!(records, [&b">seq1 first\nACGT\nGGCA"[..], b">seq2\nTTAG"]);
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn fasta_records(source: R) -> Self {
+        Self::from_boundary(source, RecordStartBoundary::fasta())
+    }
+
+    /**
+    Return a new [`ByteChunker`] wrapping the given reader that will chunk
+    a FASTQ file into its four-line records. See [`FastqBoundary`].
+    */
+    pub fn fastq_records(source: R) -> Self {
+        Self::from_boundary(source, FastqBoundary::new())
+    }
+
+    /**
+    Return a new [`ByteChunker`] wrapping the given reader that will chunk
+    an mbox file into messages, each starting with its `From ` line. The
+    blank line that separates each message from the next is removed. See
+    [`RecordStartBoundary::mbox`], and [`MboxMessage`] for splitting each
+    message into its headers and body.
+    */
+    pub fn mbox_messages(source: R) -> Self {
+        Self::from_boundary(source, RecordStartBoundary::mbox())
+    }
+
+    /**
+    Return a new [`ByteChunker`] wrapping the given reader that will chunk
+    a WARC file into its records, using each record's `Content-Length` to
+    find where it ends. See [`WarcBoundary`], and [`WarcRecord`] for
+    splitting each record into its headers and content.
+    */
+    pub fn warc_records(source: R) -> Self {
+        Self::from_boundary(source, WarcBoundary::new())
+    }
+
+    /**
+    Return a new [`ByteChunker`] wrapping the given reader that will chunk
+    a Markdown document into sections, each starting with a heading of
+    level `level` or higher, which is kept with the section it heads. See
+    [`MarkdownBoundary`].
+    */
+    pub fn markdown_sections(source: R, level: usize) -> Self {
+        Self::from_boundary(source, MarkdownBoundary::new(level))
+    }
+
+    /**
+    Builder-pattern method that wraps the chunker's source in a
+    [`BomReader`], which strips any byte-order mark from the beginning of
+    the source and transcodes UTF-16 and UTF-32 sources to UTF-8, so the
+    delimiter can be written without regard to the source's encoding.
+    Offsets reported by the chunker are then offsets into the transcoded
+    data. This should be called before any chunks are read.
+    */
+    pub fn with_bom_sniffing(self) -> ByteChunker<BomReader<R>> {
+        ByteChunker {
+            source: BomReader::new(self.source),
+            engine: self.engine,
+            read_buff: self.read_buff,
+            search_buff: self.search_buff,
+            peak_buffered: self.peak_buffered,
+            buff_start: self.buff_start,
+            buff_end: self.buff_end,
+            error_status: self.error_status,
+            last_scan_matched: self.last_scan_matched,
+            at_end: self.at_end,
+            scan_start_offset: self.scan_start_offset,
+            matched_pattern: self.matched_pattern,
+            offset: self.offset,
+            char_offset: self.char_offset,
+            keep_delimiters: self.keep_delimiters,
+            trimmer: self.trimmer,
+            strip_bom: self.strip_bom,
+            strict_tail: self.strict_tail,
+            vectored_reads: self.vectored_reads,
+            read_size: self.read_size,
+            buffer_policy: self.buffer_policy,
+            timer: self.timer,
+            tracer: self.tracer,
+            seq: self.seq,
+            gap: self.gap,
+        }
+    }
+
+    /**
+    Like [`ByteChunker::with_bom_sniffing`], but sources with no byte-order
+    mark are assumed to be in the given `encoding` and transcoded from it
+    (see [`BomReader::with_default_encoding`]). This lets UTF-16 sources be
+    chunked as they're read, with no need to transcode the whole thing
+    first; the delimiter is matched against the transcoded UTF-8, so it
+    can be used with a [`StringAdapter`](crate::StringAdapter) to produce
+    `String`s.
+
+    ```rust
+    use regex_chunker::{ByteChunker, Encoding, RcErr, StringAdapter};
+    use std::io::Cursor;
+
+    // Big-endian UTF-16 with no byte-order mark.
+    let text: Vec<u8> = "día 1|día 2|día 3"
+        .encode_utf16()
+        .flat_map(|c| c.to_be_bytes())
+        .collect();
+
+    let chunks: Vec<String> = ByteChunker::new(Cursor::new(text), r"\|")?
+        .with_encoding(Encoding::Utf16Be)
+        .with_adapter(StringAdapter::default())
+        .collect::<Result<_, RcErr>>()?;
+    assert_eq!(chunks, ["día 1", "día 2", "día 3"]);
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn with_encoding(self, encoding: Encoding) -> ByteChunker<BomReader<R>> {
+        let mut chunker = self.with_bom_sniffing();
+        chunker.source.set_default_encoding(encoding);
+        chunker
     }
 
     /**
@@ -124,6 +504,102 @@ impl<R> ByteChunker<R> {
         self
     }
 
+    /**
+    Builder-pattern method for reading from the source with
+    [`Read::read_vectored`], which lets the source write directly into the
+    chunker's search buffer, rather than into the read buffer, from which
+    it would have to be copied. This is worthwhile for high-throughput
+    sources like sockets and pipes that implement vectored reads; for
+    sources that don't, `read_vectored` falls back to an ordinary read of
+    just the first buffer, which is still correct but saves nothing.
+    Default is `false`.
+
+    This is only an option for the synchronous chunker. The async
+    `stream::ByteChunker` always reads straight into its buffer's
+    uninitialised spare capacity, so there's no copy for it to save.
+    */
+    pub fn with_vectored_reads(mut self, vectored: bool) -> Self {
+        self.vectored_reads = vectored;
+        self
+    }
+
+    /**
+    Builder-pattern method for deliberately breaking up the chunker's
+    reads, asking the source for a random number of bytes each time
+    (between one and the size of the read buffer). The sizes are drawn
+    from a generator seeded with `seed`, so a given seed always splits the
+    data the same way. Turning this on in tests flushes out code that
+    assumes a chunk (or a delimiter, or a multibyte character) arrives in
+    a single read, without having to wrap the source in a special reader
+    like a [`SlowReader`](crate::SlowReader). It also turns off vectored
+    reads.
+
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr};
+    use std::io::Cursor;
+
+    let text = "αβγ, δεζ, ηθι";
+    for seed in 0..20 {
+        let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new(text), ", ")?
+            .with_buffer_size(8)
+            .with_fragmented_reads(seed)
+            .collect::<Result<_, RcErr>>()?;
+        assert_eq!(chunks, ["αβγ".as_bytes(), "δεζ".as_bytes(), "ηθι".as_bytes()]);
+    }
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    #[cfg(feature = "test-util")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+    pub fn with_fragmented_reads(mut self, seed: u64) -> Self {
+        self.read_size = Some(crate::mock::fragmenter(seed));
+        self
+    }
+
+    /**
+    Builder-pattern method for setting the [`BufferPolicy`] that controls
+    how the chunker's search buffer grows, and when it shrinks. By default
+    the buffer grows as a `Vec` normally does, and is never shrunk.
+    */
+    pub fn with_buffer_policy<P>(mut self, policy: P) -> Self
+    where
+        P: BufferPolicy + 'static,
+    {
+        self.buffer_policy = Some(Box::new(policy));
+        self
+    }
+
+    /**
+    Builder-pattern method for setting a hook that is called with a
+    [`ChunkTiming`] each time a chunk is returned, reporting how long the
+    chunker spent reading, searching, and copying to produce it. When no
+    hook is set (the default), no timing is done.
+    */
+    pub fn with_timing<F>(mut self, hook: F) -> Self
+    where
+        F: FnMut(&ChunkTiming) + Send + 'static,
+    {
+        self.timer = Some(Timer::new(hook));
+        self
+    }
+
+    /**
+    Builder-pattern method for setting a hook that is called with a
+    [`DecodeEvent`](crate::DecodeEvent) each time the chunker searches its
+    buffer for a delimiter and decides what to do about what it found (or
+    didn't), and when it returns the last chunk, for working out why a
+    chunk boundary landed where it did.
+    */
+    #[cfg(feature = "debug-internals")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "debug-internals")))]
+    pub fn with_trace<F>(mut self, hook: F) -> Self
+    where
+        F: FnMut(&crate::DecodeEvent) + Send + 'static,
+    {
+        self.tracer = Some(Box::new(hook));
+        self
+    }
+
     /**
     Builder-pattern method for controlling how the chunker behaves when
     encountering an error in the course of its operation. Default value
@@ -149,16 +625,462 @@ impl<R> ByteChunker<R> {
     matched text. Default value is [`MatchDisposition::Drop`].
      */
     pub fn with_match(mut self, behavior: MatchDisposition) -> Self {
-        self.match_dispo = behavior;
-        if matches!(behavior, MatchDisposition::Drop | MatchDisposition::Append) {
-            // If we swtich to one of these two dispositions, we
-            // need to be sure we reset the scan_start_offset, or
-            // else we'll never scan the beginning of our buffer.
+        if !matches!(behavior, MatchDisposition::Prepend | MatchDisposition::Wrap) {
+            // If we swtich to anything but Prepend or Wrap, we need to be
+            // sure we reset the scan_start_offset, or else we'll
+            // never scan the beginning of our buffer.
             self.scan_start_offset = 0;
         }
+        self.engine.match_dispo = behavior;
+        self
+    }
+
+    /**
+    Builder-pattern method for controlling where the search for the next
+    delimiter resumes after a dropped or replaced one, which decides
+    whether overlapping matches each end a chunk. Default value is
+    [`OverlapPolicy::Skip`]; see [`OverlapPolicy`] for an example.
+    */
+    pub fn with_overlap(mut self, policy: OverlapPolicy) -> Self {
+        self.engine.overlap = policy;
+        self
+    }
+
+    /**
+    Builder-pattern method for splitting only where the delimiter matches
+    at least `k` times in a row, each match starting where the last one
+    ended. The whole run is then treated as a single delimiter, while
+    shorter runs stay inside the chunk. This is clearer (and faster) than
+    writing the repetition into the pattern. The default is 1, which
+    splits at every match.
+
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr};
+    use std::io::Cursor;
+
+    // Paragraphs are separated by two or more blank lines.
+    let text = "one\nstill one\n\ntwo\n\n\n\nthree";
+    let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new(text), r"\n")?
+        .with_min_repeats(3)
+        .collect::<Result<_, RcErr>>()?;
+
+    assert_eq!(chunks, [&b"one\nstill one\n\ntwo"[..], b"three"]);
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn with_min_repeats(mut self, k: usize) -> Self {
+        self.engine.min_repeats = k.max(1);
+        self
+    }
+
+    /**
+    Builder-pattern method for ignoring any delimiter that starts fewer
+    than `n` bytes after the start of the chunk it would end, for data
+    where a separator sometimes turns up near the start of a record and
+    distance alone tells it apart from a real one. An ignored delimiter
+    stays in the chunk. The default is 0, which ignores nothing.
+
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr};
+    use std::io::Cursor;
+
+    // Each record starts with a three-byte code that may contain a `;`.
+    let text = b"A;B first;C;D second;EFG third";
+    let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new(text), ";")?
+        .with_min_gap(4)
+        .collect::<Result<_, RcErr>>()?;
+
+    assert_eq!(chunks, [&b"A;B first"[..], b"C;D second", b"EFG third"]);
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn with_min_gap(mut self, n: usize) -> Self {
+        self.engine.min_gap = n;
+        self
+    }
+
+    /**
+    Builder-pattern method for only splitting at delimiters that are
+    preceded by a match for `pattern`, like the start of a line or a blank
+    line. This does what a lookbehind would, which the regex crate doesn't
+    support, without searching the whole buffer for it: the pattern only
+    has to match the data that ends where the delimiter begins, and it can
+    see at most the 256 bytes before it. `^` matches only at the start of
+    the source. Delimiters that fail the test stay in the chunk.
+
+    Returns an error if `pattern` isn't a valid regex.
+
+    ```rust
+    use regex_chunker::{ByteChunker, MatchDisposition, RcErr};
+    use std::io::Cursor;
+
+    // Only a `#` at the start of a line starts a section.
+    let text = b"# One\nsee #3\n# Two";
+    let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new(text), "#")?
+        .with_match(MatchDisposition::Prepend)
+        .with_anchor(r"^|\n")?
+        .collect::<Result<_, RcErr>>()?;
+
+    assert_eq!(chunks, [&b""[..], b"# One\nsee #3\n", b"# Two"]);
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn with_anchor(mut self, pattern: &str) -> Result<Self, RcErr> {
+        self.engine.set_anchor(pattern)?;
+        Ok(self)
+    }
+
+    /**
+    Builder-pattern method for deciding what to do with each match
+    individually. The supplied function is passed the matched text, and
+    its return value is used instead of the chunker's [`MatchDisposition`].
+    (A [`Boundary`] that reports its own dispositions, like a
+    [`MultiRegex`], still takes precedence.) The function may be called
+    more than once for the same match, so it shouldn't depend on being
+    called exactly once per delimiter.
+
+    ```rust
+    use regex_chunker::{ByteChunker, MatchDisposition};
+    use std::io::Cursor;
+
+    // A "!" ends a chunk and should be kept; a "." just ends it.
+    let text = b"Go. Stop! Wait.";
+    let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new(text), r"[.!] ?")?
+        .with_match_fn(|delim| match delim[0] {
+            b'!' => MatchDisposition::Append,
+            _ => MatchDisposition::Drop,
+        })
+        .collect::<Result<_, _>>()?;
+
+    assert_eq!(chunks, [&b"Go"[..], b"Stop! ", b"Wait"]);
+    # Ok::<(), regex_chunker::RcErr>(())
+    ```
+    */
+    pub fn with_match_fn<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&[u8]) -> MatchDisposition + Send + 'static,
+    {
+        self.engine.match_fn = Some(Box::new(f));
+        self
+    }
+
+    /**
+    Builder-pattern method for vetoing splits. At each delimiter found,
+    `f` is passed the chunk so far and the delimiter, and if it returns
+    `false` the delimiter is kept in the chunk (whatever the
+    [`MatchDisposition`]) and the chunk carries on to the next one. This is
+    for heuristics a pattern can't express, like not splitting inside an
+    open quote.
+
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr};
+    use std::io::Cursor;
+
+    let text = br#"a,"b,c",d"#;
+    let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new(text), ",")?
+        .with_split_fn(|chunk, _delim| chunk.iter().filter(|&&b| b == b'"').count() % 2 == 0)
+        .collect::<Result<_, RcErr>>()?;
+
+    assert_eq!(chunks, [&b"a"[..], br#""b,c""#, b"d"]);
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn with_split_fn<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&[u8], &[u8]) -> bool + Send + 'static,
+    {
+        self.engine.split_fn = Some(Box::new(f));
+        self
+    }
+
+    /**
+    Builder-pattern method for limiting the length of the chunks returned.
+    If no delimiter has been found within `max` bytes (including the
+    matched text, if it's being kept), the chunker splits there anyway, so
+    every chunk is at most `max` bytes long. Such forced splits are marked
+    by the [`forced`](Chunk::forced) field of the [`Chunk`]s yielded by
+    [`ByteChunker::with_metadata`]. The minimum value of `max` is 1.
+
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr};
+    use std::io::Cursor;
+
+    let text = b"short\nsomewhat longer\n";
+    let chunks: Vec<_> = ByteChunker::new(Cursor::new(text), r"\n")?
+        .with_max_chunk_len(8)
+        .with_metadata()
+        .map(|res| res.unwrap())
+        .collect();
+
+    let bodies: Vec<&[u8]> = chunks.iter().map(|c| &c.body[..]).collect();
+    assert_eq!(&bodies, &[&b"short"[..], b"somewhat", b" longer"]);
+    assert!(chunks[1].forced && !chunks[2].forced);
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn with_max_chunk_len(mut self, max: usize) -> Self {
+        self.engine.max_chunk_len = Some(max.max(1));
         self
     }
 
+    /**
+    Builder-pattern method for trimming text that matches `pattern` from
+    the beginning and end of every chunk, like quotes, brackets, or
+    whitespace. The trimming is done before each chunk is copied out of the
+    chunker's buffer, so the trimmed text is never allocated. The `span` of
+    a [`Chunk`] yielded by [`ByteChunker::with_metadata`] covers only the
+    part that's left.
+
+    Returns an error if `pattern` isn't a valid regex.
+
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr};
+    use std::io::Cursor;
+
+    let text = br#""one", 'two',three , "four""#;
+    let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new(text), ",")?
+        .trim_matches(r#"[\s"']+"#)?
+        .collect::<Result<_, RcErr>>()?;
+
+    assert_eq!(chunks, [&b"one"[..], b"two", b"three", b"four"]);
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn trim_matches(mut self, pattern: &str) -> Result<Self, RcErr> {
+        self.trimmer = Some(Trimmer::new(pattern)?);
+        Ok(self)
+    }
+
+    /**
+    Builder-pattern method for making the forced splits imposed by
+    [`ByteChunker::with_max_chunk_len`] fall between grapheme clusters,
+    so that a character, or an emoji sequence, or a letter and its combining
+    marks, never ends up divided between two chunks. The split is moved back
+    to the start of the cluster it would have divided, which then begins
+    the next chunk. Default is `false`.
+
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr, StringAdapter};
+    use std::io::Cursor;
+
+    // "e" followed by a combining acute accent.
+    let text = "cafe\u{301}s".as_bytes();
+    let chunks: Vec<String> = ByteChunker::new(Cursor::new(text), r"\n")?
+        .with_max_chunk_len(4)
+        .with_grapheme_safe_splits(true)
+        .with_adapter(StringAdapter::default())
+        .collect::<Result<_, RcErr>>()?;
+
+    assert_eq!(chunks, ["caf", "e\u{301}s"]);
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    #[cfg(feature = "unicode")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "unicode")))]
+    pub fn with_grapheme_safe_splits(mut self, safe: bool) -> Self {
+        self.engine.grapheme_safe = safe;
+        self
+    }
+
+    /**
+    Builder-pattern method for removing a UTF-8 byte-order mark
+    (`U+FEFF`) from the very beginning of the source, so it doesn't end
+    up at the front of the first chunk. The BOM is removed before the
+    source is searched, but the spans of [`Chunk`]s still count it.
+    Default is `false`.
+
+    Unlike [`ByteChunker::with_bom_sniffing`], this doesn't transcode
+    anything, and leaves UTF-16 and UTF-32 BOMs alone. To strip the BOM
+    of each of several files read one after the other, wrap each file in
+    its own [`BomReader`] before chaining them.
+
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr};
+    use std::io::Cursor;
+
+    let text = b"\xef\xbb\xbfid,name\n1,one";
+    let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new(text), r"\n")?
+        .with_bom_stripping(true)
+        .collect::<Result<_, RcErr>>()?;
+
+    assert_eq!(chunks, [&b"id,name"[..], b"1,one"]);
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn with_bom_stripping(mut self, strip: bool) -> Self {
+        self.strip_bom = strip;
+        self
+    }
+
+    /**
+    Builder-pattern method for treating data at the end of the source
+    that isn't followed by a delimiter as an error, for record formats in
+    which every record is terminated and anything else means the source
+    was cut short. If `strict` is set, such data is yielded as an
+    [`RcErr::Unterminated`] carrying it, in place of the final chunk.
+    Default is `false`.
+
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr};
+    use std::io::Cursor;
+
+    let text = b"one\ntwo\nthr";
+    let mut chunker = ByteChunker::new(Cursor::new(text), r"\n")?
+        .with_strict_termination(true);
+
+    assert_eq!(chunker.next().unwrap()?, b"one");
+    assert_eq!(chunker.next().unwrap()?, b"two");
+    assert!(matches!(chunker.next(), Some(Err(RcErr::Unterminated(rest))) if rest == b"thr"));
+    assert!(chunker.next().is_none());
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn with_strict_termination(mut self, strict: bool) -> Self {
+        self.strict_tail = strict;
+        self
+    }
+
+    /**
+    Builder-pattern method for formats in which every record must be
+    terminated: if the source doesn't end with a delimiter, the chunker
+    yields an error (an [`RcErr::Unterminated`] carrying the leftover
+    data) instead of a final chunk. This is shorthand for
+    [`with_strict_termination(true)`](ByteChunker::with_strict_termination).
+
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr};
+    use std::io::Cursor;
+
+    let ok: Result<Vec<_>, _> = ByteChunker::new(Cursor::new("a\nb\n"), r"\n")?
+        .require_terminator()
+        .collect();
+    assert_eq!(ok?, [b"a", b"b"]);
+
+    let truncated: Result<Vec<_>, _> = ByteChunker::new(Cursor::new("a\nb"), r"\n")?
+        .require_terminator()
+        .collect();
+    assert!(matches!(truncated, Err(RcErr::Unterminated(_))));
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn require_terminator(self) -> Self {
+        self.with_strict_termination(true)
+    }
+
+    /**
+    Return the index of the pattern whose match terminated the most
+    recently returned chunk, if the chunker's [`Boundary`] reports one
+    (as a [`LiteralBoundary`] does). Returns `None` after the final,
+    unterminated chunk.
+    */
+    pub fn matched_pattern(&self) -> Option<usize> {
+        self.matched_pattern
+    }
+
+    /**
+    Return the number of bytes that have been read from the source but
+    not yet returned as part of a chunk.
+    */
+    pub fn buffered_len(&self) -> usize {
+        self.buffered().len()
+    }
+
+    /**
+    Return the largest number of bytes that have ever been buffered at
+    once (see [`ByteChunker::buffered_len`]). This is the high-water mark
+    of the chunker's memory use, and is what to watch when choosing a
+    [`with_max_chunk_len`](ByteChunker::with_max_chunk_len) limit or a
+    [`BufferPolicy`] for a long-running source.
+
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr};
+    use std::io::Cursor;
+
+    let text = "short\na much, much longer line\nshort\n";
+    let mut chunker = ByteChunker::new(Cursor::new(text), r"\n")?;
+    assert_eq!(chunker.peak_buffered_len(), 0);
+
+    while let Some(chunk) = chunker.next() {
+        chunk?;
+    }
+    assert_eq!(chunker.buffered_len(), 0);
+    assert!(chunker.peak_buffered_len() >= text.len());
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn peak_buffered_len(&self) -> usize {
+        self.peak_buffered
+    }
+
+    /**
+    Converts this `ByteChunker` into a [`MetaChunker`], which yields
+    [`Chunk`]s that carry the delimiter that terminated each chunk and
+    its position in the source, rather than bare `Vec<u8>`s.
+    */
+    pub fn with_metadata(mut self) -> MetaChunker<R> {
+        self.keep_delimiters = true;
+        self.into()
+    }
+
+    /**
+    Converts this `ByteChunker` into a [`DelimitedChunker`], which yields
+    each chunk paired with the delimiter that terminated it.
+    */
+    pub fn with_delimiters(self) -> DelimitedChunker<R> {
+        self.into()
+    }
+
+    /**
+    Converts this `ByteChunker` into a [`ChunkInspector`], which yields
+    the same chunks, but first passes each one as a [`Chunk`] (with its
+    span and delimiter) to `inspect`.
+    */
+    pub fn with_inspector<F>(self, inspect: F) -> ChunkInspector<R, F>
+    where
+        F: FnMut(&Chunk),
+    {
+        ChunkInspector::new(self, inspect)
+    }
+
+    /**
+    Replace the pattern the chunker splits its source on. The new pattern
+    takes effect from the next search, which includes any data that has
+    already been read but not yet returned, so this can be called between
+    chunks to change the framing of a long-lived source partway through
+    (after a protocol upgrade line, say).
+
+    ```rust
+    use regex_chunker::ByteChunker;
+    use std::io::Cursor;
+
+    let text = b"HELLO\nUPGRADE\none\0two\0";
+    let mut chunker = ByteChunker::new(Cursor::new(text), r"\n")?;
+
+    assert_eq!(chunker.next().unwrap()?, b"HELLO");
+    assert_eq!(chunker.next().unwrap()?, b"UPGRADE");
+    chunker.set_pattern(r"\x00")?;
+    assert_eq!(chunker.next().unwrap()?, b"one");
+    assert_eq!(chunker.next().unwrap()?, b"two");
+    assert!(chunker.next().is_none());
+    # Ok::<(), regex_chunker::RcErr>(())
+    ```
+    */
+    pub fn set_pattern(&mut self, pattern: &str) -> Result<(), RcErr> {
+        self.set_regex(Regex::new(pattern)?);
+        Ok(())
+    }
+
+    /**
+    Like [`ByteChunker::set_pattern`], but takes an already-compiled
+    regex.
+    */
+    pub fn set_regex(&mut self, regex: Regex) {
+        self.engine.fence = Box::new(regex);
+        // Search what's already buffered before reading any more; the old
+        // pattern may not have matched it, but the new one might.
+        self.last_scan_matched = true;
+    }
+
     /**
     Consumes the [`ByteChunker`] and returns its wrapped `Read`er.
     The `ByteChunker` may have read some data from its source that may not
@@ -175,7 +1097,9 @@ impl<R> ByteChunker<R> {
     data is unimportant, and you just want the reader back, use the more
     traditional [`ByteChunker::into_inner`].
     */
-    pub fn into_innards(self) -> (R, Vec<u8>) {
+    pub fn into_innards(mut self) -> (R, Vec<u8>) {
+        self.compact();
+        self.search_buff.truncate(self.buff_end);
         (self.source, self.search_buff)
     }
 
@@ -187,54 +1111,348 @@ impl<R> ByteChunker<R> {
         (self, adapter).into()
     }
 
+    /**
+    Creates a [`CustomChunker`] that returns its chunks as `B`, rather than
+    `Vec<u8>`, without copying them. See [`ChunkBuf`].
+    */
+    pub fn with_output<B: ChunkBuf>(self) -> CustomChunker<R, OutputAdapter<B>> {
+        self.with_adapter(OutputAdapter::new())
+    }
+
+    /**
+    Converts the `ByteChunker` into an [`InfallibleChunker`], which
+    returns plain `Vec<u8>`s rather than `Result`s, as reading from an
+    [`InfallibleRead`] source can't fail.
+    */
+    pub fn infallible(self) -> InfallibleChunker<R>
+    where
+        R: InfallibleRead,
+    {
+        InfallibleChunker::new(self)
+    }
+
+    /**
+    Converts the `ByteChunker` into an [`InfallibleChunker`] that skips
+    over any errors reading from its source, as with
+    [`ErrorResponse::Ignore`], and so returns plain `Vec<u8>`s rather than
+    `Result`s. The same caveat applies: a source that fails every read
+    will make it loop forever.
+    */
+    pub fn ignoring_errors(self) -> InfallibleChunker<R> {
+        InfallibleChunker::new(self.on_error(ErrorResponse::Ignore))
+    }
+
+    /**
+    Converts the `ByteChunker` into a [`LendingChunker`], which lends out
+    its chunks as slices of the chunker's internal buffer, rather than
+    allocating a `Vec<u8>` for each one.
+    */
+    pub fn lending(self) -> LendingChunker<R> {
+        LendingChunker::new(self)
+    }
+
+    /**
+    Converts the `ByteChunker` into an [`AllocChunker`], which returns
+    each chunk in a vector allocated with `alloc`.
+    */
+    #[cfg(feature = "allocator-api2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "allocator-api2")))]
+    pub fn with_allocator<A>(self, alloc: A) -> crate::AllocChunker<R, A> {
+        crate::AllocChunker::new(self, alloc)
+    }
+
     pub fn with_simple_adapter<A>(self, adapter: A) -> SimpleCustomChunker<R, A>
     {
         (self, adapter).into()
     }
 
+    /**
+    Creates a [`CustomChunker`] by combining this `ByteChunker` with a
+    [`TryAdapter`](crate::TryAdapter), yielding its results (and the
+    chunker's own errors, converted) with the adapter's error type.
+    */
+    pub fn with_try_adapter<A>(self, adapter: A) -> CustomChunker<R, Fallible<A>>
+    where
+        A: TryAdapter,
+    {
+        (self, Fallible(adapter)).into()
+    }
+
+    /**
+    Creates an [`InPlaceChunker`] by combining this `ByteChunker` with an
+    [`InPlaceAdapter`](crate::InPlaceAdapter) type.
+    */
+    pub fn with_in_place_adapter<A>(self, adapter: A) -> InPlaceChunker<R, A> {
+        (self, adapter).into()
+    }
+
     /*
-    Search the search_buffer for a match; if found, return the next chunk
-    of bytes to be returned from ]`Iterator::next`].
-    */
-    fn scan_buffer(&mut self) -> Option<Vec<u8>> {
-        let (start, end) = match self
-            .fence
-            .find_at(&self.search_buff, self.scan_start_offset)
-        {
-            Some(m) => {
+    Search the search_buffer for a match. If one is found, return the length
+    of the chunk it terminates (chunks always begin at the start of the
+    search buffer), the span of the match, and the number of bytes of the
+    search buffer that returning the chunk will consume.
+
+    If there's a maximum chunk length and the next chunk would exceed it,
+    return a forced split at that length instead.
+    */
+    fn find_chunk(&mut self) -> Option<Found> {
+        let scan_offset = self.scan_start_offset;
+        let buff = &self.search_buff[self.buff_start..self.buff_end];
+        match self.engine.decide(buff, scan_offset, self.at_end) {
+            Verdict::Wait => {
+                self.trace(scan_offset, None, Decision::Wait);
+                self.last_scan_matched = false;
+                None
+            }
+            Verdict::Forced { first, len, found } => {
+                self.trace(scan_offset, found.as_ref(), Decision::Forced { len });
                 self.last_scan_matched = true;
-                (m.start(), m.end())
+                self.matched_pattern = None;
+                self.scan_start_offset = self.scan_start_offset.saturating_sub(len);
+                Some(Found {
+                    first,
+                    len,
+                    delimiter: (len, len),
+                    consumed: len,
+                    forced: true,
+                    replacement: None,
+                })
             }
-            None => {
-                self.last_scan_matched = false;
-                return None;
+            Verdict::Split {
+                found,
+                first,
+                len,
+                consumed,
+                rescan,
+            } => {
+                self.last_scan_matched = true;
+                self.matched_pattern = self.engine.matched_pattern();
+                self.scan_start_offset = rescan;
+                self.trace(scan_offset, Some(&found), Decision::Split { len, consumed });
+                let (start, end, dispo) = found;
+                let replacement = match dispo {
+                    MatchDisposition::Replace(bytes) => Some(bytes),
+                    _ => None,
+                };
+                Some(Found {
+                    first,
+                    len,
+                    delimiter: (start, end),
+                    consumed,
+                    forced: false,
+                    replacement,
+                })
             }
+        }
+    }
+
+    /* Remove the chunk described by `found` from the search buffer. */
+    fn take_chunk(&mut self, found: Found) -> Chunk {
+        let buff = self.buffered();
+        let delimiter = if self.keep_delimiters {
+            let (start, end) = found.delimiter;
+            Some(buff[start..end].to_vec())
+        } else {
+            None
         };
+        let kept = self.kept(found.first, found.len);
+        let mut body = buff[kept.clone()].to_vec();
+        if let Some(replacement) = found.replacement {
+            body.extend_from_slice(&replacement);
+        }
+
+        let char_span = self.count_consumed(&kept, found.consumed);
 
-        let mut new_buff;
-        match self.match_dispo {
-            MatchDisposition::Drop => {
-                new_buff = self.search_buff.split_off(end);
-                self.search_buff.resize(start, 0);
+        self.buff_start += found.consumed;
+        self.apply_shrink(found.consumed);
+
+        let span = (self.offset + kept.start as u64)..(self.offset + kept.end as u64);
+        self.offset += found.consumed as u64;
+        let (seq, gap) = self.sequence();
+        Chunk {
+            body,
+            delimiter,
+            span,
+            char_span,
+            eof: false,
+            forced: found.forced,
+            seq,
+            gap,
+        }
+    }
+
+    /* Pass a `DecodeEvent` describing a decision to the trace hook, if any. */
+    fn trace(
+        &mut self,
+        scan_offset: usize,
+        found: Option<&(usize, usize, MatchDisposition)>,
+        decision: Decision,
+    ) {
+        if self.tracer.is_none() {
+            return;
+        }
+        let event = DecodeEvent {
+            offset: self.offset,
+            buffered: self.buffered().len(),
+            scan_offset,
+            at_end: self.at_end,
+            found: found.map(|(start, end, _)| *start..*end),
+            disposition: found.map(|(_, _, dispo)| dispo.clone()),
+            decision,
+        };
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer(&event);
+        }
+    }
+
+    /* Start counting characters, for the `MetaChunker`. */
+    pub(crate) fn count_chars(&mut self) {
+        self.char_offset.get_or_insert(0);
+    }
+
+    /*
+    If counting characters, count those in the first `consumed` bytes of
+    the search buffer, and return the character span of the `kept` range.
+    */
+    fn count_consumed(&mut self, kept: &Range<usize>, consumed: usize) -> Option<Range<u64>> {
+        let chars = self.char_offset?;
+        let buff = self.buffered();
+        let start = chars + char_count(&buff[..kept.start]);
+        let end = start + char_count(&buff[kept.clone()]);
+        self.char_offset = Some(chars + char_count(&buff[..consumed]));
+        Some(start..end)
+    }
+
+    /* The current time, if the chunker is timing itself. */
+    fn clock(&self) -> Option<Instant> {
+        self.timer.as_ref().map(|_| Instant::now())
+    }
+
+    /* The data in the search buffer that hasn't yet been returned. */
+    fn buffered(&self) -> &[u8] {
+        &self.search_buff[self.buff_start..self.buff_end]
+    }
+
+    /* Remove the already-returned bytes from the front of the search buffer. */
+    fn compact(&mut self) {
+        if self.buff_start > 0 {
+            self.search_buff
+                .copy_within(self.buff_start..self.buff_end, 0);
+            self.buff_end -= self.buff_start;
+            self.buff_start = 0;
+        }
+    }
+
+    /* Tell the buffer policy, if any, that `consumed` bytes have been
+    removed from the search buffer, and give it a chance to shrink it. */
+    fn apply_shrink(&mut self, consumed: usize) {
+        let (len, capacity) = (self.buffered().len(), self.search_buff.capacity());
+        let target = match self.buffer_policy.as_mut() {
+            Some(policy) => {
+                policy.observe(consumed);
+                policy.shrink(len, capacity)
             }
-            MatchDisposition::Append => {
-                new_buff = self.search_buff.split_off(end);
+            None => None,
+        };
+        if let Some(target) = target {
+            self.compact();
+            self.search_buff.truncate(self.buff_end);
+            self.search_buff.shrink_to(target);
+        }
+    }
+
+    /* Remove a peeked chunk (and its delimiter) from the search buffer. */
+    pub(crate) fn release(&mut self, consumed: usize) {
+        self.buff_start += consumed;
+        self.apply_shrink(consumed);
+        self.offset += consumed as u64;
+        self.sequence();
+        if let Some(timer) = self.timer.as_mut() {
+            timer.report();
+        }
+    }
+
+    /* Give the buffer policy, if any, a chance to grow the search buffer
+    before `additional` more bytes are added to it. */
+    fn apply_grow(&mut self, additional: usize) {
+        let (len, capacity) = (self.buff_end, self.search_buff.capacity());
+        if len + additional <= capacity {
+            return;
+        }
+        if let Some(policy) = self.buffer_policy.as_mut() {
+            if let Some(n) = policy.grow(len, capacity) {
+                self.search_buff.reserve(n.max(additional));
             }
-            MatchDisposition::Prepend => {
-                new_buff = self.search_buff.split_off(start);
-                self.scan_start_offset = end - start;
+        }
+    }
+
+    /* The part of `first..len` of the unreturned data that's left after
+    trimming. */
+    fn kept(&self, first: usize, len: usize) -> Range<usize> {
+        match self.trimmer.as_ref() {
+            Some(trimmer) => {
+                let kept = trimmer.range(&self.buffered()[first..len]);
+                (first + kept.start)..(first + kept.end)
             }
+            None => first..len,
+        }
+    }
+
+    /* Remove `n` bytes that don't belong to any chunk from the front of
+    the search buffer. */
+    fn skip(&mut self, n: usize) {
+        if let Some(chars) = self.char_offset.as_mut() {
+            *chars += char_count(&self.search_buff[self.buff_start..][..n]);
+        }
+        self.buff_start += n;
+        self.offset += n as u64;
+    }
+
+    /* Remove the whole search buffer as the final chunk. */
+    fn take_tail(&mut self) -> Chunk {
+        let lead = self.engine.take_lead();
+        self.skip(lead);
+        self.compact();
+        self.search_buff.truncate(self.buff_end);
+        let len = self.search_buff.len();
+        self.trace(self.scan_start_offset, None, Decision::Tail { len });
+        let kept = match self.trimmer.as_ref() {
+            Some(trimmer) => trimmer.range(&self.search_buff),
+            None => 0..self.search_buff.len(),
+        };
+        let char_span = self.count_consumed(&kept, self.search_buff.len());
+        let mut body = std::mem::take(&mut self.search_buff);
+        self.buff_end = 0;
+        let start = self.offset;
+        self.offset += body.len() as u64;
+        body.truncate(kept.end);
+        body.drain(..kept.start);
+        let (seq, gap) = self.sequence();
+        Chunk {
+            body,
+            delimiter: None,
+            span: (start + kept.start as u64)..(start + kept.end as u64),
+            char_span,
+            eof: true,
+            forced: false,
+            seq,
+            gap,
         }
+    }
 
-        std::mem::swap(&mut new_buff, &mut self.search_buff);
-        Some(new_buff)
+    /* Number the chunk about to be returned, and report whether data may
+    have been lost since the last one. */
+    fn sequence(&mut self) -> (u64, bool) {
+        let seq = self.seq;
+        self.seq += 1;
+        (seq, std::mem::take(&mut self.gap))
     }
 
     // Function for wrapping types that need this information.
     #[allow(dead_code)]
     #[inline(always)]
     fn buff_size(&self) -> usize {
-        return self.read_buff.len();
+        self.read_buff.len()
     }
 }
 
@@ -242,80 +1460,470 @@ impl<R> Debug for ByteChunker<R> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ByteChunker")
             .field("source", &std::any::type_name::<R>())
-            .field("fence", &self.fence)
+            .field("engine", &self.engine)
             .field("read_buff", &String::from_utf8_lossy(&self.read_buff))
-            .field("search_buff", &String::from_utf8_lossy(&self.search_buff))
+            .field("search_buff", &String::from_utf8_lossy(self.buffered()))
+            .field("peak_buffered", &self.peak_buffered)
             .field("error_status", &self.error_status)
-            .field("match_dispo", &self.match_dispo)
             .field("last_scan_matched", &self.last_scan_matched)
+            .field("at_end", &self.at_end)
             .field("scan_start_offset", &self.scan_start_offset)
+            .field("matched_pattern", &self.matched_pattern)
+            .field("offset", &self.offset)
+            .field("char_offset", &self.char_offset)
+            .field("keep_delimiters", &self.keep_delimiters)
+            .field("trimmer", &self.trimmer)
+            .field("strip_bom", &self.strip_bom)
+            .field("strict_tail", &self.strict_tail)
+            .field("vectored_reads", &self.vectored_reads)
+            .field("read_size", &self.read_size.is_some())
+            .field("buffer_policy", &self.buffer_policy)
+            .field("timer", &self.timer)
+            .field("tracer", &self.tracer.is_some())
+            .field("seq", &self.seq)
+            .field("gap", &self.gap)
             .finish()
     }
 }
 
-/**
-The [`ByteChunker`] specifically doesn't supply an implementation of
-[`Iterator::size_hint`] because, in general, it's impossible to tell
-how much data is left in a reader.
-*/
-impl<R: Read> Iterator for ByteChunker<R> {
-    type Item = Result<Vec<u8>, RcErr>;
+impl<R: Read> ByteChunker<R> {
+    /*
+    Read from the source into the search buffer. Returns whether any
+    data was read (`false` at the end of the source), or an error to be
+    passed on according to the chunker's `ErrorStatus`.
+    */
+    fn fill_buffer(&mut self) -> Result<bool, RcErr> {
+        loop {
+            let since = self.clock();
+            let res = self.read_source();
+            if let (Some(timer), Some(since)) = (self.timer.as_mut(), since) {
+                timer.add_read(since);
+            }
 
-    fn next(&mut self) -> Option<Self::Item> {
+            match res {
+                Err(e) => match e.kind() {
+                    ErrorKind::WouldBlock | ErrorKind::Interrupted => {
+                        spin_loop();
+                        continue;
+                    }
+                    _ => match self.error_status {
+                        ErrorStatus::Ok | ErrorStatus::Errored => {
+                            self.error_status = ErrorStatus::Errored;
+                            return Err(e.into());
+                        }
+                        ErrorStatus::Continue => {
+                            self.gap = true;
+                            return Err(e.into());
+                        }
+                        ErrorStatus::Ignore => {
+                            self.gap = true;
+                            continue;
+                        }
+                    },
+                },
+                Ok(0) => return Ok(false),
+                Ok(_) => return Ok(true),
+            }
+        }
+    }
+
+    /*
+    Make one read from the source, appending whatever is read to the
+    search buffer.
+
+    A vectored read goes straight into the search buffer, spilling over
+    into the read buffer only if it's filled, which saves copying every
+    byte read. The room it reads into is only zeroed the first time the
+    search buffer grows that far; after that it's reused as is.
+    */
+    fn read_source(&mut self) -> std::io::Result<usize> {
+        self.compact();
+        if !self.vectored_reads || self.read_size.is_some() {
+            let room = self.read_buff.len();
+            let len = match self.read_size.as_mut() {
+                Some(read_size) => read_size(room).min(room),
+                None => room,
+            };
+            let n = self.source.read(&mut self.read_buff[..len])?;
+            self.apply_grow(n);
+            self.search_buff.truncate(self.buff_end);
+            self.search_buff.extend_from_slice(&self.read_buff[..n]);
+            self.buff_end += n;
+            self.peak_buffered = self.peak_buffered.max(self.buffered().len());
+            return Ok(n);
+        }
+
+        let start = self.buff_end;
+        let spare = self.read_buff.len();
+        self.apply_grow(spare);
+        if self.search_buff.len() < start + spare {
+            self.search_buff.resize(start + spare, 0);
+        }
+        let res = self.source.read_vectored(&mut [
+            IoSliceMut::new(&mut self.search_buff[start..start + spare]),
+            IoSliceMut::new(&mut self.read_buff),
+        ]);
+
+        match res {
+            Ok(n) if n > spare => {
+                self.search_buff.truncate(start + spare);
+                self.search_buff
+                    .extend_from_slice(&self.read_buff[..n - spare]);
+                self.buff_end = self.search_buff.len();
+            }
+            Ok(n) => self.buff_end = start + n,
+            Err(_) => {}
+        }
+        self.peak_buffered = self.peak_buffered.max(self.buffered().len());
+        res
+    }
+
+    /*
+    Read until the next chunk is found. Returns `Next::Tail` if the source
+    runs out with unreturned data in the search buffer, and `None` if it
+    runs out with the search buffer empty.
+    */
+    fn advance(&mut self) -> Option<Result<Next, RcErr>> {
         if self.error_status == ErrorStatus::Errored {
             return None;
         }
 
         loop {
             if !self.last_scan_matched {
-                match self.source.read(&mut self.read_buff) {
-                    Err(e) => match e.kind() {
-                        ErrorKind::WouldBlock | ErrorKind::Interrupted => {
-                            spin_loop();
-                            continue;
-                        }
-                        _ => match self.error_status {
-                            ErrorStatus::Ok | ErrorStatus::Errored => {
-                                self.error_status = ErrorStatus::Errored;
-                                return Some(Err(e.into()));
-                            }
-                            ErrorStatus::Continue => {
-                                return Some(Err(e.into()));
-                            }
-                            ErrorStatus::Ignore => {
-                                continue;
-                            }
-                        },
-                    },
-                    Ok(0) => {
-                        if self.search_buff.is_empty() {
+                match self.fill_buffer() {
+                    Err(e) => return Some(Err(e)),
+                    Ok(true) => self.at_end = false,
+                    Ok(false) => {
+                        if self.buffered().len() <= self.engine.spent() {
+                            self.engine.take_lead();
+                            self.skip(self.buffered().len());
                             return None;
-                        } else {
-                            let mut new_buff: Vec<u8> = Vec::new();
-                            std::mem::swap(&mut self.search_buff, &mut new_buff);
-                            return Some(Ok(new_buff));
                         }
-                    }
-                    Ok(n) => {
-                        self.search_buff.extend_from_slice(&self.read_buff[..n]);
-                        match self.scan_buffer() {
-                            Some(v) => return Some(Ok(v)),
-                            None => {
-                                spin_loop();
-                                continue;
-                            }
+                        if self.at_end {
+                            self.matched_pattern = None;
+                            return Some(Ok(Next::Tail));
                         }
+                        // Give the fence one last look before giving up.
+                        self.at_end = true;
                     }
                 }
-            } else {
-                match self.scan_buffer() {
-                    Some(v) => return Some(Ok(v)),
-                    None => {
-                        spin_loop();
-                        continue;
-                    }
+            }
+
+            if self.strip_bom && !self.strip_leading_bom() {
+                continue;
+            }
+
+            let since = self.clock();
+            let found = self.find_chunk();
+            if let (Some(timer), Some(since)) = (self.timer.as_mut(), since) {
+                timer.add_search(since);
+            }
+
+            match found {
+                Some(found) => return Some(Ok(Next::Chunk(found))),
+                None => spin_loop(),
+            }
+        }
+    }
+
+    /*
+    Remove a UTF-8 BOM from the front of the search buffer, if it's there.
+    Returns `false` if there isn't enough data yet to tell.
+    */
+    fn strip_leading_bom(&mut self) -> bool {
+        let buff = self.buffered();
+        if buff.len() < UTF8_BOM.len() && UTF8_BOM.starts_with(buff) && !self.at_end {
+            return false;
+        }
+        if buff.starts_with(UTF8_BOM) {
+            self.buff_start += UTF8_BOM.len();
+            self.offset += UTF8_BOM.len() as u64;
+            if let Some(chars) = self.char_offset.as_mut() {
+                *chars += 1;
+            }
+        }
+        self.strip_bom = false;
+        true
+    }
+
+    /*
+    The guts of `Iterator::next`, shared with the `MetaChunker`, which
+    wants the whole `Chunk` and not just its body.
+    */
+    pub(crate) fn next_chunk(&mut self) -> Option<Result<Chunk, RcErr>> {
+        let next = match self.advance()? {
+            Ok(next) => next,
+            Err(e) => return Some(Err(e)),
+        };
+
+        let since = self.clock();
+        let chunk = match next {
+            Next::Chunk(found) => self.take_chunk(found),
+            Next::Tail => self.take_tail(),
+        };
+        if let (Some(timer), Some(since)) = (self.timer.as_mut(), since) {
+            timer.add_copy(since);
+            timer.report();
+        }
+        match chunk.eof && self.strict_tail {
+            true => Some(Err(RcErr::Unterminated(chunk.body))),
+            false => Some(Ok(chunk)),
+        }
+    }
+
+    /*
+    Like `next_chunk`, but rather than returning the chunk, passes it to
+    `f` as a slice of the search buffer, which avoids allocating.
+    */
+    pub(crate) fn next_with<T, F>(&mut self, f: F) -> Option<Result<T, RcErr>>
+    where
+        F: FnOnce(&[u8]) -> T,
+    {
+        let peeked = match self.peek_chunk()? {
+            Ok(peeked) => peeked,
+            Err(e) => return Some(Err(e)),
+        };
+
+        let chunk = &self.buffered()[peeked.range];
+        let t = match peeked.replacement {
+            None => f(chunk),
+            Some(replacement) => {
+                let mut chunk = chunk.to_vec();
+                chunk.extend_from_slice(&replacement);
+                f(&chunk)
+            }
+        };
+        self.release(peeked.consumed);
+        Some(Ok(t))
+    }
+
+    /*
+    Find the next chunk, but leave it in the search buffer, to be removed
+    with `release` once the caller is done with it.
+    */
+    pub(crate) fn peek_chunk(&mut self) -> Option<Result<Peeked, RcErr>> {
+        let (first, len, consumed, replacement) = match self.advance()? {
+            Ok(Next::Chunk(f)) => (f.first, f.len, f.consumed, f.replacement),
+            Ok(Next::Tail) => {
+                let lead = self.engine.take_lead();
+                self.skip(lead);
+                let len = self.buffered().len();
+                if self.strict_tail {
+                    let rest = self.buffered().to_vec();
+                    self.release(len);
+                    return Some(Err(RcErr::Unterminated(rest)));
                 }
+                (0, len, len, None)
+            }
+            Err(e) => return Some(Err(e)),
+        };
+
+        let range = self.kept(first, len);
+        Some(Ok(Peeked {
+            range,
+            consumed,
+            replacement,
+        }))
+    }
+
+    /* The bytes of a peeked chunk, not counting any replacement. */
+    pub(crate) fn peeked(&self, peeked: &Peeked) -> &[u8] {
+        &self.buffered()[peeked.range.clone()]
+    }
+
+    /**
+    Consume the `ByteChunker` and return the number of chunks in its
+    source. This doesn't allocate a vector for each chunk, so it's much
+    cheaper than `.count()` for answering "how many records are in this
+    file?"
+
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr};
+    use std::io::Cursor;
+
+    let text = b"one\ntwo\nthree\n";
+    assert_eq!(ByteChunker::new(Cursor::new(text), r"\n")?.count_chunks()?, 3);
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn count_chunks(mut self) -> Result<usize, RcErr> {
+        let mut n = 0;
+        while let Some(res) = self.next_with(|_| ()) {
+            res?;
+            n += 1;
+        }
+        Ok(n)
+    }
+
+    /**
+    Consume the `ByteChunker` and return a [`ChunkIndex`] of the positions
+    of the chunks in its source. No chunk data is kept, so this is cheap
+    in memory even for huge sources; chunks can be read back out of a
+    seekable source afterward with [`ChunkIndex::extract`].
+    */
+    pub fn build_index(mut self) -> Result<ChunkIndex, RcErr> {
+        let mut index = ChunkIndex::default();
+        while let Some(res) = self.peek_chunk() {
+            let peeked = res?;
+            let range = peeked.range.clone();
+            index.push(
+                self.offset + range.start as u64,
+                self.offset + range.end as u64,
+            );
+            self.release(peeked.consumed);
+        }
+        Ok(index)
+    }
+
+    /**
+    Consume the `ByteChunker`, calling `f` on each chunk in turn. The
+    chunks are passed as slices borrowed from the chunker's internal
+    buffer, so no per-chunk copies are made. Stops at (and returns) the
+    first error.
+    */
+    pub fn for_each_chunk<F>(mut self, mut f: F) -> Result<(), RcErr>
+    where
+        F: FnMut(&[u8]),
+    {
+        while let Some(res) = self.next_with(&mut f) {
+            res?;
+        }
+        Ok(())
+    }
+
+    /**
+    Consume the `ByteChunker`, folding every chunk into an accumulator
+    with `f`, like [`Iterator::fold`], but with chunks passed as slices
+    borrowed from the chunker's internal buffer. Stops at (and returns)
+    the first error.
+
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr};
+    use std::io::Cursor;
+
+    let text = b"3,14,15,92,65";
+    let total = ByteChunker::new(Cursor::new(text), ",")?
+        .fold_chunks(0, |acc, chunk| acc + chunk.len())?;
+    assert_eq!(total, 9);
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn fold_chunks<B, F>(mut self, init: B, mut f: F) -> Result<B, RcErr>
+    where
+        F: FnMut(B, &[u8]) -> B,
+    {
+        let mut acc = Some(init);
+        while let Some(res) = self.next_with(|chunk| {
+            // This is always `Some` outside of this closure.
+            let prev = acc.take().unwrap();
+            acc = Some(f(prev, chunk));
+        }) {
+            res?;
+        }
+        Ok(acc.unwrap())
+    }
+
+    /**
+    Consume the `ByteChunker` and collect all its chunks. Unlike
+    `.collect::<Result<Vec<_>, _>>()`, this doesn't stop at the first
+    error, but returns a single [`RcErr::Collect`] error summarizing all
+    of them. (Under the default [`ErrorResponse::Halt`], though, there
+    will be at most one read error.)
+    */
+    pub fn collect_bytes(mut self) -> Result<Vec<Vec<u8>>, RcErr> {
+        let mut tally = ErrorTally::default();
+        let mut v = Vec::new();
+        loop {
+            let offset = self.offset;
+            match self.next_chunk() {
+                None => break,
+                Some(res) => tally.record(res.map(|c| c.body), offset, &mut v),
+            }
+        }
+        tally.finish(v)
+    }
+
+    /**
+    Like [`ByteChunker::collect_bytes`], but converts each chunk to a
+    `String`, returning a summary error if any chunks aren't valid UTF-8.
+    The reported offset of a UTF-8 error is that of the first invalid byte.
+
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr};
+    use std::io::Cursor;
+
+    let text = b"ok\nbad\xff\nfine\n\xfe";
+    let err = ByteChunker::new(Cursor::new(text), r"\n")?
+        .collect_strings()
+        .unwrap_err();
+
+    match err {
+        RcErr::Collect(e) => {
+            assert_eq!((e.chunks, e.errors, e.first_offset), (2, 2, 6));
+        }
+        _ => panic!("expected a collect error"),
+    }
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn collect_strings(mut self) -> Result<Vec<String>, RcErr> {
+        let mut tally = ErrorTally::default();
+        let mut v = Vec::new();
+        loop {
+            let offset = self.offset;
+            match self.next_chunk() {
+                None => break,
+                Some(Ok(c)) => match String::from_utf8(c.body) {
+                    Ok(s) => tally.record(Ok(s), c.span.start, &mut v),
+                    Err(e) => {
+                        let offset = c.span.start + e.utf8_error().valid_up_to() as u64;
+                        tally.record(Err(e.into()), offset, &mut v);
+                    }
+                },
+                Some(Err(e)) => tally.record(Err(e), offset, &mut v),
             }
         }
+        tally.finish(v)
     }
-}
\ No newline at end of file
+}
+
+/* Where the next chunk is in the search buffer; see `find_chunk`. */
+struct Found {
+    /* Where the chunk starts; nonzero only after a rescanned delimiter. */
+    first: usize,
+    len: usize,
+    delimiter: (usize, usize),
+    consumed: usize,
+    forced: bool,
+    /* Bytes to append to the chunk, under `MatchDisposition::Replace`. */
+    replacement: Option<Vec<u8>>,
+}
+
+/* A chunk found by `ByteChunker::peek_chunk`, still in the search buffer. */
+pub(crate) struct Peeked {
+    /* Where the chunk is, relative to the start of the unreturned data. */
+    range: Range<usize>,
+    pub(crate) consumed: usize,
+    pub(crate) replacement: Option<Vec<u8>>,
+}
+
+/* What `ByteChunker::advance` found. */
+enum Next {
+    /// A chunk terminated by a match.
+    Chunk(Found),
+    /// The rest of the search buffer, at the end of the source.
+    Tail,
+}
+
+/**
+The [`ByteChunker`] specifically doesn't supply an implementation of
+[`Iterator::size_hint`] because, in general, it's impossible to tell
+how much data is left in a reader.
+*/
+impl<R: Read> Iterator for ByteChunker<R> {
+    type Item = Result<Vec<u8>, RcErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_chunk().map(|res| res.map(|c| c.body))
+    }
+}
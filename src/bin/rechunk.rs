@@ -0,0 +1,161 @@
+/*!
+Split standard input (or files) on a regular expression and write each
+chunk on its own line, or NUL-terminated.
+
+```text
+rechunk [OPTIONS] PATTERN [FILE ...]
+```
+*/
+use std::{
+    fs::File,
+    io::{stdin, stdout, BufWriter, ErrorKind, Read, Write},
+    process::ExitCode,
+};
+
+use regex_chunker::{ByteChunker, MatchDisposition, RcErr};
+
+const USAGE: &str = "\
+usage: rechunk [OPTIONS] PATTERN [FILE ...]
+
+Split each FILE (or standard input, if there are none or FILE is -) into
+chunks delimited by matches of the regular expression PATTERN, and write
+each chunk followed by a newline (or a NUL, with -z).
+
+options:
+  -m, --match MODE      what to do with the matched text: drop it (the
+                        default), append it to the preceding chunk,
+                        prepend it to the following one, or wrap it
+                        around both
+  -F, --fixed-strings   treat PATTERN as a literal string, not a regex
+  -z, --null            terminate chunks with NUL rather than newline
+  -h, --help            print this message and exit
+";
+
+struct Opts {
+    pattern: String,
+    files: Vec<String>,
+    dispo: MatchDisposition,
+    literal: bool,
+    terminator: u8,
+}
+
+/* Parse the command line, or return a message explaining why not. */
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Opts>, String> {
+    let mut dispo = MatchDisposition::Drop;
+    let mut literal = false;
+    let mut terminator = b'\n';
+    let mut positional = Vec::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-F" | "--fixed-strings" => literal = true,
+            "-z" | "--null" => terminator = b'\0',
+            "-m" | "--match" => {
+                dispo = match args.next().as_deref() {
+                    Some("drop") => MatchDisposition::Drop,
+                    Some("append") => MatchDisposition::Append,
+                    Some("prepend") => MatchDisposition::Prepend,
+                    Some("wrap") => MatchDisposition::Wrap,
+                    Some(mode) => return Err(format!("unknown match mode: {}", mode)),
+                    None => return Err(format!("{} requires a MODE", arg)),
+                }
+            }
+            "--" => {
+                positional.extend(args.by_ref());
+            }
+            opt if opt.starts_with('-') && opt != "-" => {
+                return Err(format!("unknown option: {}", opt));
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let pattern = positional.next().ok_or("no PATTERN given")?;
+    let mut files: Vec<String> = positional.collect();
+    if files.is_empty() {
+        files.push("-".to_string());
+    }
+
+    Ok(Some(Opts {
+        pattern,
+        files,
+        dispo,
+        literal,
+        terminator,
+    }))
+}
+
+/* Why a source couldn't be completely rechunked. */
+enum Failure {
+    Chunk(RcErr),
+    Write(std::io::Error),
+}
+
+/* Write the chunks of `source` to `out`. */
+fn rechunk<R: Read, W: Write>(source: R, opts: &Opts, out: &mut W) -> Result<(), Failure> {
+    let chunker = match opts.literal {
+        true => ByteChunker::new_literal(source, &opts.pattern),
+        false => ByteChunker::new(source, &opts.pattern),
+    };
+    let chunker = chunker.map_err(Failure::Chunk)?;
+    for chunk in chunker.with_match(opts.dispo.clone()) {
+        let chunk = chunk.map_err(Failure::Chunk)?;
+        out.write_all(&chunk).map_err(Failure::Write)?;
+        out.write_all(&[opts.terminator]).map_err(Failure::Write)?;
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let opts = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(opts)) => opts,
+        Ok(None) => {
+            print!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(msg) => {
+            eprintln!("rechunk: {}\n\n{}", msg, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut out = BufWriter::new(stdout().lock());
+    let mut status = ExitCode::SUCCESS;
+    for name in opts.files.iter() {
+        let res = match name.as_str() {
+            "-" => rechunk(stdin().lock(), &opts, &mut out),
+            _ => match File::open(name) {
+                Ok(f) => rechunk(f, &opts, &mut out),
+                Err(e) => Err(Failure::Chunk(e.into())),
+            },
+        };
+        match res {
+            Ok(()) => {}
+            // Whoever was reading our output has stopped.
+            Err(Failure::Write(e)) if e.kind() == ErrorKind::BrokenPipe => return status,
+            Err(Failure::Write(e)) => {
+                eprintln!("rechunk: write error: {}", e);
+                return ExitCode::FAILURE;
+            }
+            // A bad pattern will be just as bad for the next file.
+            Err(Failure::Chunk(e @ (RcErr::Regex(_) | RcErr::Literal(_)))) => {
+                eprintln!("rechunk: {}", e);
+                return ExitCode::from(2);
+            }
+            Err(Failure::Chunk(e)) => {
+                eprintln!("rechunk: {}: {}", name, e);
+                status = ExitCode::FAILURE;
+            }
+        }
+    }
+
+    match out.flush() {
+        Err(e) if e.kind() != ErrorKind::BrokenPipe => {
+            eprintln!("rechunk: {}", e);
+            ExitCode::FAILURE
+        }
+        _ => status,
+    }
+}
@@ -1,5 +1,5 @@
 #![allow(dead_code)]
-/**!
+/*!
 Experimenting with code and generating output for tests, doc tests.
 */
 use std::error::Error;
@@ -0,0 +1,259 @@
+/*!
+Detecting byte-order marks and transcoding to UTF-8.
+*/
+use std::io::{ErrorKind, Read};
+
+// Size of the blocks read from the source when transcoding.
+const TRANSCODE_BLOCK_SIZE: usize = 4096;
+
+/// The UTF-8 encoding of `U+FEFF`, the byte-order mark.
+pub(crate) const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// The encodings a [`BomReader`] can detect from a byte-order mark.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Encoding {
+    /// UTF-8, which is passed through once its BOM is stripped.
+    Utf8,
+    /// Little-endian UTF-16.
+    Utf16Le,
+    /// Big-endian UTF-16.
+    Utf16Be,
+    /// Little-endian UTF-32.
+    Utf32Le,
+    /// Big-endian UTF-32.
+    Utf32Be,
+}
+
+impl Encoding {
+    /* Identify the encoding from the beginning of the source, returning
+    it and the length of the BOM. */
+    fn sniff(start: &[u8]) -> Option<(Encoding, usize)> {
+        match start {
+            [0xef, 0xbb, 0xbf, ..] => Some((Encoding::Utf8, 3)),
+            [0xff, 0xfe, 0, 0, ..] => Some((Encoding::Utf32Le, 4)),
+            [0, 0, 0xfe, 0xff, ..] => Some((Encoding::Utf32Be, 4)),
+            [0xff, 0xfe, ..] => Some((Encoding::Utf16Le, 2)),
+            [0xfe, 0xff, ..] => Some((Encoding::Utf16Be, 2)),
+            _ => None,
+        }
+    }
+
+    /* The size of a code unit, for the encodings that need transcoding. */
+    fn unit_size(&self) -> usize {
+        match self {
+            Encoding::Utf8 => 1,
+            Encoding::Utf16Le | Encoding::Utf16Be => 2,
+            Encoding::Utf32Le | Encoding::Utf32Be => 4,
+        }
+    }
+}
+
+/**
+A [`Read`] wrapper that inspects the first bytes of its source for a
+byte-order mark. The BOM is stripped, and if it indicates UTF-16 or UTF-32,
+the rest of the source is transcoded to UTF-8 as it is read, so that a
+chunker's delimiter can be written as though the source were UTF-8.
+Invalid code units are replaced with `U+FFFD REPLACEMENT CHARACTER`.
+Sources with no BOM are passed through unchanged.
+
+This is most easily used through
+[`ByteChunker::with_bom_sniffing`](crate::ByteChunker::with_bom_sniffing).
+
+```rust
+use regex_chunker::{ByteChunker, RcErr};
+use std::io::Cursor;
+
+// "one\r\ntwo" as exported by many Windows programs.
+let mut text = vec![0xff, 0xfe];
+for c in "one\r\ntwo".encode_utf16() {
+    text.extend_from_slice(&c.to_le_bytes());
+}
+
+let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new(text), r"\r\n")?
+    .with_bom_sniffing()
+    .map(|res| res.unwrap())
+    .collect();
+assert_eq!(&chunks, &[b"one".to_vec(), b"two".to_vec()]);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Debug)]
+pub struct BomReader<R> {
+    source: R,
+    sniffed: bool,
+    encoding: Option<Encoding>,
+    /* The encoding to assume if the source has no BOM. */
+    default_encoding: Option<Encoding>,
+    /* Bytes read from the source but not yet passed on or transcoded. */
+    raw: Vec<u8>,
+    /* Transcoded bytes not yet passed on, starting at `out_pos`. */
+    out: Vec<u8>,
+    out_pos: usize,
+    eof: bool,
+}
+
+impl<R> BomReader<R> {
+    /// Wrap `source` in a new `BomReader`.
+    pub fn new(source: R) -> Self {
+        Self {
+            source,
+            sniffed: false,
+            encoding: None,
+            default_encoding: None,
+            raw: Vec::new(),
+            out: Vec::new(),
+            out_pos: 0,
+            eof: false,
+        }
+    }
+
+    /**
+    Builder-pattern method for setting the encoding to assume if the source
+    turns out not to start with a byte-order mark, for sources (like many
+    log exports) that are known to be UTF-16 or UTF-32 but don't say so.
+    A BOM, if there is one, still takes precedence. By default a source
+    with no BOM is passed through unchanged.
+    */
+    pub fn with_default_encoding(mut self, encoding: Encoding) -> Self {
+        self.set_default_encoding(encoding);
+        self
+    }
+
+    pub(crate) fn set_default_encoding(&mut self, encoding: Encoding) {
+        self.default_encoding = Some(encoding);
+    }
+
+    /// Return the encoding indicated by the source's byte-order mark (or
+    /// the default encoding, if it had none and one was set), or `None`
+    /// if it had none (or hasn't been read from yet).
+    pub fn encoding(&self) -> Option<Encoding> {
+        self.encoding
+    }
+
+    /// Consume the `BomReader` and return its wrapped reader.
+    pub fn into_inner(self) -> R {
+        self.source
+    }
+
+    /* Transcode as much of `raw` as possible into `out`. */
+    fn transcode(&mut self, encoding: Encoding) {
+        let unit = encoding.unit_size();
+        let mut n_units = self.raw.len() / unit;
+        let unit_at = |raw: &[u8], n: usize| -> u32 {
+            let b = &raw[n * unit..(n + 1) * unit];
+            match encoding {
+                Encoding::Utf16Le => u16::from_le_bytes([b[0], b[1]]) as u32,
+                Encoding::Utf16Be => u16::from_be_bytes([b[0], b[1]]) as u32,
+                Encoding::Utf32Le => u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+                Encoding::Utf32Be => u32::from_be_bytes([b[0], b[1], b[2], b[3]]),
+                Encoding::Utf8 => b[0] as u32,
+            }
+        };
+
+        // Hold back a high surrogate until we see what follows it.
+        if unit == 2 && !self.eof && n_units > 0 {
+            let last = unit_at(&self.raw, n_units - 1);
+            if (0xd800..0xdc00).contains(&last) {
+                n_units -= 1;
+            }
+        }
+
+        self.out.clear();
+        self.out_pos = 0;
+        if unit == 2 {
+            let units = (0..n_units).map(|n| unit_at(&self.raw, n) as u16);
+            for res in char::decode_utf16(units) {
+                push_char(&mut self.out, res.unwrap_or(char::REPLACEMENT_CHARACTER));
+            }
+        } else {
+            for n in 0..n_units {
+                let c = char::from_u32(unit_at(&self.raw, n));
+                push_char(&mut self.out, c.unwrap_or(char::REPLACEMENT_CHARACTER));
+            }
+        }
+        self.raw.drain(..n_units * unit);
+
+        // A partial code unit at the end of the source.
+        if self.eof && !self.raw.is_empty() {
+            self.raw.clear();
+            push_char(&mut self.out, char::REPLACEMENT_CHARACTER);
+        }
+    }
+
+    /* Read one block from the source, appending it to `raw`. */
+    fn read_raw(&mut self) -> std::io::Result<()>
+    where
+        R: Read,
+    {
+        let start = self.raw.len();
+        self.raw.resize(start + TRANSCODE_BLOCK_SIZE, 0);
+        match self.source.read(&mut self.raw[start..]) {
+            Ok(n) => {
+                self.raw.truncate(start + n);
+                self.eof = n == 0;
+                Ok(())
+            }
+            Err(e) => {
+                self.raw.truncate(start);
+                Err(e)
+            }
+        }
+    }
+}
+
+fn push_char(out: &mut Vec<u8>, c: char) {
+    let mut utf8 = [0u8; 4];
+    out.extend_from_slice(c.encode_utf8(&mut utf8).as_bytes());
+}
+
+impl<R: Read> Read for BomReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if !self.sniffed {
+            while self.raw.len() < 4 && !self.eof {
+                match self.read_raw() {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            self.sniffed = true;
+            if let Some((encoding, bom_len)) = Encoding::sniff(&self.raw) {
+                self.encoding = Some(encoding);
+                self.raw.drain(..bom_len);
+            } else {
+                self.encoding = self.default_encoding;
+            }
+        }
+
+        loop {
+            if self.out_pos < self.out.len() {
+                let n = buf.len().min(self.out.len() - self.out_pos);
+                buf[..n].copy_from_slice(&self.out[self.out_pos..self.out_pos + n]);
+                self.out_pos += n;
+                return Ok(n);
+            }
+
+            match self.encoding {
+                None | Some(Encoding::Utf8) => {
+                    if self.raw.is_empty() {
+                        return self.source.read(buf);
+                    }
+                    let n = buf.len().min(self.raw.len());
+                    buf[..n].copy_from_slice(&self.raw[..n]);
+                    self.raw.drain(..n);
+                    return Ok(n);
+                }
+                Some(encoding) => {
+                    if self.eof && self.raw.is_empty() {
+                        return Ok(0);
+                    }
+                    if !self.eof {
+                        self.read_raw()?;
+                    }
+                    self.transcode(encoding);
+                }
+            }
+        }
+    }
+}
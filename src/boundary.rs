@@ -0,0 +1,863 @@
+/*!
+The trait used for types that find the delimiters between chunks.
+*/
+use std::fmt::{Debug, Formatter};
+
+use aho_corasick::{AhoCorasick, Input, MatchKind};
+use regex::bytes::Regex;
+
+use crate::{MatchDisposition, MatchPolicy, RcErr};
+
+/**
+Trait for types that can locate the next delimiter in a buffer of bytes.
+
+The chunkers in this crate use a
+[`bytes::Regex`](https://docs.rs/regex/latest/regex/bytes/struct.Regex.html)
+for this by default, but any type implementing `Boundary` can be supplied
+instead (with [`ByteChunker::from_boundary`](crate::ByteChunker::from_boundary)),
+and will get the same buffering, [`MatchDisposition`](crate::MatchDisposition),
+and error-handling behavior as a regex would.
+
+The example below chunks a stream of fixed-length records by reporting a
+zero-width delimiter every `n` bytes.
+
+```rust
+use regex_chunker::{Boundary, ByteChunker, RcErr};
+use std::io::Cursor;
+
+#[derive(Debug)]
+struct FixedLength(usize);
+
+impl Boundary for FixedLength {
+    fn find_at(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        let end = start + self.0;
+        if end <= haystack.len() {
+            Some((end, end))
+        } else {
+            None
+        }
+    }
+}
+
+let c = Cursor::new(b"0001000200030004xx");
+let chunks: Vec<Vec<u8>> = ByteChunker::from_boundary(c, FixedLength(4))
+    .map(|res| res.unwrap())
+    .collect();
+
+assert_eq!(
+    &chunks,
+    &[b"0001".to_vec(), b"0002".to_vec(), b"0003".to_vec(),
+    b"0004".to_vec(), b"xx".to_vec()]
+);
+# Ok::<(), RcErr>(())
+```
+
+The buffer passed to `find_at` contains all the data that has been read
+but not yet returned, so implementors should report offsets relative to
+the start of `haystack`, and should not report a match that begins
+before `start`.
+*/
+pub trait Boundary: Debug {
+    /// Search `haystack`, beginning at offset `start`, for the next
+    /// delimiter, and return its `(start, end)` offsets, or `None` if
+    /// more data is needed to find one.
+    fn find_at(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)>;
+
+    /// Like [`Boundary::find_at`], but called once the source has been
+    /// exhausted, so that `haystack` holds all the remaining data. A type
+    /// that holds back matches that more data could change should report
+    /// them here. The default implementation just calls `find_at`.
+    fn find_at_end(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        self.find_at(haystack, start)
+    }
+
+    /// Return how far short of the end of a haystack in which
+    /// [`Boundary::find_at`] found nothing a later search has to start to
+    /// be sure of finding any delimiter that more data could complete; that
+    /// is, no match found once more data has been appended can begin
+    /// further back than this. The async chunker's incremental search
+    /// uses this to avoid searching the same bytes over and over. The
+    /// default implementation returns `None`, meaning there's no such
+    /// bound, and every search has to start over.
+    fn lookback(&self) -> Option<usize> {
+        None
+    }
+
+    /// For types that search for one of several patterns, return the
+    /// index of the pattern that produced the most recent match found
+    /// by [`Boundary::find_at`]. The default implementation returns `None`.
+    fn matched_pattern(&self) -> Option<usize> {
+        None
+    }
+
+    /// Return the [`MatchDisposition`] that should be applied to the most
+    /// recent match found by [`Boundary::find_at`], overriding the one
+    /// the chunker was configured with. The default implementation
+    /// returns `None`, deferring to the chunker.
+    fn disposition(&self) -> Option<MatchDisposition> {
+        None
+    }
+}
+
+impl Boundary for Regex {
+    fn find_at(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        Regex::find_at(self, haystack, start).map(|m| (m.start(), m.end()))
+    }
+
+    fn lookback(&self) -> Option<usize> {
+        max_match_len(self.as_str(), false)
+    }
+}
+
+/*
+The most bytes a match for `pattern` can span, or `None` if there's no
+limit. Case-insensitive matching is assumed, since a regex built with it
+can match longer strings than its pattern alone suggests.
+*/
+fn max_match_len(pattern: &str, utf8: bool) -> Option<usize> {
+    let hir = regex_syntax::ParserBuilder::new()
+        .utf8(utf8)
+        .case_insensitive(true)
+        .build()
+        .parse(pattern)
+        .ok()?;
+    hir.properties().maximum_len()
+}
+
+/**
+A Unicode [`regex::Regex`](https://docs.rs/regex/latest/regex/struct.Regex.html)
+can also be used as a fence. In this case the buffered bytes are decoded
+as UTF-8 and the regex is applied to the resulting _text_, so patterns
+with Unicode classes like `\p{Sentence_Terminal}` match whole characters,
+even when multi-byte characters straddle reads from the source.
+
+A match that reaches the end of the data read so far is held back until
+more has been read (or the source is exhausted), since a word boundary
+there, or a repetition that the next read would continue, could match
+differently on the whole text.
+
+Any invalid UTF-8 sequences in the buffer are skipped over (the regex is
+applied to the valid text on either side of them), and an incomplete
+sequence at the end of the buffer is left until more data arrives.
+
+```rust
+use regex_chunker::{ByteChunker, RcErr, StringAdapter};
+use std::io::Cursor;
+
+let text = "Un café? Deux cafés! Trois.".as_bytes();
+let chunks: Vec<String> = ByteChunker::new_text(Cursor::new(text), r"[.!?]\s*")?
+    .with_adapter(StringAdapter::default())
+    .map(|res| res.unwrap())
+    .collect();
+
+assert_eq!(&chunks, &["Un café", "Deux cafés", "Trois"]);
+# Ok::<(), RcErr>(())
+```
+*/
+impl Boundary for regex::Regex {
+    fn find_at(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        find_text(self, haystack, start, true)
+    }
+
+    fn find_at_end(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        find_text(self, haystack, start, false)
+    }
+
+    fn lookback(&self) -> Option<usize> {
+        // An incomplete character at the end of the haystack, up to three
+        // bytes of it, isn't searched until the rest arrives.
+        max_match_len(self.as_str(), true).map(|n| n + 3)
+    }
+}
+
+/*
+Search the valid UTF-8 in `haystack` for a match of `re`. If `hold_back`
+is set, a match that reaches the end of the text is ignored, as more data
+might extend it or rule it out.
+*/
+fn find_text(
+    re: &regex::Regex,
+    haystack: &[u8],
+    start: usize,
+    hold_back: bool,
+) -> Option<(usize, usize)> {
+    // Offset of the current run of valid UTF-8 within the haystack.
+    let mut offset = 0;
+    loop {
+        let rest = &haystack[offset..];
+        let (text, next_offset) = match std::str::from_utf8(rest) {
+            Ok(text) => (text, None),
+            Err(e) => {
+                let valid = e.valid_up_to();
+                // This can't fail; we've just been told it's valid.
+                let text = std::str::from_utf8(&rest[..valid]).unwrap();
+                // If the error has no length, we've hit an incomplete
+                // sequence at the end of the buffer, and there's no
+                // more text to search.
+                (text, e.error_len().map(|n| offset + valid + n))
+            }
+        };
+
+        if start <= offset + text.len() {
+            let text_start = start.saturating_sub(offset);
+            if let Some(m) = re.find_at(text, text_start) {
+                if hold_back && next_offset.is_none() && m.end() == text.len() {
+                    return None;
+                }
+                return Some((offset + m.start(), offset + m.end()));
+            }
+        }
+
+        offset = next_offset?;
+    }
+}
+
+/**
+A [`Boundary`] that matches a Unicode regex against the decoded text of
+the source, compiled from a pattern string. It behaves exactly like a
+[`regex::Regex`](https://docs.rs/regex/latest/regex/struct.Regex.html)
+fence, waiting for more data before accepting a match that reaches the
+end of the data read so far, so `r"\p{Sentence_Terminal}+"` takes in a
+whole run of terminators split between two reads, and `r"\bEND\b"`
+doesn't match the start of `"ENDING"` when only `"END"` has been read.
+
+```rust
+use regex_chunker::{ByteChunker, RcErr, StringAdapter, TextBoundary};
+use std::io::Cursor;
+
+let text = "Wait... what?! Oh.".as_bytes();
+let chunks: Vec<String> = ByteChunker::from_boundary(
+        Cursor::new(text),
+        TextBoundary::new(r"[.!?]+\s*")?,
+    )
+    .with_buffer_size(5)
+    .with_adapter(StringAdapter::default())
+    .collect::<Result<_, RcErr>>()?;
+
+assert_eq!(chunks, ["Wait", "what", "Oh"]);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Clone, Debug)]
+pub struct TextBoundary {
+    re: regex::Regex,
+}
+
+impl TextBoundary {
+    /// Return a new [`TextBoundary`] that matches the supplied Unicode
+    /// regex pattern.
+    pub fn new(pattern: &str) -> Result<Self, RcErr> {
+        let re = regex::Regex::new(pattern)?;
+        Ok(Self { re })
+    }
+}
+
+impl Boundary for TextBoundary {
+    fn find_at(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        find_text(&self.re, haystack, start, true)
+    }
+
+    fn find_at_end(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        find_text(&self.re, haystack, start, false)
+    }
+
+    fn lookback(&self) -> Option<usize> {
+        self.re.lookback()
+    }
+}
+
+/**
+A [`Boundary`] that searches for any of a set of literal byte strings,
+using the [`aho-corasick`](https://docs.rs/aho-corasick/latest/aho_corasick/)
+crate directly. When splitting on a large number of fixed delimiters, this
+is much faster than compiling them into a regex alternation.
+
+If more than one literal matches at the same position, the one listed
+first wins, unless the searcher is built with
+[`new_with_policy`](LiteralBoundary::new_with_policy) and
+[`MatchPolicy::Longest`]. The index of the literal that matched is available through
+[`Boundary::matched_pattern`], and is reported by the chunker's
+`matched_pattern()` method.
+
+```rust
+use regex_chunker::{ByteChunker, RcErr};
+use std::io::Cursor;
+
+let text = b"alpha<>beta||gamma<>delta";
+let mut chunker = ByteChunker::from_literals(Cursor::new(text), ["<>", "||"])?;
+
+assert_eq!(chunker.next().unwrap()?, b"alpha");
+assert_eq!(chunker.matched_pattern(), Some(0));
+assert_eq!(chunker.next().unwrap()?, b"beta");
+assert_eq!(chunker.matched_pattern(), Some(1));
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Debug)]
+pub struct LiteralBoundary {
+    searcher: AhoCorasick,
+    matched: Option<usize>,
+}
+
+impl LiteralBoundary {
+    /// Build a new `LiteralBoundary` that matches any of the supplied
+    /// literals.
+    pub fn new<I, P>(literals: I) -> Result<Self, RcErr>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<[u8]>,
+    {
+        Self::new_with_policy(literals, MatchPolicy::FirstListed)
+    }
+
+    /// Build a new `LiteralBoundary` that matches any of the supplied
+    /// literals, choosing between literals that match at the same
+    /// position according to `policy`.
+    pub fn new_with_policy<I, P>(literals: I, policy: MatchPolicy) -> Result<Self, RcErr>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<[u8]>,
+    {
+        let kind = match policy {
+            MatchPolicy::FirstListed => MatchKind::LeftmostFirst,
+            MatchPolicy::Longest => MatchKind::LeftmostLongest,
+        };
+        let searcher = AhoCorasick::builder().match_kind(kind).build(literals)?;
+        Ok(Self {
+            searcher,
+            matched: None,
+        })
+    }
+}
+
+impl Boundary for LiteralBoundary {
+    fn find_at(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        let m = self.searcher.find(Input::new(haystack).range(start..))?;
+        self.matched = Some(m.pattern().as_usize());
+        Some((m.start(), m.end()))
+    }
+
+    fn lookback(&self) -> Option<usize> {
+        Some(self.searcher.max_pattern_len())
+    }
+
+    fn matched_pattern(&self) -> Option<usize> {
+        self.matched
+    }
+}
+
+/**
+A [`Boundary`] that matches runs of one or more bytes from a given set,
+like `strtok()` or
+[`str::split_whitespace`](https://doc.rust-lang.org/std/primitive.str.html#method.split_whitespace)
+on an arbitrary class of bytes. Membership is checked with a 256-entry
+lookup table, which avoids the overhead of a regex for the very common
+"split on any of these characters" case.
+
+```rust
+use regex_chunker::{ByteChunker, RcErr};
+use std::io::Cursor;
+
+let text = b"One, two, three, four. Can I have a little more?";
+let chunks: Vec<Vec<u8>> = ByteChunker::from_byte_set(Cursor::new(text), b" .,?")
+    .map(|res| res.unwrap())
+    .collect();
+
+assert_eq!(chunks[3], b"four");
+assert_eq!(chunks.len(), 10);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Clone)]
+pub struct ByteSetBoundary {
+    table: [bool; 256],
+}
+
+impl ByteSetBoundary {
+    /// Build a new `ByteSetBoundary` that matches runs of any of the
+    /// supplied bytes.
+    pub fn new(bytes: &[u8]) -> Self {
+        let mut table = [false; 256];
+        for &b in bytes.iter() {
+            table[b as usize] = true;
+        }
+        Self { table }
+    }
+
+    #[inline(always)]
+    fn contains(&self, b: u8) -> bool {
+        self.table[b as usize]
+    }
+}
+
+impl Debug for ByteSetBoundary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let bytes: Vec<u8> = (0..=255u8).filter(|&b| self.contains(b)).collect();
+        f.debug_struct("ByteSetBoundary")
+            .field("bytes", &bytes.escape_ascii().to_string())
+            .finish()
+    }
+}
+
+impl Boundary for ByteSetBoundary {
+    fn find_at(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        let match_start = start + haystack[start..].iter().position(|&b| self.contains(b))?;
+        let match_end = match haystack[match_start..]
+            .iter()
+            .position(|&b| !self.contains(b))
+        {
+            Some(n) => match_start + n,
+            None => haystack.len(),
+        };
+        Some((match_start, match_end))
+    }
+}
+
+/**
+A [`Boundary`] that matches line endings: either `\n` alone, or (as
+with [`LineBoundary::crlf`]) `\r?\n`. The search is done with
+[`memchr`](https://docs.rs/memchr/latest/memchr/), which makes this
+considerably faster than the equivalent regex for the very common case of
+chunking a source into lines.
+
+[`ByteChunker::lines`](crate::ByteChunker::lines) uses the `\r?\n`
+flavor, splitting lines the same way
+[`BufRead::lines`](std::io::BufRead::lines) does.
+
+```rust
+use regex_chunker::{ByteChunker, MatchDisposition, RcErr};
+use std::io::Cursor;
+
+let text = b"one\r\ntwo\nthree";
+let chunks: Vec<Vec<u8>> = ByteChunker::lines(Cursor::new(text))
+    .with_match(MatchDisposition::Append)
+    .map(|res| res.unwrap())
+    .collect();
+
+assert_eq!(
+    &chunks,
+    &[b"one\r\n".to_vec(), b"two\n".to_vec(), b"three".to_vec()]
+);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Clone, Copy, Debug)]
+pub struct LineBoundary {
+    terminator: u8,
+    crlf: bool,
+}
+
+impl LineBoundary {
+    /// Build a new `LineBoundary` that matches `\n`.
+    pub fn new() -> Self {
+        Self {
+            terminator: b'\n',
+            crlf: false,
+        }
+    }
+
+    /// Build a new `LineBoundary` that matches `\r?\n`.
+    pub fn crlf() -> Self {
+        Self {
+            terminator: b'\n',
+            crlf: true,
+        }
+    }
+
+    /// Build a new `LineBoundary` that matches `\0`, for "lines" that are
+    /// NUL-terminated, like the output of `find -print0`.
+    pub fn null() -> Self {
+        Self {
+            terminator: b'\0',
+            crlf: false,
+        }
+    }
+}
+
+impl Default for LineBoundary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Boundary for LineBoundary {
+    fn find_at(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        let newline = start + memchr::memchr(self.terminator, &haystack[start..])?;
+        if self.crlf && newline > start && haystack[newline - 1] == b'\r' {
+            Some((newline - 1, newline + 1))
+        } else {
+            Some((newline, newline + 1))
+        }
+    }
+}
+
+/**
+A [`Boundary`] that searches for matches of any of several regular
+expressions, each with its own [`MatchDisposition`]. This makes it
+possible to, say, drop newlines but keep sentence-ending punctuation
+with the sentences it ends.
+
+The leftmost match of any pattern is used; if several patterns match
+at the same position, the one listed first wins, unless a different
+[`MatchPolicy`] is set with [`with_policy`](MultiRegex::with_policy).
+Because each pattern
+carries its own disposition, the chunker's own
+[`with_match`](crate::ByteChunker::with_match) setting has no effect.
+
+```rust
+use regex_chunker::{ByteChunker, MatchDisposition, RcErr};
+use std::io::Cursor;
+
+let text = b"Line one\nStop. Go!\nNow";
+let chunks: Vec<Vec<u8>> = ByteChunker::from_patterns(
+    Cursor::new(text),
+    [
+        (r"[.!]\s*", MatchDisposition::Append),
+        (r"\n", MatchDisposition::Drop),
+    ],
+)?
+.map(|res| res.unwrap())
+.collect();
+
+assert_eq!(
+    &chunks,
+    &[b"Line one".to_vec(), b"Stop. ".to_vec(), b"Go!\n".to_vec(), b"Now".to_vec()]
+);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Debug)]
+pub struct MultiRegex {
+    patterns: Vec<(Regex, MatchDisposition)>,
+    policy: MatchPolicy,
+    matched: Option<usize>,
+}
+
+impl MultiRegex {
+    /// Build a new `MultiRegex` from `(pattern, disposition)` pairs.
+    pub fn new<I, P>(patterns: I) -> Result<Self, RcErr>
+    where
+        I: IntoIterator<Item = (P, MatchDisposition)>,
+        P: AsRef<str>,
+    {
+        let patterns = patterns
+            .into_iter()
+            .map(|(p, dispo)| Ok((Regex::new(p.as_ref())?, dispo)))
+            .collect::<Result<Vec<_>, RcErr>>()?;
+        Ok(Self {
+            patterns,
+            policy: MatchPolicy::default(),
+            matched: None,
+        })
+    }
+
+    /// Builder-pattern method for choosing between patterns that match at
+    /// the same position. Default is [`MatchPolicy::FirstListed`].
+    pub fn with_policy(mut self, policy: MatchPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+}
+
+impl Boundary for MultiRegex {
+    fn find_at(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        let mut best: Option<(usize, (usize, usize))> = None;
+        for (n, (re, _)) in self.patterns.iter().enumerate() {
+            if let Some(m) = re.find_at(haystack, start) {
+                let is_better = match best {
+                    Some((_, (best_start, best_end))) => {
+                        m.start() < best_start
+                            || (m.start() == best_start
+                                && self.policy == MatchPolicy::Longest
+                                && m.end() > best_end)
+                    }
+                    None => true,
+                };
+                if is_better {
+                    best = Some((n, (m.start(), m.end())));
+                }
+            }
+        }
+
+        let (n, span) = best?;
+        self.matched = Some(n);
+        Some(span)
+    }
+
+    fn lookback(&self) -> Option<usize> {
+        self.patterns
+            .iter()
+            .try_fold(0, |most, (re, _)| Some(most.max(re.lookback()?)))
+    }
+
+    fn matched_pattern(&self) -> Option<usize> {
+        self.matched
+    }
+
+    fn disposition(&self) -> Option<MatchDisposition> {
+        self.matched.map(|n| self.patterns[n].1.clone())
+    }
+}
+
+/**
+A [`Boundary`] that splits a stream of JSON documents (like
+newline-delimited JSON) into one complete document per chunk. Rather than
+looking for newlines, it tracks the nesting of braces and brackets outside
+of strings, so pretty-printed documents that span several lines come out
+whole. The delimiter is the whitespace between documents; documents with
+nothing between them (like `{"a":1}{"b":2}`) are split by a zero-width
+delimiter.
+
+The documents aren't validated, so malformed input is split as well as
+can be managed. Whitespace at the very start of the source stays with the
+first document. Each search starts over from the beginning of the current
+document, so very large documents are best read with a large buffer.
+
+```rust
+use regex_chunker::{ByteChunker, RcErr};
+use std::io::Cursor;
+
+let text = br#"{"id": 1, "tags": ["a", "b"]}
+{
+  "id": 2,
+  "note": "a \"}\" in a string"
+}
+[3]{"id": 4}"#;
+let docs: Vec<Vec<u8>> = ByteChunker::json_documents(Cursor::new(&text[..]))
+    .collect::<Result<_, RcErr>>()?;
+
+assert_eq!(docs.len(), 4);
+assert!(docs[1].starts_with(b"{\n") && docs[1].ends_with(b"string\"\n}"));
+assert_eq!(&docs[2..], [&b"[3]"[..], br#"{"id": 4}"#]);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonBoundary;
+
+impl JsonBoundary {
+    /// Return a new [`JsonBoundary`].
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Boundary for JsonBoundary {
+    fn find_at(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        find_json_gap(haystack, start, false)
+    }
+
+    fn find_at_end(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        find_json_gap(haystack, start, true)
+    }
+}
+
+fn is_json_space(b: &u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\r' | b'\n')
+}
+
+/*
+Find the gap after the JSON document that starts at `start`. Unless
+`at_end` is set, more data might follow `haystack`.
+*/
+fn find_json_gap(haystack: &[u8], start: usize, at_end: bool) -> Option<(usize, usize)> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut started = false;
+
+    for (n, b) in haystack.iter().enumerate().skip(start) {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if *b == b'\\' {
+                escaped = true;
+            } else if *b == b'"' {
+                in_string = false;
+                if depth == 0 {
+                    return json_gap_at(haystack, n + 1, at_end);
+                }
+            }
+            continue;
+        }
+
+        match b {
+            b'"' => {
+                in_string = true;
+                started = true;
+            }
+            b'{' | b'[' => {
+                depth += 1;
+                started = true;
+            }
+            b'}' | b']' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    return json_gap_at(haystack, n + 1, at_end);
+                }
+            }
+            // A run of whitespace after a bare number or literal.
+            b if is_json_space(b) && depth == 0 && started => {
+                return json_gap_at(haystack, n, at_end);
+            }
+            b if is_json_space(b) => {}
+            _ => started = true,
+        }
+    }
+    None
+}
+
+/* The gap between the document that ends at `end` and the next one. */
+fn json_gap_at(haystack: &[u8], end: usize, at_end: bool) -> Option<(usize, usize)> {
+    let space = haystack[end..]
+        .iter()
+        .take_while(|b| is_json_space(b))
+        .count();
+    if end + space < haystack.len() || (at_end && space > 0) {
+        Some((end, end + space))
+    } else {
+        None
+    }
+}
+
+/**
+A [`Boundary`] for records that each begin with a line matching a given
+pattern, and continue over any number of following lines that don't, like
+log entries that begin with a timestamp followed by the lines of a stack
+trace. The delimiter is the line ending (`\n` or `\r\n`) before each line
+that starts a new record, or the one that ends the source. Any lines
+before the first record start end up in the first chunk.
+
+A line is only checked against the pattern once the whole line has been
+read, so the pattern can safely look further into the line than whatever
+happens to have been read so far. This is most easily used through
+[`ByteChunker::log_records`](crate::ByteChunker::log_records).
+
+```rust
+use regex_chunker::{ByteChunker, RcErr};
+use std::io::Cursor;
+
+let text = b"\
+2024-05-01 12:00:00 INFO starting
+2024-05-01 12:00:01 ERROR request failed
+Traceback (most recent call last):
+  File \"app.py\", line 3, in <module>
+ZeroDivisionError: division by zero
+2024-05-01 12:00:02 INFO recovered
+";
+let records: Vec<Vec<u8>> = ByteChunker::log_records(Cursor::new(text), r"\d{4}-\d\d-\d\d ")?
+    .collect::<Result<_, RcErr>>()?;
+
+assert_eq!(records.len(), 3);
+assert!(records[1].starts_with(b"2024-05-01 12:00:01 ERROR"));
+assert!(records[1].ends_with(b"division by zero"));
+assert_eq!(&records[2], b"2024-05-01 12:00:02 INFO recovered");
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Clone, Debug)]
+pub struct RecordStartBoundary {
+    start: Regex,
+}
+
+impl RecordStartBoundary {
+    /// Build a new `RecordStartBoundary` for records whose first lines
+    /// begin with a match for `pattern`.
+    pub fn new(pattern: &str) -> Result<Self, RcErr> {
+        let start = Regex::new(&format!("^(?:{})", pattern))?;
+        Ok(Self { start })
+    }
+
+    /// Build a new `RecordStartBoundary` for the records of a FASTA file,
+    /// each of which starts with a header line beginning with `>`.
+    pub fn fasta() -> Self {
+        // This is a valid regex.
+        let start = Regex::new("^>").unwrap();
+        Self { start }
+    }
+
+    /// Build a new `RecordStartBoundary` for the messages of an mbox file,
+    /// each of which starts with a line beginning with `From `.
+    pub fn mbox() -> Self {
+        // This is a valid regex.
+        let start = Regex::new("^From ").unwrap();
+        Self { start }
+    }
+
+    /* Find the line ending before the next record start, considering the
+    last line of `haystack` complete only if `at_end` is set. */
+    fn find_record(&self, haystack: &[u8], start: usize, at_end: bool) -> Option<(usize, usize)> {
+        let mut newlines = memchr::memchr_iter(b'\n', &haystack[start..]).map(|n| start + n);
+        let mut newline = newlines.next()?;
+        loop {
+            let next = newlines.next();
+            let line = match next {
+                Some(end) => &haystack[newline + 1..end],
+                None if at_end => &haystack[newline + 1..],
+                None => return None,
+            };
+            // The last line ending in the source also ends a record.
+            if self.start.is_match(line) || (next.is_none() && line.is_empty()) {
+                return match newline > start && haystack[newline - 1] == b'\r' {
+                    true => Some((newline - 1, newline + 1)),
+                    false => Some((newline, newline + 1)),
+                };
+            }
+            newline = next?;
+        }
+    }
+}
+
+impl Boundary for RecordStartBoundary {
+    fn find_at(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        self.find_record(haystack, start, false)
+    }
+
+    fn find_at_end(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        self.find_record(haystack, start, true)
+    }
+}
+
+/**
+A [`Boundary`] for FASTQ files, which splits them into four-line records:
+a header line starting with `@`, the sequence, a separator line starting
+with `+`, and the quality scores. Since a quality line can also begin with
+`@`, records are found by counting lines rather than by looking for
+headers; the delimiter is the line ending after every fourth line. FASTQ
+files with sequences wrapped over several lines aren't supported.
+
+This is most easily used through
+[`ByteChunker::fastq_records`](crate::ByteChunker::fastq_records).
+
+```rust
+use regex_chunker::{ByteChunker, RcErr};
+use std::io::Cursor;
+
+let text = b"@r1\nGATT\n+\n@@AB\n@r2\nACA\n+r2\n!!#\n";
+let records: Vec<Vec<u8>> = ByteChunker::fastq_records(Cursor::new(text))
+    .collect::<Result<_, RcErr>>()?;
+
+assert_eq!(records, [&b"@r1\nGATT\n+\n@@AB"[..], b"@r2\nACA\n+r2\n!!#"]);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FastqBoundary;
+
+impl FastqBoundary {
+    /// Return a new [`FastqBoundary`].
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Boundary for FastqBoundary {
+    fn find_at(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        let newline = start + memchr::memchr_iter(b'\n', &haystack[start..]).nth(3)?;
+        if haystack[newline - 1] == b'\r' {
+            Some((newline - 1, newline + 1))
+        } else {
+            Some((newline, newline + 1))
+        }
+    }
+}
@@ -0,0 +1,152 @@
+/*!
+Controlling the growth and shrinkage of chunkers' internal buffers.
+*/
+use std::fmt::Debug;
+
+/**
+Trait for types that decide how a chunker's internal buffer grows and
+when it gives capacity back.
+
+A chunker's buffer has to grow to hold the largest chunk it encounters,
+and by default it never shrinks again, so a single multi-megabyte chunk
+leaves a multi-megabyte buffer for the rest of the stream. Supply a
+`BufferPolicy` with the chunker's `with_buffer_policy()` method to change
+that. [`ShrinkToBaseline`] covers the usual case, and [`AdaptivePrealloc`]
+sizes the buffer's growth to the chunks it has seen.
+
+```rust
+use regex_chunker::{BufferPolicy, ByteChunker, RcErr};
+use std::io::Cursor;
+
+/// Grow in big steps, and never shrink.
+#[derive(Debug)]
+struct BigSteps;
+
+impl BufferPolicy for BigSteps {
+    fn grow(&mut self, _len: usize, capacity: usize) -> Option<usize> {
+        Some(capacity.max(64 * 1024))
+    }
+}
+
+let chunker = ByteChunker::new(Cursor::new(b"a,b,c"), ",")?
+    .with_buffer_policy(BigSteps);
+assert_eq!(chunker.count(), 3);
+# Ok::<(), RcErr>(())
+```
+*/
+pub trait BufferPolicy: Debug + Send {
+    /// Called when the buffer, holding `len` bytes, has no room for more
+    /// data. Return the amount of additional capacity to reserve, or
+    /// `None` to let the buffer grow the way it normally would. The
+    /// default implementation returns `None`.
+    fn grow(&mut self, len: usize, capacity: usize) -> Option<usize> {
+        let _ = (len, capacity);
+        None
+    }
+
+    /// Called after a chunk has been removed from the buffer, which still
+    /// holds `len` bytes. Return `Some(n)` to shrink the buffer's capacity
+    /// to `n` (or to `len`, if that's larger), or `None` to leave it alone.
+    /// The default implementation returns `None`.
+    fn shrink(&mut self, len: usize, capacity: usize) -> Option<usize> {
+        let _ = (len, capacity);
+        None
+    }
+
+    /// Called with the number of bytes removed from the buffer each time a
+    /// chunk is taken from it (including any dropped delimiter), before
+    /// [`BufferPolicy::shrink`]. The default implementation does nothing.
+    fn observe(&mut self, consumed: usize) {
+        let _ = consumed;
+    }
+}
+
+/**
+A [`BufferPolicy`] that releases capacity once the buffer has grown to
+more than twice a baseline size, shrinking it back to the baseline as
+soon as the data it holds would fit.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShrinkToBaseline {
+    baseline: usize,
+}
+
+impl ShrinkToBaseline {
+    /// Return a new `ShrinkToBaseline` policy with the given baseline
+    /// capacity, in bytes.
+    pub fn new(baseline: usize) -> Self {
+        Self { baseline }
+    }
+}
+
+impl BufferPolicy for ShrinkToBaseline {
+    fn shrink(&mut self, len: usize, capacity: usize) -> Option<usize> {
+        if capacity / 2 > self.baseline && len <= self.baseline {
+            Some(self.baseline)
+        } else {
+            None
+        }
+    }
+}
+
+/**
+A [`BufferPolicy`] that keeps a running estimate of the typical chunk
+size, and when the buffer fills, grows it in one step to hold a whole
+typical chunk (plus some headroom), rather than letting it double its
+way up repeatedly. For workloads whose records are all around the same
+size, this cuts down on reallocation.
+
+```rust
+use regex_chunker::{AdaptivePrealloc, ByteChunker, RcErr};
+use std::io::Cursor;
+
+let text = vec![b'x'; 100_000]
+    .into_iter()
+    .chain(*b"\n")
+    .cycle()
+    .take(1_000_000)
+    .collect::<Vec<u8>>();
+
+let n = ByteChunker::new(Cursor::new(text), r"\n")?
+    .with_buffer_policy(AdaptivePrealloc::new())
+    .count();
+assert_eq!(n, 10);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdaptivePrealloc {
+    estimate: Option<usize>,
+}
+
+impl AdaptivePrealloc {
+    /// Return a new `AdaptivePrealloc` policy, with no estimate yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The current estimate of the typical chunk size, if any chunks have
+    /// been observed.
+    pub fn estimate(&self) -> Option<usize> {
+        self.estimate
+    }
+}
+
+impl BufferPolicy for AdaptivePrealloc {
+    fn grow(&mut self, len: usize, _capacity: usize) -> Option<usize> {
+        // Room for the rest of a typical chunk, and a quarter again.
+        let target = self.estimate? + self.estimate? / 4;
+        target.checked_sub(len).filter(|&n| n > 0)
+    }
+
+    fn observe(&mut self, consumed: usize) {
+        // An exponentially weighted moving average, weighting the newest
+        // chunk by 1/8.
+        self.estimate = Some(match self.estimate {
+            Some(estimate) => estimate - estimate / 8 + consumed / 8,
+            None => consumed,
+        });
+    }
+}
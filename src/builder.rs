@@ -0,0 +1,241 @@
+/*!
+A builder that checks a chunker's settings against each other.
+*/
+use std::marker::PhantomData;
+
+use crate::{
+    ctrl::MatchFn, ByteChunker, ConfigError, CustomChunker, ErrorResponse, MatchDisposition, RcErr,
+    StringAdapter, Utf8FailureMode,
+};
+
+/// Marker for a [`ChunkerBuilder`] that builds a chunker of raw bytes.
+#[derive(Clone, Copy, Debug)]
+pub struct ByteOutput;
+
+/// Marker for a [`ChunkerBuilder`] that builds a chunker of `String`s.
+#[derive(Clone, Copy, Debug)]
+pub struct TextOutput;
+
+/**
+An alternative to configuring a [`ByteChunker`] with its builder-pattern
+methods, for when the settings come from somewhere else (a config file, a
+command line) and nonsensical combinations should be caught rather than
+quietly resolved one way or the other.
+
+Settings that only make sense for text, like what to do with invalid
+UTF-8, are only available after [`text`](ChunkerBuilder::text), so asking
+for lossy decoding of a byte pipeline doesn't compile. Settings that
+conflict with each other are reported by [`build`](ChunkerBuilder::build)
+as an [`RcErr::Config`] describing the conflict; see [`ConfigError`].
+
+```rust
+use regex_chunker::{ChunkerBuilder, ConfigError, MatchDisposition, RcErr, Utf8FailureMode};
+use std::io::Cursor;
+
+let lines: Vec<String> = ChunkerBuilder::new(r"\r?\n")
+    .with_match(MatchDisposition::Replace(b"\n".to_vec()))
+    .text()
+    .on_invalid_utf8(Utf8FailureMode::Lossy)
+    .build(Cursor::new("one\r\ntwo\n"))?
+    .collect::<Result<_, _>>()?;
+assert_eq!(lines, ["one\n", "two\n"]);
+
+let res = ChunkerBuilder::new(",")
+    .with_match(MatchDisposition::Replace(b"<comma>".to_vec()))
+    .with_max_chunk_len(4)
+    .build(Cursor::new("a,b"));
+assert!(matches!(
+    res,
+    Err(RcErr::Config(ConfigError::ReplacementTooLong { replacement: 7, max_chunk_len: 4 }))
+));
+# Ok::<(), RcErr>(())
+```
+
+This doesn't compile:
+
+```rust,compile_fail
+use regex_chunker::{ChunkerBuilder, Utf8FailureMode};
+
+let builder = ChunkerBuilder::new(",").on_invalid_utf8(Utf8FailureMode::Lossy);
+```
+*/
+pub struct ChunkerBuilder<O = ByteOutput> {
+    pattern: String,
+    match_dispo: Option<MatchDisposition>,
+    match_fn: Option<MatchFn>,
+    max_chunk_len: Option<usize>,
+    trim: Option<String>,
+    buffer_size: Option<usize>,
+    error_response: Option<ErrorResponse>,
+    require_terminator: bool,
+    utf8_failure: Utf8FailureMode,
+    output: PhantomData<O>,
+}
+
+impl ChunkerBuilder<ByteOutput> {
+    /// Start building a chunker that splits its source at matches for the
+    /// regex `pattern`.
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            match_dispo: None,
+            match_fn: None,
+            max_chunk_len: None,
+            trim: None,
+            buffer_size: None,
+            error_response: None,
+            require_terminator: false,
+            utf8_failure: Utf8FailureMode::default(),
+            output: PhantomData,
+        }
+    }
+
+    /// Build a chunker that returns `String`s rather than byte vectors.
+    pub fn text(self) -> ChunkerBuilder<TextOutput> {
+        ChunkerBuilder {
+            pattern: self.pattern,
+            match_dispo: self.match_dispo,
+            match_fn: self.match_fn,
+            max_chunk_len: self.max_chunk_len,
+            trim: self.trim,
+            buffer_size: self.buffer_size,
+            error_response: self.error_response,
+            require_terminator: self.require_terminator,
+            utf8_failure: self.utf8_failure,
+            output: PhantomData,
+        }
+    }
+
+    /// Check the settings and build a [`ByteChunker`] reading from
+    /// `source`.
+    pub fn build<R>(self, source: R) -> Result<ByteChunker<R>, RcErr> {
+        self.chunker(source)
+    }
+}
+
+impl ChunkerBuilder<TextOutput> {
+    /// Set what to do with invalid UTF-8. Default is
+    /// [`Utf8FailureMode::Fatal`].
+    pub fn on_invalid_utf8(mut self, mode: Utf8FailureMode) -> Self {
+        self.utf8_failure = mode;
+        self
+    }
+
+    /// Check the settings and build a chunker reading from `source` that
+    /// returns `String`s.
+    pub fn build<R>(self, source: R) -> Result<CustomChunker<R, StringAdapter>, RcErr> {
+        let adapter = StringAdapter::new(self.utf8_failure);
+        Ok(self.chunker(source)?.with_adapter(adapter))
+    }
+}
+
+impl<O> ChunkerBuilder<O> {
+    /// See [`ByteChunker::with_match`].
+    pub fn with_match(mut self, behavior: MatchDisposition) -> Self {
+        self.match_dispo = Some(behavior);
+        self
+    }
+
+    /// See [`ByteChunker::with_match_fn`]. Can't be combined with
+    /// [`with_match`](ChunkerBuilder::with_match).
+    pub fn with_match_fn<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&[u8]) -> MatchDisposition + Send + 'static,
+    {
+        self.match_fn = Some(Box::new(f));
+        self
+    }
+
+    /// See [`ByteChunker::with_max_chunk_len`].
+    pub fn with_max_chunk_len(mut self, max: usize) -> Self {
+        self.max_chunk_len = Some(max.max(1));
+        self
+    }
+
+    /// See [`ByteChunker::trim_matches`]. The pattern isn't compiled until
+    /// [`build`](ChunkerBuilder::build).
+    pub fn trim_matches(mut self, pattern: &str) -> Self {
+        self.trim = Some(pattern.to_string());
+        self
+    }
+
+    /// See [`ByteChunker::with_buffer_size`].
+    pub fn with_buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    /// See [`ByteChunker::on_error`].
+    pub fn on_error(mut self, response: ErrorResponse) -> Self {
+        self.error_response = Some(response);
+        self
+    }
+
+    /// See [`ByteChunker::require_terminator`].
+    pub fn require_terminator(mut self) -> Self {
+        self.require_terminator = true;
+        self
+    }
+
+    /// Check the settings against each other without building anything.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.match_dispo.is_some() && self.match_fn.is_some() {
+            return Err(ConfigError::DispositionAndMatchFn);
+        }
+        if let (Some(MatchDisposition::Replace(bytes)), Some(max)) =
+            (self.match_dispo.as_ref(), self.max_chunk_len)
+        {
+            if bytes.len() > max {
+                return Err(ConfigError::ReplacementTooLong {
+                    replacement: bytes.len(),
+                    max_chunk_len: max,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn chunker<R>(self, source: R) -> Result<ByteChunker<R>, RcErr> {
+        self.validate().map_err(RcErr::Config)?;
+
+        let mut chunker = ByteChunker::new(source, &self.pattern)?;
+        if let Some(behavior) = self.match_dispo {
+            chunker = chunker.with_match(behavior);
+        }
+        if let Some(f) = self.match_fn {
+            chunker = chunker.with_match_fn(f);
+        }
+        if let Some(max) = self.max_chunk_len {
+            chunker = chunker.with_max_chunk_len(max);
+        }
+        if let Some(pattern) = self.trim.as_deref() {
+            chunker = chunker.trim_matches(pattern)?;
+        }
+        if let Some(size) = self.buffer_size {
+            chunker = chunker.with_buffer_size(size);
+        }
+        if let Some(response) = self.error_response {
+            chunker = chunker.on_error(response);
+        }
+        if self.require_terminator {
+            chunker = chunker.require_terminator();
+        }
+        Ok(chunker)
+    }
+}
+
+impl<O> std::fmt::Debug for ChunkerBuilder<O> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChunkerBuilder")
+            .field("pattern", &self.pattern)
+            .field("match_dispo", &self.match_dispo)
+            .field("match_fn", &self.match_fn.is_some())
+            .field("max_chunk_len", &self.max_chunk_len)
+            .field("trim", &self.trim)
+            .field("buffer_size", &self.buffer_size)
+            .field("error_response", &self.error_response)
+            .field("require_terminator", &self.require_terminator)
+            .field("utf8_failure", &self.utf8_failure)
+            .finish()
+    }
+}
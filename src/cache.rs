@@ -0,0 +1,88 @@
+/*!
+A cache of compiled regular expressions.
+*/
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
+
+use regex::bytes::Regex;
+
+use crate::RcErr;
+
+static GLOBAL_CACHE: OnceLock<RegexCache> = OnceLock::new();
+
+/**
+Maps pattern strings to compiled regular expressions, so that creating
+many short-lived chunkers from a small, fixed set of patterns doesn't
+involve compiling the same regex over and over again.
+
+A `RegexCache` can be created and passed around by the user, or the
+process-wide cache returned by [`RegexCache::global`] can be used (this is
+what [`ByteChunker::new_cached`](crate::ByteChunker::new_cached) does).
+Entries are never evicted unless [`RegexCache::clear`] is called, so
+don't use a cache with an unbounded set of patterns.
+
+```rust
+use regex_chunker::{ByteChunker, RcErr, RegexCache};
+use std::io::Cursor;
+
+let cache = RegexCache::new();
+
+for text in [&b"a,b,c"[..], b"d,e", b"f"] {
+    let fence = cache.get(",")?;
+    let n = ByteChunker::from_boundary(Cursor::new(text), fence).count();
+    assert_eq!(n, text.len() / 2 + 1);
+}
+
+assert_eq!(cache.len(), 1);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Debug, Default)]
+pub struct RegexCache {
+    map: Mutex<HashMap<String, Regex>>,
+}
+
+impl RegexCache {
+    /// Return a new, empty `RegexCache`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return a reference to the process-wide `RegexCache`.
+    pub fn global() -> &'static RegexCache {
+        GLOBAL_CACHE.get_or_init(RegexCache::new)
+    }
+
+    /**
+    Return the compiled regex for the given `pattern`, compiling it and
+    adding it to the cache if it isn't already present. Patterns that fail
+    to compile are not cached.
+    */
+    pub fn get(&self, pattern: &str) -> Result<Regex, RcErr> {
+        let mut map = self.map.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(re) = map.get(pattern) {
+            return Ok(re.clone());
+        }
+
+        let re = Regex::new(pattern)?;
+        map.insert(pattern.to_owned(), re.clone());
+        Ok(re)
+    }
+
+    /// Return the number of patterns currently cached.
+    pub fn len(&self) -> usize {
+        self.map.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Return whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all the cached patterns.
+    pub fn clear(&self) {
+        self.map.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
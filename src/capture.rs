@@ -0,0 +1,126 @@
+/*!
+Chunking with the named capture groups of each delimiter.
+*/
+use std::{collections::BTreeMap, io::Read};
+
+use regex::bytes::Regex;
+
+use crate::{ByteChunker, MatchDisposition, MetaChunker, RcErr};
+
+/// The text matched by each named capture group of a delimiter, by name.
+pub type CaptureMap = BTreeMap<String, Vec<u8>>;
+
+/**
+A chunker for data whose separators carry structured information, like
+`--- id=42 ts=1700000000 ---`. Each chunk is yielded along with a
+[`CaptureMap`] of the text matched by the named capture groups of the
+delimiter that ended it, so the fields don't have to be pulled out of the
+delimiter with a second regex pass.
+
+Groups that didn't participate in the match are left out of the map, as
+are unnamed groups. A final chunk not followed by a delimiter comes with
+an empty map.
+
+```rust
+use regex_chunker::{CaptureChunker, RcErr};
+use std::io::Cursor;
+
+let text = b"first\n--- id=41 ---\nsecond\n--- id=42 flag ---\n";
+let records: Vec<_> = CaptureChunker::new(
+    Cursor::new(text),
+    r"\n--- id=(?P<id>\d+)(?: (?P<flag>flag))? ---\n",
+)?
+.collect::<Result<_, RcErr>>()?;
+
+assert_eq!(records[0].0, b"first");
+assert_eq!(records[0].1["id"], b"41");
+assert!(!records[0].1.contains_key("flag"));
+assert_eq!(records[1].0, b"second");
+assert_eq!(records[1].1["flag"], b"flag");
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Debug)]
+pub struct CaptureChunker<R> {
+    chunker: MetaChunker<R>,
+    re: Regex,
+}
+
+impl<R> CaptureChunker<R> {
+    /**
+    Return a new [`CaptureChunker`] wrapping the given reader that will
+    chunk its output by delimiting it with the supplied regex pattern,
+    collecting the named capture groups of each delimiter.
+    */
+    pub fn new(source: R, delimiter: &str) -> Result<Self, RcErr> {
+        let re = Regex::new(delimiter)?;
+        let chunker = ByteChunker::from_boundary(source, re.clone()).with_metadata();
+        Ok(Self { chunker, re })
+    }
+
+    /**
+    Builder-pattern method for setting the read buffer size.
+    Default size is 1024 bytes.
+    */
+    pub fn with_buffer_size(self, size: usize) -> Self {
+        let chunker = self
+            .chunker
+            .into_inner()
+            .with_buffer_size(size)
+            .with_metadata();
+        Self { chunker, ..self }
+    }
+
+    /**
+    Builder-pattern method for controlling what the chunker does with the
+    matched text. Default value is [`MatchDisposition::Drop`]. Whatever
+    becomes of the delimiter, its captures still come with the chunk it
+    ended.
+    */
+    pub fn with_match(self, behavior: MatchDisposition) -> Self {
+        let chunker = self
+            .chunker
+            .into_inner()
+            .with_match(behavior)
+            .with_metadata();
+        Self { chunker, ..self }
+    }
+
+    /// Consumes the [`CaptureChunker`] and returns its wrapped `Read`er.
+    pub fn into_inner(self) -> R {
+        self.chunker.into_inner().into_inner()
+    }
+
+    /* Collect the named groups that participated in the match of
+    `delimiter`. */
+    fn captures(&self, delimiter: &[u8]) -> CaptureMap {
+        let caps = match self.re.captures(delimiter) {
+            Some(caps) => caps,
+            None => return CaptureMap::new(),
+        };
+        self.re
+            .capture_names()
+            .flatten()
+            .filter_map(|name| {
+                let m = caps.name(name)?;
+                Some((name.to_string(), m.as_bytes().to_vec()))
+            })
+            .collect()
+    }
+}
+
+impl<R: Read> Iterator for CaptureChunker<R> {
+    type Item = Result<(Vec<u8>, CaptureMap), RcErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = match self.chunker.next()? {
+            Ok(chunk) => chunk,
+            Err(e) => return Some(Err(e)),
+        };
+        let captures = match chunk.delimiter.as_deref() {
+            Some(delimiter) => self.captures(delimiter),
+            None => CaptureMap::new(),
+        };
+        Some(Ok((chunk.body, captures)))
+    }
+}
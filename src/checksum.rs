@@ -0,0 +1,186 @@
+/*!
+Chunking with verification of per-chunk checksums.
+*/
+use std::io::Read;
+
+use regex::bytes::Regex;
+
+use crate::{ByteChunker, ChecksumError, MetaChunker, RcErr};
+
+/* Lookup table for the CRC-32 (IEEE 802.3) polynomial. */
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 == 1 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+/**
+Compute the CRC-32 (as used by zlib, gzip, PNG, &c.) of `data`. This is
+the default checksum algorithm used by a [`ChecksumChunker`].
+
+```rust
+assert_eq!(regex_chunker::crc32(b"123456789"), 0xcbf4_3926);
+```
+*/
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &b in data.iter() {
+        crc = CRC32_TABLE[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+/**
+A chunker for record-oriented data in which the delimiter following each
+record carries a checksum of it. The delimiter pattern's first capture
+group should match the checksum, written in hexadecimal; each chunk is
+yielded only if its checksum matches, and an [`RcErr::Checksum`] error
+item is yielded in its place otherwise.
+
+The delimiters are always dropped (as with
+[`MatchDisposition::Drop`](crate::MatchDisposition::Drop)), and the
+checksum is computed over exactly the bytes of the chunk. A final chunk
+not followed by a delimiter has no checksum, and so is reported as an
+error too.
+
+```rust
+use regex_chunker::{crc32, ChecksumChunker, ChecksumError, RcErr};
+use std::io::Cursor;
+
+let mut text = Vec::new();
+for record in [&b"first record"[..], b"second record"] {
+    text.extend_from_slice(record);
+    text.extend_from_slice(format!("\nCRC={:08x}\n", crc32(record)).as_bytes());
+}
+// Corrupt the second record.
+text[26] = b'S';
+
+let mut chunker = ChecksumChunker::new(Cursor::new(text), r"\nCRC=([0-9a-f]{8})\n")?;
+assert_eq!(chunker.next().unwrap()?, b"first record");
+assert!(matches!(
+    chunker.next(),
+    Some(Err(RcErr::Checksum(ChecksumError::Mismatch { .. })))
+));
+assert!(chunker.next().is_none());
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Debug)]
+pub struct ChecksumChunker<R> {
+    chunker: MetaChunker<R>,
+    re: Regex,
+    algorithm: fn(&[u8]) -> u32,
+}
+
+impl<R> ChecksumChunker<R> {
+    /**
+    Return a new [`ChecksumChunker`] wrapping the given reader that will
+    chunk its output by delimiting it with the supplied regex pattern,
+    verifying each chunk against the checksum captured by the pattern's
+    first capture group.
+    */
+    pub fn new(source: R, delimiter: &str) -> Result<Self, RcErr> {
+        let re = Regex::new(delimiter)?;
+        if re.captures_len() < 2 {
+            return Err(RcErr::Checksum(ChecksumError::NoCaptureGroup));
+        }
+
+        let chunker = ByteChunker::from_boundary(source, re.clone()).with_metadata();
+        Ok(Self {
+            chunker,
+            re,
+            algorithm: crc32,
+        })
+    }
+
+    /**
+    Builder-pattern method for setting the read buffer size.
+    Default size is 1024 bytes.
+    */
+    pub fn with_buffer_size(self, size: usize) -> Self {
+        let chunker = self
+            .chunker
+            .into_inner()
+            .with_buffer_size(size)
+            .with_metadata();
+        Self { chunker, ..self }
+    }
+
+    /**
+    Builder-pattern method for setting the function used to compute the
+    checksum of each chunk. Default is [`crc32`].
+    */
+    pub fn with_algorithm(mut self, algorithm: fn(&[u8]) -> u32) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Consumes the [`ChecksumChunker`] and returns its wrapped `Read`er.
+    pub fn into_inner(self) -> R {
+        self.chunker.into_inner().into_inner()
+    }
+}
+
+impl<R: Read> Iterator for ChecksumChunker<R> {
+    type Item = Result<Vec<u8>, RcErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = match self.chunker.next()? {
+            Ok(chunk) => chunk,
+            Err(e) => return Some(Err(e)),
+        };
+
+        let span = chunk.span;
+        let delimiter = match chunk.delimiter {
+            Some(delimiter) => delimiter,
+            None => return Some(Err(RcErr::Checksum(ChecksumError::Missing { span }))),
+        };
+        // The delimiter has already matched, so this only fails if the
+        // capture group is optional and didn't participate.
+        let field = self
+            .re
+            .captures(&delimiter)
+            .and_then(|caps| caps.get(1))
+            .map(|m| m.as_bytes())
+            .unwrap_or_default();
+
+        let expected = match std::str::from_utf8(field)
+            .ok()
+            .and_then(|s| u32::from_str_radix(s, 16).ok())
+        {
+            Some(n) => n,
+            None => {
+                let field = field.to_vec();
+                return Some(Err(RcErr::Checksum(ChecksumError::Unparseable {
+                    span,
+                    field,
+                })));
+            }
+        };
+
+        let computed = (self.algorithm)(&chunk.body);
+        if computed == expected {
+            Some(Ok(chunk.body))
+        } else {
+            Some(Err(RcErr::Checksum(ChecksumError::Mismatch {
+                span,
+                expected,
+                computed,
+            })))
+        }
+    }
+}
@@ -0,0 +1,278 @@
+/*!
+The metadata-carrying chunk type, and the chunkers that yield it.
+*/
+use std::{io::Read, ops::Range};
+
+use crate::{ByteChunker, RcErr};
+
+/**
+A chunk of bytes along with some information about where it came from.
+
+These are yielded by a [`MetaChunker`] (or its async analog,
+[`stream::MetaChunker`](crate::stream::MetaChunker)), for downstream code
+that needs to know more than just the contents of each chunk.
+*/
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Chunk {
+    /// The contents of the chunk, as a plain chunker would have returned
+    /// them.
+    pub body: Vec<u8>,
+    /// The delimiter that terminated this chunk, or `None` if it was
+    /// terminated by the end of the source. The delimiter is reported
+    /// here regardless of the [`MatchDisposition`](crate::MatchDisposition)
+    /// in effect.
+    pub delimiter: Option<Vec<u8>>,
+    /// The position of `body` in the source, as byte offsets from the
+    /// beginning of the stream. This always covers exactly the source
+    /// bytes that went into `body`, wherever the
+    /// [`MatchDisposition`](crate::MatchDisposition) put the delimiters:
+    /// a delimiter appended to a chunk is inside its span, a delimiter
+    /// prepended to the next chunk is inside that one's, and a dropped
+    /// delimiter is in neither. Under
+    /// [`MatchDisposition::Wrap`](crate::MatchDisposition::Wrap), a
+    /// delimiter is in the spans of the chunks on both sides of it, so
+    /// they overlap. Under
+    /// [`MatchDisposition::Replace`](crate::MatchDisposition::Replace),
+    /// the replacement isn't from the source, so the span ends before it.
+    /// Trimmed bytes and a stripped byte-order mark are outside the span.
+    pub span: Range<u64>,
+    /// The position of `body` in the source, as offsets in characters
+    /// (Unicode scalar values) from the beginning of the stream, if the
+    /// chunker was asked to count them with
+    /// [`MetaChunker::with_char_offsets`].
+    pub char_span: Option<Range<u64>>,
+    /// Whether this chunk was terminated by the end of the source rather
+    /// than by a delimiter.
+    pub eof: bool,
+    /// Whether this chunk was split off because it reached the chunker's
+    /// maximum chunk length, rather than at a delimiter. The `delimiter`
+    /// of such a chunk is empty.
+    pub forced: bool,
+    /// The position of this chunk in the sequence of chunks returned by
+    /// the chunker, starting from 0.
+    pub seq: u64,
+    /// Whether an error occurred since the previous chunk was returned
+    /// (and was passed on under
+    /// [`ErrorResponse::Continue`](crate::ErrorResponse::Continue), or
+    /// swallowed under [`ErrorResponse::Ignore`](crate::ErrorResponse::Ignore)),
+    /// so that some of the source's data may be missing from between the
+    /// two. The async `stream` chunkers end at the first error, so this is
+    /// never set on the chunks they return.
+    pub gap: bool,
+}
+
+impl From<Chunk> for (Vec<u8>, Option<Vec<u8>>) {
+    fn from(chunk: Chunk) -> Self {
+        (chunk.body, chunk.delimiter)
+    }
+}
+
+/**
+A chunker that yields [`Chunk`]s, which carry the delimiter that ended
+each chunk and the chunk's position in the source, along with its contents.
+It is built from a [`ByteChunker`] with [`ByteChunker::with_metadata`].
+
+```rust
+use regex_chunker::{ByteChunker, MatchDisposition, RcErr};
+use std::io::Cursor;
+
+let text = b"one, two;three";
+let chunks: Vec<_> = ByteChunker::new(Cursor::new(text), "[,;] *")?
+    .with_match(MatchDisposition::Append)
+    .with_metadata()
+    .map(|res| res.unwrap())
+    .collect();
+
+assert_eq!(&chunks[1].body, b"two;");
+assert_eq!(chunks[1].delimiter.as_deref(), Some(&b";"[..]));
+assert_eq!(chunks[1].span, 5..9);
+assert!(chunks[2].eof);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Debug)]
+pub struct MetaChunker<R> {
+    chunker: ByteChunker<R>,
+}
+
+impl<R> MetaChunker<R> {
+    /**
+    Builder-pattern method for also reporting each chunk's position in
+    characters, in the `char_span` field of each [`Chunk`]. Characters are
+    counted as the source is consumed, so this should be called before
+    any chunks are read.
+
+    The source is assumed to be UTF-8; each byte that doesn't continue a
+    multibyte sequence counts as one character, so the counts of invalid
+    text are only approximate.
+
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr};
+    use std::io::Cursor;
+
+    let text = "naïve café, 日本";
+    let chunks: Vec<_> = ByteChunker::new(Cursor::new(text), ", ")?
+        .with_metadata()
+        .with_char_offsets()
+        .map(|res| res.unwrap())
+        .collect();
+
+    assert_eq!(chunks[1].span, 14..20);
+    assert_eq!(chunks[1].char_span, Some(12..14));
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn with_char_offsets(mut self) -> Self {
+        self.chunker.count_chars();
+        self
+    }
+
+    /// Consume this `MetaChunker` and return the underlying [`ByteChunker`].
+    pub fn into_inner(self) -> ByteChunker<R> {
+        self.chunker
+    }
+
+    /// Get a reference to the underlying [`ByteChunker`].
+    pub fn get_ref(&self) -> &ByteChunker<R> {
+        &self.chunker
+    }
+}
+
+impl<R> From<ByteChunker<R>> for MetaChunker<R> {
+    fn from(chunker: ByteChunker<R>) -> Self {
+        Self { chunker }
+    }
+}
+
+impl<R: Read> Iterator for MetaChunker<R> {
+    type Item = Result<Chunk, RcErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunker.next_chunk()
+    }
+}
+
+/* The number of UTF-8 encoded characters in `bytes`. */
+pub(crate) fn char_count(bytes: &[u8]) -> u64 {
+    // Every byte except continuation bytes starts a character.
+    bytes.iter().filter(|&&b| (b as i8) >= -0x40).count() as u64
+}
+
+/**
+A chunker that yields `(chunk, delimiter)` tuples, where the delimiter is
+the text that terminated the chunk (or `None` for a final chunk terminated
+by the end of the source). This is a lighter-weight alternative to the
+[`MetaChunker`] for when all that's needed is to not lose variable
+delimiters under [`MatchDisposition::Drop`](crate::MatchDisposition::Drop).
+It is built from a [`ByteChunker`] with [`ByteChunker::with_delimiters`].
+
+```rust
+use regex_chunker::{ByteChunker, RcErr};
+use std::io::Cursor;
+
+let text = b"a=1;b:=2";
+let pairs: Vec<_> = ByteChunker::new(Cursor::new(text), ":?=|;")?
+    .with_delimiters()
+    .map(|res| res.unwrap())
+    .collect();
+
+assert_eq!(pairs[1], (b"1".to_vec(), Some(b";".to_vec())));
+assert_eq!(pairs[2], (b"b".to_vec(), Some(b":=".to_vec())));
+assert_eq!(pairs[3], (b"2".to_vec(), None));
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Debug)]
+pub struct DelimitedChunker<R> {
+    chunker: MetaChunker<R>,
+}
+
+impl<R> DelimitedChunker<R> {
+    /// Consume this `DelimitedChunker` and return the underlying
+    /// [`ByteChunker`].
+    pub fn into_inner(self) -> ByteChunker<R> {
+        self.chunker.into_inner()
+    }
+}
+
+impl<R> From<ByteChunker<R>> for DelimitedChunker<R> {
+    fn from(chunker: ByteChunker<R>) -> Self {
+        Self {
+            chunker: chunker.with_metadata(),
+        }
+    }
+}
+
+impl<R: Read> Iterator for DelimitedChunker<R> {
+    type Item = Result<(Vec<u8>, Option<Vec<u8>>), RcErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunker.next().map(|res| res.map(Into::into))
+    }
+}
+
+/**
+A chunker that yields the same chunks as the [`ByteChunker`] it wraps,
+but first passes each one, as a [`Chunk`] carrying its span in the source
+and the delimiter that ended it, to a callback. This gives a tap point for
+debugging a pipeline without restructuring it: log, count, or assert on
+the chunks going past, and take the inspector out again when done. Errors
+are passed along without calling the callback.
+
+It is built from a [`ByteChunker`] with [`ByteChunker::with_inspector`].
+
+```rust
+use regex_chunker::{ByteChunker, RcErr};
+use std::io::Cursor;
+
+let text = b"one, two;three";
+let mut seen = Vec::new();
+let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new(text), "[,;] *")?
+    .with_inspector(|chunk| {
+        seen.push((chunk.span.clone(), chunk.delimiter.clone()));
+    })
+    .collect::<Result<_, RcErr>>()?;
+
+assert_eq!(chunks, [&b"one"[..], b"two", b"three"]);
+assert_eq!(seen[0], (0..3, Some(b", ".to_vec())));
+assert_eq!(seen[2], (9..14, None));
+# Ok::<(), RcErr>(())
+```
+*/
+pub struct ChunkInspector<R, F> {
+    chunker: MetaChunker<R>,
+    inspect: F,
+}
+
+impl<R, F> ChunkInspector<R, F> {
+    pub(crate) fn new(chunker: ByteChunker<R>, inspect: F) -> Self {
+        Self {
+            chunker: chunker.with_metadata(),
+            inspect,
+        }
+    }
+
+    /// Consume this `ChunkInspector` and return the underlying
+    /// [`ByteChunker`].
+    pub fn into_inner(self) -> ByteChunker<R> {
+        self.chunker.into_inner()
+    }
+}
+
+impl<R, F> Iterator for ChunkInspector<R, F>
+where
+    R: Read,
+    F: FnMut(&Chunk),
+{
+    type Item = Result<Vec<u8>, RcErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = match self.chunker.next()? {
+            Ok(chunk) => chunk,
+            Err(e) => return Some(Err(e)),
+        };
+        (self.inspect)(&chunk);
+        Some(Ok(chunk.body))
+    }
+}
@@ -11,7 +11,8 @@ pub(crate) enum ErrorStatus {
 impl Eq for ErrorStatus {}
 
 /// Type for specifying a Chunker's behavior upon encountering an error.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ErrorResponse {
     /// Return `Some(Err(error))` once then None thereafter. This is
     /// the default behavior.
@@ -24,8 +25,22 @@ pub enum ErrorResponse {
     Ignore,
 }
 
+/* A function choosing the disposition of each match; see
+`ByteChunker::with_match_fn`. */
+pub(crate) type MatchFn = Box<dyn FnMut(&[u8]) -> MatchDisposition + Send>;
+
+/* A function deciding whether a chunk should end at a delimiter, given the
+chunk so far and the delimiter; see `ByteChunker::with_split_fn`. */
+pub(crate) type SplitFn = Box<dyn FnMut(&[u8], &[u8]) -> bool + Send>;
+
+/* A function choosing how many bytes (at least one) to ask the source for,
+given the room available; see `ByteChunker::with_fragmented_reads`. */
+pub(crate) type ReadSizeFn = Box<dyn FnMut(usize) -> usize + Send>;
+
 /// Specify what the chunker should do with the matched text.
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum MatchDisposition {
     /// Discard the matched text; only return the text
     /// _between_ matches. This is the default behavior.
@@ -36,11 +51,125 @@ pub enum MatchDisposition {
     /// Treat the matched text like the beginning of the
     /// following chunk.
     Prepend,
+    /// Treat the matched text like both the end of the preceding chunk
+    /// and the beginning of the following one, so each chunk carries the
+    /// delimiters on either side of it, as for fenced blocks. A delimiter
+    /// at the very end of the source doesn't make a chunk of its own, as
+    /// it already ends the one before it.
+    ///
+    /// ```rust
+    /// use regex_chunker::{ByteChunker, MatchDisposition};
+    /// use std::io::Cursor;
+    ///
+    /// let text = b"intro\n---\nbody\n---\n";
+    /// let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new(text), "---\n")?
+    ///     .with_match(MatchDisposition::Wrap)
+    ///     .collect::<Result<_, _>>()?;
+    ///
+    /// assert_eq!(chunks, [&b"intro\n---\n"[..], b"---\nbody\n---\n"]);
+    /// # Ok::<(), regex_chunker::RcErr>(())
+    /// ```
+    Wrap,
+    /// Discard the matched text, and append the supplied bytes to the
+    /// preceding chunk in its place. This can be used to normalize a
+    /// variety of delimiters into a single one while chunking.
+    ///
+    /// ```rust
+    /// use regex_chunker::{ByteChunker, MatchDisposition};
+    /// use std::io::Cursor;
+    ///
+    /// let text = b"one, two;three\r\nfour";
+    /// let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new(text), r"[,;]\s*|\r?\n")?
+    ///     .with_match(MatchDisposition::Replace(b"\n".to_vec()))
+    ///     .collect::<Result<_, _>>()?;
+    ///
+    /// assert_eq!(chunks, [&b"one\n"[..], b"two\n", b"three\n", b"four"]);
+    /// # Ok::<(), regex_chunker::RcErr>(())
+    /// ```
+    Replace(Vec<u8>),
+}
+
+/// Specify which match a multi-pattern [`Boundary`](crate::Boundary)
+/// ([`MultiRegex`](crate::MultiRegex) or
+/// [`LiteralBoundary`](crate::LiteralBoundary)) uses when more than one of
+/// its patterns matches at the leftmost position. This decides where
+/// chunks end when delimiters overlap, like `"\n"` and `"\n\n"`.
+///
+/// This only chooses between patterns. The alternatives within a single
+/// regex, like `\n|\n\n`, are always tried in order, as the
+/// [`regex`](https://docs.rs/regex/latest/regex/) crate does; list them as
+/// separate patterns to have the longest of them win.
+///
+/// ```rust
+/// use regex_chunker::{ByteChunker, MatchDisposition, MatchPolicy, MultiRegex, RcErr};
+/// use std::io::Cursor;
+///
+/// let text = b"one\ntwo\n\nthree";
+/// let patterns = [(r"\n", MatchDisposition::Drop), (r"\n\n", MatchDisposition::Drop)];
+///
+/// let fence = MultiRegex::new(patterns.clone())?;
+/// let chunks: Vec<Vec<u8>> = ByteChunker::from_boundary(Cursor::new(text), fence)
+///     .collect::<Result<_, RcErr>>()?;
+/// assert_eq!(chunks, [&b"one"[..], b"two", b"", b"three"]);
+///
+/// let fence = MultiRegex::new(patterns)?.with_policy(MatchPolicy::Longest);
+/// let chunks: Vec<Vec<u8>> = ByteChunker::from_boundary(Cursor::new(text), fence)
+///     .collect::<Result<_, RcErr>>()?;
+/// assert_eq!(chunks, [&b"one"[..], b"two", b"three"]);
+/// # Ok::<(), RcErr>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MatchPolicy {
+    /// The pattern listed first wins. This is the default.
+    #[default]
+    FirstListed,
+    /// The pattern with the longest match wins; if more than one has a
+    /// match of that length, the one listed first does.
+    Longest,
+}
+
+/// Specify where a chunker resumes searching for a delimiter after one it
+/// drops (or replaces), which matters when the next match could overlap
+/// the last: with the pattern `aa`, the input `aaa` holds a match at
+/// offset 0 and another at offset 1.
+///
+/// Under [`Skip`](OverlapPolicy::Skip), the search resumes after the end
+/// of the match, so a match can't reuse its predecessor's bytes. Under
+/// [`Rescan`](OverlapPolicy::Rescan), it resumes one byte past the start
+/// of the match, so every position where a match starts ends a chunk.
+/// The bytes shared by overlapping delimiters belong to no chunk. Matches
+/// that are appended to or prepended to a chunk always belong wholly to
+/// that chunk, so they're never rescanned.
+///
+/// ```rust
+/// use regex_chunker::{ByteChunker, OverlapPolicy, RcErr};
+/// use std::io::Cursor;
+///
+/// let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new("aaab"), "aa")?
+///     .collect::<Result<_, RcErr>>()?;
+/// assert_eq!(chunks, [&b""[..], b"ab"]);
+///
+/// let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new("aaab"), "aa")?
+///     .with_overlap(OverlapPolicy::Rescan)
+///     .collect::<Result<_, RcErr>>()?;
+/// assert_eq!(chunks, [&b""[..], b"", b"b"]);
+/// # Ok::<(), RcErr>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OverlapPolicy {
+    /// Resume searching after the end of each match. This is the default.
+    #[default]
+    Skip,
+    /// Resume searching one byte past the start of each match.
+    Rescan,
 }
 
 /// Type for specifying a [`StringAdapter`](crate::StringAdapter)'s
 /// behavior upon encountering non-UTF-8 data.
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Utf8FailureMode {
     /// Lossily convert to UTF-8 (with
     /// [`String::from_utf8_lossy`](std::string::String::from_utf8_lossy)).
@@ -53,4 +182,22 @@ pub enum Utf8FailureMode {
     /// `Some(Err(RcErr))` until the it starts reading UTF-8 from the
     /// `source` again.
     Continue,
-}
\ No newline at end of file
+    /// Silently drop any invalid byte sequences and keep the rest of the
+    /// chunk. The number of bytes dropped is available from
+    /// [`StringAdapter::dropped_bytes`](crate::StringAdapter::dropped_bytes).
+    Skip,
+}
+/// Specify how a [`FrameWriter`](crate::FrameWriter) encodes (and a
+/// [`FrameReader`](crate::FrameReader) decodes) the length of each frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LengthPrefix {
+    /// A four-byte, big-endian (network order) unsigned integer. Frames
+    /// longer than `u32::MAX` bytes can't be written. This is the default.
+    #[default]
+    U32,
+    /// An unsigned
+    /// [LEB128](https://en.wikipedia.org/wiki/LEB128) varint, as used by
+    /// Protocol Buffers.
+    Varint,
+}
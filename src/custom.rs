@@ -3,7 +3,10 @@ The custom chunker type.
 */
 use std::io::Read;
 
-use crate::{Adapter, ByteChunker, RcErr, SimpleAdapter};
+use crate::{
+    Adapter, BufferPolicy, ByteChunker, CatchPanics, ChunkTiming, ErrorResponse, InPlaceAdapter,
+    MatchDisposition, OverlapPolicy, RcErr, SimpleAdapter, StringAdapter,
+};
 
 /**
 A chunker that has additionally been supplied with an [`Adapter`], so it
@@ -38,7 +41,6 @@ to yield [`String`]s instead of byte vectors.
 #   Ok(()) }
 ```
 */
-
 pub struct CustomChunker<R, A> {
     chunker: ByteChunker<R>,
     adapter: A,
@@ -52,11 +54,174 @@ impl<R, A> CustomChunker<R, A> {
     }
 
     /// Get a reference to the underlying [`Adapter`].
-    pub fn get_adapter(&self) -> &A { &self.adapter }
+    pub fn get_adapter(&self) -> &A {
+        &self.adapter
+    }
 
     /// Get a mutable reference to the underlying [`Adapter`].
-    pub fn get_adapter_mut(&mut self) -> &mut A { &mut self.adapter }
+    pub fn get_adapter_mut(&mut self) -> &mut A {
+        &mut self.adapter
+    }
+
+    /// Wrap the [`Adapter`] in a [`CatchPanics`], so that a panic while
+    /// adapting a chunk is yielded as an [`RcErr::Panic`] error instead
+    /// of unwinding out of `.next()`.
+    pub fn isolate_panics(self) -> CustomChunker<R, CatchPanics<A>> {
+        CustomChunker {
+            chunker: self.chunker,
+            adapter: CatchPanics::new(self.adapter),
+        }
+    }
+
+    /*
+    The builder-pattern methods below are forwarded to the wrapped
+    `ByteChunker`, so they can be called before or after `.with_adapter()`.
+    */
+
+    /// See [`ByteChunker::with_buffer_size`].
+    pub fn with_buffer_size(mut self, size: usize) -> Self {
+        self.chunker = self.chunker.with_buffer_size(size);
+        self
+    }
+
+    /// See [`ByteChunker::with_vectored_reads`].
+    pub fn with_vectored_reads(mut self, vectored: bool) -> Self {
+        self.chunker = self.chunker.with_vectored_reads(vectored);
+        self
+    }
+
+    /// See [`ByteChunker::with_fragmented_reads`].
+    #[cfg(feature = "test-util")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+    pub fn with_fragmented_reads(mut self, seed: u64) -> Self {
+        self.chunker = self.chunker.with_fragmented_reads(seed);
+        self
+    }
+
+    /// See [`ByteChunker::with_buffer_policy`].
+    pub fn with_buffer_policy<P>(mut self, policy: P) -> Self
+    where
+        P: BufferPolicy + 'static,
+    {
+        self.chunker = self.chunker.with_buffer_policy(policy);
+        self
+    }
+
+    /// See [`ByteChunker::with_timing`].
+    pub fn with_timing<F>(mut self, hook: F) -> Self
+    where
+        F: FnMut(&ChunkTiming) + Send + 'static,
+    {
+        self.chunker = self.chunker.with_timing(hook);
+        self
+    }
+
+    /// See [`ByteChunker::on_error`].
+    pub fn on_error(mut self, response: ErrorResponse) -> Self {
+        self.chunker = self.chunker.on_error(response);
+        self
+    }
+
+    /// See [`ByteChunker::with_match`].
+    pub fn with_match(mut self, behavior: MatchDisposition) -> Self {
+        self.chunker = self.chunker.with_match(behavior);
+        self
+    }
+
+    /// See [`ByteChunker::with_overlap`].
+    pub fn with_overlap(mut self, policy: OverlapPolicy) -> Self {
+        self.chunker = self.chunker.with_overlap(policy);
+        self
+    }
+
+    /// See [`ByteChunker::with_min_repeats`].
+    pub fn with_min_repeats(mut self, k: usize) -> Self {
+        self.chunker = self.chunker.with_min_repeats(k);
+        self
+    }
+
+    /// See [`ByteChunker::with_min_gap`].
+    pub fn with_min_gap(mut self, n: usize) -> Self {
+        self.chunker = self.chunker.with_min_gap(n);
+        self
+    }
+
+    /// See [`ByteChunker::with_strict_termination`].
+    pub fn with_strict_termination(mut self, strict: bool) -> Self {
+        self.chunker = self.chunker.with_strict_termination(strict);
+        self
+    }
+
+    /// See [`ByteChunker::require_terminator`].
+    pub fn require_terminator(mut self) -> Self {
+        self.chunker = self.chunker.require_terminator();
+        self
+    }
+
+    /// See [`ByteChunker::with_anchor`].
+    pub fn with_anchor(mut self, pattern: &str) -> Result<Self, RcErr> {
+        self.chunker = self.chunker.with_anchor(pattern)?;
+        Ok(self)
+    }
+
+    /// See [`ByteChunker::with_match_fn`].
+    pub fn with_match_fn<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&[u8]) -> MatchDisposition + Send + 'static,
+    {
+        self.chunker = self.chunker.with_match_fn(f);
+        self
+    }
+
+    /// See [`ByteChunker::with_split_fn`].
+    pub fn with_split_fn<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&[u8], &[u8]) -> bool + Send + 'static,
+    {
+        self.chunker = self.chunker.with_split_fn(f);
+        self
+    }
+
+    /// See [`ByteChunker::with_max_chunk_len`].
+    pub fn with_max_chunk_len(mut self, max: usize) -> Self {
+        self.chunker = self.chunker.with_max_chunk_len(max);
+        self
+    }
+
+    /// See [`ByteChunker::trim_matches`].
+    pub fn trim_matches(mut self, pattern: &str) -> Result<Self, RcErr> {
+        self.chunker = self.chunker.trim_matches(pattern)?;
+        Ok(self)
+    }
+
+    /// See [`ByteChunker::with_grapheme_safe_splits`].
+    #[cfg(feature = "unicode")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "unicode")))]
+    pub fn with_grapheme_safe_splits(mut self, safe: bool) -> Self {
+        self.chunker = self.chunker.with_grapheme_safe_splits(safe);
+        self
+    }
+
+    /// See [`ByteChunker::with_bom_stripping`].
+    pub fn with_bom_stripping(mut self, strip: bool) -> Self {
+        self.chunker = self.chunker.with_bom_stripping(strip);
+        self
+    }
+
+    /// See [`ByteChunker::matched_pattern`].
+    pub fn matched_pattern(&self) -> Option<usize> {
+        self.chunker.matched_pattern()
+    }
 
+    /// See [`ByteChunker::buffered_len`].
+    pub fn buffered_len(&self) -> usize {
+        self.chunker.buffered_len()
+    }
+
+    /// See [`ByteChunker::peak_buffered_len`].
+    pub fn peak_buffered_len(&self) -> usize {
+        self.chunker.peak_buffered_len()
+    }
 }
 
 impl<R, A> From<(ByteChunker<R>, A)> for CustomChunker<R, A> {
@@ -78,6 +243,47 @@ where
     }
 }
 
+// The same size as a `BufReader`'s default buffer.
+const STRING_LINES_BUFFER_SIZE: usize = 8 * 1024;
+
+/**
+Return an iterator over the lines of `source`, as `String`s, with the
+line endings (`\n` or `\r\n`) removed. This is a faster replacement for
+[`BufRead::lines`](std::io::BufRead::lines), which also doesn't need the
+source to be wrapped in a `BufReader`. It reads 8 KiB at a time, as a
+`BufReader` does by default.
+
+A chunk that isn't valid UTF-8 is yielded as an error, and ends the
+iteration. For other line-ending dispositions or UTF-8 failure modes,
+build the equivalent chunker by hand:
+
+```rust
+# use regex_chunker::RcErr;
+use regex_chunker::{
+    string_lines, ByteChunker, MatchDisposition, StringAdapter, Utf8FailureMode,
+};
+use std::io::Cursor;
+
+let text = b"one\r\ntwo\nthree";
+
+let lines: Vec<String> = string_lines(Cursor::new(text))
+    .collect::<Result<_, RcErr>>()?;
+assert_eq!(&lines, &["one", "two", "three"]);
+
+let lines: Vec<String> = ByteChunker::lines(Cursor::new(text))
+    .with_match(MatchDisposition::Append)
+    .with_adapter(StringAdapter::new(Utf8FailureMode::Lossy))
+    .collect::<Result<_, RcErr>>()?;
+assert_eq!(&lines, &["one\r\n", "two\n", "three"]);
+# Ok::<(), RcErr>(())
+```
+*/
+pub fn string_lines<R: Read>(source: R) -> CustomChunker<R, StringAdapter> {
+    ByteChunker::lines(source)
+        .with_buffer_size(STRING_LINES_BUFFER_SIZE)
+        .with_adapter(StringAdapter::default())
+}
+
 /**
 A version of [`CustomChunker`] that takes a [`SimpleAdapter`] type.
 
@@ -102,10 +308,14 @@ impl<R, A> SimpleCustomChunker<R, A> {
     }
 
     /// Get a reference to the underlying [`SimpleAdapter`].
-    pub fn get_adapter(&self) -> &A { &self.adapter }
+    pub fn get_adapter(&self) -> &A {
+        &self.adapter
+    }
 
     /// Get a mutable reference to the underlying [`SimpleAdapter`].
-    pub fn get_adapter_mut(&mut self) -> &mut A { &mut self.adapter }
+    pub fn get_adapter_mut(&mut self) -> &mut A {
+        &mut self.adapter
+    }
 }
 
 impl<R, A> From<(ByteChunker<R>, A)> for SimpleCustomChunker<R, A> {
@@ -127,4 +337,55 @@ where
             Err(e) => Some(Err(e)),
         }
     }
-}
\ No newline at end of file
+}
+
+/**
+A chunker that transforms each chunk in place with an [`InPlaceAdapter`]
+before yielding it. It is built by combining a `ByteChunker` with an
+`InPlaceAdapter` using [`ByteChunker::with_in_place_adapter`].
+*/
+pub struct InPlaceChunker<R, A> {
+    chunker: ByteChunker<R>,
+    adapter: A,
+}
+
+impl<R, A> InPlaceChunker<R, A> {
+    /// Consume this `InPlaceChunker` and return the underlying
+    /// [`ByteChunker`] and [`InPlaceAdapter`].
+    pub fn into_innards(self) -> (ByteChunker<R>, A) {
+        (self.chunker, self.adapter)
+    }
+
+    /// Get a reference to the underlying [`InPlaceAdapter`].
+    pub fn get_adapter(&self) -> &A {
+        &self.adapter
+    }
+
+    /// Get a mutable reference to the underlying [`InPlaceAdapter`].
+    pub fn get_adapter_mut(&mut self) -> &mut A {
+        &mut self.adapter
+    }
+}
+
+impl<R, A> From<(ByteChunker<R>, A)> for InPlaceChunker<R, A> {
+    fn from((chunker, adapter): (ByteChunker<R>, A)) -> Self {
+        Self { chunker, adapter }
+    }
+}
+
+impl<R, A> Iterator for InPlaceChunker<R, A>
+where
+    R: Read,
+    A: InPlaceAdapter,
+{
+    type Item = Result<Vec<u8>, RcErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut v = match self.chunker.next()? {
+            Ok(v) => v,
+            Err(e) => return Some(Err(e)),
+        };
+        self.adapter.adapt_in_place(&mut v);
+        Some(Ok(v))
+    }
+}
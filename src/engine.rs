@@ -0,0 +1,358 @@
+/*!
+The decisions about where chunks end, shared by the sync and async
+chunkers so they can't drift apart.
+
+Each chunker frontend owns its buffer and does its own reading, trimming,
+and bookkeeping; the [`ChunkEngine`] owns everything that decides where
+the next chunk ends: the [`Boundary`], the [`MatchDisposition`] (or the
+function choosing one), and the length limit.
+*/
+use std::{
+    borrow::Cow,
+    fmt::{Debug, Formatter},
+};
+
+use regex::bytes::Regex;
+
+use crate::{
+    ctrl::{MatchFn, SplitFn},
+    Boundary, MatchDisposition, OverlapPolicy, RcErr,
+};
+
+// How many bytes before a delimiter the anchor pattern can see.
+const ANCHOR_WINDOW: usize = 256;
+
+/* A delimiter found in the buffer, and what's to be done with it. */
+pub(crate) type Found = (usize, usize, MatchDisposition);
+
+/* What the engine made of the data buffered so far. In each case, the
+chunk is the bytes `first..len` of the buffer; `first` is nonzero only
+when the front of the buffer is the tail of a delimiter that's being
+rescanned for overlapping matches (see `OverlapPolicy::Rescan`). */
+pub(crate) enum Verdict {
+    /* No delimiter yet; more data is needed. */
+    Wait,
+    /* The chunk has reached the length limit, so the first `len` bytes are
+    returned whether or not a delimiter was found. */
+    Forced {
+        first: usize,
+        len: usize,
+        found: Option<Found>,
+    },
+    /* The chunk ends at a delimiter. `consumed` bytes are removed from
+    the buffer, and the next search should start `rescan` bytes in (to
+    skip a delimiter kept for the next chunk under
+    `MatchDisposition::Prepend` or `MatchDisposition::Wrap`). */
+    Split {
+        found: Found,
+        first: usize,
+        len: usize,
+        consumed: usize,
+        rescan: usize,
+    },
+}
+
+pub(crate) struct ChunkEngine {
+    pub(crate) fence: Box<dyn Boundary + Send>,
+    pub(crate) match_dispo: MatchDisposition,
+    /* If set, chooses the disposition of each match instead of `match_dispo`. */
+    pub(crate) match_fn: Option<MatchFn>,
+    /* If set, can veto a split at each delimiter found. */
+    pub(crate) split_fn: Option<SplitFn>,
+    /* If set, no chunk will be longer than this; see `with_max_chunk_len`. */
+    pub(crate) max_chunk_len: Option<usize>,
+    /* Whether forced splits should avoid breaking up grapheme clusters. */
+    pub(crate) grapheme_safe: bool,
+    pub(crate) overlap: OverlapPolicy,
+    /* Only runs of at least this many back-to-back matches are delimiters;
+    see `with_min_repeats`. */
+    pub(crate) min_repeats: usize,
+    /* Delimiters starting fewer than this many bytes into a chunk are
+    ignored; see `with_min_gap`. */
+    pub(crate) min_gap: usize,
+    /* If set, a delimiter only counts if the data before it ends with a
+    match for this; see `with_anchor`. */
+    anchor: Option<Regex>,
+    /* Up to `ANCHOR_WINDOW` of the bytes most recently removed from the
+    front of the buffer, for the anchor to look back into, and whether
+    any earlier ones have been forgotten. */
+    history: Vec<u8>,
+    forgotten: bool,
+    /* The number of bytes at the front of the buffer that are left over
+    from the last delimiter, kept only so they can be searched again for
+    an overlapping match. They don't belong to the next chunk. */
+    lead: usize,
+    /* The length of the delimiter at the front of the buffer that opens
+    the next chunk under `MatchDisposition::Wrap`. It's already been
+    returned at the end of the last chunk, so if nothing follows it, it
+    doesn't make a chunk of its own. */
+    opened: usize,
+    /* Where to resume searching after delimiters the `split_fn` vetoed,
+    so it isn't asked about them again. */
+    vetoed: usize,
+}
+
+impl ChunkEngine {
+    pub(crate) fn new(fence: Box<dyn Boundary + Send>) -> Self {
+        Self {
+            fence,
+            match_dispo: MatchDisposition::default(),
+            match_fn: None,
+            split_fn: None,
+            max_chunk_len: None,
+            grapheme_safe: false,
+            overlap: OverlapPolicy::default(),
+            min_repeats: 1,
+            min_gap: 0,
+            anchor: None,
+            history: Vec::new(),
+            forgotten: false,
+            lead: 0,
+            opened: 0,
+            vetoed: 0,
+        }
+    }
+
+    /* The number of bytes at the front of the buffer that, if the source
+    ends with nothing after them, don't make a final chunk. */
+    pub(crate) fn spent(&self) -> usize {
+        self.lead.max(self.opened)
+    }
+
+    /* Forget what's at the front of the buffer, returning the number of
+    bytes that don't belong to the next chunk, for when the caller is
+    about to take the rest of the buffer as the final chunk. */
+    pub(crate) fn take_lead(&mut self) -> usize {
+        self.vetoed = 0;
+        self.opened = 0;
+        std::mem::take(&mut self.lead)
+    }
+
+    /* Require delimiters to be preceded by a match for `pattern`. */
+    pub(crate) fn set_anchor(&mut self, pattern: &str) -> Result<(), RcErr> {
+        self.anchor = Some(Regex::new(&format!("(?:{})$", pattern))?);
+        Ok(())
+    }
+
+    /* Index of the pattern whose match ended the last split, if the
+    fence reports one. */
+    pub(crate) fn matched_pattern(&self) -> Option<usize> {
+        self.fence.matched_pattern()
+    }
+
+    /*
+    Search `buff` for a delimiter, starting `from` bytes in, and decide
+    where the next chunk ends. `at_end` is whether the source is exhausted,
+    so nothing more will be appended to `buff`.
+    */
+    pub(crate) fn decide(&mut self, buff: &[u8], from: usize, at_end: bool) -> Verdict {
+        let found = self.find_split(buff, from, at_end);
+        let found = found.map(|(start, end)| (start, end, self.disposition(&buff[start..end])));
+
+        let lead = self.lead;
+        if let Some(max) = self.max_chunk_len {
+            let too_long = match &found {
+                Some((_, end, MatchDisposition::Append | MatchDisposition::Wrap)) => {
+                    end.saturating_sub(lead) > max
+                }
+                Some((start, _, MatchDisposition::Replace(bytes))) => {
+                    start.saturating_sub(lead) + bytes.len() > max
+                }
+                Some((start, _, _)) => start.saturating_sub(lead) > max,
+                None => buff.len().saturating_sub(lead) > max,
+            };
+            if too_long {
+                self.vetoed = 0;
+                self.lead = 0;
+                self.opened = 0;
+                let len = lead + self.cut(&buff[lead..], max);
+                self.remember(&buff[..len]);
+                return Verdict::Forced {
+                    first: lead,
+                    len,
+                    found,
+                };
+            }
+        }
+
+        let (start, end, dispo) = match found {
+            Some(found) => found,
+            None => return Verdict::Wait,
+        };
+        self.vetoed = 0;
+        // `reach` is how far into the buffer the delimiter's claim on
+        // the source extends, for working out how much of it is left over.
+        let (len, consumed, rescan, reach) = match &dispo {
+            MatchDisposition::Drop | MatchDisposition::Replace(_) => {
+                match self.overlap == OverlapPolicy::Rescan && end > start + 1 {
+                    true => (start, start + 1, 0, end),
+                    false => (start, end, 0, end),
+                }
+            }
+            MatchDisposition::Append => (end, end, 0, end),
+            MatchDisposition::Prepend => (start, start, end - start, start),
+            MatchDisposition::Wrap => (end, start, end - start, start),
+        };
+        self.lead = reach.max(lead) - consumed;
+        self.remember(&buff[..consumed]);
+        self.opened = match dispo {
+            MatchDisposition::Wrap => end - start,
+            _ => 0,
+        };
+        Verdict::Split {
+            found: (start, end, dispo),
+            first: lead.min(len),
+            len,
+            consumed,
+            rescan,
+        }
+    }
+
+    /* Ask the fence for a match. */
+    fn find_one(&mut self, buff: &[u8], from: usize, at_end: bool) -> Option<(usize, usize)> {
+        if at_end {
+            self.fence.find_at_end(buff, from)
+        } else {
+            self.fence.find_at(buff, from)
+        }
+    }
+
+    /* Find the next run of at least `min_repeats` matches, each starting
+    where the last ended, and return it as a single delimiter. A run that
+    reaches the end of the buffer might continue, so it isn't reported
+    until more data arrives (or the source ends); like a greedy regex, a
+    run is otherwise taken to end where the fence stops matching. */
+    fn find(&mut self, buff: &[u8], from: usize, at_end: bool) -> Option<(usize, usize)> {
+        let mut next = self.find_one(buff, from, at_end)?;
+        if self.min_repeats <= 1 {
+            return Some(next);
+        }
+        loop {
+            let (start, mut end) = next;
+            let mut count = 1;
+            // An empty match can't be repeated back-to-back.
+            let after = loop {
+                if end == start {
+                    break None;
+                }
+                match self.find_one(buff, end, at_end) {
+                    Some((s, e)) if s == end && e > s => {
+                        end = e;
+                        count += 1;
+                    }
+                    Some(found) => break Some(found),
+                    None if at_end || end < buff.len() => break None,
+                    None => return None,
+                }
+            };
+            if count >= self.min_repeats {
+                return Some((start, end));
+            }
+            next = match after {
+                Some(found) => found,
+                None if end == start && start < buff.len() => {
+                    self.find_one(buff, start + 1, at_end)?
+                }
+                None => return None,
+            };
+        }
+    }
+
+    /* Find the next delimiter that's far enough into the chunk and that
+    the `split_fn`, if any, doesn't veto. */
+    fn find_split(&mut self, buff: &[u8], from: usize, at_end: bool) -> Option<(usize, usize)> {
+        let mut from = from.max(self.vetoed).min(buff.len());
+        loop {
+            let (start, end) = self.find(buff, from, at_end)?;
+            let first = self.lead.min(start);
+            let split = start - first >= self.min_gap
+                && self.anchored(buff, start)
+                && match self.split_fn.as_mut() {
+                    Some(f) => f(&buff[first..start], &buff[start..end]),
+                    None => true,
+                };
+            if split {
+                return Some((start, end));
+            }
+            from = end.max(start + 1);
+            self.vetoed = from;
+            if from > buff.len() {
+                return None;
+            }
+        }
+    }
+
+    /* Whether the data before offset `start` of `buff` ends with a match
+    for the anchor pattern (if there is one). */
+    fn anchored(&self, buff: &[u8], start: usize) -> bool {
+        let anchor = match self.anchor.as_ref() {
+            Some(anchor) => anchor,
+            None => return true,
+        };
+        // The search starts after the first byte of the haystack unless
+        // that's the start of the source, so that `^` can't match there.
+        let (haystack, from) = if start > ANCHOR_WINDOW {
+            (Cow::Borrowed(&buff[..start]), start - ANCHOR_WINDOW)
+        } else {
+            let haystack = [&self.history[..], &buff[..start]].concat();
+            let from = haystack.len().saturating_sub(ANCHOR_WINDOW);
+            (Cow::Owned(haystack), from.max(self.forgotten as usize))
+        };
+        anchor.find_at(&haystack, from).is_some()
+    }
+
+    /* Keep the tail of the bytes being removed from the front of the
+    buffer, for the anchor pattern. */
+    fn remember(&mut self, removed: &[u8]) {
+        if self.anchor.is_none() {
+            return;
+        }
+        self.history.extend_from_slice(removed);
+        if self.history.len() > ANCHOR_WINDOW {
+            let excess = self.history.len() - ANCHOR_WINDOW;
+            self.history.drain(..excess);
+            self.forgotten = true;
+        }
+    }
+
+    /* Choose what to do with the matched `delimiter`. */
+    fn disposition(&mut self, delimiter: &[u8]) -> MatchDisposition {
+        match (self.fence.disposition(), self.match_fn.as_mut()) {
+            (Some(dispo), _) => dispo,
+            (None, Some(f)) => f(delimiter),
+            (None, None) => self.match_dispo.clone(),
+        }
+    }
+
+    /* Where to force a split in a chunk that's reached `max` bytes. */
+    #[cfg_attr(not(feature = "unicode"), allow(unused_variables))]
+    fn cut(&self, buff: &[u8], max: usize) -> usize {
+        #[cfg(feature = "unicode")]
+        if self.grapheme_safe {
+            return crate::grapheme::grapheme_cut(buff, max);
+        }
+        max
+    }
+}
+
+impl Debug for ChunkEngine {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChunkEngine")
+            .field("fence", &self.fence)
+            .field("match_dispo", &self.match_dispo)
+            .field("match_fn", &self.match_fn.is_some())
+            .field("split_fn", &self.split_fn.is_some())
+            .field("max_chunk_len", &self.max_chunk_len)
+            .field("grapheme_safe", &self.grapheme_safe)
+            .field("overlap", &self.overlap)
+            .field("min_repeats", &self.min_repeats)
+            .field("min_gap", &self.min_gap)
+            .field("anchor", &self.anchor)
+            .field("history", &String::from_utf8_lossy(&self.history))
+            .field("forgotten", &self.forgotten)
+            .field("lead", &self.lead)
+            .field("opened", &self.opened)
+            .field("vetoed", &self.vetoed)
+            .finish()
+    }
+}
@@ -1,7 +1,7 @@
 /*!
 Error types returned by the various chunkers.
 */
-use std::{error::Error, fmt::Display, string::FromUtf8Error};
+use std::{error::Error, fmt::Display, ops::Range, string::FromUtf8Error};
 
 /**
 Wraps various types of errors that can happen in the internals of a
@@ -11,23 +11,217 @@ controlled through builder-pattern methods that take the
 [`Utf8FailureMode`](crate::Utf8FailureMode) types.
 */
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum RcErr {
     /// Error returned during creation of a regex.
     Regex(regex::Error),
+    /// Error returned during creation of a
+    /// [`LiteralBoundary`](crate::LiteralBoundary).
+    Literal(aho_corasick::BuildError),
     /// Error returned during reading from a `*Chunker`'s source.
     Read(std::io::Error),
     /// Error returned by a
     // [`CustomChunker<StringAdapter>`](crate::StringChunker)
     /// upon encountering non-UTF-8 data.
     Utf8(FromUtf8Error),
+    /// Summary of the errors encountered by one of the `collect_*()`
+    /// methods.
+    Collect(CollectError),
+    /// A chunk failed verification by a
+    /// [`ChecksumChunker`](crate::ChecksumChunker).
+    Checksum(ChecksumError),
+    /// An [`Adapter`](crate::Adapter) wrapped in a
+    /// [`CatchPanics`](crate::CatchPanics) panicked; this carries the
+    /// panic's message.
+    Panic(String),
+    /// A [`ChunkerBuilder`](crate::ChunkerBuilder) was given settings that
+    /// don't make sense together.
+    Config(ConfigError),
+    /// The source ended with data that wasn't followed by a delimiter,
+    /// which a chunker set up with
+    /// [`with_strict_termination`](crate::ByteChunker::with_strict_termination)
+    /// reports instead of yielding it as a final chunk. This carries the
+    /// data.
+    Unterminated(Vec<u8>),
+}
+
+/**
+The ways in which a [`ChecksumChunker`](crate::ChecksumChunker) can fail to
+verify a chunk. Each of these carries the span of the offending chunk in
+the source.
+*/
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChecksumError {
+    /// The delimiter pattern has no capture group for the checksum.
+    NoCaptureGroup,
+    /// The chunk was terminated by the end of the source, so it has no
+    /// checksum.
+    Missing { span: Range<u64> },
+    /// The captured checksum field couldn't be parsed as a hexadecimal
+    /// `u32`.
+    Unparseable { span: Range<u64>, field: Vec<u8> },
+    /// The checksum computed from the chunk doesn't match the captured one.
+    Mismatch {
+        span: Range<u64>,
+        expected: u32,
+        computed: u32,
+    },
+}
+
+impl Display for ChecksumError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChecksumError::NoCaptureGroup => {
+                write!(f, "delimiter pattern has no checksum capture group")
+            }
+            ChecksumError::Missing { span } => {
+                write!(f, "chunk at {:?} has no checksum", span)
+            }
+            ChecksumError::Unparseable { span, field } => write!(
+                f,
+                "chunk at {:?} has unparseable checksum {:?}",
+                span,
+                &String::from_utf8_lossy(field)
+            ),
+            ChecksumError::Mismatch {
+                span,
+                expected,
+                computed,
+            } => write!(
+                f,
+                "chunk at {:?} has checksum {:08x}, expected {:08x}",
+                span, computed, expected
+            ),
+        }
+    }
+}
+
+impl Error for ChecksumError {}
+
+/**
+The combinations of settings a [`ChunkerBuilder`](crate::ChunkerBuilder)
+refuses to build a chunker from, because one setting would be silently
+ignored or would defeat another.
+*/
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConfigError {
+    /// Both a fixed [`MatchDisposition`](crate::MatchDisposition) and a
+    /// function choosing one for each match were supplied; the function
+    /// would always win.
+    DispositionAndMatchFn,
+    /// The replacement text of a
+    /// [`MatchDisposition::Replace`](crate::MatchDisposition::Replace) is
+    /// longer than the maximum chunk length, so every chunk would be
+    /// force-split before a delimiter could be replaced.
+    ReplacementTooLong {
+        replacement: usize,
+        max_chunk_len: usize,
+    },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::DispositionAndMatchFn => write!(
+                f,
+                "a match disposition and a match function can't both be set"
+            ),
+            ConfigError::ReplacementTooLong {
+                replacement,
+                max_chunk_len,
+            } => write!(
+                f,
+                "replacement of {} bytes can't fit in chunks of at most {} bytes",
+                replacement, max_chunk_len
+            ),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/**
+Summarizes the errors encountered by a chunker's `collect_*()` method
+(like [`ByteChunker::collect_strings`](crate::ByteChunker::collect_strings)),
+which keeps going after a failure so that a single error can report how
+many chunks were affected.
+*/
+#[derive(Debug)]
+pub struct CollectError {
+    /// The number of chunks successfully collected.
+    pub chunks: usize,
+    /// The number of errors encountered.
+    pub errors: usize,
+    /// The offset in the source of the beginning of the chunk in which the
+    /// first error occurred.
+    pub first_offset: u64,
+    /// The first error encountered.
+    pub first: Box<RcErr>,
+}
+
+impl Display for CollectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} error(s) ({} chunk(s) collected); first at offset {}: {}",
+            self.errors, self.chunks, self.first_offset, &self.first
+        )
+    }
+}
+
+/* Accumulates the results of a `collect_*()` method. */
+#[derive(Debug, Default)]
+pub(crate) struct ErrorTally {
+    chunks: usize,
+    errors: usize,
+    first: Option<(u64, RcErr)>,
+}
+
+impl ErrorTally {
+    pub(crate) fn record<T>(&mut self, res: Result<T, RcErr>, offset: u64, v: &mut Vec<T>) {
+        match res {
+            Ok(t) => {
+                self.chunks += 1;
+                v.push(t);
+            }
+            Err(e) => {
+                self.errors += 1;
+                if self.first.is_none() {
+                    self.first = Some((offset, e));
+                }
+            }
+        }
+    }
+
+    pub(crate) fn finish<T>(self, v: Vec<T>) -> Result<Vec<T>, RcErr> {
+        match self.first {
+            None => Ok(v),
+            Some((first_offset, e)) => Err(RcErr::Collect(CollectError {
+                chunks: self.chunks,
+                errors: self.errors,
+                first_offset,
+                first: Box::new(e),
+            })),
+        }
+    }
 }
 
 impl Display for RcErr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             RcErr::Regex(e) => write!(f, "regex error: {}", &e),
+            RcErr::Literal(e) => write!(f, "literal searcher error: {}", &e),
             RcErr::Read(e) => write!(f, "read error: {}", &e),
             RcErr::Utf8(e) => write!(f, "UTF-8 decoding error: {}", &e),
+            RcErr::Collect(e) => write!(f, "{}", &e),
+            RcErr::Checksum(e) => write!(f, "checksum error: {}", &e),
+            RcErr::Panic(msg) => write!(f, "adapter panicked: {}", msg),
+            RcErr::Config(e) => write!(f, "invalid configuration: {}", &e),
+            RcErr::Unterminated(rest) => {
+                write!(f, "source ended with {} unterminated byte(s)", rest.len())
+            }
         }
     }
 }
@@ -38,6 +232,12 @@ impl From<regex::Error> for RcErr {
     }
 }
 
+impl From<aho_corasick::BuildError> for RcErr {
+    fn from(e: aho_corasick::BuildError) -> Self {
+        RcErr::Literal(e)
+    }
+}
+
 impl From<std::io::Error> for RcErr {
     fn from(e: std::io::Error) -> Self {
         RcErr::Read(e)
@@ -51,11 +251,23 @@ impl From<FromUtf8Error> for RcErr {
 }
 
 impl Error for RcErr {
-    fn source<'a>(&'a self) -> Option<&(dyn Error + 'static)> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
             RcErr::Regex(e) => Some(e),
+            RcErr::Literal(e) => Some(e),
             RcErr::Read(e) => Some(e),
             RcErr::Utf8(e) => Some(e),
+            RcErr::Collect(e) => Some(e),
+            RcErr::Checksum(e) => Some(e),
+            RcErr::Panic(_) => None,
+            RcErr::Config(e) => Some(e),
+            RcErr::Unterminated(_) => None,
         }
     }
 }
+
+impl Error for CollectError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.first.as_ref())
+    }
+}
@@ -0,0 +1,56 @@
+/*!
+Generators for the test corpora the crate's benchmarks run on, for
+benchmarking your own patterns against the same kinds of data.
+
+Each generator is seeded, so a given seed always produces the same data,
+and fills its records with printable ASCII.
+
+```rust
+use regex_chunker::{fixtures, ByteChunker, RcErr};
+use std::io::Cursor;
+
+let text = fixtures::records(1000, 10..20, b"\t", 2718);
+let n = ByteChunker::new(Cursor::new(text), r"\t")?
+    .collect::<Result<Vec<_>, RcErr>>()?
+    .len();
+assert_eq!(n, 1000);
+# Ok::<(), RcErr>(())
+```
+*/
+use std::ops::Range;
+
+/// Return `count` records, each of a random length in `lens` and followed
+/// by `delimiter`. Records never contain bytes outside the printable
+/// ASCII range, so a `delimiter` made of other bytes (like `\n`) can't
+/// turn up inside them.
+pub fn records(count: usize, lens: Range<usize>, delimiter: &[u8], seed: u64) -> Vec<u8> {
+    let mut rng = fastrand::Rng::with_seed(seed);
+    let mut text = Vec::new();
+    for _ in 0..count {
+        let len = match lens.is_empty() {
+            true => lens.start,
+            false => rng.usize(lens.clone()),
+        };
+        text.extend((0..len).map(|_| rng.u8(b' '..=b'~')));
+        text.extend_from_slice(delimiter);
+    }
+    text
+}
+
+/// Return `count` newline-terminated lines of up to 120 bytes each, like
+/// a typical text file or log.
+pub fn short_lines(count: usize, seed: u64) -> Vec<u8> {
+    records(count, 0..120, b"\n", seed)
+}
+
+/// Return `count` newline-terminated records of `len` bytes each, for
+/// measuring how a chunker copes with chunks much larger than its buffer.
+pub fn huge_records(count: usize, len: usize, seed: u64) -> Vec<u8> {
+    records(count, len..len, b"\n", seed)
+}
+
+/// Return `len` bytes with no newlines in them at all, for measuring how
+/// long a chunker spends searching for a delimiter that isn't there.
+pub fn no_match(len: usize, seed: u64) -> Vec<u8> {
+    records(1, len..len, b"", seed)
+}
@@ -0,0 +1,422 @@
+/*!
+Types for re-framing chunks with length prefixes, terminators, or separators.
+*/
+use std::io::{ErrorKind, Read, Write};
+
+use crate::{LengthPrefix, RcErr};
+
+// A varint encoding a u64 is never longer than this.
+const MAX_VARINT_LEN: usize = 10;
+
+/**
+Writes chunks to a [`Write`]r, each preceded by its length, turning
+regex-delimited data into a binary-safe framing that can be handed to
+downstream systems (and read back with a [`FrameReader`]).
+
+```rust
+use regex_chunker::{ByteChunker, FrameReader, FrameWriter, LengthPrefix, RcErr};
+use std::io::Cursor;
+
+let text = b"alpha\nbeta\n\ngamma";
+let chunker = ByteChunker::new(Cursor::new(text), r"\n")?;
+
+let mut writer = FrameWriter::new(Vec::new(), LengthPrefix::Varint);
+assert_eq!(writer.write_all(chunker)?, 4);
+let framed = writer.into_inner();
+assert_eq!(&framed[..6], b"\x05alpha");
+
+let chunks: Vec<Vec<u8>> = FrameReader::new(Cursor::new(framed), LengthPrefix::Varint)
+    .map(|res| res.unwrap())
+    .collect();
+assert_eq!(
+    &chunks,
+    &[b"alpha".to_vec(), b"beta".to_vec(), b"".to_vec(), b"gamma".to_vec()]
+);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Debug)]
+pub struct FrameWriter<W> {
+    writer: W,
+    prefix: LengthPrefix,
+}
+
+impl<W> FrameWriter<W> {
+    /// Return a new `FrameWriter` that writes frames to `writer`, with
+    /// lengths encoded according to `prefix`.
+    pub fn new(writer: W, prefix: LengthPrefix) -> Self {
+        Self { writer, prefix }
+    }
+
+    /// Consume the `FrameWriter` and return the wrapped writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> FrameWriter<W> {
+    /// Write a single length-prefixed frame.
+    pub fn write_frame(&mut self, frame: &[u8]) -> Result<(), RcErr> {
+        match self.prefix {
+            LengthPrefix::U32 => {
+                let len = u32::try_from(frame.len()).map_err(|_| {
+                    std::io::Error::new(ErrorKind::InvalidInput, "frame too long for u32 prefix")
+                })?;
+                self.writer.write_all(&len.to_be_bytes())?;
+            }
+            LengthPrefix::Varint => {
+                let mut buff = [0u8; MAX_VARINT_LEN];
+                let mut len = frame.len() as u64;
+                let mut n = 0;
+                loop {
+                    let byte = (len & 0x7f) as u8;
+                    len >>= 7;
+                    if len == 0 {
+                        buff[n] = byte;
+                        n += 1;
+                        break;
+                    }
+                    buff[n] = byte | 0x80;
+                    n += 1;
+                }
+                self.writer.write_all(&buff[..n])?;
+            }
+        }
+        self.writer.write_all(frame)?;
+        Ok(())
+    }
+
+    /**
+    Write every chunk yielded by `chunks` as a frame, then flush the
+    writer. Returns the number of frames written, or the first error
+    encountered, either from `chunks` or from writing.
+    */
+    pub fn write_all<I, T>(&mut self, chunks: I) -> Result<usize, RcErr>
+    where
+        I: IntoIterator<Item = Result<T, RcErr>>,
+        T: AsRef<[u8]>,
+    {
+        let mut n = 0;
+        for res in chunks {
+            self.write_frame(res?.as_ref())?;
+            n += 1;
+        }
+        self.writer.flush()?;
+        Ok(n)
+    }
+}
+
+/**
+Writes chunks to a [`Write`]r, each followed by a terminator byte, which
+joins back up what was split at that byte. This is most useful with
+[`ByteChunker::null_delimited`](crate::ByteChunker::null_delimited), to
+write lists of file names the way `find -print0` does.
+*/
+#[derive(Debug)]
+pub struct JoinWriter<W> {
+    writer: W,
+    terminator: u8,
+}
+
+impl<W> JoinWriter<W> {
+    /// Return a new `JoinWriter` that writes chunks to `writer`, each
+    /// followed by `terminator`.
+    pub fn new(writer: W, terminator: u8) -> Self {
+        Self { writer, terminator }
+    }
+
+    /// Return a new `JoinWriter` that writes NUL-terminated chunks to
+    /// `writer`.
+    pub fn null(writer: W) -> Self {
+        Self::new(writer, b'\0')
+    }
+
+    /// Consume the `JoinWriter` and return the wrapped writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> JoinWriter<W> {
+    /// Write a single chunk and its terminator.
+    pub fn write_chunk(&mut self, chunk: &[u8]) -> Result<(), RcErr> {
+        self.writer.write_all(chunk)?;
+        self.writer.write_all(&[self.terminator])?;
+        Ok(())
+    }
+
+    /**
+    Write every chunk yielded by `chunks`, then flush the writer. Returns
+    the number of chunks written, or the first error encountered, either
+    from `chunks` or from writing.
+    */
+    pub fn write_all<I, T>(&mut self, chunks: I) -> Result<usize, RcErr>
+    where
+        I: IntoIterator<Item = Result<T, RcErr>>,
+        T: AsRef<[u8]>,
+    {
+        let mut n = 0;
+        for res in chunks {
+            self.write_chunk(res?.as_ref())?;
+            n += 1;
+        }
+        self.writer.flush()?;
+        Ok(n)
+    }
+}
+
+/**
+Writes chunks to a [`Write`]r with a separator between them: the write-side
+dual of a [`ByteChunker`](crate::ByteChunker), for rejoining chunks after
+they've been transformed. Chunks split with
+[`MatchDisposition::Drop`](crate::MatchDisposition::Drop) on a fixed
+delimiter come back out exactly as they went in when rejoined with the same
+delimiter as the separator.
+
+By default the separator goes only _between_ chunks; with
+[`with_trailing_separator`](ChunkJoiner::with_trailing_separator), it
+follows every chunk, including the last, making it a terminator. The
+writer is flushed at the end of [`write_all`](ChunkJoiner::write_all) and
+by [`finish`](ChunkJoiner::finish), and can also be flushed after every
+chunk, for consumers that need to see each one as soon as it's written.
+
+```rust
+use regex_chunker::{ByteChunker, ChunkJoiner, RcErr};
+use std::io::Cursor;
+
+let text = b"one, two, three";
+let shouted = ByteChunker::new(Cursor::new(text), ", ")?
+    .with_simple_adapter(|v: Vec<u8>| v.to_ascii_uppercase());
+
+let mut joiner = ChunkJoiner::new(Vec::new(), " | ");
+assert_eq!(joiner.write_all(shouted)?, 3);
+assert_eq!(joiner.finish()?, b"ONE | TWO | THREE");
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Debug)]
+pub struct ChunkJoiner<W> {
+    writer: W,
+    separator: Vec<u8>,
+    trailing: bool,
+    flush_per_chunk: bool,
+    count: usize,
+}
+
+impl<W> ChunkJoiner<W> {
+    /// Return a new `ChunkJoiner` that writes chunks to `writer`, with
+    /// `separator` between them.
+    pub fn new<S: AsRef<[u8]>>(writer: W, separator: S) -> Self {
+        Self {
+            writer,
+            separator: separator.as_ref().to_vec(),
+            trailing: false,
+            flush_per_chunk: false,
+            count: 0,
+        }
+    }
+
+    /// Builder-pattern method for writing the separator after every
+    /// chunk, including the last, rather than only between chunks.
+    /// Default is `false`.
+    pub fn with_trailing_separator(mut self, trailing: bool) -> Self {
+        self.trailing = trailing;
+        self
+    }
+
+    /// Builder-pattern method for flushing the writer after every chunk.
+    /// Default is `false`.
+    pub fn with_flush_per_chunk(mut self, flush: bool) -> Self {
+        self.flush_per_chunk = flush;
+        self
+    }
+
+    /// Return the number of chunks written so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Get a reference to the wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Consume the `ChunkJoiner` and return the wrapped writer, without
+    /// flushing it.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> ChunkJoiner<W> {
+    /// Write a single chunk, along with the separator that belongs before
+    /// or after it.
+    pub fn write_chunk(&mut self, chunk: &[u8]) -> Result<(), RcErr> {
+        if !self.trailing && self.count > 0 {
+            self.writer.write_all(&self.separator)?;
+        }
+        self.writer.write_all(chunk)?;
+        if self.trailing {
+            self.writer.write_all(&self.separator)?;
+        }
+        self.count += 1;
+        if self.flush_per_chunk {
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /**
+    Write every chunk yielded by `chunks`, then flush the writer. Returns
+    the number of chunks written, or the first error encountered, either
+    from `chunks` or from writing.
+    */
+    pub fn write_all<I, T>(&mut self, chunks: I) -> Result<usize, RcErr>
+    where
+        I: IntoIterator<Item = Result<T, RcErr>>,
+        T: AsRef<[u8]>,
+    {
+        let mut n = 0;
+        for res in chunks {
+            self.write_chunk(res?.as_ref())?;
+            n += 1;
+        }
+        self.writer.flush()?;
+        Ok(n)
+    }
+
+    /// Flush the wrapped writer.
+    pub fn flush(&mut self) -> Result<(), RcErr> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Flush the wrapped writer, then consume the `ChunkJoiner` and
+    /// return it.
+    pub fn finish(mut self) -> Result<W, RcErr> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/**
+Reads the length-prefixed frames written by a [`FrameWriter`] back from a
+[`Read`]er, yielding them the same way a
+[`ByteChunker`](crate::ByteChunker) yields chunks.
+
+Reaching the end of the source between frames ends the iteration;
+reaching it in the middle of a frame (or its prefix) returns an
+[`ErrorKind::UnexpectedEof`] error, after which the iteration ends.
+*/
+#[derive(Debug)]
+pub struct FrameReader<R> {
+    source: R,
+    prefix: LengthPrefix,
+    done: bool,
+}
+
+impl<R> FrameReader<R> {
+    /// Return a new `FrameReader` that reads frames from `source`, with
+    /// lengths encoded according to `prefix`.
+    pub fn new(source: R, prefix: LengthPrefix) -> Self {
+        Self {
+            source,
+            prefix,
+            done: false,
+        }
+    }
+
+    /// Consume the `FrameReader` and return the wrapped reader.
+    pub fn into_inner(self) -> R {
+        self.source
+    }
+}
+
+impl<R: Read> FrameReader<R> {
+    /* Read one byte; `None` at the end of the source. */
+    fn read_byte(&mut self) -> Result<Option<u8>, std::io::Error> {
+        let mut byte = [0u8];
+        loop {
+            match self.source.read(&mut byte) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(byte[0])),
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /* Read the length of the next frame; `None` at a clean end of source. */
+    fn read_len(&mut self) -> Result<Option<u64>, std::io::Error> {
+        let first = match self.read_byte()? {
+            Some(b) => b,
+            None => return Ok(None),
+        };
+
+        match self.prefix {
+            LengthPrefix::U32 => {
+                let mut bytes = [first, 0, 0, 0];
+                self.source.read_exact(&mut bytes[1..])?;
+                Ok(Some(u32::from_be_bytes(bytes) as u64))
+            }
+            LengthPrefix::Varint => {
+                let mut len = 0u64;
+                let mut byte = first;
+                for n in 0..MAX_VARINT_LEN {
+                    // The last byte has room for only the top bit of a u64.
+                    if n == MAX_VARINT_LEN - 1 && byte > 1 {
+                        break;
+                    }
+                    len |= ((byte & 0x7f) as u64) << (7 * n);
+                    if byte & 0x80 == 0 {
+                        return Ok(Some(len));
+                    }
+                    byte = self
+                        .read_byte()?
+                        .ok_or_else(|| std::io::Error::from(ErrorKind::UnexpectedEof))?;
+                }
+                Err(std::io::Error::new(
+                    ErrorKind::InvalidData,
+                    "varint length prefix too long",
+                ))
+            }
+        }
+    }
+
+    fn read_frame(&mut self) -> Result<Option<Vec<u8>>, std::io::Error> {
+        let len = match self.read_len()? {
+            Some(len) => len,
+            None => return Ok(None),
+        };
+
+        // Don't trust the prefix enough to allocate it all up front.
+        let mut frame = Vec::new();
+        (&mut self.source).take(len).read_to_end(&mut frame)?;
+        if (frame.len() as u64) < len {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+        Ok(Some(frame))
+    }
+}
+
+impl<R: Read> Iterator for FrameReader<R> {
+    type Item = Result<Vec<u8>, RcErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.read_frame() {
+            Ok(Some(frame)) => Some(Ok(frame)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e.into()))
+            }
+        }
+    }
+}
@@ -0,0 +1,45 @@
+/*!
+Finding grapheme cluster boundaries for forced splits.
+*/
+use unicode_segmentation::UnicodeSegmentation;
+
+/*
+Return where to cut `buff` so that the piece before the cut is at most
+`max` bytes long and doesn't end partway through a grapheme cluster.
+`buff` must be longer than `max`, so the character following the cut is
+available to decide whether it continues the cluster.
+
+If the bytes around `max` aren't valid UTF-8 there are no clusters to
+respect, and the cut is made at `max`, unless they're just the start of a
+character that hasn't been completely read yet. If a single cluster at the
+start of `buff` is longer than `max`, it's cut at a character boundary
+instead, or at `max` if even that isn't possible.
+*/
+pub(crate) fn grapheme_cut(buff: &[u8], max: usize) -> usize {
+    let (text, incomplete) = match std::str::from_utf8(buff) {
+        Ok(text) => (text, false),
+        Err(e) => {
+            // This can't fail; we've just been told it's valid.
+            let text = std::str::from_utf8(&buff[..e.valid_up_to()]).unwrap();
+            (text, e.error_len().is_none())
+        }
+    };
+    if text.len() <= max && !incomplete {
+        return max;
+    }
+
+    let cut = text
+        .grapheme_indices(true)
+        .map(|(n, _)| n)
+        .take_while(|&n| n <= max)
+        .last()
+        .unwrap_or(0);
+    if cut > 0 {
+        return cut;
+    }
+
+    match (1..=max).rev().find(|&n| text.is_char_boundary(n)) {
+        Some(n) => n,
+        None => max,
+    }
+}
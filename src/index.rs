@@ -0,0 +1,144 @@
+/*!
+Indexing the chunks of a source, for extracting them later by seeking.
+*/
+use std::{
+    io::{Read, Seek, SeekFrom},
+    ops::Range,
+};
+
+use crate::RcErr;
+
+/**
+A compact index of the positions of the chunks in a source, built by
+[`ByteChunker::build_index`](crate::ByteChunker::build_index).
+
+Building the index streams through the source without keeping any of the
+chunks' contents, so it takes memory proportional only to the number of
+chunks. Afterward, any subset of the chunks can be read back out of a
+seekable source (a [`File`](std::fs::File), say) in any order, which makes
+this a good fit for huge files where only some records are wanted, or
+where the same records are visited more than once.
+
+```rust
+use regex_chunker::{ByteChunker, RcErr};
+use std::io::Cursor;
+
+let text = b"zero\none\ntwo\nthree\nfour\n";
+let index = ByteChunker::new(Cursor::new(text), r"\n")?.build_index()?;
+assert_eq!(index.len(), 5);
+assert_eq!(index.span(3), Some(13..18));
+
+let mut source = Cursor::new(text);
+assert_eq!(index.extract(&mut source, 1).unwrap()?, b"one");
+
+let picked: Vec<Vec<u8>> = index
+    .extract_many(source, [4, 0, 2])
+    .collect::<Result<_, RcErr>>()?;
+assert_eq!(picked, [b"four".to_vec(), b"zero".to_vec(), b"two".to_vec()]);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChunkIndex {
+    spans: Vec<(u64, u64)>,
+}
+
+impl ChunkIndex {
+    pub(crate) fn push(&mut self, start: u64, end: u64) {
+        self.spans.push((start, end));
+    }
+
+    /// The number of chunks in the index.
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    /// Whether the index is empty (that is, the source had no chunks).
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// The position in the source of the `n`th chunk, as byte offsets from
+    /// the beginning of the stream, or `None` if there is no `n`th chunk.
+    pub fn span(&self, n: usize) -> Option<Range<u64>> {
+        self.spans.get(n).map(|&(start, end)| start..end)
+    }
+
+    /// Iterate over the positions of all the chunks in the source.
+    pub fn spans(&self) -> impl Iterator<Item = Range<u64>> + '_ {
+        self.spans.iter().map(|&(start, end)| start..end)
+    }
+
+    /**
+    Read the `n`th chunk from `source`, which should be the same data the
+    index was built from. Returns `None` if there is no `n`th chunk.
+    */
+    pub fn extract<R>(&self, source: &mut R, n: usize) -> Option<Result<Vec<u8>, RcErr>>
+    where
+        R: Read + Seek,
+    {
+        let span = self.span(n)?;
+        Some(read_span(source, span))
+    }
+
+    /**
+    Return an iterator that reads the chunks with the given indices from
+    `source`, in the order given. Indices with no corresponding chunk are
+    skipped.
+    */
+    pub fn extract_many<R, I>(&self, source: R, indices: I) -> Extractor<'_, R, I::IntoIter>
+    where
+        R: Read + Seek,
+        I: IntoIterator<Item = usize>,
+    {
+        Extractor {
+            index: self,
+            source,
+            indices: indices.into_iter(),
+        }
+    }
+}
+
+/* Read the bytes in `span` from `source`. */
+fn read_span<R: Read + Seek>(source: &mut R, span: Range<u64>) -> Result<Vec<u8>, RcErr> {
+    source.seek(SeekFrom::Start(span.start))?;
+    let mut chunk = vec![0u8; (span.end - span.start) as usize];
+    source.read_exact(&mut chunk)?;
+    Ok(chunk)
+}
+
+/**
+An iterator over chunks read back out of a source using a [`ChunkIndex`].
+It is returned by [`ChunkIndex::extract_many`].
+*/
+#[derive(Debug)]
+pub struct Extractor<'a, R, I> {
+    index: &'a ChunkIndex,
+    source: R,
+    indices: I,
+}
+
+impl<'a, R, I> Extractor<'a, R, I> {
+    /// Consumes the [`Extractor`] and returns its wrapped source.
+    pub fn into_inner(self) -> R {
+        self.source
+    }
+}
+
+impl<'a, R, I> Iterator for Extractor<'a, R, I>
+where
+    R: Read + Seek,
+    I: Iterator<Item = usize>,
+{
+    type Item = Result<Vec<u8>, RcErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let n = self.indices.next()?;
+            if let Some(res) = self.index.extract(&mut self.source, n) {
+                return Some(res);
+            }
+        }
+    }
+}
@@ -0,0 +1,77 @@
+/*!
+Chunkers over sources that can't fail, which return their chunks without
+wrapping them in `Result`s.
+*/
+use std::{
+    collections::VecDeque,
+    io::{Chain, Cursor, Empty, Read, Repeat, Take},
+};
+
+use crate::ByteChunker;
+
+/**
+Marker trait for sources whose reads never fail, like byte slices and
+in-memory [`Cursor`]s. A chunker over one of these can be turned into an
+[`InfallibleChunker`] with [`ByteChunker::infallible`].
+
+It's implemented for `&[u8]`, `Cursor`s over anything that's
+`AsRef<[u8]>`, `VecDeque<u8>`, [`std::io::Empty`], [`std::io::Repeat`],
+and [`Chain`]s and [`Take`]s of these. Implementing it for a type whose
+reads _can_ fail won't cause undefined behavior, but its errors will be
+silently skipped.
+*/
+pub trait InfallibleRead {}
+
+impl InfallibleRead for &[u8] {}
+impl<T: AsRef<[u8]>> InfallibleRead for Cursor<T> {}
+impl InfallibleRead for VecDeque<u8> {}
+impl InfallibleRead for Empty {}
+impl InfallibleRead for Repeat {}
+impl<A: InfallibleRead, B: InfallibleRead> InfallibleRead for Chain<A, B> {}
+impl<R: InfallibleRead> InfallibleRead for Take<R> {}
+
+/**
+A [`ByteChunker`] whose `Iterator` implementation returns plain `Vec<u8>`s,
+rather than `Result`s, because it can't encounter any errors: either its
+source is [`InfallibleRead`] (see [`ByteChunker::infallible`]) or it's been
+told to skip over them (see [`ByteChunker::ignoring_errors`]).
+
+```rust
+use regex_chunker::{ByteChunker, RcErr};
+
+let text = "one two  three";
+let words: Vec<Vec<u8>> = ByteChunker::new(text.as_bytes(), " +")?
+    .infallible()
+    .collect();
+assert_eq!(words, [&b"one"[..], b"two", b"three"]);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Debug)]
+pub struct InfallibleChunker<R> {
+    chunker: ByteChunker<R>,
+}
+
+impl<R> InfallibleChunker<R> {
+    pub(crate) fn new(chunker: ByteChunker<R>) -> Self {
+        Self { chunker }
+    }
+
+    /// Consume the `InfallibleChunker` and return the wrapped
+    /// [`ByteChunker`].
+    pub fn into_inner(self) -> ByteChunker<R> {
+        self.chunker
+    }
+}
+
+impl<R: Read> Iterator for InfallibleChunker<R> {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Vec<u8>> {
+        loop {
+            if let Ok(v) = self.chunker.next()? {
+                return Some(v);
+            }
+        }
+    }
+}
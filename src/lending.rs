@@ -0,0 +1,130 @@
+/*!
+A lending iterator over chunks borrowed from a chunker's internal buffer.
+*/
+use std::io::Read;
+
+use crate::{ByteChunker, RcErr};
+
+/**
+An iterator whose items may borrow from the iterator itself, so each one
+has to be dropped before the next is asked for. This is what lets a
+[`LendingChunker`] hand out chunks as slices of its internal buffer.
+
+Only the methods needed for scanning are provided; `while let` covers most
+of the rest.
+*/
+pub trait LendingIterator {
+    /// The type of the items, which may borrow from the iterator.
+    type Item<'a>
+    where
+        Self: 'a;
+
+    /// Advance the iterator and return the next item, if any.
+    fn next(&mut self) -> Option<Self::Item<'_>>;
+
+    /// Call `f` on each item in turn.
+    fn for_each<F>(mut self, mut f: F)
+    where
+        Self: Sized,
+        F: FnMut(Self::Item<'_>),
+    {
+        while let Some(item) = self.next() {
+            f(item);
+        }
+    }
+
+    /// Fold every item into an accumulator with `f`, like
+    /// [`Iterator::fold`].
+    fn fold<B, F>(mut self, init: B, mut f: F) -> B
+    where
+        Self: Sized,
+        F: FnMut(B, Self::Item<'_>) -> B,
+    {
+        let mut acc = init;
+        while let Some(item) = self.next() {
+            acc = f(acc, item);
+        }
+        acc
+    }
+}
+
+/**
+A [`ByteChunker`] that lends out its chunks as `&[u8]` slices of its
+internal buffer, rather than copying each one into a new `Vec<u8>`,
+returned by [`ByteChunker::lending`]. Unlike
+[`for_each_chunk`](ByteChunker::for_each_chunk) and
+[`fold_chunks`](ByteChunker::fold_chunks), it can be driven a chunk at a
+time, stopped partway, and resumed.
+
+Under [`MatchDisposition::Replace`](crate::MatchDisposition::Replace), the
+chunk has to be copied to append the replacement, but into a scratch
+buffer that's reused from chunk to chunk.
+
+```rust
+use regex_chunker::{ByteChunker, LendingIterator, RcErr};
+
+let text = "id=7;id=12;id=3";
+let mut chunks = ByteChunker::new(text.as_bytes(), ";")?.lending();
+
+let mut biggest = 0;
+while let Some(res) = chunks.next() {
+    let chunk: &[u8] = res?;
+    let id: u32 = std::str::from_utf8(&chunk[3..]).unwrap().parse().unwrap();
+    biggest = biggest.max(id);
+}
+assert_eq!(biggest, 12);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Debug)]
+pub struct LendingChunker<R> {
+    chunker: ByteChunker<R>,
+    /* How much of the chunker's buffer the last chunk lent out occupies,
+    to be released before the next is found. */
+    lent: usize,
+    scratch: Vec<u8>,
+}
+
+impl<R> LendingChunker<R> {
+    pub(crate) fn new(chunker: ByteChunker<R>) -> Self {
+        Self {
+            chunker,
+            lent: 0,
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Consume the `LendingChunker` and return the wrapped
+    /// [`ByteChunker`].
+    pub fn into_inner(mut self) -> ByteChunker<R> {
+        self.chunker.release(self.lent);
+        self.chunker
+    }
+}
+
+impl<R: Read> LendingIterator for LendingChunker<R> {
+    type Item<'a>
+        = Result<&'a [u8], RcErr>
+    where
+        R: 'a;
+
+    fn next(&mut self) -> Option<Result<&[u8], RcErr>> {
+        self.chunker.release(std::mem::take(&mut self.lent));
+        let peeked = match self.chunker.peek_chunk()? {
+            Ok(peeked) => peeked,
+            Err(e) => return Some(Err(e)),
+        };
+        self.lent = peeked.consumed;
+
+        let chunk = self.chunker.peeked(&peeked);
+        match peeked.replacement.as_ref() {
+            None => Some(Ok(chunk)),
+            Some(replacement) => {
+                self.scratch.clear();
+                self.scratch.extend_from_slice(chunk);
+                self.scratch.extend_from_slice(replacement);
+                Some(Ok(&self.scratch))
+            }
+        }
+    }
+}
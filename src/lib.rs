@@ -39,18 +39,116 @@ and implementing
 (This also pulls in several crates of
 [`tokio`](https://docs.rs/tokio/latest/tokio/index.html) machinery, which is why
 it's behind a feature flag.)
+
+The `unicode` feature pulls in the
+[`unicode-segmentation`](https://docs.rs/unicode-segmentation/latest/unicode_segmentation/)
+crate, for chunking that respects grapheme cluster boundaries, and for
+splitting text into words with [`StringChunker::unicode_words`].
+
+The `serde` feature implements `Serialize` and `Deserialize` for the
+plain-data types, like [`MatchDisposition`], [`Chunk`], and
+[`ChunkIndex`], so they can be kept in configuration files or sent
+between processes.
+
+The `bytes`, `bstr`, and `smallvec` features implement [`ChunkBuf`] for
+the crates' respective buffer types, so the chunkers can return chunks as
+them directly with `with_output()`.
+
+The `allocator-api2` feature adds [`ByteChunker::with_allocator`], for
+returning chunks allocated with a custom (say, arena) allocator.
+
+The `debug-internals` feature adds a `with_trace` method to the chunkers,
+which reports each of their decisions about where a chunk ends as a
+[`DecodeEvent`].
+
+The `test-util` feature exposes [`SlowReader`] and [`ChaosReader`], which
+deliver data a little at a time, fail with spurious errors, and stall, for
+testing code built on the chunkers against misbehaving sources, the
+[`ByteChunker::with_fragmented_reads`] builder, which breaks up a chunker's
+own reads at random, and the [`fixtures`] module, which generates the data
+the crate's benchmarks use.
 */
 
 pub(crate) mod adapter;
 pub use adapter::*;
+#[cfg(feature = "allocator-api2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "allocator-api2")))]
+mod allocator;
+#[cfg(feature = "allocator-api2")]
+pub use allocator::*;
 mod base;
 pub use base::*;
+mod bom;
+pub use bom::*;
+mod boundary;
+pub use boundary::*;
+mod buffer;
+pub use buffer::*;
+mod builder;
+pub use builder::*;
+mod cache;
+pub use cache::*;
+mod capture;
+pub use capture::*;
+mod checksum;
+pub use checksum::*;
+mod chunk;
+pub use chunk::*;
 pub(crate) mod ctrl;
 pub use ctrl::*;
 mod custom;
 pub use custom::*;
+mod engine;
 mod err;
-pub use err::RcErr;
+pub use err::{ChecksumError, CollectError, ConfigError, RcErr};
+#[cfg(feature = "test-util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+pub mod fixtures;
+mod frame;
+pub use frame::*;
+#[cfg(feature = "unicode")]
+mod grapheme;
+mod index;
+pub use index::*;
+mod infallible;
+pub use infallible::*;
+mod lending;
+pub use lending::*;
+mod mail;
+pub use mail::MboxMessage;
+mod markdown;
+pub use markdown::*;
+#[cfg(feature = "test-util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+mod mock;
+#[cfg(feature = "test-util")]
+pub use mock::*;
+mod output;
+pub use output::*;
+mod parallel;
+pub use parallel::*;
+mod replay;
+pub use replay::*;
+pub(crate) mod rev;
+pub use rev::*;
+mod sanitize;
+pub use sanitize::*;
+mod sql;
+pub use sql::*;
+mod timing;
+pub use timing::ChunkTiming;
+mod trace;
+#[cfg(feature = "debug-internals")]
+#[cfg_attr(docsrs, doc(cfg(feature = "debug-internals")))]
+pub use trace::{Decision, DecodeEvent};
+mod trim;
+mod warc;
+pub use warc::*;
+#[cfg(feature = "unicode")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unicode")))]
+mod words;
+#[cfg(feature = "unicode")]
+pub use words::*;
 #[cfg(any(feature = "async", docsrs))]
 #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
 pub mod stream;
@@ -60,6 +158,7 @@ pub(crate) mod tests {
     use super::*;
 
     use std::{
+        borrow::Cow,
         fmt::Debug,
         fs::File,
         io::{Cursor, Read, Write},
@@ -74,8 +173,8 @@ pub(crate) mod tests {
     pub static HTTP_URL: &str = "https://www.zombo.com";
     pub static HTTP_PATT: &str = r#">[^<]*"#;
 
-    pub fn chunk_vec<'a>(re: &Regex, v: &'a [u8], mode: MatchDisposition) -> Vec<&'a [u8]> {
-        let mut u: Vec<&[u8]> = Vec::new();
+    pub fn chunk_vec<'a>(re: &Regex, v: &'a [u8], mode: MatchDisposition) -> Vec<Cow<'a, [u8]>> {
+        let mut u: Vec<Cow<[u8]>> = Vec::new();
         let mut offs: usize = 0;
         let mut prev_offs: usize = 0;
         while let Some(m) = re.find_at(v, offs) {
@@ -96,17 +195,34 @@ pub(crate) mod tests {
                     prev_offs = m.start();
                     (start, m.start())
                 }
+                MatchDisposition::Wrap => {
+                    let start = prev_offs;
+                    offs = m.end();
+                    prev_offs = m.start();
+                    (start, m.end())
+                }
+                MatchDisposition::Replace(ref bytes) => {
+                    let start = offs;
+                    offs = m.end();
+                    u.push([&v[start..m.start()], &bytes[..]].concat().into());
+                    continue;
+                }
             };
 
-            u.push(&v[start..end]);
+            u.push(v[start..end].into());
         }
 
         match mode {
-            MatchDisposition::Drop | MatchDisposition::Append => {
-                u.push(&v[offs..]);
+            MatchDisposition::Drop | MatchDisposition::Append | MatchDisposition::Replace(_) => {
+                u.push(v[offs..].into());
             }
             MatchDisposition::Prepend => {
-                u.push(&v[prev_offs..]);
+                u.push(v[prev_offs..].into());
+            }
+            // A delimiter at the very end doesn't make a chunk of its own.
+            MatchDisposition::Wrap if offs == v.len() && offs > 0 => {}
+            MatchDisposition::Wrap => {
+                u.push(v[prev_offs..].into());
             }
         }
 
@@ -142,6 +258,26 @@ pub(crate) mod tests {
         ref_slice_cmp(&vec_vec, &slice_vec);
     }
 
+    #[test]
+    fn vectored_reads() {
+        let byte_vec = std::fs::read(TEST_PATH).unwrap();
+        let re = Regex::new(TEST_PATT).unwrap();
+        let slice_vec = chunk_vec(&re, &byte_vec, MatchDisposition::Drop);
+
+        for size in [7, 1024] {
+            let f = File::open(TEST_PATH).unwrap();
+            let vec_vec: Vec<Vec<u8>> = ByteChunker::new(f, TEST_PATT)
+                .unwrap()
+                .with_buffer_size(size)
+                .with_vectored_reads(true)
+                .map(|res| res.unwrap())
+                .collect();
+
+            assert_eq!(vec_vec.len(), slice_vec.len());
+            ref_slice_cmp(&vec_vec, &slice_vec);
+        }
+    }
+
     #[test]
     fn bytes_append_prepend() {
         let byte_vec = std::fs::read(PASSWD_PATH).unwrap();
@@ -211,6 +347,1724 @@ pub(crate) mod tests {
         ref_slice_cmp(&vec_vec, &slice_vec);
     }
 
+    #[test]
+    fn custom_boundary() {
+        #[derive(Debug)]
+        struct Every(usize);
+
+        impl Boundary for Every {
+            fn find_at(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+                let end = start + self.0;
+                (end < haystack.len()).then_some((end, end + 1))
+            }
+        }
+
+        let byte_vec = std::fs::read(PASSWD_PATH).unwrap();
+        let slice_vec: Vec<&[u8]> = byte_vec.chunks(8).map(|c| &c[..c.len().min(7)]).collect();
+
+        let f = File::open(PASSWD_PATH).unwrap();
+        let vec_vec: Vec<Vec<u8>> = ByteChunker::from_boundary(f, Every(7))
+            .with_buffer_size(5)
+            .map(|res| res.unwrap())
+            .collect();
+
+        assert_eq!(vec_vec.len(), slice_vec.len());
+        ref_slice_cmp(&vec_vec, &slice_vec);
+    }
+
+    #[test]
+    fn literal_boundary() {
+        let byte_vec = std::fs::read(PASSWD_PATH).unwrap();
+        let re = Regex::new(r#"::|:|\n"#).unwrap();
+        let slice_vec = chunk_vec(&re, &byte_vec, MatchDisposition::Append);
+
+        let f = File::open(PASSWD_PATH).unwrap();
+        let mut chunker = ByteChunker::from_literals(f, ["::", ":", "\n"])
+            .unwrap()
+            .with_match(MatchDisposition::Append);
+        let mut vec_vec: Vec<Vec<u8>> = Vec::new();
+        while let Some(res) = chunker.next() {
+            let v = res.unwrap();
+            match chunker.matched_pattern() {
+                Some(0) => assert!(v.ends_with(b"::")),
+                Some(1) => assert!(v.ends_with(b":") && !v.ends_with(b"::")),
+                Some(2) => assert!(v.ends_with(b"\n")),
+                x => assert_eq!(x, None),
+            }
+            vec_vec.push(v);
+        }
+
+        ref_slice_cmp(&vec_vec, &slice_vec);
+    }
+
+    #[test]
+    fn byte_set_boundary() {
+        let byte_vec = std::fs::read(PASSWD_PATH).unwrap();
+        let re = Regex::new(PASSWD_PATT).unwrap();
+
+        for mode in [
+            MatchDisposition::Drop,
+            MatchDisposition::Append,
+            MatchDisposition::Prepend,
+        ] {
+            let slice_vec = chunk_vec(&re, &byte_vec, mode.clone());
+
+            let f = File::open(PASSWD_PATH).unwrap();
+            let vec_vec: Vec<Vec<u8>> = ByteChunker::from_byte_set(f, b":\r\n")
+                .with_match(mode.clone())
+                .map(|res| res.unwrap())
+                .collect();
+
+            ref_slice_cmp(&vec_vec, &slice_vec);
+        }
+    }
+
+    #[test]
+    fn line_boundary() {
+        let mut byte_vec = Vec::new();
+        for (n, line) in std::fs::read(PASSWD_PATH)
+            .unwrap()
+            .split(|&b| b == b'\n')
+            .enumerate()
+        {
+            byte_vec.extend_from_slice(line);
+            byte_vec.extend_from_slice(if n % 3 == 0 { b"\r\n" } else { b"\n" });
+        }
+        let re = Regex::new(r"\r?\n").unwrap();
+
+        for mode in [
+            MatchDisposition::Drop,
+            MatchDisposition::Append,
+            MatchDisposition::Prepend,
+        ] {
+            let slice_vec = chunk_vec(&re, &byte_vec, mode.clone());
+
+            for size in [3, 1024] {
+                let vec_vec: Vec<Vec<u8>> = ByteChunker::lines(Cursor::new(&byte_vec))
+                    .with_buffer_size(size)
+                    .with_match(mode.clone())
+                    .map(|res| res.unwrap())
+                    .collect();
+
+                ref_slice_cmp(&vec_vec, &slice_vec);
+            }
+        }
+    }
+
+    #[test]
+    fn string_lines_matches_bufread() {
+        use std::io::{BufRead, BufReader};
+
+        let expected: Vec<String> = BufReader::new(File::open(PASSWD_PATH).unwrap())
+            .lines()
+            .map(|res| res.unwrap())
+            .collect();
+        let lines: Vec<String> = string_lines(File::open(PASSWD_PATH).unwrap())
+            .map(|res| res.unwrap())
+            .collect();
+        assert_eq!(lines, expected);
+    }
+
+    #[test]
+    fn text_boundary() {
+        let mut bytes = "uno,é,dós".as_bytes().to_vec();
+        bytes.push(0xff);
+        bytes.extend_from_slice(",trés,cuatro".as_bytes());
+
+        let chunks: Vec<Vec<u8>> = ByteChunker::new_text(Cursor::new(bytes), ",|é")
+            .unwrap()
+            .with_buffer_size(1)
+            .map(|res| res.unwrap())
+            .collect();
+
+        let mut dos = "dós".as_bytes().to_vec();
+        dos.push(0xff);
+        ref_slice_cmp(
+            &chunks,
+            &[&b"uno"[..], b"", b"", &dos, b"tr", b"s", b"cuatro"],
+        );
+        assert_eq!(chunks.len(), 7);
+    }
+
+    #[test]
+    fn text_boundary_split_match() {
+        let chunks: Vec<String> =
+            ByteChunker::new_text(Cursor::new("Wait... what?! Oh."), r"[.!?]+\s*")
+                .unwrap()
+                .with_buffer_size(1)
+                .with_adapter(StringAdapter::default())
+                .map(|res| res.unwrap())
+                .collect();
+        assert_eq!(chunks, ["Wait", "what", "Oh"]);
+
+        let chunks: Vec<String> = ByteChunker::new_text(
+            Cursor::new("one END two ENDING three END"),
+            r"\s*\bEND\b\s*",
+        )
+        .unwrap()
+        .with_buffer_size(1)
+        .with_adapter(StringAdapter::default())
+        .map(|res| res.unwrap())
+        .collect();
+        assert_eq!(chunks, ["one", "two ENDING three"]);
+    }
+
+    #[test]
+    fn cached_regex() {
+        let cache = RegexCache::new();
+        let a = cache.get(PASSWD_PATT).unwrap();
+        let b = cache.get(PASSWD_PATT).unwrap();
+        assert_eq!(a.as_str(), b.as_str());
+        assert_eq!(cache.len(), 1);
+
+        assert!(matches!(cache.get("(unclosed"), Err(RcErr::Regex(_))));
+        assert_eq!(cache.len(), 1);
+
+        let byte_vec = std::fs::read(PASSWD_PATH).unwrap();
+        let slice_vec = chunk_vec(&a, &byte_vec, MatchDisposition::Drop);
+
+        let f = File::open(PASSWD_PATH).unwrap();
+        let vec_vec: Vec<Vec<u8>> = ByteChunker::new_cached(f, PASSWD_PATT)
+            .unwrap()
+            .map(|res| res.unwrap())
+            .collect();
+        assert!(!RegexCache::global().is_empty());
+
+        ref_slice_cmp(&vec_vec, &slice_vec);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn per_pattern_disposition() {
+        let text = b"one two.three\nfour five.";
+        let patterns = [
+            (" ", MatchDisposition::Drop),
+            (r"\.", MatchDisposition::Append),
+            ("\n", MatchDisposition::Prepend),
+        ];
+
+        for size in [1, 3, 1024] {
+            let mut chunker = ByteChunker::from_patterns(Cursor::new(text), patterns.clone())
+                .unwrap()
+                .with_buffer_size(size);
+            let mut vec_vec: Vec<Vec<u8>> = Vec::new();
+            let mut pattern_vec: Vec<Option<usize>> = Vec::new();
+            while let Some(res) = chunker.next() {
+                vec_vec.push(res.unwrap());
+                pattern_vec.push(chunker.matched_pattern());
+            }
+
+            ref_slice_cmp(&vec_vec, &["one", "two.", "three", "\nfour", "five."]);
+            assert_eq!(vec_vec.len(), 5);
+            assert_eq!(&pattern_vec, &[Some(0), Some(1), Some(2), Some(0), Some(1)]);
+        }
+    }
+
+    #[test]
+    fn rev_chunker() {
+        let byte_vec = std::fs::read(PASSWD_PATH).unwrap();
+        let re = Regex::new(PASSWD_PATT).unwrap();
+
+        for mode in [
+            MatchDisposition::Drop,
+            MatchDisposition::Append,
+            MatchDisposition::Prepend,
+            MatchDisposition::Wrap,
+            MatchDisposition::Replace(b"|".to_vec()),
+        ] {
+            let mut slice_vec = chunk_vec(&re, &byte_vec, mode.clone());
+            if slice_vec.last().map(|v| v.is_empty()) == Some(true) {
+                slice_vec.pop();
+            }
+            slice_vec.reverse();
+
+            for size in [1, 7, 1024] {
+                let f = File::open(PASSWD_PATH).unwrap();
+                let vec_vec: Vec<Vec<u8>> = RevChunker::new(f, PASSWD_PATT)
+                    .unwrap()
+                    .with_buffer_size(size)
+                    .with_match(mode.clone())
+                    .map(|res| res.unwrap())
+                    .collect();
+
+                assert_eq!(vec_vec.len(), slice_vec.len());
+                ref_slice_cmp(&vec_vec, &slice_vec);
+            }
+        }
+
+        let chunks: Vec<Vec<u8>> = RevChunker::new(Cursor::new(b",a,,b,"), ",")
+            .unwrap()
+            .map(|res| res.unwrap())
+            .collect();
+        ref_slice_cmp(&chunks, &["b", "", "a", ""]);
+        assert_eq!(chunks.len(), 4);
+
+        // Records much longer than a block, with delimiters split across
+        // blocks.
+        let text = format!("{0};;;{0};{0}", "x".repeat(3000));
+        let mut forward: Vec<Vec<u8>> = ByteChunker::new(Cursor::new(&text), ";+")
+            .unwrap()
+            .map(|res| res.unwrap())
+            .collect();
+        forward.reverse();
+        let backward: Vec<Vec<u8>> = RevChunker::new(Cursor::new(&text), ";+")
+            .unwrap()
+            .with_buffer_size(7)
+            .map(|res| res.unwrap())
+            .collect();
+        assert_eq!(backward, forward);
+    }
+
+    #[test]
+    fn chunk_metadata() {
+        let byte_vec = std::fs::read(PASSWD_PATH).unwrap();
+        let re = Regex::new(PASSWD_PATT).unwrap();
+
+        for mode in [
+            MatchDisposition::Drop,
+            MatchDisposition::Append,
+            MatchDisposition::Prepend,
+        ] {
+            let f = File::open(PASSWD_PATH).unwrap();
+            let chunks: Vec<Chunk> = ByteChunker::new(f, PASSWD_PATT)
+                .unwrap()
+                .with_buffer_size(7)
+                .with_match(mode.clone())
+                .with_metadata()
+                .map(|res| res.unwrap())
+                .collect();
+
+            for chunk in chunks.iter() {
+                let (start, end) = (chunk.span.start as usize, chunk.span.end as usize);
+                assert_eq!(&byte_vec[start..end], &chunk.body[..]);
+                match &chunk.delimiter {
+                    Some(delim) => assert!(re.is_match(delim)),
+                    None => assert!(chunk.eof),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn frame_round_trip() {
+        let f = File::open(PASSWD_PATH).unwrap();
+        let chunks: Vec<Vec<u8>> = ByteChunker::new(f, PASSWD_PATT)
+            .unwrap()
+            .map(|res| res.unwrap())
+            .collect();
+
+        for prefix in [LengthPrefix::U32, LengthPrefix::Varint] {
+            let mut writer = FrameWriter::new(Vec::new(), prefix);
+            let n = writer.write_all(chunks.iter().map(Ok)).unwrap();
+            assert_eq!(n, chunks.len());
+            let framed = writer.into_inner();
+
+            let vec_vec: Vec<Vec<u8>> = FrameReader::new(Cursor::new(&framed), prefix)
+                .map(|res| res.unwrap())
+                .collect();
+            assert_eq!(&vec_vec, &chunks);
+
+            let mut truncated = FrameReader::new(Cursor::new(&framed[..framed.len() - 1]), prefix);
+            assert!(truncated.any(|res| res.is_err()));
+            assert!(truncated.next().is_none());
+        }
+
+        // A ten-byte varint with more than a u64's worth of bits.
+        let overlong = [&[0x80; 9][..], &[0x02]].concat();
+        let mut reader = FrameReader::new(Cursor::new(overlong), LengthPrefix::Varint);
+        assert!(matches!(reader.next(), Some(Err(_))));
+    }
+
+    #[test]
+    fn count_chunks() {
+        for mode in [
+            MatchDisposition::Drop,
+            MatchDisposition::Append,
+            MatchDisposition::Prepend,
+        ] {
+            let chunker = || {
+                ByteChunker::new(File::open(PASSWD_PATH).unwrap(), PASSWD_PATT)
+                    .unwrap()
+                    .with_buffer_size(13)
+                    .with_match(mode.clone())
+            };
+            assert_eq!(chunker().count_chunks().unwrap(), chunker().count());
+        }
+    }
+
+    #[test]
+    fn borrowed_chunks() {
+        let byte_vec = std::fs::read(PASSWD_PATH).unwrap();
+        let re = Regex::new(PASSWD_PATT).unwrap();
+        let slice_vec = chunk_vec(&re, &byte_vec, MatchDisposition::Prepend);
+
+        let mut vec_vec: Vec<Vec<u8>> = Vec::new();
+        ByteChunker::new(File::open(PASSWD_PATH).unwrap(), PASSWD_PATT)
+            .unwrap()
+            .with_match(MatchDisposition::Prepend)
+            .for_each_chunk(|chunk| vec_vec.push(chunk.to_vec()))
+            .unwrap();
+        ref_slice_cmp(&vec_vec, &slice_vec);
+
+        let total = ByteChunker::new(File::open(PASSWD_PATH).unwrap(), PASSWD_PATT)
+            .unwrap()
+            .with_match(MatchDisposition::Prepend)
+            .fold_chunks(0, |acc, chunk| acc + chunk.len())
+            .unwrap();
+        assert_eq!(total, byte_vec.len());
+    }
+
+    #[test]
+    fn chunk_index() {
+        let byte_vec = std::fs::read(TEST_PATH).unwrap();
+        let re = Regex::new(TEST_PATT).unwrap();
+        let slice_vec = chunk_vec(&re, &byte_vec, MatchDisposition::Append);
+
+        let index = ByteChunker::new(File::open(TEST_PATH).unwrap(), TEST_PATT)
+            .unwrap()
+            .with_buffer_size(7)
+            .with_match(MatchDisposition::Append)
+            .build_index()
+            .unwrap();
+        assert_eq!(index.len(), slice_vec.len());
+
+        let mut f = File::open(TEST_PATH).unwrap();
+        for n in (0..index.len()).rev() {
+            assert_eq!(
+                index.extract(&mut f, n).unwrap().unwrap(),
+                &slice_vec[n][..]
+            );
+        }
+        assert!(index.extract(&mut f, index.len()).is_none());
+
+        let odd: Vec<Vec<u8>> = index
+            .extract_many(f, (1..index.len() + 5).step_by(2))
+            .map(|res| res.unwrap())
+            .collect();
+        let expected: Vec<&[u8]> = slice_vec
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .map(|s| &s[..])
+            .collect();
+        ref_slice_cmp(&odd, &expected);
+
+        // The index holds where each chunk is in the source, whatever is
+        // done to it on the way out.
+        type Config = fn(ByteChunker<Cursor<&[u8]>>) -> ByteChunker<Cursor<&[u8]>>;
+        let cases: [(&[u8], &str, Config); 4] = [
+            (b"one;two;three", ";", |c| {
+                c.with_match(MatchDisposition::Replace(b"<>".to_vec()))
+            }),
+            (b" ab ;  cd ; e", ";", |c| c.trim_matches(" ").unwrap()),
+            (b"\xef\xbb\xbfone\ntwo", r"\n", |c| {
+                c.with_bom_stripping(true)
+            }),
+            (b"a;;;b;;c", ";;", |c| c.with_overlap(OverlapPolicy::Rescan)),
+        ];
+        for (text, pattern, config) in cases {
+            let chunker = || config(ByteChunker::new(Cursor::new(text), pattern).unwrap());
+            let spans: Vec<std::ops::Range<u64>> = chunker()
+                .with_metadata()
+                .map(|res| res.unwrap().span)
+                .collect();
+            let index = chunker().build_index().unwrap();
+            assert_eq!(index.spans().collect::<Vec<_>>(), spans);
+        }
+        let index = ByteChunker::new(Cursor::new(b"\xef\xbb\xbfone\ntwo"), r"\n")
+            .unwrap()
+            .with_bom_stripping(true)
+            .build_index()
+            .unwrap();
+        assert_eq!(index.span(0), Some(3..6));
+    }
+
+    #[test]
+    fn chunk_timing() {
+        use std::sync::{Arc, Mutex};
+
+        let timings = Arc::new(Mutex::new(Vec::new()));
+        let t = timings.clone();
+        let n = ByteChunker::new(File::open(PASSWD_PATH).unwrap(), PASSWD_PATT)
+            .unwrap()
+            .with_buffer_size(16)
+            .with_timing(move |timing| t.lock().unwrap().push(*timing))
+            .count();
+
+        let timings = timings.lock().unwrap();
+        assert_eq!(timings.len(), n);
+        let reads: usize = timings.iter().map(|t| t.reads).sum();
+        let len = std::fs::metadata(PASSWD_PATH).unwrap().len() as usize;
+        assert!(reads >= len / 16);
+    }
+
+    #[test]
+    fn in_place_adapter() {
+        struct Lower;
+
+        impl InPlaceAdapter for Lower {
+            fn adapt_in_place(&mut self, chunk: &mut Vec<u8>) {
+                chunk.make_ascii_lowercase();
+            }
+        }
+
+        let byte_vec = std::fs::read(TEST_PATH).unwrap();
+        let re = Regex::new(TEST_PATT).unwrap();
+        let slice_vec: Vec<Vec<u8>> = chunk_vec(&re, &byte_vec, MatchDisposition::Drop)
+            .iter()
+            .map(|s| s.to_ascii_lowercase())
+            .collect();
+
+        let vec_vec: Vec<Vec<u8>> = ByteChunker::new(File::open(TEST_PATH).unwrap(), TEST_PATT)
+            .unwrap()
+            .with_in_place_adapter(Lower)
+            .map(|res| res.unwrap())
+            .collect();
+        assert_eq!(vec_vec, slice_vec);
+    }
+
+    #[test]
+    fn parallel_chunker() {
+        for dispo in [
+            MatchDisposition::Drop,
+            MatchDisposition::Append,
+            MatchDisposition::Prepend,
+            MatchDisposition::Wrap,
+        ] {
+            let serial: Vec<Vec<u8>> = ByteChunker::new(File::open(TEST_PATH).unwrap(), TEST_PATT)
+                .unwrap()
+                .with_match(dispo.clone())
+                .map(|res| res.unwrap())
+                .collect();
+
+            for threads in 1..=7 {
+                let parallel: Vec<Vec<u8>> = ParallelChunker::new(TEST_PATH, TEST_PATT)
+                    .unwrap()
+                    .with_threads(threads)
+                    .with_match(dispo.clone())
+                    .map(|v| v)
+                    .unwrap()
+                    .map(|res| res.unwrap())
+                    .collect();
+                assert_eq!(parallel, serial, "{:?}, {} threads", dispo, threads);
+            }
+        }
+    }
+
+    #[test]
+    fn buffer_policy() {
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Debug)]
+        struct Spy(ShrinkToBaseline, Arc<Mutex<Vec<usize>>>);
+
+        impl BufferPolicy for Spy {
+            fn shrink(&mut self, len: usize, capacity: usize) -> Option<usize> {
+                self.1.lock().unwrap().push(capacity);
+                self.0.shrink(len, capacity)
+            }
+        }
+
+        let mut text = vec![b'x'; 200_000];
+        for _ in 0..100 {
+            text.extend_from_slice(b"\nshort");
+        }
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut lens = Vec::new();
+        ByteChunker::new(Cursor::new(&text), r"\n")
+            .unwrap()
+            .with_buffer_policy(Spy(ShrinkToBaseline::new(4096), seen.clone()))
+            .for_each_chunk(|chunk| lens.push(chunk.len()))
+            .unwrap();
+        assert_eq!(lens.len(), 101);
+        assert_eq!(lens[0], 200_000);
+
+        let seen = seen.lock().unwrap();
+        assert!(seen[0] >= 200_000);
+        assert!(seen[1..].iter().all(|&cap| cap <= 2 * 4096));
+    }
+
+    #[test]
+    fn checksum_chunker() {
+        assert!(matches!(
+            ChecksumChunker::new(Cursor::new(b""), r"\n"),
+            Err(RcErr::Checksum(ChecksumError::NoCaptureGroup))
+        ));
+
+        let byte_vec = std::fs::read(PASSWD_PATH).unwrap();
+        let mut text = Vec::new();
+        for line in byte_vec.split(|&b| b == b'\n') {
+            text.extend_from_slice(line);
+            text.extend_from_slice(format!("|{:08x}|", crc32(line)).as_bytes());
+        }
+        text.extend_from_slice(b"tail");
+
+        let results: Vec<Result<Vec<u8>, RcErr>> =
+            ChecksumChunker::new(Cursor::new(text), r"\|([0-9a-f]+)\|")
+                .unwrap()
+                .with_buffer_size(16)
+                .collect();
+        let (tail, lines) = results.split_last().unwrap();
+        for (res, line) in lines.iter().zip(byte_vec.split(|&b| b == b'\n')) {
+            assert_eq!(res.as_ref().unwrap(), line);
+        }
+        assert!(matches!(
+            tail,
+            Err(RcErr::Checksum(ChecksumError::Missing { .. }))
+        ));
+    }
+
+    #[test]
+    fn max_chunk_len() {
+        let byte_vec = std::fs::read(PASSWD_PATH).unwrap();
+
+        for mode in [MatchDisposition::Append, MatchDisposition::Prepend] {
+            for size in [3, 1024] {
+                let chunks: Vec<Chunk> = ByteChunker::new(Cursor::new(&byte_vec), PASSWD_PATT)
+                    .unwrap()
+                    .with_buffer_size(size)
+                    .with_match(mode.clone())
+                    .with_max_chunk_len(5)
+                    .with_metadata()
+                    .map(|res| res.unwrap())
+                    .collect();
+
+                assert!(chunks.iter().all(|c| c.body.len() <= 5));
+                assert!(chunks.iter().any(|c| c.forced));
+                let bodies: Vec<u8> = chunks.iter().flat_map(|c| c.body.clone()).collect();
+                assert_eq!(bodies, byte_vec);
+            }
+        }
+    }
+
+    #[test]
+    fn bom_sniffing() {
+        // A reader that returns at most three bytes at a time.
+        struct Trickle(Cursor<Vec<u8>>);
+        impl Read for Trickle {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let n = buf.len().min(3);
+                self.0.read(&mut buf[..n])
+            }
+        }
+
+        // Single-byte delimiters, so small reads can't split a match.
+        let patt = "[:\n]";
+        let text = String::from_utf8(std::fs::read(PASSWD_PATH).unwrap()).unwrap() + "🦀:x";
+        let expected: Vec<Vec<u8>> = ByteChunker::new(Cursor::new(text.as_bytes()), patt)
+            .unwrap()
+            .map(|res| res.unwrap())
+            .collect();
+
+        let mut utf16be = vec![0xfe, 0xff];
+        let mut utf32le = vec![0xff, 0xfe, 0, 0];
+        for c in text.encode_utf16() {
+            utf16be.extend_from_slice(&c.to_be_bytes());
+        }
+        for c in text.chars() {
+            utf32le.extend_from_slice(&(c as u32).to_le_bytes());
+        }
+        let mut utf8 = vec![0xef, 0xbb, 0xbf];
+        utf8.extend_from_slice(text.as_bytes());
+
+        for (encoded, encoding) in [
+            (utf16be, Encoding::Utf16Be),
+            (utf32le, Encoding::Utf32Le),
+            (utf8, Encoding::Utf8),
+        ] {
+            let mut chunker = ByteChunker::new(Trickle(Cursor::new(encoded)), patt)
+                .unwrap()
+                .with_bom_sniffing();
+            let vec_vec: Vec<Vec<u8>> = (&mut chunker).map(|res| res.unwrap()).collect();
+            assert_eq!(&vec_vec, &expected);
+            assert_eq!(chunker.into_inner().encoding(), Some(encoding));
+        }
+
+        // No BOM, but the encoding is known.
+        let utf16le: Vec<u8> = text.encode_utf16().flat_map(|c| c.to_le_bytes()).collect();
+        let mut chunker = ByteChunker::new(Trickle(Cursor::new(utf16le)), patt)
+            .unwrap()
+            .with_encoding(Encoding::Utf16Le);
+        let vec_vec: Vec<Vec<u8>> = (&mut chunker).map(|res| res.unwrap()).collect();
+        assert_eq!(&vec_vec, &expected);
+        assert_eq!(chunker.into_inner().encoding(), Some(Encoding::Utf16Le));
+    }
+
+    #[test]
+    fn replace_disposition() {
+        let byte_vec = std::fs::read(TEST_PATH).unwrap();
+        let re = Regex::new(TEST_PATT).unwrap();
+        let dispo = MatchDisposition::Replace(b"<>".to_vec());
+        let expected: Vec<Vec<u8>> = chunk_vec(&re, &byte_vec, dispo.clone())
+            .into_iter()
+            .map(Cow::into_owned)
+            .collect();
+
+        for size in [1, 5, 1024] {
+            let vec_vec: Vec<Vec<u8>> = ByteChunker::new(File::open(TEST_PATH).unwrap(), TEST_PATT)
+                .unwrap()
+                .with_buffer_size(size)
+                .with_match(dispo.clone())
+                .map(|res| res.unwrap())
+                .collect();
+            assert_eq!(vec_vec, expected);
+
+            let mut rev_vec: Vec<Vec<u8>> =
+                RevChunker::new(File::open(TEST_PATH).unwrap(), TEST_PATT)
+                    .unwrap()
+                    .with_buffer_size(size)
+                    .with_match(dispo.clone())
+                    .map(|res| res.unwrap())
+                    .collect();
+            rev_vec.reverse();
+            assert_eq!(rev_vec, expected);
+        }
+    }
+
+    #[test]
+    fn match_fn() {
+        let byte_vec = std::fs::read(TEST_PATH).unwrap();
+        let re = Regex::new(TEST_PATT).unwrap();
+        let pick = |delim: &[u8]| match delim[0] {
+            b'A'..=b'M' => MatchDisposition::Append,
+            _ => MatchDisposition::Drop,
+        };
+
+        let mut expected: Vec<&[u8]> = Vec::new();
+        let mut offs = 0;
+        for m in re.find_iter(&byte_vec) {
+            let end = match pick(m.as_bytes()) {
+                MatchDisposition::Append => m.end(),
+                _ => m.start(),
+            };
+            expected.push(&byte_vec[offs..end]);
+            offs = m.end();
+        }
+        expected.push(&byte_vec[offs..]);
+
+        for size in [1, 5, 1024] {
+            let vec_vec: Vec<Vec<u8>> = ByteChunker::new(File::open(TEST_PATH).unwrap(), TEST_PATT)
+                .unwrap()
+                .with_buffer_size(size)
+                .with_match_fn(pick)
+                .map(|res| res.unwrap())
+                .collect();
+            ref_slice_cmp(&vec_vec, &expected);
+
+            let mut rev_vec: Vec<Vec<u8>> =
+                RevChunker::new(File::open(TEST_PATH).unwrap(), TEST_PATT)
+                    .unwrap()
+                    .with_buffer_size(size)
+                    .with_match_fn(pick)
+                    .map(|res| res.unwrap())
+                    .collect();
+            rev_vec.reverse();
+            ref_slice_cmp(&rev_vec, &expected);
+        }
+    }
+
+    #[test]
+    fn custom_builder_order() {
+        let chunker = || ByteChunker::new(File::open(PASSWD_PATH).unwrap(), PASSWD_PATT).unwrap();
+        let before: Vec<String> = chunker()
+            .with_buffer_size(7)
+            .with_match(MatchDisposition::Append)
+            .with_max_chunk_len(9)
+            .with_adapter(StringAdapter::default())
+            .map(|res| res.unwrap())
+            .collect();
+        let after: Vec<String> = chunker()
+            .with_adapter(StringAdapter::default())
+            .with_buffer_size(7)
+            .with_match(MatchDisposition::Append)
+            .with_max_chunk_len(9)
+            .map(|res| res.unwrap())
+            .collect();
+        assert_eq!(before, after);
+        assert!(after.iter().all(|s| s.len() <= 9));
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn grapheme_safe_splits() {
+        use unicode_segmentation::UnicodeSegmentation;
+
+        // A family emoji (18 bytes), a combining accent, and a flag.
+        let family = "\u{1F469}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let text = format!("{} cafe\u{301} \u{1F1EF}\u{1F1F5}!", family).repeat(20);
+        let boundaries: Vec<usize> = text.grapheme_indices(true).map(|(n, _)| n).collect();
+
+        for size in [1, 3, 1024] {
+            let chunks: Vec<String> = ByteChunker::new(Cursor::new(text.as_bytes()), "\n")
+                .unwrap()
+                .with_buffer_size(size)
+                .with_max_chunk_len(21)
+                .with_grapheme_safe_splits(true)
+                .with_adapter(StringAdapter::default())
+                .map(|res| res.unwrap())
+                .collect();
+
+            assert_eq!(chunks.concat(), text);
+            let mut offset = 0;
+            for chunk in chunks.iter() {
+                assert!(chunk.len() <= 21);
+                assert!(boundaries.contains(&offset), "split at {}", offset);
+                offset += chunk.len();
+            }
+        }
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn unicode_words() {
+        use unicode_segmentation::UnicodeSegmentation;
+
+        let text = "\"Well,\" she said -- 東京へ行きます!\n  ...naïve café 42 times?  ".repeat(10);
+        let expected: Vec<&str> = text.unicode_words().collect();
+
+        for size in [1, 2, 7, 1024] {
+            let words: Vec<String> = StringChunker::unicode_words(Cursor::new(text.as_bytes()))
+                .with_buffer_size(size)
+                .map(|res| res.unwrap())
+                .collect();
+            assert_eq!(words, expected, "buffer size {}", size);
+        }
+    }
+
+    #[test]
+    fn trim_matches() {
+        let text = b"[a] , [ b ],[[c]]  ,, [] ,d";
+        let expected: Vec<&[u8]> = vec![b"a", b"b", b"c", b"", b"", b"d"];
+
+        for size in [1, 4, 1024] {
+            let chunks: Vec<Chunk> = ByteChunker::new(Cursor::new(text), ",")
+                .unwrap()
+                .with_buffer_size(size)
+                .trim_matches(r"[\[\] ]+")
+                .unwrap()
+                .with_metadata()
+                .map(|res| res.unwrap())
+                .collect();
+            let bodies: Vec<&[u8]> = chunks.iter().map(|c| &c.body[..]).collect();
+            assert_eq!(bodies, expected);
+            for chunk in chunks.iter() {
+                let span = (chunk.span.start as usize)..(chunk.span.end as usize);
+                assert_eq!(&text[span], &chunk.body[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn bom_stripping() {
+        let cases: [(&[u8], Vec<&[u8]>); 4] = [
+            (b"\xef\xbb\xbfa;b", vec![b"a", b"b"]),
+            (b"\xef\xbb\xbf", vec![]),
+            (b"\xef\xbb", vec![b"\xef\xbb"]),
+            (b"a;\xef\xbb\xbfb", vec![b"a", b"\xef\xbb\xbfb"]),
+        ];
+        for (text, expected) in cases.iter() {
+            for size in [1, 2, 1024] {
+                let chunks: Vec<Chunk> = ByteChunker::new(Cursor::new(text), ";")
+                    .unwrap()
+                    .with_buffer_size(size)
+                    .with_bom_stripping(true)
+                    .with_metadata()
+                    .map(|res| res.unwrap())
+                    .collect();
+                let bodies: Vec<&[u8]> = chunks.iter().map(|c| &c.body[..]).collect();
+                assert_eq!(&bodies, expected);
+                for chunk in chunks.iter() {
+                    let span = (chunk.span.start as usize)..(chunk.span.end as usize);
+                    assert_eq!(&text[span], &chunk.body[..]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn json_documents() {
+        let text = b"{\"a\": \"\\\\\", \"b\": [1, {}]}\n\n  42 true\t\"x y\"\r\n[\n]{}\n";
+        let expected: Vec<&[u8]> = vec![
+            b"{\"a\": \"\\\\\", \"b\": [1, {}]}",
+            b"42",
+            b"true",
+            b"\"x y\"",
+            b"[\n]",
+            b"{}",
+        ];
+        for size in [1, 3, 1024] {
+            let docs: Vec<Vec<u8>> = ByteChunker::json_documents(Cursor::new(text))
+                .with_buffer_size(size)
+                .map(|res| res.unwrap())
+                .collect();
+            assert_eq!(docs, expected);
+        }
+    }
+
+    #[test]
+    fn log_records() {
+        let text = b"preamble\r\n# one\n  more\n\n# two\n#three\r\n# four\n";
+        let expected: Vec<&[u8]> =
+            vec![b"preamble", b"# one\n  more\n", b"# two\n#three", b"# four"];
+        for size in [1, 4, 1024] {
+            let records: Vec<Vec<u8>> = ByteChunker::log_records(Cursor::new(text), "# ")
+                .unwrap()
+                .with_buffer_size(size)
+                .map(|res| res.unwrap())
+                .collect();
+            assert_eq!(records, expected);
+        }
+    }
+
+    #[test]
+    fn sequence_records() {
+        let fasta = b">a\nAC\nGT\r\n>b desc\n\n>c\nT";
+        let fastq = b"@a\nAC\n+\n@@\r\n@b\nG\n+b\n#\n@c\nT\n+\n!";
+        for size in [1, 2, 5, 1024] {
+            let records: Vec<Vec<u8>> = ByteChunker::fasta_records(Cursor::new(fasta))
+                .with_buffer_size(size)
+                .map(|res| res.unwrap())
+                .collect();
+            assert_eq!(records, [&b">a\nAC\nGT"[..], b">b desc\n", b">c\nT"]);
+
+            let records: Vec<Vec<u8>> = ByteChunker::fastq_records(Cursor::new(fastq))
+                .with_buffer_size(size)
+                .map(|res| res.unwrap())
+                .collect();
+            assert_eq!(
+                records,
+                [&b"@a\nAC\n+\n@@"[..], b"@b\nG\n+b\n#", b"@c\nT\n+\n!"]
+            );
+        }
+    }
+
+    #[test]
+    fn warc_records() {
+        let records: [&[u8]; 3] = [
+            b"WARC/1.1\r\nContent-Length: 10\r\n\r\n\r\n\r\nWARC/1",
+            b"WARC/1.1\r\nWARC-Type: warcinfo\r\n\r\n",
+            b"WARC/1.1\r\ncontent-length:  3 \r\n\r\nabc",
+        ];
+        let text = records.join(&b"\r\n\r\n"[..]);
+        for size in [1, 7, 1024] {
+            let chunks: Vec<Vec<u8>> = ByteChunker::warc_records(Cursor::new(&text))
+                .with_buffer_size(size)
+                .map(|res| res.unwrap())
+                .collect();
+            assert_eq!(chunks, records);
+        }
+    }
+
+    #[test]
+    fn markdown_sections() {
+        let text = b"pre\r\n# A\r\n#tag\n````\n# x\n```\n````\n  ## B\n####### C\n### D";
+        let cases: [(usize, Vec<&[u8]>); 2] = [
+            (
+                1,
+                vec![
+                    b"pre\r\n",
+                    b"# A\r\n#tag\n````\n# x\n```\n````\n  ## B\n####### C\n### D",
+                ],
+            ),
+            (
+                0,
+                vec![
+                    b"pre\r\n",
+                    b"# A\r\n#tag\n````\n# x\n```\n````\n",
+                    b"  ## B\n####### C\n",
+                    b"### D",
+                ],
+            ),
+        ];
+        for (level, expected) in cases.iter() {
+            for size in [1, 5, 1024] {
+                let sections: Vec<Vec<u8>> =
+                    ByteChunker::markdown_sections(Cursor::new(text), *level)
+                        .with_buffer_size(size)
+                        .map(|res| res.unwrap())
+                        .collect();
+                assert_eq!(&sections, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn sql_statements() {
+        let text = b"SELECT ';' AS \"x;\"; -- a;\nSELECT $$;$$, $1, a$b$;c$;;\n\
+            /* ; */ SELECT `;`;\n";
+        let expected: Vec<&[u8]> = vec![
+            b"SELECT ';' AS \"x;\"",
+            b"-- a;\nSELECT $$;$$, $1, a$b$",
+            b"c$",
+            b"",
+            b"/* ; */ SELECT `;`",
+        ];
+        for size in [1, 4, 1024] {
+            let statements: Vec<Vec<u8>> = ByteChunker::sql_statements(Cursor::new(text))
+                .with_buffer_size(size)
+                .map(|res| res.unwrap())
+                .collect();
+            assert_eq!(statements, expected);
+        }
+    }
+
+    #[test]
+    fn null_delimited() {
+        let text = b"one\0two\r\n\0\0three";
+        for size in [1, 4, 1024] {
+            let chunker = ByteChunker::null_delimited(Cursor::new(text)).with_buffer_size(size);
+            let mut writer = JoinWriter::null(Vec::new());
+            assert_eq!(writer.write_all(chunker).unwrap(), 4);
+            assert_eq!(writer.into_inner(), b"one\0two\r\n\0\0three\0");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn misbehaving_readers() {
+        let byte_vec = std::fs::read(TEST_PATH).unwrap();
+        let re = Regex::new(TEST_PATT).unwrap();
+        let slice_vec = chunk_vec(&re, &byte_vec, MatchDisposition::Drop);
+
+        for seed in 1..4 {
+            let slow = SlowReader::new(Cursor::new(&byte_vec)).with_random_reads(1, 7, seed);
+            let chaos = ChaosReader::new(slow, seed)
+                .with_would_block(0.2)
+                .with_interrupted(0.2)
+                .with_stalls(0.01, std::time::Duration::from_micros(10));
+            let vec_vec: Vec<Vec<u8>> = ByteChunker::new(chaos, TEST_PATT)
+                .unwrap()
+                .map(|res| res.unwrap())
+                .collect();
+            ref_slice_cmp(&vec_vec, &slice_vec);
+        }
+
+        let chaos = ChaosReader::new(Cursor::new(&byte_vec), 0)
+            .with_error_after(100, std::io::ErrorKind::Other);
+        let mut chunker = ByteChunker::new(chaos, TEST_PATT).unwrap();
+        assert!(chunker.by_ref().any(|res| res.is_err()));
+        assert!(chunker.next().is_none());
+        assert_eq!(chunker.into_inner().delivered(), 100);
+    }
+
+    #[test]
+    fn read_replay() {
+        // A reader that returns 1, 2, 3, ... bytes from successive reads.
+        struct Uneven(Cursor<Vec<u8>>, usize);
+        impl Read for Uneven {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.1 += 1;
+                let n = buf.len().min(self.1);
+                self.0.read(&mut buf[..n])
+            }
+        }
+
+        let byte_vec = std::fs::read(PASSWD_PATH).unwrap();
+        let mut chunker = ByteChunker::new(
+            ReadRecorder::new(Uneven(Cursor::new(byte_vec.clone()), 0)),
+            "[:\n]",
+        )
+        .unwrap()
+        .with_buffer_size(64);
+        let expected: Vec<Vec<u8>> = (&mut chunker).map(|res| res.unwrap()).collect();
+        let sizes = chunker.into_inner().into_sizes();
+        assert_eq!(sizes.iter().sum::<usize>(), byte_vec.len());
+        assert_eq!(sizes[..5], [1, 2, 3, 4, 5]);
+
+        let replay = ReadRecorder::new(ReplayReader::new(Cursor::new(byte_vec), &sizes));
+        let mut chunker = ByteChunker::new(replay, "[:\n]")
+            .unwrap()
+            .with_buffer_size(64);
+        let replayed: Vec<Vec<u8>> = (&mut chunker).map(|res| res.unwrap()).collect();
+        assert_eq!(replayed, expected);
+        assert_eq!(chunker.into_inner().sizes(), &sizes[..]);
+    }
+
+    #[test]
+    fn derived_traits() {
+        let dispos: std::collections::HashSet<MatchDisposition> = [
+            MatchDisposition::Drop,
+            MatchDisposition::Replace(b"\n".to_vec()),
+            MatchDisposition::Replace(b"\n".to_vec()),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(dispos.len(), 2);
+
+        let chunks: Vec<Chunk> = ByteChunker::new(Cursor::new(b"a, b"), ", ")
+            .unwrap()
+            .with_metadata()
+            .map(|res| res.unwrap())
+            .collect();
+        let first = Chunk {
+            body: b"a".to_vec(),
+            delimiter: Some(b", ".to_vec()),
+            span: 0..1,
+            char_span: None,
+            eof: false,
+            forced: false,
+            seq: 0,
+            gap: false,
+        };
+        assert_eq!(chunks[0], first);
+        assert_ne!(chunks[1], first);
+
+        #[cfg(feature = "serde")]
+        {
+            fn is_serde<T: serde::Serialize + serde::de::DeserializeOwned>() {}
+            is_serde::<MatchDisposition>();
+            is_serde::<Chunk>();
+            is_serde::<ChecksumError>();
+            is_serde::<ChunkIndex>();
+            is_serde::<ChunkTiming>();
+        }
+    }
+
+    #[test]
+    fn isolated_panics() {
+        let text = b"ok\nboom\nok\n";
+        let mut chunker = ByteChunker::new(Cursor::new(text), r"\n")
+            .unwrap()
+            .with_adapter(|res: Result<Vec<u8>, RcErr>| {
+                let v = res.ok()?;
+                assert!(v != b"boom", "malformed record");
+                Some(Ok(v))
+            })
+            .isolate_panics();
+
+        assert_eq!(chunker.next().unwrap().unwrap(), b"ok");
+        match chunker.next() {
+            Some(Err(RcErr::Panic(msg))) => assert_eq!(msg, "malformed record"),
+            x => panic!("expected a panic error, got {:?}", x),
+        }
+        assert_eq!(chunker.next().unwrap().unwrap(), b"ok");
+        assert!(chunker.next().is_none());
+
+        // An adapter that panics at the end of the source only does so once.
+        struct EndPanicker;
+        impl Adapter for EndPanicker {
+            type Item = Result<(), RcErr>;
+            fn adapt(&mut self, v: Option<Result<Vec<u8>, RcErr>>) -> Option<Self::Item> {
+                v.map(|_| Ok(())).or_else(|| panic!("at the end"))
+            }
+        }
+        let mut adapter = CatchPanics::new(EndPanicker);
+        assert!(matches!(adapter.adapt(None), Some(Err(RcErr::Panic(_)))));
+        assert!(adapter.adapt(None).is_none());
+    }
+
+    #[test]
+    fn chunk_joiner() {
+        let text = b"alpha\n\nbeta\ngamma";
+        let chunker = ByteChunker::new(Cursor::new(text), r"\n").unwrap();
+        let mut joiner = ChunkJoiner::new(Vec::new(), "\n");
+        assert_eq!(joiner.write_all(chunker).unwrap(), 4);
+        assert_eq!(joiner.count(), 4);
+        assert_eq!(joiner.finish().unwrap(), text);
+
+        let mut joiner = ChunkJoiner::new(Vec::new(), b"\0")
+            .with_trailing_separator(true)
+            .with_flush_per_chunk(true);
+        joiner.write_chunk(b"a").unwrap();
+        assert_eq!(joiner.get_ref(), b"a\0");
+        joiner.write_chunk(b"").unwrap();
+        assert_eq!(joiner.into_inner(), b"a\0\0");
+    }
+
+    #[test]
+    fn typed_adapter_errors() {
+        #[derive(Debug)]
+        enum ParseErr {
+            Chunker(RcErr),
+            Empty,
+        }
+        impl From<RcErr> for ParseErr {
+            fn from(e: RcErr) -> Self {
+                ParseErr::Chunker(e)
+            }
+        }
+
+        let text = b"a;;b;boom";
+        let results: Vec<Result<usize, ParseErr>> = ByteChunker::new(Cursor::new(text), ";")
+            .unwrap()
+            .with_try_adapter(|v: Vec<u8>| match v.len() {
+                0 => Err(ParseErr::Empty),
+                4 => panic!("boom"),
+                n => Ok(n),
+            })
+            .isolate_panics()
+            .collect();
+
+        assert_eq!(results.len(), 4);
+        assert!(matches!(results[0], Ok(1)));
+        assert!(matches!(results[1], Err(ParseErr::Empty)));
+        assert!(matches!(results[2], Ok(1)));
+        assert!(matches!(&results[3], Err(ParseErr::Chunker(RcErr::Panic(msg))) if msg == "boom"));
+    }
+
+    #[test]
+    #[cfg(all(feature = "bytes", feature = "bstr", feature = "smallvec"))]
+    fn output_buffers() {
+        use bstr::BString;
+        use bytes::Bytes;
+        use smallvec::SmallVec;
+
+        let text = "a,bb,ccc";
+        let bytes: Vec<Bytes> = ByteChunker::new(Cursor::new(text), ",")
+            .unwrap()
+            .with_output::<Bytes>()
+            .map(|res| res.unwrap())
+            .collect();
+        assert_eq!(bytes, [Bytes::from_static(b"a"), "bb".into(), "ccc".into()]);
+
+        let strings: Vec<BString> = ByteChunker::new(Cursor::new(text), ",")
+            .unwrap()
+            .with_output::<BString>()
+            .map(|res| res.unwrap())
+            .collect();
+        assert_eq!(strings, ["a", "bb", "ccc"]);
+
+        let small: Vec<SmallVec<[u8; 4]>> = ByteChunker::new(Cursor::new(text), ",")
+            .unwrap()
+            .with_output::<SmallVec<[u8; 4]>>()
+            .map(|res| res.unwrap())
+            .collect();
+        assert_eq!(&small[2][..], b"ccc");
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn infallible_chunkers() {
+        let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new("a\nb\n"), r"\n")
+            .unwrap()
+            .infallible()
+            .collect();
+        assert_eq!(chunks, [b"a", b"b"]);
+
+        let flaky = ChaosReader::new(&b"one;two;three"[..], 7)
+            .with_error_after(5, std::io::ErrorKind::ConnectionReset);
+        let chunks: Vec<Vec<u8>> = ByteChunker::new(flaky, ";")
+            .unwrap()
+            .ignoring_errors()
+            .collect();
+        assert_eq!(chunks, [&b"one"[..], b"two", b"three"]);
+    }
+
+    #[test]
+    fn lending_chunker() {
+        let byte_vec = std::fs::read(TEST_PATH).unwrap();
+        let re = Regex::new(TEST_PATT).unwrap();
+        let slice_vec = chunk_vec(&re, &byte_vec, MatchDisposition::Drop);
+
+        let mut lender = ByteChunker::new(File::open(TEST_PATH).unwrap(), TEST_PATT)
+            .unwrap()
+            .with_buffer_size(64)
+            .lending();
+        let mut n = 0;
+        while let Some(res) = lender.next() {
+            assert_eq!(res.unwrap(), &slice_vec[n][..]);
+            n += 1;
+        }
+        assert_eq!(n, slice_vec.len());
+
+        // Replacements go through the scratch buffer, and the chunker can
+        // be taken back partway through.
+        let mut lender = ByteChunker::new(Cursor::new(" a ; b ; c "), ";")
+            .unwrap()
+            .with_match(MatchDisposition::Replace(b"!".to_vec()))
+            .trim_matches(" ")
+            .unwrap()
+            .lending();
+        assert_eq!(lender.next().unwrap().unwrap(), b"a!");
+        let rest: Vec<Vec<u8>> = lender.into_inner().map(|res| res.unwrap()).collect();
+        assert_eq!(rest, [&b"b!"[..], b"c"]);
+    }
+
+    #[test]
+    fn builder_conflicts() {
+        let builder = ChunkerBuilder::new(",")
+            .with_match(MatchDisposition::Append)
+            .with_match_fn(|_| MatchDisposition::Drop);
+        assert_eq!(builder.validate(), Err(ConfigError::DispositionAndMatchFn));
+        let res = builder.build(Cursor::new("a,b"));
+        assert!(matches!(
+            res,
+            Err(RcErr::Config(ConfigError::DispositionAndMatchFn))
+        ));
+
+        let chunks: Vec<Vec<u8>> = ChunkerBuilder::new(",")
+            .with_match(MatchDisposition::Append)
+            .trim_matches(" ")
+            .build(Cursor::new(" a, b "))
+            .unwrap()
+            .map(|res| res.unwrap())
+            .collect();
+        assert_eq!(chunks, [&b"a,"[..], b"b"]);
+
+        let res = ChunkerBuilder::new(",")
+            .trim_matches("(")
+            .build(Cursor::new(""));
+        assert!(matches!(res, Err(RcErr::Regex(_))));
+    }
+
+    #[test]
+    fn capture_maps() {
+        let text = "a<n=1>b<n=2 x=y>c";
+        let mut chunker = CaptureChunker::new(Cursor::new(text), r"<n=(?P<n>\d)(?: x=(\w))?>")
+            .unwrap()
+            .with_buffer_size(3)
+            .with_match(MatchDisposition::Append);
+
+        let (body, caps) = chunker.next().unwrap().unwrap();
+        assert_eq!(
+            (body, caps.len(), &caps["n"][..]),
+            (b"a<n=1>".to_vec(), 1, &b"1"[..])
+        );
+        // Unnamed groups are left out.
+        let (body, caps) = chunker.next().unwrap().unwrap();
+        assert_eq!(
+            (body, caps.len(), &caps["n"][..]),
+            (b"b<n=2 x=y>".to_vec(), 1, &b"2"[..])
+        );
+        let (body, caps) = chunker.next().unwrap().unwrap();
+        assert_eq!((body, caps.is_empty()), (b"c".to_vec(), true));
+        assert!(chunker.next().is_none());
+    }
+
+    #[test]
+    fn sequence_gaps() {
+        use std::collections::VecDeque;
+
+        /* Yields each of its parts in a separate read; `None` is an error. */
+        struct Flaky(VecDeque<Option<&'static [u8]>>);
+
+        impl Read for Flaky {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                match self.0.pop_front() {
+                    Some(Some(part)) => {
+                        buf[..part.len()].copy_from_slice(part);
+                        Ok(part.len())
+                    }
+                    Some(None) => Err(std::io::Error::other("flaky")),
+                    None => Ok(0),
+                }
+            }
+        }
+
+        let parts = || Flaky([Some(&b"a\nb\n"[..]), None, Some(b"c\n"), Some(b"d")].into());
+        let summary = |res: Result<Chunk, RcErr>| res.map(|c| (c.body, c.seq, c.gap)).ok();
+
+        let chunks: Vec<_> = ByteChunker::new(parts(), r"\n")
+            .unwrap()
+            .on_error(ErrorResponse::Continue)
+            .with_metadata()
+            .map(summary)
+            .collect();
+        assert_eq!(
+            chunks,
+            [
+                Some((b"a".to_vec(), 0, false)),
+                Some((b"b".to_vec(), 1, false)),
+                None,
+                Some((b"c".to_vec(), 2, true)),
+                Some((b"d".to_vec(), 3, false)),
+            ]
+        );
+
+        let chunks: Vec<_> = ByteChunker::new(parts(), r"\n")
+            .unwrap()
+            .on_error(ErrorResponse::Ignore)
+            .with_metadata()
+            .map(summary)
+            .collect();
+        assert_eq!(chunks[2], Some((b"c".to_vec(), 2, true)));
+        assert_eq!(chunks.len(), 4);
+    }
+
+    #[test]
+    fn spans_under_dispositions() {
+        let text = b"one;;two; three;four";
+        let dispos = [
+            MatchDisposition::Drop,
+            MatchDisposition::Append,
+            MatchDisposition::Prepend,
+            MatchDisposition::Replace(b"|".to_vec()),
+        ];
+        for dispo in dispos {
+            for size in [1, 3, 1024] {
+                let chunks: Vec<Chunk> = ByteChunker::new(Cursor::new(text), "; ?")
+                    .unwrap()
+                    .with_match(dispo.clone())
+                    .with_buffer_size(size)
+                    .with_metadata()
+                    .map(Result::unwrap)
+                    .collect();
+                // Every byte of the source belongs to exactly one chunk or
+                // one dropped delimiter, in order.
+                let mut end = 0;
+                for chunk in chunks.iter() {
+                    let span = chunk.span.start as usize..chunk.span.end as usize;
+                    assert!(span.start >= end, "{:?}: {:?}", dispo, chunk);
+                    end = span.end;
+                    let source = &text[span];
+                    match &dispo {
+                        MatchDisposition::Replace(r) if !chunk.eof => {
+                            assert_eq!([source, &r[..]].concat(), chunk.body)
+                        }
+                        _ => assert_eq!(source, &chunk.body[..], "{:?}", dispo),
+                    }
+                }
+                assert_eq!(end, text.len());
+            }
+        }
+
+        // Prepended delimiters are counted where they are in the source.
+        let chunks: Vec<Chunk> = ByteChunker::new(Cursor::new(text), "; ?")
+            .unwrap()
+            .with_match(MatchDisposition::Prepend)
+            .with_metadata()
+            .map(Result::unwrap)
+            .collect();
+        let spans: Vec<_> = chunks.iter().map(|c| c.span.clone()).collect();
+        assert_eq!(spans, [0..3, 3..4, 4..8, 8..15, 15..20]);
+
+        // Trimmed bytes aren't part of the span, and neither are those of
+        // a stripped byte-order mark.
+        let chunks: Vec<Chunk> = ByteChunker::new(Cursor::new("\u{feff} a ,b"), ",")
+            .unwrap()
+            .with_bom_stripping(true)
+            .trim_matches(" ")
+            .unwrap()
+            .with_metadata()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            (chunks[0].span.clone(), chunks[1].span.clone()),
+            (4..5, 7..8)
+        );
+    }
+
+    #[test]
+    fn match_policies() {
+        let literals = ["=", ":=", "=="];
+        fn split<B: Boundary + Send + 'static>(fence: B) -> Vec<Vec<u8>> {
+            ByteChunker::from_boundary(Cursor::new(b"a=b:=c==d"), fence)
+                .map(Result::unwrap)
+                .collect()
+        }
+
+        let first = LiteralBoundary::new(literals).unwrap();
+        assert_eq!(split(first), [&b"a"[..], b"b", b"c", b"", b"d"]);
+        let longest = LiteralBoundary::new_with_policy(literals, MatchPolicy::Longest).unwrap();
+        assert_eq!(split(longest), [&b"a"[..], b"b", b"c", b"d"]);
+
+        // The winning pattern's disposition is the one applied.
+        let patterns = [
+            ("=", MatchDisposition::Drop),
+            ("==", MatchDisposition::Append),
+        ];
+        let fence = MultiRegex::new(patterns)
+            .unwrap()
+            .with_policy(MatchPolicy::Longest);
+        assert_eq!(split(fence), [&b"a"[..], b"b:", b"c==", b"d"]);
+    }
+
+    #[test]
+    fn overlap_policies() {
+        let text = b"xaaayaaaaz";
+        let check = |policy, bodies: &[&[u8]], spans: &[std::ops::Range<u64>]| {
+            for size in [1, 2, 1024] {
+                let chunks: Vec<Chunk> = ByteChunker::new(Cursor::new(text), "aa")
+                    .unwrap()
+                    .with_overlap(policy)
+                    .with_buffer_size(size)
+                    .with_metadata()
+                    .map(Result::unwrap)
+                    .collect();
+                let got: Vec<&[u8]> = chunks.iter().map(|c| &c.body[..]).collect();
+                assert_eq!(got, bodies, "{:?}, {}", policy, size);
+                let got: Vec<_> = chunks.iter().map(|c| c.span.clone()).collect();
+                assert_eq!(got, spans, "{:?}, {}", policy, size);
+            }
+        };
+        check(
+            OverlapPolicy::Skip,
+            &[b"x", b"ay", b"", b"z"],
+            &[0..1, 3..5, 7..7, 9..10],
+        );
+        check(
+            OverlapPolicy::Rescan,
+            &[b"x", b"", b"y", b"", b"", b"z"],
+            &[0..1, 2..2, 4..5, 6..6, 7..7, 9..10],
+        );
+
+        // The tail of a trailing delimiter doesn't make a final chunk.
+        let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new("baaa"), "aa")
+            .unwrap()
+            .with_overlap(OverlapPolicy::Rescan)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(chunks, [&b"b"[..], b""]);
+
+        // Kept delimiters aren't rescanned.
+        let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new("baaab"), "aa")
+            .unwrap()
+            .with_match(MatchDisposition::Append)
+            .with_overlap(OverlapPolicy::Rescan)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(chunks, [&b"baa"[..], b"ab"]);
+    }
+
+    #[test]
+    fn wrap_disposition() {
+        let byte_vec = std::fs::read(PASSWD_PATH).unwrap();
+        // A greedy pattern could match differently across reads.
+        let re = Regex::new(r"\n").unwrap();
+        let slice_vec = chunk_vec(&re, &byte_vec, MatchDisposition::Wrap);
+        for size in [1, 7, 1024] {
+            let vec_vec: Vec<Vec<u8>> = ByteChunker::new(File::open(PASSWD_PATH).unwrap(), r"\n")
+                .unwrap()
+                .with_match(MatchDisposition::Wrap)
+                .with_buffer_size(size)
+                .map(|res| res.unwrap())
+                .collect();
+            assert_eq!(vec_vec.len(), slice_vec.len());
+            ref_slice_cmp(&vec_vec, &slice_vec);
+        }
+
+        // Each chunk's span covers both its delimiters, so neighbours overlap.
+        let text = b"```a```b```c";
+        let chunks: Vec<Chunk> = ByteChunker::new(Cursor::new(text), "```")
+            .unwrap()
+            .with_match(MatchDisposition::Wrap)
+            .with_metadata()
+            .map(Result::unwrap)
+            .collect();
+        let bodies: Vec<&[u8]> = chunks.iter().map(|c| &c.body[..]).collect();
+        assert_eq!(bodies, [&b"```"[..], b"```a```", b"```b```", b"```c"]);
+        let spans: Vec<_> = chunks.iter().map(|c| c.span.clone()).collect();
+        assert_eq!(spans, [0..3, 0..7, 4..11, 8..12]);
+
+        // The opening delimiter counts toward the length limit.
+        let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new("a|bcdef|"), r"\|")
+            .unwrap()
+            .with_match(MatchDisposition::Wrap)
+            .with_max_chunk_len(4)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(chunks, [&b"a|"[..], b"|bcd", b"ef|"]);
+    }
+
+    #[test]
+    fn min_repeats() {
+        let text = b"a--b----c---d-";
+        let byte_vec = std::fs::read(PASSWD_PATH).unwrap();
+        let re = Regex::new(r"::+").unwrap();
+        let slice_vec = chunk_vec(&re, &byte_vec, MatchDisposition::Append);
+        for size in [1, 2, 1024] {
+            let chunks: Vec<Chunk> = ByteChunker::new(Cursor::new(text), "-")
+                .unwrap()
+                .with_min_repeats(3)
+                .with_buffer_size(size)
+                .with_metadata()
+                .map(Result::unwrap)
+                .collect();
+            let bodies: Vec<&[u8]> = chunks.iter().map(|c| &c.body[..]).collect();
+            assert_eq!(bodies, [&b"a--b"[..], b"c", b"d-"], "{}", size);
+            let delims: Vec<_> = chunks.iter().map(|c| c.delimiter.as_deref()).collect();
+            assert_eq!(delims, [Some(&b"----"[..]), Some(b"---"), None]);
+
+            // The same as a regex with the repetition written out.
+            let vec_vec: Vec<Vec<u8>> = ByteChunker::new(File::open(PASSWD_PATH).unwrap(), ":")
+                .unwrap()
+                .with_min_repeats(2)
+                .with_match(MatchDisposition::Append)
+                .with_buffer_size(size)
+                .map(|res| res.unwrap())
+                .collect();
+            assert_eq!(vec_vec.len(), slice_vec.len());
+            ref_slice_cmp(&vec_vec, &slice_vec);
+        }
+
+        // A run at the very end of the source is still a delimiter.
+        let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new("x\n\ny\n\n"), r"\n")
+            .unwrap()
+            .with_min_repeats(2)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(chunks, [&b"x"[..], b"y"]);
+    }
+
+    #[test]
+    fn split_fn_vetoes() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let text = br#"a,"b,c",d,"e,,f""#;
+        let quoted = |chunk: &[u8]| chunk.iter().filter(|&&b| b == b'"').count() % 2 == 1;
+        for dispo in [MatchDisposition::Drop, MatchDisposition::Append] {
+            for size in [1, 3, 1024] {
+                let calls = Arc::new(AtomicUsize::new(0));
+                let counter = calls.clone();
+                let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new(text), ",")
+                    .unwrap()
+                    .with_match(dispo.clone())
+                    .with_buffer_size(size)
+                    .with_split_fn(move |chunk, delim| {
+                        assert_eq!(delim, b",");
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        !quoted(chunk)
+                    })
+                    .collect::<Result<_, _>>()
+                    .unwrap();
+                let expected: [&[u8]; 4] = match dispo {
+                    MatchDisposition::Append => [b"a,", br#""b,c","#, b"d,", br#""e,,f""#],
+                    _ => [b"a", br#""b,c""#, b"d", br#""e,,f""#],
+                };
+                assert_eq!(chunks, expected, "{:?}, {}", dispo, size);
+                // Each delimiter is considered once.
+                assert_eq!(calls.load(Ordering::SeqCst), 6);
+            }
+        }
+    }
+
+    #[test]
+    fn min_gap() {
+        let text = b";A;B first;C;D second;;EFG";
+        for dispo in [MatchDisposition::Drop, MatchDisposition::Prepend] {
+            for size in [1, 3, 1024] {
+                let chunks: Vec<Chunk> = ByteChunker::new(Cursor::new(text), ";")
+                    .unwrap()
+                    .with_match(dispo.clone())
+                    .with_min_gap(4)
+                    .with_buffer_size(size)
+                    .with_metadata()
+                    .map(Result::unwrap)
+                    .collect();
+                let bodies: Vec<&[u8]> = chunks.iter().map(|c| &c.body[..]).collect();
+                let expected: [&[u8]; 3] = match dispo {
+                    MatchDisposition::Prepend => [b";A;B first", b";C;D second", b";;EFG"],
+                    _ => [b";A;B first", b"C;D second", b";EFG"],
+                };
+                assert_eq!(bodies, expected, "{:?}, {}", dispo, size);
+            }
+        }
+    }
+
+    #[test]
+    fn anchored_splits() {
+        let text = b"a\n\n---b---c\n\n---d";
+        for size in [1, 3, 1024] {
+            let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new(text), "---")
+                .unwrap()
+                .with_anchor(r"\n\n")
+                .unwrap()
+                .with_buffer_size(size)
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(chunks, [&b"a\n\n"[..], b"b---c\n\n", b"d"], "{}", size);
+        }
+
+        // The anchor can look back past the start of the chunk, and `^`
+        // only matches at the start of the source, however far back.
+        let long = "x".repeat(300);
+        let text = format!(";{long};;{long};\n;");
+        let chunks: Vec<Vec<u8>> = ByteChunker::new(Cursor::new(text), ";")
+            .unwrap()
+            .with_anchor(r"^|;|\n")
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let expected = [String::new(), format!("{long};"), format!("{long};\n")];
+        assert_eq!(chunks, expected.map(String::into_bytes));
+
+        assert!(ByteChunker::new(Cursor::new(""), ";")
+            .unwrap()
+            .with_anchor("(")
+            .is_err());
+    }
+
+    #[test]
+    fn strict_termination() {
+        for size in [1, 4, 1024] {
+            let results: Vec<_> = ByteChunker::new(Cursor::new("one;two;thr"), ";")
+                .unwrap()
+                .with_strict_termination(true)
+                .with_buffer_size(size)
+                .with_metadata()
+                .collect();
+            assert_eq!(results.len(), 3);
+            assert_eq!(results[1].as_ref().unwrap().body, b"two");
+            assert!(matches!(&results[2], Err(RcErr::Unterminated(rest)) if rest == b"thr"));
+        }
+
+        // Terminated data is fine, and so is nothing at all.
+        for text in ["one;two;", ""] {
+            let chunker = ByteChunker::new(Cursor::new(text), ";")
+                .unwrap()
+                .with_strict_termination(true);
+            assert!(chunker.collect::<Result<Vec<_>, _>>().is_ok());
+        }
+
+        // Chunkers that lend out their chunks report it too.
+        let mut lending = ByteChunker::new(Cursor::new("a;b"), ";")
+            .unwrap()
+            .with_strict_termination(true)
+            .lending();
+        assert_eq!(lending.next().unwrap().unwrap(), b"a");
+        assert!(matches!(lending.next(), Some(Err(RcErr::Unterminated(_)))));
+        assert!(lending.next().is_none());
+    }
+
+    #[test]
+    fn require_terminator() {
+        let lines: Result<Vec<String>, _> = ChunkerBuilder::new(r"\n")
+            .require_terminator()
+            .text()
+            .build(Cursor::new("one\ntwo\n"))
+            .unwrap()
+            .collect();
+        assert_eq!(lines.unwrap(), ["one", "two"]);
+
+        let mut lines = ChunkerBuilder::new(r"\n")
+            .require_terminator()
+            .text()
+            .build(Cursor::new("one\ntw"))
+            .unwrap();
+        assert_eq!(lines.next().unwrap().unwrap(), "one");
+        assert!(matches!(lines.next(), Some(Err(RcErr::Unterminated(rest))) if rest == b"tw"));
+        assert!(lines.next().is_none());
+    }
+
+    #[test]
+    fn buffered_len() {
+        let text = format!("a;{};b;", "x".repeat(20));
+        let mut chunker = ByteChunker::new(Cursor::new(text), ";")
+            .unwrap()
+            .with_buffer_size(4);
+        assert_eq!(
+            (chunker.buffered_len(), chunker.peak_buffered_len()),
+            (0, 0)
+        );
+
+        assert_eq!(chunker.next().unwrap().unwrap(), b"a");
+        assert_eq!(chunker.buffered_len(), 2);
+        assert_eq!(chunker.peak_buffered_len(), 4);
+
+        for chunk in chunker.by_ref() {
+            chunk.unwrap();
+        }
+        assert_eq!(chunker.buffered_len(), 0);
+        // The long chunk, its delimiter, and at most one read past them.
+        assert!((21..25).contains(&chunker.peak_buffered_len()));
+    }
+
     #[test]
     fn basic_string() {
         let byte_vec = std::fs::read(TEST_PATH).unwrap();
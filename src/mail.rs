@@ -0,0 +1,152 @@
+/*!
+Splitting mail messages into their parts.
+*/
+
+/*
+Find the blank line that ends the headers of `message`, returning the end
+of the headers and the start of the body.
+*/
+pub(crate) fn blank_line(message: &[u8]) -> Option<(usize, usize)> {
+    // A message with no headers starts with the blank line.
+    if message.starts_with(b"\r\n") {
+        return Some((0, 2));
+    } else if message.starts_with(b"\n") {
+        return Some((0, 1));
+    }
+
+    let mut newlines = memchr::memchr_iter(b'\n', message);
+    let mut prev = newlines.next()?;
+    for newline in newlines {
+        match &message[prev + 1..newline] {
+            b"" => return Some((prev, newline + 1)),
+            b"\r" => {
+                let end = prev - usize::from(message[prev - 1] == b'\r');
+                return Some((end, newline + 1));
+            }
+            _ => prev = newline,
+        }
+    }
+    None
+}
+
+/* Split `message` into its headers and its body at the first blank line. */
+pub(crate) fn split_headers(mut message: Vec<u8>) -> (Vec<u8>, Vec<u8>) {
+    let (headers_end, body_start) = match blank_line(&message) {
+        Some(range) => range,
+        // No blank line at all, so it's all headers.
+        None => (message.len(), message.len()),
+    };
+    let body = message.split_off(body_start);
+    message.truncate(headers_end);
+    (message, body)
+}
+
+/*
+Return the value of the first header in `headers` named `name` (compared
+case-insensitively), with surrounding whitespace removed.
+*/
+pub(crate) fn find_header<'a>(headers: &'a [u8], name: &str) -> Option<&'a [u8]> {
+    headers.split(|&b| b == b'\n').find_map(|line| {
+        let colon = line.iter().position(|&b| b == b':')?;
+        if !line[..colon].eq_ignore_ascii_case(name.as_bytes()) {
+            return None;
+        }
+        let value = &line[colon + 1..];
+        let start = value.iter().position(|b| !b.is_ascii_whitespace());
+        let end = value.iter().rposition(|b| !b.is_ascii_whitespace());
+        match (start, end) {
+            (Some(start), Some(end)) => Some(&value[start..=end]),
+            _ => Some(&value[..0]),
+        }
+    })
+}
+
+/* Remove one `>` from the start of each line of `body` that begins with
+one or more `>`s followed by `From `, reversing mboxrd escaping. */
+fn unescape_from_lines(body: Vec<u8>) -> Vec<u8> {
+    let mut unescaped = Vec::with_capacity(body.len());
+    for line in body.split_inclusive(|&b| b == b'\n') {
+        let quotes = line.iter().take_while(|&&b| b == b'>').count();
+        if quotes > 0 && line[quotes..].starts_with(b"From ") {
+            unescaped.extend_from_slice(&line[1..]);
+        } else {
+            unescaped.extend_from_slice(line);
+        }
+    }
+    unescaped
+}
+
+/**
+A message from an mbox file, separated into its `From ` line, its headers,
+and its body. Converting a chunk yielded by
+[`ByteChunker::mbox_messages`](crate::ByteChunker::mbox_messages) into an
+`MboxMessage` splits it at the first blank line, and undoes the escaping
+of any lines in the body that begin with `From ` (which are stored with a
+`>` in front of them so they aren't mistaken for the start of the next
+message).
+
+```rust
+use regex_chunker::{ByteChunker, MboxMessage, RcErr};
+use std::io::Cursor;
+
+let mbox = b"\
+From alice@example.com Mon Jan  1 00:00:00 2024
+Subject: hello
+From: Alice <alice@example.com>
+
+Hi!
+>From here on, it's all good.
+
+From bob@example.com Tue Jan  2 00:00:00 2024
+Subject: re: hello
+
+Great.
+";
+let messages: Vec<MboxMessage> = ByteChunker::mbox_messages(Cursor::new(mbox))
+    .map(|res| res.map(MboxMessage::from))
+    .collect::<Result<_, RcErr>>()?;
+
+assert_eq!(messages.len(), 2);
+assert_eq!(messages[0].header("subject"), Some(&b"hello"[..]));
+assert_eq!(messages[0].body, b"Hi!\nFrom here on, it's all good.\n");
+assert_eq!(messages[1].from_line, b"From bob@example.com Tue Jan  2 00:00:00 2024");
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MboxMessage {
+    /// The `From ` line that starts the message, without its line ending.
+    pub from_line: Vec<u8>,
+    /// The message's header lines, without the blank line that ends them.
+    pub headers: Vec<u8>,
+    /// The message's body.
+    pub body: Vec<u8>,
+}
+
+impl MboxMessage {
+    /// Return the value of the first header named `name` (compared
+    /// case-insensitively), with surrounding whitespace removed. Only
+    /// the first line of a header folded over several lines is returned.
+    pub fn header(&self, name: &str) -> Option<&[u8]> {
+        find_header(&self.headers, name)
+    }
+}
+
+impl From<Vec<u8>> for MboxMessage {
+    fn from(mut message: Vec<u8>) -> Self {
+        let mut from_line = Vec::new();
+        if message.starts_with(b"From ") {
+            let end = memchr::memchr(b'\n', &message).map_or(message.len(), |n| n + 1);
+            from_line = message.drain(..end).collect();
+            while matches!(from_line.last(), Some(b'\n' | b'\r')) {
+                from_line.pop();
+            }
+        }
+        let (headers, body) = split_headers(message);
+        Self {
+            from_line,
+            headers,
+            body: unescape_from_lines(body),
+        }
+    }
+}
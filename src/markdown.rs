@@ -0,0 +1,137 @@
+/*!
+Chunking Markdown documents into sections.
+*/
+use crate::{Boundary, MatchDisposition};
+
+/* A fenced code block: the fence character and how many of them. */
+type Fence = (u8, usize);
+
+/* If `line` is a code fence, return its character and length. */
+fn fence(line: &[u8]) -> Option<Fence> {
+    let indent = line.iter().take_while(|&&b| b == b' ').count();
+    if indent > 3 {
+        return None;
+    }
+    let c = *line.get(indent).filter(|&&c| c == b'`' || c == b'~')?;
+    let len = line[indent..].iter().take_while(|&&b| b == c).count();
+    (len >= 3).then_some((c, len))
+}
+
+/* Whether `line` closes the code block opened by `open`. */
+fn closes(line: &[u8], open: Fence) -> bool {
+    match fence(line) {
+        Some((c, len)) if c == open.0 && len >= open.1 => {
+            let indent = line.iter().take_while(|&&b| b == b' ').count();
+            line[indent + len..].iter().all(u8::is_ascii_whitespace)
+        }
+        _ => false,
+    }
+}
+
+/* If `line` is an ATX heading (like `## Title`), return its level. */
+fn heading_level(line: &[u8]) -> Option<usize> {
+    let indent = line.iter().take_while(|&&b| b == b' ').count();
+    if indent > 3 {
+        return None;
+    }
+    let level = line[indent..].iter().take_while(|&&b| b == b'#').count();
+    match line.get(indent + level) {
+        _ if !(1..=6).contains(&level) => None,
+        None | Some(b' ' | b'\t' | b'\r') => Some(level),
+        Some(_) => None,
+    }
+}
+
+/**
+A [`Boundary`] that splits a Markdown document into sections, each of
+which starts with an ATX heading (a line like `## Title`) of a given
+level or higher. The heading line is the delimiter, and it's always
+prepended to the section it heads, regardless of the chunker's
+[`MatchDisposition`]. Anything before the first heading is a section of
+its own, and lines that look like headings inside fenced code blocks
+are ignored. Setext headings (underlined with `===` or `---`) aren't
+recognized.
+
+This is most easily used through
+[`ByteChunker::markdown_sections`](crate::ByteChunker::markdown_sections).
+
+```rust
+use regex_chunker::{ByteChunker, RcErr, StringAdapter};
+use std::io::Cursor;
+
+let text = concat!(
+    "# Title\nIntro.\n",
+    "## Usage\n```sh\n# not a heading\n```\n### Details\nMore.\n",
+    "## Licence\nMIT\n",
+);
+let sections: Vec<String> = ByteChunker::markdown_sections(Cursor::new(text), 2)
+    .with_adapter(StringAdapter::default())
+    .collect::<Result<_, RcErr>>()?;
+
+assert_eq!(sections.len(), 3);
+assert_eq!(sections[0], "# Title\nIntro.\n");
+assert!(sections[1].starts_with("## Usage\n") && sections[1].ends_with("More.\n"));
+assert_eq!(sections[2], "## Licence\nMIT\n");
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Clone, Copy, Debug)]
+pub struct MarkdownBoundary {
+    level: usize,
+}
+
+impl MarkdownBoundary {
+    /// Build a new `MarkdownBoundary` that starts a new section at each
+    /// heading of level `level` or higher (that is, with at most `level`
+    /// `#`s). A `level` of 0 splits at every heading.
+    pub fn new(level: usize) -> Self {
+        let level = match level {
+            0 => 6,
+            n => n.min(6),
+        };
+        Self { level }
+    }
+
+    /* Find the next section heading after the one at the start of
+    `haystack`, considering an unterminated last line only if `at_end`. */
+    fn find_heading(&self, haystack: &[u8], start: usize, at_end: bool) -> Option<(usize, usize)> {
+        let mut open: Option<Fence> = None;
+        let mut line_start = 0;
+        while line_start < haystack.len() {
+            let line_end = match memchr::memchr(b'\n', &haystack[line_start..]) {
+                Some(n) => line_start + n,
+                None if at_end => haystack.len(),
+                None => return None,
+            };
+            let line = &haystack[line_start..line_end];
+
+            match open {
+                Some(fenced) if closes(line, fenced) => open = None,
+                Some(_) => {}
+                None => match heading_level(line) {
+                    Some(level) if level <= self.level && line_start > 0 && line_start >= start => {
+                        let end = line_end - usize::from(line.ends_with(b"\r"));
+                        return Some((line_start, end));
+                    }
+                    _ => open = fence(line),
+                },
+            }
+            line_start = line_end + 1;
+        }
+        None
+    }
+}
+
+impl Boundary for MarkdownBoundary {
+    fn find_at(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        self.find_heading(haystack, start, false)
+    }
+
+    fn find_at_end(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        self.find_heading(haystack, start, true)
+    }
+
+    fn disposition(&self) -> Option<MatchDisposition> {
+        Some(MatchDisposition::Prepend)
+    }
+}
@@ -0,0 +1,326 @@
+/*!
+Misbehaving readers, for testing code built on the chunkers.
+*/
+use std::{
+    io::{Error, ErrorKind, Read},
+    thread,
+    time::Duration,
+};
+
+#[cfg(feature = "async")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, ReadBuf};
+
+use crate::ctrl::ReadSizeFn;
+
+/* Choose read sizes at random (from a generator seeded with `seed`)
+between one byte and all the room available; see
+`ByteChunker::with_fragmented_reads`. */
+pub(crate) fn fragmenter(seed: u64) -> ReadSizeFn {
+    let mut rng = fastrand::Rng::with_seed(seed);
+    Box::new(move |room| rng.usize(1..=room.max(1)))
+}
+
+/**
+A reader that delivers the data from the reader it wraps a little at a
+time: by default a single byte per read, or some fixed or (reproducibly)
+random number of bytes, optionally pausing before each read. This
+exercises all the places where a delimiter, a multi-byte character, or a
+byte-order mark gets split across reads.
+
+With the `async` feature, a `SlowReader` wrapping an
+[`AsyncRead`](https://docs.rs/tokio/latest/tokio/io/trait.AsyncRead.html)er
+is an `AsyncRead`er too. It doesn't sleep, though, as that would block
+the executor; instead of each pause, it returns `Poll::Pending` once
+(after arranging to be polled again right away).
+
+```rust
+use regex_chunker::{ByteChunker, RcErr, SlowReader};
+use std::io::Cursor;
+
+let text = b"one, two,three";
+let slow = SlowReader::new(Cursor::new(text)).with_random_reads(1, 3, 0x5eed);
+let chunks: Vec<Vec<u8>> = ByteChunker::new(slow, r",\s*")?
+    .collect::<Result<_, RcErr>>()?;
+
+assert_eq!(chunks, [&b"one"[..], b"two", b"three"]);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Debug)]
+pub struct SlowReader<R> {
+    inner: R,
+    min: usize,
+    max: usize,
+    rng: fastrand::Rng,
+    delay: Option<Duration>,
+    #[cfg(feature = "async")]
+    paused: bool,
+}
+
+impl<R> SlowReader<R> {
+    /// Wrap `inner` in a `SlowReader` that delivers one byte per read.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            min: 1,
+            max: 1,
+            rng: fastrand::Rng::with_seed(0),
+            delay: None,
+            #[cfg(feature = "async")]
+            paused: false,
+        }
+    }
+
+    /// Builder-pattern method for delivering (at most) `size` bytes per
+    /// read. The minimum value is 1.
+    pub fn with_read_size(mut self, size: usize) -> Self {
+        self.min = size.max(1);
+        self.max = self.min;
+        self
+    }
+
+    /// Builder-pattern method for delivering a random number of bytes
+    /// between `min` and `max` (inclusive) per read. The sizes are drawn
+    /// from a generator seeded with `seed`, so a given seed always
+    /// produces the same sequence of reads.
+    pub fn with_random_reads(mut self, min: usize, max: usize, seed: u64) -> Self {
+        self.min = min.max(1);
+        self.max = max.max(self.min);
+        self.rng = fastrand::Rng::with_seed(seed);
+        self
+    }
+
+    /// Builder-pattern method for pausing for `delay` before each read.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Consume the `SlowReader` and return the wrapped reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /* How many bytes to deliver with the next read. */
+    fn next_size(&mut self) -> usize {
+        self.rng.usize(self.min..=self.max)
+    }
+}
+
+impl<R: Read> Read for SlowReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if let Some(delay) = self.delay {
+            thread::sleep(delay);
+        }
+        let n = self.next_size().min(buf.len());
+        self.inner.read(&mut buf[..n])
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R: AsyncRead + Unpin> AsyncRead for SlowReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        if self.delay.is_some() && !self.paused {
+            self.paused = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        self.paused = false;
+
+        let n = self.next_size().min(buf.remaining());
+        let mut limited = ReadBuf::new(buf.initialize_unfilled_to(n));
+        let res = Pin::new(&mut self.inner).poll_read(cx, &mut limited);
+        let n = limited.filled().len();
+        buf.advance(n);
+        res
+    }
+}
+
+/**
+A reader that passes along the data from the reader it wraps, but, at
+random (though reproducibly, given the same seed), fails reads with
+[`ErrorKind::WouldBlock`] or [`ErrorKind::Interrupted`] errors, or stalls
+for a while before reading. It can also fail once with an error of any
+other kind after passing along a given number of bytes, to test recovery
+from (or reporting of) a failed source.
+
+The chunkers retry reads that fail with `WouldBlock` or `Interrupted`,
+so they should produce exactly the same chunks from a `ChaosReader` as
+from the reader it wraps.
+
+With the `async` feature, a `ChaosReader` wrapping an
+[`AsyncRead`](https://docs.rs/tokio/latest/tokio/io/trait.AsyncRead.html)er
+is an `AsyncRead`er too. As async readers signal that they're not ready
+by returning `Poll::Pending` rather than with `WouldBlock` or
+`Interrupted` errors, it does that instead (after arranging to be polled
+again right away), and does the same in place of stalling.
+
+```rust
+use regex_chunker::{ByteChunker, ChaosReader, ErrorResponse, RcErr};
+use std::io::{Cursor, ErrorKind};
+
+let text = b"one\ntwo\nthree\nfour\n";
+let chaos = ChaosReader::new(Cursor::new(text), 42)
+    .with_would_block(0.3)
+    .with_interrupted(0.3)
+    .with_error_after(6, ErrorKind::ConnectionReset);
+let results: Vec<Result<Vec<u8>, RcErr>> = ByteChunker::new(chaos, r"\n")?
+    .with_buffer_size(4)
+    .on_error(ErrorResponse::Continue)
+    .collect();
+
+assert!(matches!(&results[1], Err(RcErr::Read(e)) if e.kind() == ErrorKind::ConnectionReset));
+let chunks: Vec<Vec<u8>> = results.into_iter().filter_map(Result::ok).collect();
+assert_eq!(chunks, [&b"one"[..], b"two", b"three", b"four"]);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Debug)]
+pub struct ChaosReader<R> {
+    inner: R,
+    rng: fastrand::Rng,
+    would_block: f64,
+    interrupted: f64,
+    stall: Option<(f64, Duration)>,
+    error_after: Option<(u64, ErrorKind)>,
+    delivered: u64,
+}
+
+impl<R> ChaosReader<R> {
+    /// Wrap `inner` in a `ChaosReader` whose misbehavior is driven by a
+    /// random number generator seeded with `seed`. Until one of the
+    /// builder methods below is used, it behaves itself.
+    pub fn new(inner: R, seed: u64) -> Self {
+        Self {
+            inner,
+            rng: fastrand::Rng::with_seed(seed),
+            would_block: 0.0,
+            interrupted: 0.0,
+            stall: None,
+            error_after: None,
+            delivered: 0,
+        }
+    }
+
+    /// Builder-pattern method for failing each read with probability
+    /// `probability` with an [`ErrorKind::WouldBlock`] error.
+    pub fn with_would_block(mut self, probability: f64) -> Self {
+        self.would_block = probability;
+        self
+    }
+
+    /// Builder-pattern method for failing each read with probability
+    /// `probability` with an [`ErrorKind::Interrupted`] error.
+    pub fn with_interrupted(mut self, probability: f64) -> Self {
+        self.interrupted = probability;
+        self
+    }
+
+    /// Builder-pattern method for stalling for `duration` before each
+    /// read with probability `probability`.
+    pub fn with_stalls(mut self, probability: f64, duration: Duration) -> Self {
+        self.stall = Some((probability, duration));
+        self
+    }
+
+    /// Builder-pattern method for failing a single read, once `bytes`
+    /// bytes have been passed along, with an error of kind `kind`. Reads
+    /// after that one carry on as normal.
+    pub fn with_error_after(mut self, bytes: u64, kind: ErrorKind) -> Self {
+        self.error_after = Some((bytes, kind));
+        self
+    }
+
+    /// Return the number of bytes passed along so far.
+    pub fn delivered(&self) -> u64 {
+        self.delivered
+    }
+
+    /// Consume the `ChaosReader` and return the wrapped reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /* Decide how to misbehave on this read, if at all, returning the
+    error to fail it with, or a stall. */
+    fn mischief(&mut self) -> Option<Result<Duration, Error>> {
+        if let Some((bytes, kind)) = self.error_after {
+            if self.delivered >= bytes {
+                self.error_after = None;
+                return Some(Err(Error::new(kind, "error injected by ChaosReader")));
+            }
+        }
+        if self.rng.f64() < self.would_block {
+            return Some(Err(ErrorKind::WouldBlock.into()));
+        }
+        if self.rng.f64() < self.interrupted {
+            return Some(Err(ErrorKind::Interrupted.into()));
+        }
+        match self.stall {
+            Some((probability, duration)) if self.rng.f64() < probability => Some(Ok(duration)),
+            _ => None,
+        }
+    }
+
+    /* The most that can be read before the injected error is due. */
+    fn allowance(&self, len: usize) -> usize {
+        match self.error_after {
+            Some((bytes, _)) => {
+                let left = bytes.saturating_sub(self.delivered);
+                len.min(usize::try_from(left).unwrap_or(usize::MAX))
+            }
+            None => len,
+        }
+    }
+}
+
+impl<R: Read> Read for ChaosReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self.mischief() {
+            Some(Err(e)) => return Err(e),
+            Some(Ok(duration)) => thread::sleep(duration),
+            None => {}
+        }
+        let n = self.allowance(buf.len());
+        let n = self.inner.read(&mut buf[..n])?;
+        self.delivered += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R: AsyncRead + Unpin> AsyncRead for ChaosReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        match self.mischief() {
+            Some(Err(e)) if !matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::Interrupted) => {
+                return Poll::Ready(Err(e));
+            }
+            Some(_) => {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            None => {}
+        }
+
+        let n = self.allowance(buf.remaining());
+        let mut limited = ReadBuf::new(buf.initialize_unfilled_to(n));
+        let res = Pin::new(&mut self.inner).poll_read(cx, &mut limited);
+        let n = limited.filled().len();
+        buf.advance(n);
+        self.delivered += n as u64;
+        res
+    }
+}
@@ -0,0 +1,122 @@
+/*!
+Choosing the type chunks are returned as.
+*/
+use std::marker::PhantomData;
+
+use crate::{Adapter, RcErr};
+
+/**
+Trait for byte-buffer types a chunker can return its chunks as, instead of
+`Vec<u8>`, so code that wants [`Bytes`](https://docs.rs/bytes/latest/bytes/struct.Bytes.html)
+(say) doesn't have to convert each chunk itself.
+
+A chunker assembles each chunk in a `Vec<u8>`, so the conversion should
+take over that allocation rather than copying out of it, as the
+implementations here all do: `Vec<u8>` itself, `Bytes` (with the `bytes`
+feature), [`BString`](https://docs.rs/bstr/latest/bstr/struct.BString.html)
+(with the `bstr` feature), and
+[`SmallVec`](https://docs.rs/smallvec/latest/smallvec/struct.SmallVec.html)
+(with the `smallvec` feature; a chunk that would fit inline stays on the
+heap, as moving it would be a copy).
+
+Select one with the chunkers' `with_output()` method, which returns a
+[`CustomChunker`](crate::CustomChunker) using an [`OutputAdapter`].
+
+```rust
+use regex_chunker::{ByteChunker, ChunkBuf, RcErr};
+use std::io::Cursor;
+
+// A buffer type of our own.
+#[derive(Debug, PartialEq)]
+struct Frame(Vec<u8>);
+
+impl ChunkBuf for Frame {
+    fn from_vec(v: Vec<u8>) -> Self {
+        Frame(v)
+    }
+}
+
+let frames: Vec<Frame> = ByteChunker::new(Cursor::new("a|b"), r"\|")?
+    .with_output::<Frame>()
+    .collect::<Result<_, RcErr>>()?;
+assert_eq!(frames, [Frame(b"a".to_vec()), Frame(b"b".to_vec())]);
+# Ok::<(), RcErr>(())
+```
+*/
+pub trait ChunkBuf: Sized {
+    /// Turn a finished chunk into `Self`.
+    fn from_vec(v: Vec<u8>) -> Self;
+}
+
+impl ChunkBuf for Vec<u8> {
+    fn from_vec(v: Vec<u8>) -> Self {
+        v
+    }
+}
+
+#[cfg(feature = "bytes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+impl ChunkBuf for bytes::Bytes {
+    fn from_vec(v: Vec<u8>) -> Self {
+        v.into()
+    }
+}
+
+#[cfg(feature = "bstr")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bstr")))]
+impl ChunkBuf for bstr::BString {
+    fn from_vec(v: Vec<u8>) -> Self {
+        v.into()
+    }
+}
+
+#[cfg(feature = "smallvec")]
+#[cfg_attr(docsrs, doc(cfg(feature = "smallvec")))]
+impl<A: smallvec::Array<Item = u8>> ChunkBuf for smallvec::SmallVec<A> {
+    fn from_vec(v: Vec<u8>) -> Self {
+        smallvec::SmallVec::from_vec(v)
+    }
+}
+
+/**
+An [`Adapter`] that returns each chunk as a `B`, a [`ChunkBuf`] type, as
+set up by the chunkers' `with_output()` method.
+*/
+pub struct OutputAdapter<B> {
+    output: PhantomData<fn() -> B>,
+}
+
+impl<B> OutputAdapter<B> {
+    /// Return a new `OutputAdapter`.
+    pub fn new() -> Self {
+        Self {
+            output: PhantomData,
+        }
+    }
+}
+
+impl<B> Default for OutputAdapter<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B> Clone for OutputAdapter<B> {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl<B> std::fmt::Debug for OutputAdapter<B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OutputAdapter").finish()
+    }
+}
+
+impl<B: ChunkBuf> Adapter for OutputAdapter<B> {
+    type Item = Result<B, RcErr>;
+
+    fn adapt(&mut self, v: Option<Result<Vec<u8>, RcErr>>) -> Option<Self::Item> {
+        v.map(|res| res.map(B::from_vec))
+    }
+}
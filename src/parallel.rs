@@ -0,0 +1,279 @@
+/*!
+Chunking a file on several threads at once.
+*/
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{sync_channel, Receiver, SyncSender},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+use regex::bytes::Regex;
+
+use crate::{ByteChunker, MatchDisposition, RcErr};
+
+// How many processed chunks each worker thread may get ahead of the
+// consumer before it blocks.
+const CHANNEL_BOUND: usize = 1024;
+
+/* A worker thread, and the channel on which it sends its results. */
+type Worker<T> = (Receiver<Result<T, RcErr>>, JoinHandle<()>);
+
+/**
+The `ParallelChunker` splits a file into a number of byte ranges, one per
+thread, moves the start of each range forward to the next delimiter, and
+chunks the ranges on separate threads, applying a function to each chunk.
+The results are yielded _in order_, as if the whole file had been chunked
+on a single thread, so CPU-bound per-chunk work on large files can be
+spread over several cores without the caller having to put anything back
+together.
+
+The chunks are the same as those a [`ByteChunker`] with the same delimiter
+and [`MatchDisposition`] would yield, provided that a delimiter can be
+recognized without needing to look at any of the data that precedes it.
+(This is the same caveat as applies to the [`RevChunker`](crate::RevChunker).)
+
+```rust
+use regex_chunker::{ParallelChunker, RcErr};
+
+let path = std::env::temp_dir().join("regex_chunker_parallel_doc.txt");
+let text: String = (0..1000).map(|n| format!("{}\n", n)).collect();
+std::fs::write(&path, &text)?;
+
+let numbers: Vec<u32> = ParallelChunker::new(&path, r"\n")?
+    .with_threads(4)
+    .map(|chunk| String::from_utf8(chunk).unwrap().parse().unwrap())?
+    .collect::<Result<_, RcErr>>()?;
+
+assert_eq!(numbers, (0..1000).collect::<Vec<u32>>());
+# std::fs::remove_file(&path)?;
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Clone, Debug)]
+pub struct ParallelChunker {
+    path: PathBuf,
+    fence: Regex,
+    match_dispo: MatchDisposition,
+    threads: usize,
+    buffer_size: Option<usize>,
+}
+
+impl ParallelChunker {
+    /**
+    Return a new [`ParallelChunker`] that will chunk the file at `path` by
+    delimiting it with the supplied regex pattern.
+    */
+    pub fn new<P: AsRef<Path>>(path: P, delimiter: &str) -> Result<Self, RcErr> {
+        let fence = Regex::new(delimiter)?;
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            fence,
+            match_dispo: MatchDisposition::default(),
+            threads,
+            buffer_size: None,
+        })
+    }
+
+    /**
+    Builder-pattern method for setting the number of threads (and so the
+    number of ranges the file is split into). Default is the value of
+    [`std::thread::available_parallelism`]; the minimum is 1.
+    */
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /**
+    Builder-pattern method for controlling what the chunker does with the
+    matched text. Default value is [`MatchDisposition::Drop`].
+    */
+    pub fn with_match(mut self, behavior: MatchDisposition) -> Self {
+        self.match_dispo = behavior;
+        self
+    }
+
+    /**
+    Builder-pattern method for setting the read buffer size used by each
+    thread's chunker. Default size is 1024 bytes.
+    */
+    pub fn with_buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    /*
+    Return the position of the first chunk boundary at or after `pos`.
+    A match starting exactly at `pos` is skipped, since it may only be the
+    tail end of a match that starts further back; that delimiter is then
+    handled entirely by the preceding range.
+    */
+    fn align(&self, pos: u64, len: u64) -> Result<u64, RcErr> {
+        let mut f = File::open(&self.path)?;
+        f.seek(SeekFrom::Start(pos))?;
+        let chunker = ByteChunker::from_boundary(f.take(len - pos), self.fence.clone());
+        let chunker = match self.buffer_size {
+            Some(size) => chunker.with_buffer_size(size),
+            None => chunker,
+        };
+
+        for res in chunker.with_metadata() {
+            let chunk = res?;
+            let delimiter = match chunk.delimiter {
+                Some(delimiter) => delimiter,
+                None => break,
+            };
+            if chunk.span.end == 0 {
+                continue;
+            }
+            let start = pos + chunk.span.end;
+            return Ok(match self.match_dispo {
+                MatchDisposition::Prepend | MatchDisposition::Wrap => start,
+                _ => start + delimiter.len() as u64,
+            });
+        }
+        Ok(len)
+    }
+
+    /**
+    Start chunking the file, applying `f` to each chunk, and return an
+    iterator over the results.
+
+    This returns an error if the file can't be opened or the range
+    boundaries can't be found; errors encountered while chunking are
+    yielded by the iterator in the position where they occurred.
+    */
+    pub fn map<T, F>(self, f: F) -> Result<ParallelChunks<T>, RcErr>
+    where
+        T: Send + 'static,
+        F: Fn(Vec<u8>) -> T + Send + Sync + 'static,
+    {
+        let len = File::open(&self.path)?.metadata()?.len();
+        let step = len / self.threads as u64 + 1;
+
+        let mut starts = vec![0u64];
+        for n in 1..self.threads as u64 {
+            let prev = *starts.last().unwrap();
+            let pos = (n * step).max(prev);
+            if pos >= len {
+                break;
+            }
+            starts.push(self.align(pos, len)?);
+        }
+        starts.push(len);
+
+        let f = Arc::new(f);
+        let mut workers = VecDeque::with_capacity(starts.len() - 1);
+        for (n, range) in starts.windows(2).enumerate() {
+            let (start, end) = (range[0], range[1].max(range[0]));
+            let (tx, rx) = sync_channel(CHANNEL_BOUND);
+            let chunker = self.clone();
+            let f = f.clone();
+            let handle = std::thread::spawn(move || chunker.work(start, end, n > 0, &*f, tx));
+            workers.push_back((rx, handle));
+        }
+
+        Ok(ParallelChunks { workers })
+    }
+
+    /* Chunk the range from `start` to `end`, sending the results to `tx`. */
+    fn work<T, F>(
+        self,
+        start: u64,
+        end: u64,
+        skip_empty: bool,
+        f: &F,
+        tx: SyncSender<Result<T, RcErr>>,
+    ) where
+        F: Fn(Vec<u8>) -> T,
+    {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) => {
+                let _ = tx.send(Err(e.into()));
+                return;
+            }
+        };
+        if let Err(e) = file.seek(SeekFrom::Start(start)) {
+            let _ = tx.send(Err(e.into()));
+            return;
+        }
+
+        // Under MatchDisposition::Prepend every range but the first starts
+        // with a delimiter, which produces an empty chunk that a single
+        // chunker working through the whole file wouldn't. Under Wrap, it
+        // produces a chunk of just that delimiter.
+        let wrap = matches!(self.match_dispo, MatchDisposition::Wrap);
+        let mut skip_empty = skip_empty
+            && matches!(
+                self.match_dispo,
+                MatchDisposition::Prepend | MatchDisposition::Wrap
+            );
+
+        // Under Wrap, the last chunk of a range also ends with the delimiter
+        // that starts the next one, so read on until the next chunk starts.
+        let limit = if wrap { u64::MAX } else { end - start };
+        let chunker =
+            ByteChunker::from_boundary(file.take(limit), self.fence).with_match(self.match_dispo);
+        let chunker = match self.buffer_size {
+            Some(size) => chunker.with_buffer_size(size),
+            None => chunker,
+        };
+        for res in chunker.with_metadata() {
+            let res = match res {
+                Ok(c) if wrap && c.span.start >= end - start => return,
+                Ok(c) if skip_empty && (wrap || c.body.is_empty()) => {
+                    skip_empty = false;
+                    continue;
+                }
+                Ok(c) => Ok(f(c.body)),
+                Err(e) => Err(e),
+            };
+            skip_empty = false;
+            if tx.send(res).is_err() {
+                // The ParallelChunks has been dropped.
+                return;
+            }
+        }
+    }
+}
+
+/**
+The iterator returned by [`ParallelChunker::map`]. It yields the results
+of applying the function to each chunk of the file, in order.
+
+Dropping it before it's exhausted stops the worker threads.
+*/
+#[derive(Debug)]
+pub struct ParallelChunks<T> {
+    workers: VecDeque<Worker<T>>,
+}
+
+impl<T> Iterator for ParallelChunks<T> {
+    type Item = Result<T, RcErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (rx, _) = self.workers.front()?;
+            if let Ok(res) = rx.recv() {
+                return Some(res);
+            }
+
+            // This range is finished; if its thread panicked, pass that on.
+            let (_, handle) = self.workers.pop_front()?;
+            if let Err(e) = handle.join() {
+                std::panic::resume_unwind(e);
+            }
+        }
+    }
+}
@@ -0,0 +1,183 @@
+/*!
+Recording and replaying the sizes of the reads a chunker makes.
+*/
+use std::io::Read;
+
+#[cfg(feature = "async")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, ReadBuf};
+
+/**
+A reader that passes along the data from the reader it wraps unchanged,
+but keeps a record of how many bytes each successive read returned.
+
+Whether a bug shows up can depend on exactly where a source's data gets
+split between reads, which in production is up to the network, a pipe,
+or the OS. Wrapping the source in a `ReadRecorder` captures that pattern,
+so a [`ReplayReader`] can then feed the same data to a chunker in
+exactly the same pieces, reproducing the bug deterministically in a test.
+
+```rust
+use regex_chunker::{ByteChunker, RcErr, ReadRecorder, ReplayReader};
+use std::io::Cursor;
+
+let text = b"alpha\nbeta\ngamma\n";
+let mut chunker = ByteChunker::new(ReadRecorder::new(Cursor::new(text)), r"\n")?
+    .with_buffer_size(4);
+let chunks: Vec<Vec<u8>> = (&mut chunker).collect::<Result<_, RcErr>>()?;
+let sizes = chunker.into_inner().into_sizes();
+assert_eq!(sizes, [4, 4, 4, 4, 1, 0]);
+
+// Later, in a test:
+let replay = ReplayReader::new(&text[..], &sizes);
+let replayed: Vec<Vec<u8>> = ByteChunker::new(replay, r"\n")?
+    .with_buffer_size(4)
+    .collect::<Result<_, RcErr>>()?;
+assert_eq!(replayed, chunks);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Debug)]
+pub struct ReadRecorder<R> {
+    inner: R,
+    sizes: Vec<usize>,
+}
+
+impl<R> ReadRecorder<R> {
+    /// Wrap `inner` in a `ReadRecorder`.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            sizes: Vec::new(),
+        }
+    }
+
+    /// Return the sizes of the reads made so far, in order. Reads that
+    /// returned errors aren't included, but the final zero-length read
+    /// that signals the end of the source is.
+    pub fn sizes(&self) -> &[usize] {
+        &self.sizes
+    }
+
+    /// Consume the `ReadRecorder` and return the recorded read sizes.
+    pub fn into_sizes(self) -> Vec<usize> {
+        self.sizes
+    }
+
+    /// Consume the `ReadRecorder` and return the wrapped reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for ReadRecorder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.sizes.push(n);
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R: AsyncRead + Unpin> AsyncRead for ReadRecorder<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let before = buf.filled().len();
+        let res = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = res {
+            let n = buf.filled().len() - before;
+            self.sizes.push(n);
+        }
+        res
+    }
+}
+
+/**
+A reader that delivers the data from the reader it wraps in pieces of
+the sizes recorded by a [`ReadRecorder`], to reproduce the way a source
+split up its data. Zero sizes are skipped. If a read asks for fewer bytes
+than the next recorded size, the rest of that piece is delivered by the
+following read; once the recorded sizes run out, reads are passed through
+unchanged.
+*/
+#[derive(Debug)]
+pub struct ReplayReader<R> {
+    inner: R,
+    sizes: Vec<usize>,
+    next: usize,
+    // What's left of the piece at `sizes[next]`.
+    left: usize,
+}
+
+impl<R> ReplayReader<R> {
+    /// Wrap `inner` in a `ReplayReader` that delivers its data in pieces
+    /// of the given `sizes`.
+    pub fn new(inner: R, sizes: &[usize]) -> Self {
+        let sizes: Vec<usize> = sizes.iter().copied().filter(|&n| n > 0).collect();
+        let left = sizes.first().copied().unwrap_or(0);
+        Self {
+            inner,
+            sizes,
+            next: 0,
+            left,
+        }
+    }
+
+    /// Consume the `ReplayReader` and return the wrapped reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /* The most the next read should return. */
+    fn limit(&self, len: usize) -> usize {
+        match self.next < self.sizes.len() {
+            true => len.min(self.left),
+            false => len,
+        }
+    }
+
+    /* Account for having delivered `n` bytes. */
+    fn consume(&mut self, n: usize) {
+        if self.next >= self.sizes.len() {
+            return;
+        }
+        self.left -= n;
+        if self.left == 0 {
+            self.next += 1;
+            self.left = self.sizes.get(self.next).copied().unwrap_or(0);
+        }
+    }
+}
+
+impl<R: Read> Read for ReplayReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.limit(buf.len());
+        let n = self.inner.read(&mut buf[..n])?;
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R: AsyncRead + Unpin> AsyncRead for ReplayReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let n = self.limit(buf.remaining());
+        let mut limited = ReadBuf::new(buf.initialize_unfilled_to(n));
+        let res = Pin::new(&mut self.inner).poll_read(cx, &mut limited);
+        let n = limited.filled().len();
+        buf.advance(n);
+        self.consume(n);
+        res
+    }
+}
@@ -0,0 +1,390 @@
+/*!
+Chunkers that read their sources backward, from the end.
+*/
+use std::{
+    fmt::{Debug, Formatter},
+    io::{Read, Seek, SeekFrom},
+};
+
+use regex::bytes::Regex;
+
+use crate::{ctrl::MatchFn, Boundary, MatchDisposition, RcErr};
+
+// By default we read blocks of 1 KiB from the end of the source.
+const DEFAULT_BLOCK_SIZE: usize = 1024;
+// How far past the part of the buffer that hasn't been searched yet a
+// search looks, so that a delimiter starting in it can be seen whole.
+const OVERLAP_WINDOW: usize = 1024;
+
+/* The result of asking the RevCore for its next chunk. */
+pub(crate) enum RevStep {
+    /// The next (that is, the previous) chunk.
+    Chunk(Vec<u8>),
+    /// The core needs the `usize` bytes starting at the `u64` offset in
+    /// the source before it can continue.
+    NeedMore(u64, usize),
+    /// There are no more chunks.
+    Done,
+}
+
+/*
+The source-agnostic part of reverse chunking, shared by the sync and
+async `RevChunker`s.
+
+The buffer holds the data from `pos` up to the start of the most recently
+returned chunk. If that chunk's delimiter had to stay in the buffer
+(because the preceding chunk gets it appended) its length is `trailing`,
+and it's excluded from searches.
+
+When a block is read because the buffer holds no usable delimiter, only
+the new block (and the start of the data after it) needs searching; the
+length of the rest, already known to hold no delimiter, is `searched`.
+*/
+pub(crate) struct RevCore {
+    fence: Box<dyn Boundary + Send>,
+    match_dispo: MatchDisposition,
+    match_fn: Option<MatchFn>,
+    buff: Vec<u8>,
+    pos: u64,
+    trailing: usize,
+    searched: usize,
+    pending: Option<Vec<u8>>,
+    block_size: usize,
+    seen_match: bool,
+    done: bool,
+}
+
+impl RevCore {
+    pub(crate) fn new(fence: Box<dyn Boundary + Send>) -> Self {
+        Self {
+            fence,
+            match_dispo: MatchDisposition::default(),
+            match_fn: None,
+            buff: Vec::new(),
+            pos: 0,
+            trailing: 0,
+            searched: 0,
+            pending: None,
+            block_size: DEFAULT_BLOCK_SIZE,
+            seen_match: false,
+            done: false,
+        }
+    }
+
+    pub(crate) fn set_match(&mut self, behavior: MatchDisposition) {
+        self.match_dispo = behavior;
+    }
+
+    pub(crate) fn set_match_fn(&mut self, f: MatchFn) {
+        self.match_fn = Some(f);
+    }
+
+    pub(crate) fn set_block_size(&mut self, size: usize) {
+        self.block_size = size.max(1);
+    }
+
+    /// Set the length of the source; this is where reading starts.
+    pub(crate) fn set_len(&mut self, len: u64) {
+        self.pos = len;
+    }
+
+    /// Supply the data most recently requested with `RevStep::NeedMore`.
+    pub(crate) fn prepend(&mut self, offset: u64, mut block: Vec<u8>) {
+        block.append(&mut self.buff);
+        self.buff = block;
+        self.pos = offset;
+    }
+
+    /// Stop returning chunks (after an error, for instance).
+    pub(crate) fn finish(&mut self) {
+        self.done = true;
+    }
+
+    /* Find the last delimiter in the searchable part of the buffer. */
+    fn last_match(&mut self) -> Option<(usize, usize, MatchDisposition)> {
+        let haystack = &self.buff[..self.buff.len() - self.trailing];
+        let unsearched = haystack.len() - self.searched;
+        let window = &haystack[..haystack.len().min(unsearched + OVERLAP_WINDOW)];
+        let mut last = None;
+        let mut offset = 0;
+        while offset <= haystack.len() {
+            // Everything that follows the haystack is already known.
+            let found = match window.len() == haystack.len() {
+                true => self.fence.find_at_end(haystack, offset),
+                // A delimiter starting in the window may run on past it,
+                // so it's found again in the whole haystack.
+                false => match self.fence.find_at(window, offset) {
+                    Some((start, _)) if start <= unsearched => {
+                        self.fence.find_at_end(haystack, start)
+                    }
+                    _ => None,
+                },
+            };
+            match found {
+                Some((start, end)) => {
+                    last = Some((start, end, self.fence.disposition()));
+                    offset = if end > start { end } else { end + 1 };
+                }
+                None => break,
+            }
+        }
+        self.searched = haystack.len() - offset.min(haystack.len());
+
+        // Only the last match is used, so only it is passed to `match_fn`.
+        let (start, end, dispo) = last?;
+        let dispo = match (dispo, self.match_fn.as_mut()) {
+            (Some(dispo), _) => dispo,
+            (None, Some(f)) => f(&haystack[start..end]),
+            (None, None) => self.match_dispo.clone(),
+        };
+        Some((start, end, dispo))
+    }
+
+    pub(crate) fn step(&mut self) -> RevStep {
+        loop {
+            if self.done {
+                return RevStep::Done;
+            }
+
+            match self.last_match() {
+                // A match at the very beginning of the buffer may be only
+                // part of a match that extends further back.
+                Some((start, end, dispo)) if start > 0 || self.pos == 0 => {
+                    self.searched = 0;
+                    let is_last_chunk = !self.seen_match;
+                    self.seen_match = true;
+
+                    // The chunk gets the replacement for the delimiter that
+                    // follows it, if any; this one's is held for the next.
+                    let suffix = self.pending.take();
+                    // A chunk that's nothing but the delimiter opening it.
+                    let bare = match dispo {
+                        MatchDisposition::Wrap => end - start,
+                        _ => 0,
+                    };
+                    let mut chunk = match dispo {
+                        MatchDisposition::Drop => {
+                            let chunk = self.buff.split_off(end);
+                            self.buff.truncate(start);
+                            self.trailing = 0;
+                            chunk
+                        }
+                        MatchDisposition::Replace(bytes) => {
+                            let chunk = self.buff.split_off(end);
+                            self.buff.truncate(start);
+                            self.trailing = 0;
+                            self.pending = Some(bytes);
+                            chunk
+                        }
+                        MatchDisposition::Append => {
+                            self.trailing = end - start;
+                            self.buff.split_off(end)
+                        }
+                        MatchDisposition::Prepend => {
+                            self.trailing = 0;
+                            self.buff.split_off(start)
+                        }
+                        MatchDisposition::Wrap => {
+                            let chunk = self.buff[start..].to_vec();
+                            self.buff.truncate(end);
+                            self.trailing = end - start;
+                            chunk
+                        }
+                    };
+                    if let Some(suffix) = suffix {
+                        chunk.extend_from_slice(&suffix);
+                    }
+
+                    // A forward chunker doesn't return an empty final
+                    // chunk (or a bare delimiter, under Wrap), so neither
+                    // do we.
+                    if is_last_chunk && chunk.len() == bare {
+                        continue;
+                    }
+                    return RevStep::Chunk(chunk);
+                }
+                _ => {
+                    if self.pos > 0 {
+                        let len = self.block_size.min(self.pos as usize);
+                        return RevStep::NeedMore(self.pos - len as u64, len);
+                    }
+
+                    self.done = true;
+                    if self.seen_match || !self.buff.is_empty() {
+                        let mut chunk = std::mem::take(&mut self.buff);
+                        if let Some(suffix) = self.pending.take() {
+                            chunk.extend_from_slice(&suffix);
+                        }
+                        return RevStep::Chunk(chunk);
+                    }
+                    return RevStep::Done;
+                }
+            }
+        }
+    }
+}
+
+impl Debug for RevCore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RevCore")
+            .field("fence", &self.fence)
+            .field("match_dispo", &self.match_dispo)
+            .field("match_fn", &self.match_fn.is_some())
+            .field("buff", &String::from_utf8_lossy(&self.buff))
+            .field("pos", &self.pos)
+            .field("trailing", &self.trailing)
+            .field("searched", &self.searched)
+            .field("pending", &self.pending)
+            .field("block_size", &self.block_size)
+            .field("seen_match", &self.seen_match)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+/**
+The `RevChunker` wraps a source that implements both [`Read`] and
+[`Seek`] (a [`File`](std::fs::File), say), reads it in blocks backward from
+the end, and yields its chunks _last chunk first_. This solves the "show me
+the last 50 log records" problem without scanning the whole file.
+
+The chunks it yields are the same as those a [`ByteChunker`](crate::ByteChunker)
+with the same delimiter and [`MatchDisposition`] would yield, just in the
+opposite order, provided that a delimiter can be recognized without
+needing to look at any of the data that precedes it. (This is the case for
+the sorts of things one typically delimits records with; a pattern like
+`aa` on a run of `a`s is an example where it isn't.)
+
+```rust
+use regex_chunker::{RcErr, RevChunker};
+use std::io::Cursor;
+
+let text = b"first line\nsecond line\nthird line\n";
+
+let last_two: Vec<Vec<u8>> = RevChunker::new(Cursor::new(text), r"\n")?
+    .take(2)
+    .map(|res| res.unwrap())
+    .collect();
+
+assert_eq!(&last_two, &[b"third line".to_vec(), b"second line".to_vec()]);
+# Ok::<(), RcErr>(())
+```
+*/
+pub struct RevChunker<R> {
+    source: R,
+    core: RevCore,
+    started: bool,
+}
+
+impl<R> RevChunker<R> {
+    /**
+    Return a new [`RevChunker`] wrapping the given reader that will chunk
+    its contents by delimiting it with the supplied regex pattern.
+    */
+    pub fn new(source: R, delimiter: &str) -> Result<Self, RcErr> {
+        let fence = Regex::new(delimiter)?;
+        Ok(Self::from_boundary(source, fence))
+    }
+
+    /**
+    Return a new [`RevChunker`] wrapping the given reader that will chunk
+    its contents at the delimiters found by the supplied [`Boundary`].
+    */
+    pub fn from_boundary<B>(source: R, boundary: B) -> Self
+    where
+        B: Boundary + Send + 'static,
+    {
+        Self {
+            source,
+            core: RevCore::new(Box::new(boundary)),
+            started: false,
+        }
+    }
+
+    /**
+    Builder-pattern method for setting the size of the blocks read from
+    the source. Default size is 1024 bytes.
+    */
+    pub fn with_buffer_size(mut self, size: usize) -> Self {
+        self.core.set_block_size(size);
+        self
+    }
+
+    /**
+    Builder-pattern method for controlling what the chunker does with the
+    matched text. Default value is [`MatchDisposition::Drop`].
+    */
+    pub fn with_match(mut self, behavior: MatchDisposition) -> Self {
+        self.core.set_match(behavior);
+        self
+    }
+
+    /**
+    Builder-pattern method for deciding what to do with each match
+    individually. See [`ByteChunker::with_match_fn`](crate::ByteChunker::with_match_fn).
+    */
+    pub fn with_match_fn<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&[u8]) -> MatchDisposition + Send + 'static,
+    {
+        self.core.set_match_fn(Box::new(f));
+        self
+    }
+
+    /// Consumes the [`RevChunker`] and returns its wrapped reader.
+    pub fn into_inner(self) -> R {
+        self.source
+    }
+
+    /* Read the `len` bytes at `offset` from the source. */
+    fn read_block(&mut self, offset: u64, len: usize) -> Result<Vec<u8>, RcErr>
+    where
+        R: Read + Seek,
+    {
+        self.source.seek(SeekFrom::Start(offset))?;
+        let mut block = vec![0u8; len];
+        self.source.read_exact(&mut block)?;
+        Ok(block)
+    }
+}
+
+impl<R> Debug for RevChunker<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RevChunker")
+            .field("source", &std::any::type_name::<R>())
+            .field("core", &self.core)
+            .field("started", &self.started)
+            .finish()
+    }
+}
+
+impl<R: Read + Seek> Iterator for RevChunker<R> {
+    type Item = Result<Vec<u8>, RcErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            match self.source.seek(SeekFrom::End(0)) {
+                Ok(len) => self.core.set_len(len),
+                Err(e) => {
+                    self.core.finish();
+                    return Some(Err(e.into()));
+                }
+            }
+        }
+
+        loop {
+            match self.core.step() {
+                RevStep::Chunk(v) => return Some(Ok(v)),
+                RevStep::Done => return None,
+                RevStep::NeedMore(offset, len) => match self.read_block(offset, len) {
+                    Ok(block) => self.core.prepend(offset, block),
+                    Err(e) => {
+                        self.core.finish();
+                        return Some(Err(e));
+                    }
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,146 @@
+/*!
+An adapter for scrubbing invisible and lookalike characters from text.
+*/
+use crate::{Adapter, RcErr, StringAdapter, Utf8FailureMode};
+
+/* Characters that take up no space: zero-width spaces and joiners, the
+word joiner, the BOM (as a zero-width no-break space), the soft hyphen,
+and the Mongolian vowel separator. */
+fn is_zero_width(c: char) -> bool {
+    matches!(
+        c,
+        '\u{ad}' | '\u{180e}' | '\u{200b}'..='\u{200d}' | '\u{2060}' | '\u{feff}'
+    )
+}
+
+/* Characters that change the direction of the surrounding text, which can
+make it display differently than the order it's stored in. */
+fn is_bidi_control(c: char) -> bool {
+    matches!(
+        c,
+        '\u{61c}' | '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+/* The ASCII character `c` is commonly mistaken for, if any. */
+#[rustfmt::skip]
+fn unconfuse(c: char) -> Option<char> {
+    let ascii = match c {
+        // Fullwidth forms of the printable ASCII characters.
+        '\u{ff01}'..='\u{ff5e}' => return char::from_u32(c as u32 - 0xfee0),
+        '\u{a0}' | '\u{2000}'..='\u{200a}' | '\u{202f}' | '\u{205f}' | '\u{3000}' => ' ',
+        '\u{2010}'..='\u{2015}' | '\u{2212}' => '-',
+        '\u{2018}'..='\u{201b}' | '\u{2032}' => '\'',
+        '\u{201c}'..='\u{201f}' | '\u{2033}' => '"',
+        // Cyrillic.
+        'а' => 'a', 'с' => 'c', 'е' => 'e', 'һ' => 'h', 'і' => 'i', 'ј' => 'j',
+        'о' => 'o', 'р' => 'p', 'ѕ' => 's', 'х' => 'x', 'у' => 'y',
+        'А' => 'A', 'В' => 'B', 'С' => 'C', 'Е' => 'E', 'Н' => 'H', 'І' => 'I',
+        'Ј' => 'J', 'К' => 'K', 'М' => 'M', 'О' => 'O', 'Р' => 'P', 'Ѕ' => 'S',
+        'Т' => 'T', 'Х' => 'X', 'Ү' => 'Y',
+        // Greek.
+        'ο' => 'o', 'ν' => 'v',
+        'Α' => 'A', 'Β' => 'B', 'Ε' => 'E', 'Ζ' => 'Z', 'Η' => 'H', 'Ι' => 'I',
+        'Κ' => 'K', 'Μ' => 'M', 'Ν' => 'N', 'Ο' => 'O', 'Ρ' => 'P', 'Τ' => 'T',
+        'Υ' => 'Y', 'Χ' => 'X',
+        _ => return None,
+    };
+    Some(ascii)
+}
+
+/**
+An [`Adapter`] that yields `String`s with the zero-width characters and
+bidirectional text controls removed, for sanitizing user-supplied text
+before matching it against anything or displaying it. It converts chunks
+to `String`s just like a [`StringAdapter`], and handles invalid UTF-8 the
+same way.
+
+The characters removed are the zero-width space, non-joiner and joiner,
+the word joiner, `U+FEFF`, the soft hyphen, the Mongolian vowel separator,
+and the bidi marks, embeddings, overrides and isolates. Note that removing
+the zero-width joiner also breaks up emoji sequences that are built with it.
+
+```rust
+use regex_chunker::{ByteChunker, RcErr, SanitizingAdapter};
+use std::io::Cursor;
+
+let text = "pay\u{200b}pal\nadmin\u{202e}txt.exe\nok";
+let chunks: Vec<String> = ByteChunker::new(Cursor::new(text), r"\n")?
+    .with_adapter(SanitizingAdapter::default())
+    .collect::<Result<_, RcErr>>()?;
+
+assert_eq!(chunks, ["paypal", "admintxt.exe", "ok"]);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Debug, Default)]
+pub struct SanitizingAdapter {
+    strings: StringAdapter,
+    map_confusables: bool,
+}
+
+impl SanitizingAdapter {
+    /// Return a new [`SanitizingAdapter`] that treats invalid UTF-8
+    /// according to `mode`.
+    pub fn new(mode: Utf8FailureMode) -> Self {
+        Self {
+            strings: StringAdapter::new(mode),
+            map_confusables: false,
+        }
+    }
+
+    /**
+    Builder-pattern method for also replacing characters that are easily
+    mistaken for ASCII with the ASCII characters they look like. This
+    covers the fullwidth forms of the ASCII characters, the various spaces,
+    dashes and curly quotes, and the Cyrillic and Greek letters that look
+    like Latin ones. It's a short list of the usual suspects, not the full
+    Unicode confusables data of UTS #39. Default is `false`.
+
+    ```rust
+    use regex_chunker::{ByteChunker, RcErr, SanitizingAdapter};
+    use std::io::Cursor;
+
+    let text = "раура1\u{a0}–\u{200d}ＡＢＣ";
+    let chunks: Vec<String> = ByteChunker::new(Cursor::new(text), r"\n")?
+        .with_adapter(SanitizingAdapter::default().with_confusables_mapped(true))
+        .collect::<Result<_, RcErr>>()?;
+
+    assert_eq!(chunks, ["paypa1 -ABC"]);
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn with_confusables_mapped(mut self, map: bool) -> Self {
+        self.map_confusables = map;
+        self
+    }
+
+    /* Remove (and maybe replace) the offending characters from `s`. */
+    fn sanitize(&self, s: String) -> String {
+        let dirty = |c: char| {
+            is_zero_width(c)
+                || is_bidi_control(c)
+                || (self.map_confusables && unconfuse(c).is_some())
+        };
+        if !s.chars().any(dirty) {
+            return s;
+        }
+
+        s.chars()
+            .filter(|&c| !is_zero_width(c) && !is_bidi_control(c))
+            .map(|c| match self.map_confusables {
+                true => unconfuse(c).unwrap_or(c),
+                false => c,
+            })
+            .collect()
+    }
+}
+
+impl Adapter for SanitizingAdapter {
+    type Item = Result<String, RcErr>;
+
+    fn adapt(&mut self, v: Option<Result<Vec<u8>, RcErr>>) -> Option<Self::Item> {
+        let res = self.strings.adapt(v)?;
+        Some(res.map(|s| self.sanitize(s)))
+    }
+}
@@ -0,0 +1,128 @@
+/*!
+Chunking SQL scripts into statements.
+*/
+use crate::Boundary;
+
+fn is_sql_space(b: &u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\r' | b'\n' | b'\x0c')
+}
+
+fn is_tag_byte(b: &u8) -> bool {
+    b.is_ascii_alphanumeric() || *b == b'_' || *b >= 0x80
+}
+
+/* Return the position of the first occurrence of `needle` in `text`. */
+fn find(text: &[u8], needle: &[u8]) -> Option<usize> {
+    text.windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/* If a PostgreSQL dollar-quote tag (like `$$` or `$body$`) starts at the
+beginning of `text`, return its length. */
+fn dollar_tag(text: &[u8]) -> Option<usize> {
+    let name = text[1..].iter().take_while(|b| is_tag_byte(b)).count();
+    if matches!(text.get(1), Some(b'0'..=b'9')) {
+        // That's a positional parameter, like `$1`.
+        return None;
+    }
+    match text.get(1 + name) {
+        Some(b'$') => Some(name + 2),
+        _ => None,
+    }
+}
+
+/**
+A [`Boundary`] that splits a SQL script into statements at each `;`,
+ignoring any semicolons inside string literals (`'...'`), quoted
+identifiers (`"..."` or `` `...` ``), comments (`-- ...` and `/* ... */`),
+and PostgreSQL dollar-quoted strings (like `$$ ... $$` or
+`$body$ ... $body$`), so that things like function bodies come out whole.
+
+The delimiter is the `;` and any whitespace that follows it, so comments
+between statements stay with the statement after them. Quotes inside
+literals and identifiers are escaped by doubling them, as in standard
+SQL; MySQL-style backslash escapes aren't recognized, and neither are
+nested block comments. This is most easily used through
+[`ByteChunker::sql_statements`](crate::ByteChunker::sql_statements).
+
+```rust
+use regex_chunker::{ByteChunker, RcErr, StringAdapter};
+use std::io::Cursor;
+
+let script = "
+    INSERT INTO notes VALUES ('it''s; fine', \"a;b\");
+    -- one; two
+    CREATE FUNCTION f() RETURNS int AS $$ SELECT 1; $$ LANGUAGE sql;
+    /* three; */ SELECT 3
+";
+let statements: Vec<String> = ByteChunker::sql_statements(Cursor::new(script))
+    .with_adapter(StringAdapter::default())
+    .collect::<Result<_, RcErr>>()?;
+
+assert_eq!(statements.len(), 3);
+assert!(statements[0].ends_with("VALUES ('it''s; fine', \"a;b\")"));
+assert!(statements[1].starts_with("-- one; two\n") && statements[1].ends_with("sql"));
+assert_eq!(statements[2], "/* three; */ SELECT 3\n");
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SqlBoundary;
+
+impl SqlBoundary {
+    /// Return a new [`SqlBoundary`].
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Boundary for SqlBoundary {
+    fn find_at(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        find_statement(haystack, start, false)
+    }
+
+    fn find_at_end(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        find_statement(haystack, start, true)
+    }
+}
+
+/*
+Find the end of the statement that starts at the beginning of
+`haystack`. Unless `at_end` is set, more data might follow `haystack`.
+*/
+fn find_statement(haystack: &[u8], start: usize, at_end: bool) -> Option<(usize, usize)> {
+    let mut n = 0;
+    while n < haystack.len() {
+        let rest = &haystack[n..];
+        n += match rest[0] {
+            b';' if n >= start => return statement_end(haystack, n, at_end),
+            quote @ (b'\'' | b'"' | b'`') => 1 + memchr::memchr(quote, &rest[1..])? + 1,
+            b'-' if rest.starts_with(b"--") => memchr::memchr(b'\n', rest)? + 1,
+            b'/' if rest.starts_with(b"/*") => 2 + find(&rest[2..], b"*/")? + 2,
+            // A `$` can also be part of an identifier, like `a$b$`.
+            b'$' if n == 0 || !is_tag_byte(&haystack[n - 1]) => match dollar_tag(rest) {
+                Some(len) => {
+                    let tag = &rest[..len];
+                    len + find(&rest[len..], tag)? + len
+                }
+                None => 1,
+            },
+            _ => 1,
+        };
+    }
+    None
+}
+
+/* The delimiter made up of the `;` at `semi` and the whitespace after it. */
+fn statement_end(haystack: &[u8], semi: usize, at_end: bool) -> Option<(usize, usize)> {
+    let end = semi + 1;
+    let space = haystack[end..]
+        .iter()
+        .take_while(|b| is_sql_space(b))
+        .count();
+    if end + space < haystack.len() || at_end {
+        Some((semi, end + space))
+    } else {
+        None
+    }
+}
@@ -4,61 +4,533 @@ Asynchronous analogs to the base `*Chunker` types that wrap
 [`AsyncRead`](https://docs.rs/tokio/latest/tokio/io/trait.AsyncRead.html)
 types and implement
 [`Stream`](https://docs.rs/futures/latest/futures/stream/trait.Stream.html).
+
+# Cancel safety
+
+The chunkers here are cancel-safe: everything they've read from their
+sources, and every decision about where a chunk ends, is kept in the
+chunker itself rather than in the future returned by
+[`next()`](https://docs.rs/tokio-stream/latest/tokio_stream/trait.StreamExt.html#method.next),
+so dropping that future while it's pending (because another branch of a
+`select!` won) loses nothing; the next call picks up exactly where the
+dropped one left off. The same goes for chunks held back by a
+[throttle](ByteChunker::throttle) and reads in flight on a
+[`BlockingReader`]. The writers, [`ChunkJoiner`] and [`JsonLinesSink`],
+stage each chunk before writing it, so a cancelled write is finished by
+the next one rather than lost or repeated.
 */
 
+mod join;
+pub use join::*;
+mod handle;
+pub use handle::*;
+mod json;
+pub use json::*;
+mod merge;
+pub use merge::*;
+mod multipart;
+pub use multipart::*;
+mod pipeline;
+pub use pipeline::*;
+mod progress;
+pub use progress::*;
+mod rechunk;
+pub use rechunk::*;
+mod stats;
+pub use stats::*;
+mod throttle;
+use throttle::Throttle;
+
 use std::{
+    fmt::{Debug, Formatter},
+    future::Future,
+    io::{Read, SeekFrom},
     pin::Pin,
-    task::{Context, Poll},
+    sync::Arc,
+    task::{ready, Context, Poll},
 };
 
 use bytes::{Buf, BytesMut};
+use pin_project_lite::pin_project;
 use regex::bytes::Regex;
-use tokio::io::AsyncRead;
-use tokio_stream::Stream;
+use tokio::{
+    io::{AsyncRead, AsyncSeek, ReadBuf},
+    runtime::Handle,
+    sync::watch,
+    task::JoinHandle,
+};
+use tokio_stream::{Stream, StreamExt};
 use tokio_util::codec::{Decoder, FramedRead};
 
-use crate::{Adapter, MatchDisposition, RcErr};
+use crate::{
+    bom::UTF8_BOM,
+    chunk::char_count,
+    ctrl::ReadSizeFn,
+    engine::{ChunkEngine, Verdict},
+    err::ErrorTally,
+    rev::{RevCore, RevStep},
+    trace::{Decision, DecodeEvent, TraceFn},
+    trim::Trimmer,
+    Adapter, Boundary, BufferPolicy, ByteSetBoundary, CatchPanics, Chunk, ChunkBuf, Fallible,
+    FastqBoundary, InfallibleRead, JsonBoundary, LineBoundary, LiteralBoundary, MarkdownBoundary,
+    MatchDisposition, MultiRegex, OutputAdapter, OverlapPolicy, RcErr, RecordStartBoundary,
+    RegexCache, SqlBoundary, StringAdapter, TryAdapter, WarcBoundary,
+};
+
+pin_project! {
+    /* Limits the number of bytes requested from the source per poll. */
+    struct ReadLimit<R> {
+        #[pin]
+        inner: R,
+        limit: Option<usize>,
+        // If set, chooses each read's size; see `with_fragmented_reads`.
+        read_size: Option<ReadSizeFn>,
+        // If set, the most that may be read before yielding to the
+        // executor; see `with_poll_budget`.
+        budget: Option<usize>,
+        // How much has been read since the last time the executor got
+        // control back.
+        spent: usize,
+        // Once raised, the source is treated as ended; see
+        // `shutdown_handle`.
+        shutdown: Option<Arc<Signal>>,
+        // While raised, the chunker isn't polled; see `pause_handle`.
+        pause: Option<Arc<Signal>>,
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for ReadLimit<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.project();
+        if let Some(shutdown) = this.shutdown.as_ref() {
+            if shutdown.check(cx) {
+                return Poll::Ready(Ok(()));
+            }
+        }
+        let mut room = match *this.limit {
+            Some(limit) => limit.min(buf.remaining()),
+            None => buf.remaining(),
+        };
+        if let Some(budget) = *this.budget {
+            if *this.spent >= budget {
+                *this.spent = 0;
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            room = room.min(budget - *this.spent);
+        }
+        let limit = match this.read_size.as_mut() {
+            Some(read_size) => read_size(room).min(room),
+            None => room,
+        };
+
+        let before = buf.filled().len();
+        let res = if limit == buf.remaining() {
+            this.inner.poll_read(cx, buf)
+        } else {
+            let mut limited = ReadBuf::new(buf.initialize_unfilled_to(limit));
+            let res = this.inner.poll_read(cx, &mut limited);
+            let n = limited.filled().len();
+            buf.advance(n);
+            res
+        };
+        match res {
+            Poll::Pending => *this.spent = 0,
+            _ => *this.spent += buf.filled().len() - before,
+        }
+        res
+    }
+}
+
+// Blocking reads are made 8 KiB at a time.
+const BLOCKING_READ_SIZE: usize = 8 * 1024;
+
+/* Whether a `BlockingReader`'s source is available, or off on the blocking
+thread pool being read. */
+enum BlockingState<R> {
+    /// The reader is available; it's `None` only if a read panicked.
+    Idle(Option<R>),
+    Busy(JoinHandle<(R, std::io::Result<Vec<u8>>)>),
+}
+
+/**
+Adapts a blocking [`Read`] type (a zip entry reader or a serial port, say)
+into an
+[`AsyncRead`](https://docs.rs/tokio/latest/tokio/io/trait.AsyncRead.html)
+one, by performing each read on Tokio's blocking thread pool with
+[`spawn_blocking`](https://docs.rs/tokio/latest/tokio/task/fn.spawn_blocking.html).
+This lets sync-only sources feed an async [`ByteChunker`]; see
+[`ByteChunker::from_blocking`].
+
+Like `spawn_blocking` itself, this must be polled from within a Tokio
+runtime.
+*/
+pub struct BlockingReader<R> {
+    state: BlockingState<R>,
+    buff: Vec<u8>,
+    pos: usize,
+}
+
+impl<R> BlockingReader<R> {
+    /// Wrap the blocking reader `source`.
+    pub fn new(source: R) -> Self {
+        Self {
+            state: BlockingState::Idle(Some(source)),
+            buff: Vec::new(),
+            pos: 0,
+        }
+    }
+}
+
+// The source is only ever moved, never pinned.
+impl<R> Unpin for BlockingReader<R> {}
+
+impl<R> Debug for BlockingReader<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let busy = matches!(self.state, BlockingState::Busy(_));
+        f.debug_struct("BlockingReader")
+            .field("source", &std::any::type_name::<R>())
+            .field("busy", &busy)
+            .field("buffered", &(self.buff.len() - self.pos))
+            .finish()
+    }
+}
+
+impl<R: Read + Send + 'static> AsyncRead for BlockingReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = &mut *self;
+        loop {
+            if this.pos < this.buff.len() {
+                let n = buf.remaining().min(this.buff.len() - this.pos);
+                buf.put_slice(&this.buff[this.pos..this.pos + n]);
+                this.pos += n;
+                return Poll::Ready(Ok(()));
+            }
+
+            match &mut this.state {
+                BlockingState::Idle(source) => {
+                    let mut source = source.take().ok_or_else(|| {
+                        std::io::Error::other("blocking reader panicked during an earlier read")
+                    })?;
+                    let handle = tokio::task::spawn_blocking(move || {
+                        let mut block = vec![0u8; BLOCKING_READ_SIZE];
+                        let res = source.read(&mut block).map(|n| {
+                            block.truncate(n);
+                            block
+                        });
+                        (source, res)
+                    });
+                    this.state = BlockingState::Busy(handle);
+                }
+                BlockingState::Busy(handle) => {
+                    let joined = match Pin::new(handle).poll(cx) {
+                        Poll::Pending => return Poll::Pending,
+                        Poll::Ready(joined) => joined,
+                    };
+                    let (source, res) = match joined {
+                        Ok(x) => x,
+                        Err(e) => {
+                            this.state = BlockingState::Idle(None);
+                            return Poll::Ready(Err(e.into()));
+                        }
+                    };
+                    this.state = BlockingState::Idle(Some(source));
+
+                    let block = res?;
+                    if block.is_empty() {
+                        // End of the source.
+                        return Poll::Ready(Ok(()));
+                    }
+                    this.buff = block;
+                    this.pos = 0;
+                }
+            }
+        }
+    }
+}
 
 struct ByteDecoder {
-    fence: Regex,
-    match_dispo: MatchDisposition,
+    engine: ChunkEngine,
     scan_offset: usize,
+    matched_pattern: Option<usize>,
+    offset: u64,
+    /* If set, the offset in characters of the start of the buffer. */
+    char_offset: Option<u64>,
+    keep_delimiters: bool,
+    /* When only counting chunks, don't bother copying their contents. */
+    discard_bodies: bool,
+    trimmer: Option<Trimmer>,
+    /* Set until a leading UTF-8 BOM has been looked for (and removed). */
+    strip_bom: bool,
+    /* Whether unterminated data at the end of the source is an error. */
+    strict_tail: bool,
+    buffer_policy: Option<Box<dyn BufferPolicy>>,
+    /* Whether searches should resume short of where the previous
+    fruitless search ended, rather than starting over from the beginning
+    of the buffer, and how far short (from `Boundary::lookback`), if the
+    fence says. */
+    incremental: bool,
+    lookback: Option<usize>,
+    resume_offset: usize,
+    /* Set once the source is exhausted; see `Boundary::find_at_end`. */
+    at_end: bool,
+    tracer: Option<TraceFn>,
+    /* The number of chunks returned so far, for progress reports. */
+    chunks: u64,
+    /* The most the buffer has ever held; see `peak_buffered_len`. */
+    peak_buffered: usize,
+    progress: ProgressTx,
+    stats: StatsTx,
+}
+
+impl ByteDecoder {
+    fn new(fence: Box<dyn Boundary + Send>) -> Self {
+        Self {
+            engine: ChunkEngine::new(fence),
+            //error_status: ErrorStatus::Ok,
+            scan_offset: 0,
+            matched_pattern: None,
+            offset: 0,
+            char_offset: None,
+            keep_delimiters: false,
+            discard_bodies: false,
+            trimmer: None,
+            strip_bom: false,
+            strict_tail: false,
+            buffer_policy: None,
+            incremental: false,
+            lookback: None,
+            resume_offset: 0,
+            at_end: false,
+            tracer: None,
+            chunks: 0,
+            peak_buffered: 0,
+            progress: ProgressTx::default(),
+            stats: StatsTx::default(),
+        }
+    }
+
+    /*
+    Remove the first `consumed` bytes from `src`, returning a chunk of the
+    bytes `first..len` (less anything trimmed from either end) and their
+    span in the source. The caller fills in the rest of the chunk's
+    metadata.
+    */
+    fn take(&mut self, src: &mut BytesMut, first: usize, len: usize, consumed: usize) -> Chunk {
+        let kept = match self.trimmer.as_ref() {
+            Some(trimmer) => {
+                let kept = trimmer.range(&src[first..len]);
+                (first + kept.start)..(first + kept.end)
+            }
+            None => first..len,
+        };
+        let span = (self.offset + kept.start as u64)..(self.offset + kept.end as u64);
+        self.offset += consumed as u64;
+        let char_span = self.char_offset.map(|chars| {
+            let start = chars + char_count(&src[..kept.start]);
+            let end = start + char_count(&src[kept.clone()]);
+            self.char_offset = Some(chars + char_count(&src[..consumed]));
+            start..end
+        });
+
+        let body = if self.discard_bodies {
+            src.advance(consumed);
+            Vec::new()
+        } else if kept.end > consumed {
+            // The chunk ends with a delimiter that stays in the buffer to
+            // start the next one, under `MatchDisposition::Wrap`.
+            let body = src[kept].to_vec();
+            src.advance(consumed);
+            body
+        } else {
+            let mut body = src.split_to(consumed);
+            body.truncate(kept.end);
+            body.advance(kept.start);
+            body.into()
+        };
+
+        if let Some(policy) = self.buffer_policy.as_mut() {
+            policy.observe(consumed);
+            if let Some(target) = policy.shrink(src.len(), src.capacity()) {
+                let mut new_src = BytesMut::with_capacity(target.max(src.len()));
+                new_src.extend_from_slice(src);
+                *src = new_src;
+            }
+        }
+        self.chunks += 1;
+        self.report(src, false);
+        Chunk {
+            body,
+            delimiter: None,
+            span,
+            char_span,
+            eof: false,
+            forced: false,
+            seq: self.chunks - 1,
+            gap: false,
+        }
+    }
+
+    /* Pass a `DecodeEvent` describing a decision to the trace hook, if any. */
+    fn trace(
+        &mut self,
+        src: &BytesMut,
+        scan_offset: usize,
+        found: Option<&(usize, usize, MatchDisposition)>,
+        decision: Decision,
+    ) {
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer(&DecodeEvent {
+                offset: self.offset,
+                buffered: src.len(),
+                scan_offset,
+                at_end: self.at_end,
+                found: found.map(|(start, end, _)| *start..*end),
+                disposition: found.map(|(_, _, dispo)| dispo.clone()),
+                decision,
+            });
+        }
+    }
+
+    /* Publish the chunker's progress and stats, given what's left in the
+    buffer. */
+    fn report(&self, src: &BytesMut, finished: bool) {
+        let bytes_read = self.offset + src.len() as u64;
+        self.progress.update(bytes_read, self.chunks, finished);
+        self.stats.update(bytes_read, self.chunks, src.len());
+    }
+
+    /* Give the buffer policy, if any, a chance to grow a full buffer. */
+    fn grow(&mut self, src: &mut BytesMut) {
+        if src.len() < src.capacity() {
+            return;
+        }
+        if let Some(policy) = self.buffer_policy.as_mut() {
+            if let Some(n) = policy.grow(src.len(), src.capacity()) {
+                src.reserve(n);
+            }
+        }
+    }
 }
 
 impl Decoder for ByteDecoder {
-    type Item = Vec<u8>;
+    type Item = Chunk;
     type Error = RcErr;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        let (start, end) = match self.fence.find_at(src.as_ref(), self.scan_offset) {
-            Some(m) => (m.start(), m.end()),
-            None => return Ok(None),
-        };
-        let length = end - start;
+        self.peak_buffered = self.peak_buffered.max(src.len());
+        if self.strip_bom {
+            if src.len() < UTF8_BOM.len() && UTF8_BOM.starts_with(src) && !self.at_end {
+                return Ok(None);
+            }
+            if src.starts_with(UTF8_BOM) {
+                src.advance(UTF8_BOM.len());
+                self.offset += UTF8_BOM.len() as u64;
+                if let Some(chars) = self.char_offset.as_mut() {
+                    *chars += 1;
+                }
+            }
+            self.strip_bom = false;
+        }
 
-        let new_buff = match self.match_dispo {
-            MatchDisposition::Drop => {
-                let new_buff: Vec<u8> = src.split_to(start).into();
-                src.advance(length);
-                new_buff
+        let search_start = self.scan_offset.max(self.resume_offset);
+        let verdict = self.engine.decide(src, search_start, self.at_end);
+        let (found, first, len, consumed) = match verdict {
+            Verdict::Wait => {
+                self.trace(src, search_start, None, Decision::Wait);
+                self.report(src, false);
+                // A run of repeated matches can start any distance back.
+                if let (Some(lookback), 1) = (self.lookback, self.engine.min_repeats) {
+                    self.resume_offset = src.len().saturating_sub(lookback);
+                }
+                self.grow(src);
+                return Ok(None);
             }
-            MatchDisposition::Append => src.split_to(end).into(),
-            MatchDisposition::Prepend => {
-                self.scan_offset = length;
-                src.split_to(start).into()
+            Verdict::Forced { first, len, found } => {
+                self.trace(src, search_start, found.as_ref(), Decision::Forced { len });
+                self.resume_offset = 0;
+                self.matched_pattern = None;
+                self.scan_offset = self.scan_offset.saturating_sub(len);
+                let chunk = self.take(src, first, len, len);
+                return Ok(Some(Chunk {
+                    delimiter: self.keep_delimiters.then(Vec::new),
+                    forced: true,
+                    ..chunk
+                }));
             }
+            Verdict::Split {
+                found,
+                first,
+                len,
+                consumed,
+                rescan,
+            } => {
+                self.scan_offset = rescan;
+                (found, first, len, consumed)
+            }
+        };
+        self.resume_offset = 0;
+        self.matched_pattern = self.engine.matched_pattern();
+        self.trace(
+            src,
+            search_start,
+            Some(&found),
+            Decision::Split { len, consumed },
+        );
+
+        let (start, end, dispo) = found;
+        let delimiter = if self.keep_delimiters {
+            Some(src[start..end].to_vec())
+        } else {
+            None
+        };
+        let replacement = match dispo {
+            MatchDisposition::Replace(bytes) => Some(bytes),
+            _ => None,
         };
 
-        Ok(Some(new_buff))
+        let mut chunk = self.take(src, first, len, consumed);
+        if let Some(replacement) = replacement {
+            if !self.discard_bodies {
+                chunk.body.extend_from_slice(&replacement);
+            }
+        }
+        Ok(Some(Chunk { delimiter, ..chunk }))
     }
 
     fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        if let Some(v) = self.decode(src)? {
-            Ok(Some(v))
-        } else if src.is_empty() {
+        self.at_end = true;
+        if let Some(c) = self.decode(src)? {
+            Ok(Some(c))
+        } else if src.len() <= self.engine.spent() {
+            // Only (part of) a delimiter that's already been dealt with is
+            // left.
+            self.engine.take_lead();
+            if let Some(chars) = self.char_offset.as_mut() {
+                *chars += char_count(src);
+            }
+            self.offset += src.len() as u64;
+            src.clear();
+            self.report(src, true);
             Ok(None)
         } else {
-            Ok(Some(src.split().into()))
+            self.matched_pattern = None;
+            let len = src.len();
+            self.trace(src, self.scan_offset, None, Decision::Tail { len });
+            let first = self.engine.take_lead();
+            let chunk = self.take(src, first, len, len);
+            if self.strict_tail {
+                return Err(RcErr::Unterminated(chunk.body));
+            }
+            Ok(Some(Chunk { eof: true, ..chunk }))
         }
     }
 }
@@ -76,7 +548,8 @@ handles errors; you'll get errors when Tokio's underlying black magic
 returns them.
 */
 pub struct ByteChunker<R: AsyncRead> {
-    freader: FramedRead<R, ByteDecoder>,
+    freader: FramedRead<ReadLimit<R>, ByteDecoder>,
+    throttle: Option<Throttle>,
 }
 
 impl<R: AsyncRead> ByteChunker<R> {
@@ -85,161 +558,2717 @@ impl<R: AsyncRead> ByteChunker<R> {
     /// expression pattern.
     pub fn new(source: R, pattern: &str) -> Result<Self, RcErr> {
         let fence = Regex::new(pattern)?;
-        let decoder = ByteDecoder {
-            fence,
-            //error_status: ErrorStatus::Ok,
-            match_dispo: MatchDisposition::default(),
-            scan_offset: 0,
-        };
+        Ok(Self::from_boundary(source, fence))
+    }
 
-        let freader = FramedRead::new(source, decoder);
-        Ok(Self { freader })
+    /// Return a new [`ByteChunker`] wrapping the given async reader that
+    /// will chunk its output at occurrences of the given literal string,
+    /// with any regex metacharacters in it escaped. See
+    /// [`ByteChunker::new_literal`](crate::ByteChunker::new_literal).
+    pub fn new_literal(source: R, delimiter: &str) -> Result<Self, RcErr> {
+        Self::new(source, &regex::escape(delimiter))
     }
 
-    pub fn with_adapter<A>(self, adapter: A) -> CustomChunker<R, A> {
-        CustomChunker {
-            chunker: self,
-            adapter,
-        }
+    /// Like [`ByteChunker::new`], but preallocates room for `capacity`
+    /// bytes in the chunker's internal buffer, rather than starting with
+    /// the default capacity and growing to fit the largest chunk.
+    pub fn new_with_capacity(source: R, pattern: &str, capacity: usize) -> Result<Self, RcErr> {
+        let mut chunker = Self::new(source, pattern)?;
+        chunker.freader.read_buffer_mut().reserve(capacity);
+        Ok(chunker)
     }
 
-    /// Builder-pattern for controlling what the chunker does with the
-    /// matched text; default value is [`MatchDisposition::Drop`].
-    pub fn with_match(mut self, behavior: MatchDisposition) -> Self {
-        let d = self.freader.decoder_mut();
-        d.match_dispo = behavior;
-        if matches!(behavior, MatchDisposition::Drop | MatchDisposition::Append) {
-            d.scan_offset = 0;
+    /// Like [`ByteChunker::new`], but looks the compiled regex up in (or
+    /// adds it to) the process-wide [`RegexCache`], rather than compiling
+    /// it anew.
+    pub fn new_cached(source: R, pattern: &str) -> Result<Self, RcErr> {
+        let fence = RegexCache::global().get(pattern)?;
+        Ok(Self::from_boundary(source, fence))
+    }
+
+    /// Return a new [`ByteChunker`] wrapping the given async reader that
+    /// will chunk its output by delimiting it with the given Unicode
+    /// regular expression pattern, matched against the decoded text of
+    /// the source rather than its raw bytes.
+    pub fn new_text(source: R, pattern: &str) -> Result<Self, RcErr> {
+        let fence = regex::Regex::new(pattern)?;
+        Ok(Self::from_boundary(source, fence))
+    }
+
+    /// Return a new [`ByteChunker`] wrapping the given async reader that
+    /// will chunk its output at the delimiters found by the supplied
+    /// [`Boundary`].
+    pub fn from_boundary<B>(source: R, boundary: B) -> Self
+    where
+        B: Boundary + Send + 'static,
+    {
+        let decoder = ByteDecoder::new(Box::new(boundary));
+
+        let source = ReadLimit {
+            inner: source,
+            limit: None,
+            read_size: None,
+            budget: None,
+            spent: 0,
+            shutdown: None,
+            pause: None,
+        };
+        let freader = FramedRead::new(source, decoder);
+        Self {
+            freader,
+            throttle: None,
         }
-        self
     }
-}
 
-impl<A: AsyncRead + Unpin> Stream for ByteChunker<A> {
-    type Item = Result<Vec<u8>, RcErr>;
+    /// Return a new [`ByteChunker`] wrapping the given async reader that
+    /// will chunk its output at occurrences of any of the supplied literal
+    /// delimiters. See [`LiteralBoundary`].
+    pub fn from_literals<I, P>(source: R, literals: I) -> Result<Self, RcErr>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<[u8]>,
+    {
+        let fence = LiteralBoundary::new(literals)?;
+        Ok(Self::from_boundary(source, fence))
+    }
 
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        Pin::new(&mut self.freader).poll_next(cx)
+    /// Return a new [`ByteChunker`] wrapping the given async reader that
+    /// will chunk its output at matches of any of the supplied patterns,
+    /// each paired with the [`MatchDisposition`] to apply to its matches.
+    /// See [`MultiRegex`].
+    pub fn from_patterns<I, P>(source: R, patterns: I) -> Result<Self, RcErr>
+    where
+        I: IntoIterator<Item = (P, MatchDisposition)>,
+        P: AsRef<str>,
+    {
+        let fence = MultiRegex::new(patterns)?;
+        Ok(Self::from_boundary(source, fence))
     }
-}
 
-/**
-The async analog to the base crate's
-[`CustomChunker`](`crate::CustomChunker`).
-It takes an [`Adapter`] and yields chunks based on the `Adapter`'s
-transformation.
+    /// Return a new [`ByteChunker`] wrapping the given async reader that
+    /// will chunk its output at runs of any of the supplied delimiter
+    /// bytes. See [`ByteSetBoundary`].
+    pub fn from_byte_set(source: R, delimiters: &[u8]) -> Self {
+        Self::from_boundary(source, ByteSetBoundary::new(delimiters))
+    }
 
-```rust
-# use std::error::Error;
-# #[tokio::main]
-# async fn main() -> Result<(), Box<dyn Error>> {
-    use regex_chunker::{
-        stream::ByteChunker,
-        StringAdapter,
-    };
-    use tokio_stream::StreamExt;
-    use std::io::Cursor;
+    /// Return a new [`ByteChunker`] wrapping the given async reader that
+    /// will chunk its output into lines, delimited by `\r?\n`. See
+    /// [`LineBoundary`].
+    pub fn lines(source: R) -> Self {
+        Self::from_boundary(source, LineBoundary::crlf())
+    }
 
-    let text = b"One, two, three four. Can I have a little more?";
-    let c = Cursor::new(text);
+    /// Return a new [`ByteChunker`] wrapping the given async reader that
+    /// will chunk its output at each `\0`. See
+    /// [`ByteChunker::null_delimited`](crate::ByteChunker::null_delimited).
+    pub fn null_delimited(source: R) -> Self {
+        Self::from_boundary(source, LineBoundary::null())
+    }
 
-    let chunks: Vec<_> = ByteChunker::new(c, "[ .,?]+")?
-        .with_adapter(StringAdapter::default())
-        .map(|res| res.unwrap())
-        .collect().await;
+    /// Return a new [`ByteChunker`] wrapping the given async reader that
+    /// will chunk its output into whole JSON documents. See
+    /// [`JsonBoundary`].
+    pub fn json_documents(source: R) -> Self {
+        Self::from_boundary(source, JsonBoundary::new())
+    }
 
-    assert_eq!(
-        &chunks,
-        &[
-            "One", "two", "three", "four",
-            "Can", "I", "have", "a", "little", "more"
-        ].clone()
-    );
-#   Ok(()) }
-*/
-pub struct CustomChunker<R: AsyncRead, A> {
-    chunker: ByteChunker<R>,
-    adapter: A,
-}
+    /// Return a new [`ByteChunker`] wrapping the given async reader that
+    /// will chunk a SQL script into statements. See [`SqlBoundary`].
+    pub fn sql_statements(source: R) -> Self {
+        Self::from_boundary(source, SqlBoundary::new())
+    }
 
-impl<R: AsyncRead, A> CustomChunker<R, A> {
-    /// Consumes the [`CustomChunker`] and returns the underlying
-    /// [`ByteChunker`] and [`Adapter`].
-    pub fn into_innards(self) -> (ByteChunker<R>, A) {
-        (self.chunker, self.adapter)
+    /// Return a new [`ByteChunker`] wrapping the given async reader that
+    /// will chunk its output into multi-line records, each starting with
+    /// a line that begins with a match for `record_start`. See
+    /// [`RecordStartBoundary`].
+    pub fn log_records(source: R, record_start: &str) -> Result<Self, RcErr> {
+        let fence = RecordStartBoundary::new(record_start)?;
+        Ok(Self::from_boundary(source, fence))
     }
 
-    /// Get a reference to the underlying [`Adapter`].
-    pub fn get_adapter(&self) -> &A { &self.adapter }
+    /// Return a new [`ByteChunker`] wrapping the given async reader that
+    /// will chunk a FASTA file into records. See
+    /// [`RecordStartBoundary::fasta`].
+    pub fn fasta_records(source: R) -> Self {
+        Self::from_boundary(source, RecordStartBoundary::fasta())
+    }
 
-    /// Get a mutable reference to the underlying [`Adapter`].
-    pub fn get_adapter_mut(&mut self) -> &mut A { &mut self.adapter }
-}
+    /// Return a new [`ByteChunker`] wrapping the given async reader that
+    /// will chunk a FASTQ file into its four-line records. See
+    /// [`FastqBoundary`].
+    pub fn fastq_records(source: R) -> Self {
+        Self::from_boundary(source, FastqBoundary::new())
+    }
 
-impl<R: AsyncRead, A> Unpin for CustomChunker<R, A> {}
+    /// Return a new [`ByteChunker`] wrapping the given async reader that
+    /// will chunk an mbox file into messages. See
+    /// [`ByteChunker::mbox_messages`](crate::ByteChunker::mbox_messages).
+    pub fn mbox_messages(source: R) -> Self {
+        Self::from_boundary(source, RecordStartBoundary::mbox())
+    }
 
-impl<R, A> Stream for CustomChunker<R, A>
-where
-    R: AsyncRead + Unpin,
-    A: Adapter
-{
-    type Item = A::Item;
+    /// Return a new [`ByteChunker`] wrapping the given async reader that
+    /// will chunk a WARC file into its records. See
+    /// [`ByteChunker::warc_records`](crate::ByteChunker::warc_records).
+    pub fn warc_records(source: R) -> Self {
+        Self::from_boundary(source, WarcBoundary::new())
+    }
 
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let p = Pin::new(&mut self.chunker).poll_next(cx);
-        match p {
-            Poll::Pending => Poll::Pending,
-            Poll::Ready(x) => Poll::Ready(self.adapter.adapt(x)),
-        }
+    /// Return a new [`ByteChunker`] wrapping the given async reader that
+    /// will chunk a Markdown document into sections at headings of level
+    /// `level` or higher. See [`MarkdownBoundary`].
+    pub fn markdown_sections(source: R, level: usize) -> Self {
+        Self::from_boundary(source, MarkdownBoundary::new(level))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    #[allow(unused_imports)]
-    use crate::tests::{
-        chunk_vec, ref_slice_cmp, HTTP_PATT, HTTP_URL, PASSWD_PATH, PASSWD_PATT, TEST_PATH,
-        TEST_PATT,
-    };
+    /// Builder-pattern method for setting the maximum number of bytes the
+    /// chunker will ask for each time it polls its source. This is
+    /// independent of the capacity of the chunker's internal buffer; small
+    /// reads can keep latency down, while large ones (the default is to ask
+    /// for as much as the buffer has room for) make for better throughput.
+    /// The minimum value is 1.
+    pub fn with_read_size(mut self, size: usize) -> Self {
+        self.freader.get_mut().limit = Some(size.max(1));
+        self
+    }
 
-    use std::process::Stdio;
+    /// Builder-pattern method for deliberately breaking up the chunker's
+    /// reads into pieces of random (but, for a given `seed`,
+    /// reproducible) sizes. See
+    /// [`ByteChunker::with_fragmented_reads`](crate::ByteChunker::with_fragmented_reads).
+    #[cfg(feature = "test-util")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+    pub fn with_fragmented_reads(mut self, seed: u64) -> Self {
+        self.freader.get_mut().read_size = Some(crate::mock::fragmenter(seed));
+        self
+    }
 
-    use tokio::{fs::File, process::Command};
-    use tokio_stream::StreamExt;
+    /// Builder-pattern method for capping the number of bytes the chunker
+    /// reads from its source before handing control back to the executor.
+    /// A source that always has data ready (a fast socket, a file, an
+    /// in-memory buffer) never makes a poll return `Poll::Pending` on its
+    /// own, so one poll looking for a long chunk can read, and scan, a
+    /// great deal of data while other tasks wait. With a budget set, once
+    /// `bytes` have been read without the source pausing, the chunker
+    /// arranges to be woken again and returns `Poll::Pending`, picking up
+    /// where it left off on the next poll.
+    ///
+    /// This bounds the reading done per poll; for the scanning to be
+    /// bounded too, combine it with
+    /// [`with_incremental_search`](ByteChunker::with_incremental_search),
+    /// as otherwise each search starts over from the beginning of the
+    /// chunk. The minimum value is 1.
+    pub fn with_poll_budget(mut self, bytes: usize) -> Self {
+        self.freader.get_mut().budget = Some(bytes.max(1));
+        self
+    }
 
-    static SOURCE: &str = "target/debug/slowsource";
-    static SOURCE_ARGS: &[&str] = &[TEST_PATH, "0.0", "0.1"];
+    /// Builder-pattern method for limiting the chunker to returning at
+    /// most `chunks_per_second` chunks per second (on average; up to a
+    /// second's worth can be returned in a burst), for replaying a
+    /// recorded stream at a realistic speed, or keeping from swamping a
+    /// service downstream. A chunk that arrives too early is held back
+    /// until the limit allows it. Can be combined with
+    /// [`throttle_bytes`](ByteChunker::throttle_bytes), in which case a
+    /// chunk has to satisfy both.
+    ///
+    /// # Panics
+    ///
+    /// If `chunks_per_second` isn't positive.
+    ///
+    /// ```rust
+    /// # use std::error::Error;
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    ///     use regex_chunker::stream::ByteChunker;
+    ///     use std::time::{Duration, Instant};
+    ///     use tokio_stream::StreamExt;
+    ///
+    ///     let text = b"1\n2\n3\n4\n5\n6\n";
+    ///     let mut chunker = ByteChunker::new(&text[..], r"\n")?.throttle(100.0);
+    ///
+    ///     let start = Instant::now();
+    ///     let mut n = 0;
+    ///     while let Some(chunk) = chunker.next().await {
+    ///         chunk?;
+    ///         n += 1;
+    ///     }
+    ///     assert_eq!(n, 6);
+    ///     // All within the first second's burst.
+    ///     assert!(start.elapsed() < Duration::from_secs(1));
+    /// #   Ok(()) }
+    /// ```
+    pub fn throttle(mut self, chunks_per_second: f64) -> Self {
+        self.throttle
+            .get_or_insert_with(Default::default)
+            .set_chunk_rate(chunks_per_second);
+        self
+    }
 
-    #[tokio::test]
-    async fn basic_async() {
-        let byte_vec = std::fs::read(TEST_PATH).unwrap();
-        let re = Regex::new(TEST_PATT).unwrap();
-        let slice_vec = chunk_vec(&re, &byte_vec, MatchDisposition::Drop);
+    /// Builder-pattern method for limiting the chunker to returning at
+    /// most `bytes_per_second` bytes of chunk data per second (on average;
+    /// up to a second's worth can be returned in a burst). A chunk longer
+    /// than a second's worth is let through once the allowance is full,
+    /// and the chunks after it are delayed to make up the difference. See
+    /// [`throttle`](ByteChunker::throttle).
+    ///
+    /// # Panics
+    ///
+    /// If `bytes_per_second` isn't positive.
+    pub fn throttle_bytes(mut self, bytes_per_second: f64) -> Self {
+        self.throttle
+            .get_or_insert_with(Default::default)
+            .set_byte_rate(bytes_per_second);
+        self
+    }
 
-        let f = File::open(TEST_PATH).await.unwrap();
-        let chunker = ByteChunker::new(f, TEST_PATT).unwrap();
-        let vec_vec: Vec<Vec<u8>> = chunker.map(|res| res.unwrap()).collect().await;
+    /// Return a [`ShutdownHandle`] that can be used to make the chunker
+    /// stop reading, return what it has buffered, and end. Every call
+    /// returns a handle to the same switch.
+    pub fn shutdown_handle(&mut self) -> ShutdownHandle {
+        let signal = self
+            .freader
+            .get_mut()
+            .shutdown
+            .get_or_insert_with(Default::default);
+        ShutdownHandle {
+            signal: signal.clone(),
+        }
+    }
 
-        ref_slice_cmp(&vec_vec, &slice_vec);
+    /// Return a [`PauseHandle`] that can be used to pause the chunker and
+    /// resume it later. Every call returns a handle to the same switch.
+    pub fn pause_handle(&mut self) -> PauseHandle {
+        let signal = self
+            .freader
+            .get_mut()
+            .pause
+            .get_or_insert_with(Default::default);
+        PauseHandle {
+            signal: signal.clone(),
+        }
     }
 
-    #[tokio::test]
-    async fn slow_async() {
-        let byte_vec = std::fs::read(TEST_PATH).unwrap();
-        let re = Regex::new(TEST_PATT).unwrap();
-        let slice_vec = chunk_vec(&re, &byte_vec, MatchDisposition::Drop);
+    /// Builder-pattern method for making the chunker's searches
+    /// incremental. Normally, each time more data arrives without a
+    /// delimiter having been found, the whole buffered chunk is searched
+    /// again from its beginning, so a single very long chunk can make one
+    /// poll spend milliseconds in the regex engine, blocking other tasks
+    /// on the executor. With this set, each search instead resumes just
+    /// far enough short of where the previous one gave up to catch any
+    /// delimiter the new data could complete, so (in combination with
+    /// [`with_read_size`](ByteChunker::with_read_size)) the work done per
+    /// poll stays bounded no matter how large the chunk grows.
+    ///
+    /// The chunks returned are the same either way. How far back to
+    /// resume comes from [`Boundary::lookback`]; for a regex, that's the
+    /// length of its longest possible match. If the delimiter has no
+    /// such limit (like `,+`), or with
+    /// [`with_min_repeats`](ByteChunker::with_min_repeats), each search
+    /// starts over from the beginning as usual.
+    pub fn with_incremental_search(mut self, incremental: bool) -> Self {
+        let decoder = self.freader.decoder_mut();
+        decoder.incremental = incremental;
+        decoder.lookback = decoder.engine.fence.lookback().filter(|_| incremental);
+        self
+    }
 
-        let mut child = Command::new(SOURCE)
-            .args(SOURCE_ARGS)
-            .stdout(Stdio::piped())
-            .spawn()
-            .unwrap();
-        let stdout = child.stdout.take().unwrap();
-        let chunker = ByteChunker::new(stdout, TEST_PATT).unwrap();
-        let vec_vec: Vec<Vec<u8>> = chunker.map(|res| res.unwrap()).collect().await;
-        child.wait().await.unwrap();
+    /// Builder-pattern method for limiting the length of the chunks
+    /// returned, splitting a chunk that reaches `max` bytes without a
+    /// delimiter. See
+    /// [`ByteChunker::with_max_chunk_len`](crate::ByteChunker::with_max_chunk_len).
+    pub fn with_max_chunk_len(mut self, max: usize) -> Self {
+        self.freader.decoder_mut().engine.max_chunk_len = Some(max.max(1));
+        self
+    }
 
-        ref_slice_cmp(&vec_vec, &slice_vec);
+    /// Builder-pattern method for trimming text that matches `pattern`
+    /// from both ends of every chunk. See
+    /// [`ByteChunker::trim_matches`](crate::ByteChunker::trim_matches).
+    pub fn trim_matches(mut self, pattern: &str) -> Result<Self, RcErr> {
+        self.freader.decoder_mut().trimmer = Some(Trimmer::new(pattern)?);
+        Ok(self)
+    }
+
+    /// Builder-pattern method for making forced splits fall between
+    /// grapheme clusters. See
+    /// [`ByteChunker::with_grapheme_safe_splits`](crate::ByteChunker::with_grapheme_safe_splits).
+    #[cfg(feature = "unicode")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "unicode")))]
+    pub fn with_grapheme_safe_splits(mut self, safe: bool) -> Self {
+        self.freader.decoder_mut().engine.grapheme_safe = safe;
+        self
+    }
+
+    /// Builder-pattern method for removing a UTF-8 byte-order mark from
+    /// the very beginning of the source. See
+    /// [`ByteChunker::with_bom_stripping`](crate::ByteChunker::with_bom_stripping).
+    pub fn with_bom_stripping(mut self, strip: bool) -> Self {
+        self.freader.decoder_mut().strip_bom = strip;
+        self
+    }
+
+    /// Builder-pattern method for setting a hook that is called with a
+    /// [`DecodeEvent`](crate::DecodeEvent) for each of the chunker's
+    /// decisions about where a chunk ends. See
+    /// [`ByteChunker::with_trace`](crate::ByteChunker::with_trace).
+    #[cfg(feature = "debug-internals")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "debug-internals")))]
+    pub fn with_trace<F>(mut self, hook: F) -> Self
+    where
+        F: FnMut(&DecodeEvent) + Send + 'static,
+    {
+        self.freader.decoder_mut().tracer = Some(Box::new(hook));
+        self
+    }
+
+    /// Builder-pattern method for supplying the length of the source
+    /// (say, from a file's metadata or a `Content-Length` header), so
+    /// that [`Progress::fraction`] can report how much of it has been
+    /// read.
+    pub fn with_source_len(mut self, len: u64) -> Self {
+        self.freader.decoder_mut().progress.set_source_len(len);
+        self
+    }
+
+    /// Return a receiver for [`Progress`] reports, updated as the chunker
+    /// reads data and returns chunks, so a UI or a health check can keep
+    /// an eye on it without wrapping the source. Every call returns a
+    /// receiver on the same channel; nothing is published until the first
+    /// one is asked for.
+    pub fn progress(&mut self) -> watch::Receiver<Progress> {
+        let decoder = self.freader.decoder();
+        let current = Progress {
+            bytes_read: decoder.offset + self.freader.read_buffer().len() as u64,
+            chunks: decoder.chunks,
+            ..Progress::default()
+        };
+        self.freader.decoder_mut().progress.subscribe(current)
+    }
+
+    /// Return a receiver for running [`Stats`] (bytes read, chunks
+    /// produced, errors returned, bytes buffered), kept up to date as the
+    /// chunker runs, for metrics scraping from another task. Every call
+    /// returns a receiver on the same channel; nothing is published until
+    /// the first one is asked for, but the error count covers the
+    /// chunker's whole life.
+    pub fn stats(&mut self) -> watch::Receiver<Stats> {
+        let decoder = self.freader.decoder();
+        let buffered = self.freader.read_buffer().len();
+        let current = Stats {
+            bytes_in: decoder.offset + buffered as u64,
+            chunks_out: decoder.chunks,
+            errors: 0,
+            buffered,
+        };
+        self.freader.decoder_mut().stats.subscribe(current)
+    }
+
+    /// Builder-pattern method for setting the [`BufferPolicy`] that
+    /// controls how the chunker's internal buffer grows, and when it
+    /// shrinks. See
+    /// [`ByteChunker::with_buffer_policy`](crate::ByteChunker::with_buffer_policy).
+    pub fn with_buffer_policy<P>(mut self, policy: P) -> Self
+    where
+        P: BufferPolicy + 'static,
+    {
+        self.freader.decoder_mut().buffer_policy = Some(Box::new(policy));
+        self
+    }
+
+    /// Replace the pattern the chunker splits its source on, from the
+    /// next search on. See
+    /// [`ByteChunker::set_pattern`](crate::ByteChunker::set_pattern).
+    /// Data already buffered is searched again with the new pattern
+    /// right away if the change is made between chunks; if the chunker
+    /// was waiting for more data, it's searched again when more arrives
+    /// (or the source ends).
+    pub fn set_pattern(&mut self, pattern: &str) -> Result<(), RcErr> {
+        self.set_regex(Regex::new(pattern)?);
+        Ok(())
+    }
+
+    /// Like [`ByteChunker::set_pattern`], but takes an already-compiled
+    /// regex.
+    pub fn set_regex(&mut self, regex: Regex) {
+        let decoder = self.freader.decoder_mut();
+        decoder.engine.fence = Box::new(regex);
+        decoder.lookback = decoder
+            .engine
+            .fence
+            .lookback()
+            .filter(|_| decoder.incremental);
+        decoder.resume_offset = 0;
+    }
+
+    /// Get a reference to the wrapped async reader.
+    pub fn get_ref(&self) -> &R {
+        &self.freader.get_ref().inner
+    }
+
+    /// Get a mutable reference to the wrapped async reader. Reading from
+    /// it directly will, of course, interfere with the chunking.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.freader.get_mut().inner
+    }
+
+    /// Consumes the [`ByteChunker`] and returns its wrapped async reader.
+    /// Any data that has been read from it but not yet returned as part
+    /// of a chunk is lost.
+    pub fn into_inner(self) -> R {
+        self.freader.into_inner().inner
+    }
+
+    /// Return the index of the pattern whose match terminated the most
+    /// recently returned chunk, if the chunker's [`Boundary`] reports one.
+    pub fn matched_pattern(&self) -> Option<usize> {
+        self.freader.decoder().matched_pattern
+    }
+
+    /// Return the number of bytes that have been read from the source but
+    /// not yet returned as part of a chunk.
+    pub fn buffered_len(&self) -> usize {
+        self.freader.read_buffer().len()
+    }
+
+    /// Return the largest number of bytes that have ever been buffered at
+    /// once. See [`ByteChunker::peak_buffered_len`](crate::ByteChunker::peak_buffered_len).
+    pub fn peak_buffered_len(&self) -> usize {
+        self.freader.decoder().peak_buffered
+    }
+
+    /// Converts this `ByteChunker` into a [`MetaChunker`], which yields
+    /// [`Chunk`]s carrying each chunk's delimiter and position in the
+    /// source, rather than bare `Vec<u8>`s.
+    pub fn with_metadata(mut self) -> MetaChunker<R> {
+        self.freader.decoder_mut().keep_delimiters = true;
+        MetaChunker { chunker: self }
+    }
+
+    /// Converts this `ByteChunker` into a [`DelimitedChunker`], which
+    /// yields each chunk paired with the delimiter that terminated it.
+    pub fn with_delimiters(self) -> DelimitedChunker<R> {
+        DelimitedChunker {
+            chunker: self.with_metadata(),
+        }
+    }
+
+    /// Converts this `ByteChunker` into an [`InfallibleChunker`], which
+    /// yields plain `Vec<u8>`s rather than `Result`s, as reading from an
+    /// [`InfallibleRead`](crate::InfallibleRead) source can't fail.
+    pub fn infallible(self) -> InfallibleChunker<R>
+    where
+        R: InfallibleRead,
+    {
+        InfallibleChunker { chunker: self }
+    }
+
+    /// Converts this `ByteChunker` into a [`ChunkInspector`], which
+    /// yields the same chunks, but first passes each one as a [`Chunk`]
+    /// to `inspect`. See
+    /// [`ByteChunker::with_inspector`](crate::ByteChunker::with_inspector).
+    pub fn with_inspector<F>(self, inspect: F) -> ChunkInspector<R, F>
+    where
+        F: FnMut(&Chunk),
+    {
+        ChunkInspector {
+            chunker: self.with_metadata(),
+            inspect,
+        }
+    }
+
+    pub fn with_adapter<A>(self, adapter: A) -> CustomChunker<R, A> {
+        CustomChunker {
+            chunker: self,
+            adapter,
+        }
+    }
+
+    /// Creates a [`CustomChunker`] by combining this `ByteChunker` with a
+    /// [`TryAdapter`]. See
+    /// [`ByteChunker::with_try_adapter`](crate::ByteChunker::with_try_adapter).
+    pub fn with_try_adapter<A>(self, adapter: A) -> CustomChunker<R, Fallible<A>>
+    where
+        A: TryAdapter,
+    {
+        self.with_adapter(Fallible(adapter))
+    }
+
+    /// Creates a [`CustomChunker`] that returns its chunks as `B`, rather
+    /// than `Vec<u8>`, without copying them. See
+    /// [`ChunkBuf`](crate::ChunkBuf).
+    pub fn with_output<B: ChunkBuf>(self) -> CustomChunker<R, OutputAdapter<B>> {
+        self.with_adapter(OutputAdapter::new())
+    }
+
+    /// Builder-pattern for controlling what the chunker does with the
+    /// matched text; default value is [`MatchDisposition::Drop`].
+    pub fn with_match(mut self, behavior: MatchDisposition) -> Self {
+        let d = self.freader.decoder_mut();
+        if !matches!(behavior, MatchDisposition::Prepend | MatchDisposition::Wrap) {
+            d.scan_offset = 0;
+        }
+        d.engine.match_dispo = behavior;
+        self
+    }
+
+    /// Builder-pattern method for controlling where the search for the next
+    /// delimiter resumes after a dropped or replaced one; default value is
+    /// [`OverlapPolicy::Skip`]. See [`OverlapPolicy`].
+    pub fn with_overlap(mut self, policy: OverlapPolicy) -> Self {
+        self.freader.decoder_mut().engine.overlap = policy;
+        self
+    }
+
+    /// Builder-pattern method for splitting only where the delimiter
+    /// matches at least `k` times in a row. See
+    /// [`ByteChunker::with_min_repeats`](crate::ByteChunker::with_min_repeats).
+    pub fn with_min_repeats(mut self, k: usize) -> Self {
+        self.freader.decoder_mut().engine.min_repeats = k.max(1);
+        self
+    }
+
+    /// Builder-pattern method for ignoring any delimiter that starts
+    /// fewer than `n` bytes into a chunk. See
+    /// [`ByteChunker::with_min_gap`](crate::ByteChunker::with_min_gap).
+    pub fn with_min_gap(mut self, n: usize) -> Self {
+        self.freader.decoder_mut().engine.min_gap = n;
+        self
+    }
+
+    /// Builder-pattern method for treating data at the end of the source
+    /// that isn't followed by a delimiter as an [`RcErr::Unterminated`]
+    /// error. See
+    /// [`ByteChunker::with_strict_termination`](crate::ByteChunker::with_strict_termination).
+    pub fn with_strict_termination(mut self, strict: bool) -> Self {
+        self.freader.decoder_mut().strict_tail = strict;
+        self
+    }
+
+    /// Builder-pattern method for making a source that doesn't end with a
+    /// delimiter an error. See
+    /// [`ByteChunker::require_terminator`](crate::ByteChunker::require_terminator).
+    pub fn require_terminator(self) -> Self {
+        self.with_strict_termination(true)
+    }
+
+    /// Builder-pattern method for only splitting at delimiters that are
+    /// preceded by a match for `pattern`. See
+    /// [`ByteChunker::with_anchor`](crate::ByteChunker::with_anchor).
+    pub fn with_anchor(mut self, pattern: &str) -> Result<Self, RcErr> {
+        self.freader.decoder_mut().engine.set_anchor(pattern)?;
+        Ok(self)
+    }
+
+    /// Change what the chunker does with the matched text from the next match
+    /// on, without rebuilding it; see [`with_match`](ByteChunker::with_match).
+    /// A delimiter already kept for the next chunk under
+    /// [`MatchDisposition::Prepend`] stays with it.
+    ///
+    /// ```rust
+    /// # use std::error::Error;
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    ///     use regex_chunker::{stream::ByteChunker, MatchDisposition};
+    ///     use tokio_stream::StreamExt;
+    ///
+    ///     let text = b"HEADER\nline 1\nline 2";
+    ///     let mut chunker = ByteChunker::new(&text[..], r"\n")?;
+    ///     assert_eq!(chunker.next().await.unwrap()?, b"HEADER");
+    ///
+    ///     // Past the header, each newline belongs with the line after it.
+    ///     chunker.set_match(MatchDisposition::Prepend);
+    ///     assert_eq!(chunker.next().await.unwrap()?, b"line 1");
+    ///     assert_eq!(chunker.next().await.unwrap()?, b"\nline 2");
+    ///     assert!(chunker.next().await.is_none());
+    /// #   Ok(()) }
+    /// ```
+    pub fn set_match(&mut self, behavior: MatchDisposition) {
+        // Unlike in `with_match`, the scan offset is left alone: if it's
+        // nonzero, it's skipping a delimiter that's already been matched
+        // and kept at the front of the buffer for the next chunk.
+        self.freader.decoder_mut().engine.match_dispo = behavior;
+    }
+
+    /// Builder-pattern method for deciding what to do with each match
+    /// individually. See
+    /// [`ByteChunker::with_match_fn`](crate::ByteChunker::with_match_fn).
+    pub fn with_match_fn<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&[u8]) -> MatchDisposition + Send + 'static,
+    {
+        self.freader.decoder_mut().engine.match_fn = Some(Box::new(f));
+        self
+    }
+
+    /// Builder-pattern method for vetoing splits. See
+    /// [`ByteChunker::with_split_fn`](crate::ByteChunker::with_split_fn).
+    pub fn with_split_fn<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&[u8], &[u8]) -> bool + Send + 'static,
+    {
+        self.freader.decoder_mut().engine.split_fn = Some(Box::new(f));
+        self
+    }
+}
+
+impl<R: Read + Send + 'static> ByteChunker<BlockingReader<R>> {
+    /// Return a new [`ByteChunker`] wrapping the given _blocking_ reader
+    /// that will chunk its output by delimiting it with the given regular
+    /// expression pattern. The reads are made on Tokio's blocking thread
+    /// pool; see [`BlockingReader`].
+    pub fn from_blocking(source: R, pattern: &str) -> Result<Self, RcErr> {
+        Self::new(BlockingReader::new(source), pattern)
+    }
+}
+
+impl<R: AsyncRead + Unpin> ByteChunker<R> {
+    /// Wrap this `ByteChunker` in a [`BlockingChunker`], which implements
+    /// [`Iterator`] by blocking on the runtime referred to by `handle`.
+    pub fn into_blocking(self, handle: Handle) -> BlockingChunker<Self> {
+        BlockingChunker::new(self, handle)
+    }
+
+    /// Consume the `ByteChunker` and return the number of chunks in its
+    /// source, without allocating a vector for each chunk.
+    pub async fn count_chunks(mut self) -> Result<usize, RcErr> {
+        self.freader.decoder_mut().discard_bodies = true;
+        let mut n = 0;
+        while let Some(res) = self.freader.next().await {
+            res?;
+            n += 1;
+        }
+        Ok(n)
+    }
+
+    /// Consume the `ByteChunker` and collect all its chunks, returning a
+    /// single [`RcErr::Collect`] error summarizing any errors encountered
+    /// rather than stopping at the first one.
+    pub async fn collect_bytes(mut self) -> Result<Vec<Vec<u8>>, RcErr> {
+        let mut tally = ErrorTally::default();
+        let mut v = Vec::new();
+        loop {
+            let offset = self.freader.decoder().offset;
+            match self.freader.next().await {
+                None => break,
+                Some(res) => tally.record(res.map(|c| c.body), offset, &mut v),
+            }
+        }
+        tally.finish(v)
+    }
+
+    /// Like [`ByteChunker::collect_bytes`], but converts each chunk to a
+    /// `String`, returning a summary error if any chunks aren't valid
+    /// UTF-8. The reported offset of a UTF-8 error is that of the first
+    /// invalid byte.
+    pub async fn collect_strings(mut self) -> Result<Vec<String>, RcErr> {
+        let mut tally = ErrorTally::default();
+        let mut v = Vec::new();
+        loop {
+            let offset = self.freader.decoder().offset;
+            match self.freader.next().await {
+                None => break,
+                Some(Ok(c)) => match String::from_utf8(c.body) {
+                    Ok(s) => tally.record(Ok(s), c.span.start, &mut v),
+                    Err(e) => {
+                        let offset = c.span.start + e.utf8_error().valid_up_to() as u64;
+                        tally.record(Err(e.into()), offset, &mut v);
+                    }
+                },
+                Some(Err(e)) => tally.record(Err(e), offset, &mut v),
+            }
+        }
+        tally.finish(v)
+    }
+}
+
+impl<R: AsyncRead + Unpin> ByteChunker<R> {
+    /* Poll for the next chunk, unless the chunker is paused (and not being
+    shut down), counting any error returned. */
+    fn poll_chunk(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Chunk, RcErr>>> {
+        let limit = self.freader.get_ref();
+        if let Some(pause) = limit.pause.as_ref() {
+            let stopping = limit.shutdown.as_ref().is_some_and(|s| s.check(cx));
+            if pause.check(cx) && !stopping {
+                return Poll::Pending;
+            }
+        }
+
+        let res = self.poll_throttled(cx);
+        if let Poll::Ready(Some(Err(_))) = res {
+            self.freader.decoder_mut().stats.record_error();
+        }
+        res
+    }
+
+    /* Poll for the next chunk, holding it back if the chunker is
+    throttled. */
+    fn poll_throttled(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Chunk, RcErr>>> {
+        let throttle = match self.throttle.as_mut() {
+            Some(throttle) => throttle,
+            None => return Pin::new(&mut self.freader).poll_next(cx),
+        };
+        if !throttle.is_holding() {
+            match ready!(Pin::new(&mut self.freader).poll_next(cx)) {
+                Some(Ok(chunk)) => throttle.hold(chunk),
+                other => return Poll::Ready(other),
+            }
+        }
+        throttle.poll_release(cx).map(|chunk| Some(Ok(chunk)))
+    }
+}
+
+impl<A: AsyncRead + Unpin> Stream for ByteChunker<A> {
+    type Item = Result<Vec<u8>, RcErr>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_chunk(cx)
+            .map(|opt| opt.map(|res| res.map(|c| c.body)))
+    }
+}
+
+/**
+The async analog to the base crate's [`MetaChunker`](crate::MetaChunker).
+It yields [`Chunk`]s, which carry the delimiter that terminated each chunk
+and its position in the source along with its contents.
+
+```rust
+# use std::error::Error;
+# #[tokio::main(flavor = "current_thread")]
+# async fn main() -> Result<(), Box<dyn Error>> {
+    use regex_chunker::stream::ByteChunker;
+    use tokio_stream::StreamExt;
+    use std::io::Cursor;
+
+    let text = b"one, two;three";
+    let chunks: Vec<_> = ByteChunker::new(Cursor::new(text), "[,;] *")?
+        .with_metadata()
+        .map(|res| res.unwrap())
+        .collect().await;
+
+    assert_eq!(chunks[0].delimiter.as_deref(), Some(&b", "[..]));
+    assert_eq!(chunks[1].span, 5..8);
+    assert!(chunks[2].eof);
+#   Ok(()) }
+```
+*/
+pub struct MetaChunker<R: AsyncRead> {
+    chunker: ByteChunker<R>,
+}
+
+impl<R: AsyncRead> MetaChunker<R> {
+    /// Builder-pattern method for also reporting each chunk's position in
+    /// characters. See
+    /// [`MetaChunker::with_char_offsets`](crate::MetaChunker::with_char_offsets).
+    pub fn with_char_offsets(mut self) -> Self {
+        let d = self.chunker.freader.decoder_mut();
+        d.char_offset.get_or_insert(0);
+        self
+    }
+
+    /// Consumes the [`MetaChunker`] and returns the underlying
+    /// [`ByteChunker`].
+    pub fn into_inner(self) -> ByteChunker<R> {
+        self.chunker
+    }
+}
+
+impl<R: AsyncRead + Unpin> Stream for MetaChunker<R> {
+    type Item = Result<Chunk, RcErr>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.chunker.poll_chunk(cx)
+    }
+}
+
+/**
+The async analog to the base crate's
+[`DelimitedChunker`](crate::DelimitedChunker). It yields `(chunk, delimiter)`
+tuples, where the delimiter is `None` for a final chunk terminated by the
+end of the source.
+*/
+pub struct DelimitedChunker<R: AsyncRead> {
+    chunker: MetaChunker<R>,
+}
+
+impl<R: AsyncRead> DelimitedChunker<R> {
+    /// Consumes the [`DelimitedChunker`] and returns the underlying
+    /// [`ByteChunker`].
+    pub fn into_inner(self) -> ByteChunker<R> {
+        self.chunker.into_inner()
+    }
+}
+
+impl<R: AsyncRead + Unpin> Stream for DelimitedChunker<R> {
+    type Item = Result<(Vec<u8>, Option<Vec<u8>>), RcErr>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.chunker)
+            .poll_next(cx)
+            .map(|opt| opt.map(|res| res.map(Into::into)))
+    }
+}
+
+/**
+The async analog to the base crate's
+[`InfallibleChunker`](crate::InfallibleChunker), returned by
+[`ByteChunker::infallible`]. Its `Stream` implementation yields plain
+`Vec<u8>`s, rather than `Result`s, because its source can't fail.
+
+```rust
+# use std::error::Error;
+# #[tokio::main(flavor = "current_thread")]
+# async fn main() -> Result<(), Box<dyn Error>> {
+    use regex_chunker::stream::ByteChunker;
+    use tokio_stream::StreamExt;
+
+    let mut chunker = ByteChunker::new(&b"a,b,c"[..], ",")?.infallible();
+    let mut chunks = Vec::new();
+    while let Some(chunk) = chunker.next().await {
+        chunks.push(chunk);
+    }
+    assert_eq!(chunks, [b"a", b"b", b"c"]);
+#   Ok(()) }
+```
+*/
+pub struct InfallibleChunker<R: AsyncRead> {
+    chunker: ByteChunker<R>,
+}
+
+impl<R: AsyncRead> InfallibleChunker<R> {
+    /// Consumes the [`InfallibleChunker`] and returns the underlying
+    /// [`ByteChunker`].
+    pub fn into_inner(self) -> ByteChunker<R> {
+        self.chunker
+    }
+}
+
+impl<R: AsyncRead + Unpin> Stream for InfallibleChunker<R> {
+    type Item = Vec<u8>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Vec<u8>>> {
+        loop {
+            match ready!(self.chunker.poll_chunk(cx)) {
+                Some(Ok(chunk)) => return Poll::Ready(Some(chunk.body)),
+                Some(Err(_)) => continue,
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+/**
+The async analog to the base crate's
+[`ChunkInspector`](crate::ChunkInspector). It yields the same chunks as
+the [`ByteChunker`] it wraps, but first passes each one to a callback.
+*/
+pub struct ChunkInspector<R: AsyncRead, F> {
+    chunker: MetaChunker<R>,
+    inspect: F,
+}
+
+impl<R: AsyncRead, F> ChunkInspector<R, F> {
+    /// Consumes the [`ChunkInspector`] and returns the underlying
+    /// [`ByteChunker`].
+    pub fn into_inner(self) -> ByteChunker<R> {
+        self.chunker.into_inner()
+    }
+}
+
+impl<R: AsyncRead, F> Unpin for ChunkInspector<R, F> {}
+
+impl<R, F> Stream for ChunkInspector<R, F>
+where
+    R: AsyncRead + Unpin,
+    F: FnMut(&Chunk),
+{
+    type Item = Result<Vec<u8>, RcErr>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let res = match Pin::new(&mut self.chunker).poll_next(cx) {
+            Poll::Ready(Some(res)) => res,
+            Poll::Ready(None) => return Poll::Ready(None),
+            Poll::Pending => return Poll::Pending,
+        };
+        Poll::Ready(Some(res.map(|chunk| {
+            (self.inspect)(&chunk);
+            chunk.body
+        })))
+    }
+}
+
+/**
+The async analog to the base crate's
+[`CustomChunker`](`crate::CustomChunker`).
+It takes an [`Adapter`] and yields chunks based on the `Adapter`'s
+transformation.
+
+```rust
+# use std::error::Error;
+# #[tokio::main]
+# async fn main() -> Result<(), Box<dyn Error>> {
+    use regex_chunker::{
+        stream::ByteChunker,
+        StringAdapter,
+    };
+    use tokio_stream::StreamExt;
+    use std::io::Cursor;
+
+    let text = b"One, two, three four. Can I have a little more?";
+    let c = Cursor::new(text);
+
+    let chunks: Vec<_> = ByteChunker::new(c, "[ .,?]+")?
+        .with_adapter(StringAdapter::default())
+        .map(|res| res.unwrap())
+        .collect().await;
+
+    assert_eq!(
+        &chunks,
+        &[
+            "One", "two", "three", "four",
+            "Can", "I", "have", "a", "little", "more"
+        ].clone()
+    );
+#   Ok(()) }
+*/
+pub struct CustomChunker<R: AsyncRead, A> {
+    chunker: ByteChunker<R>,
+    adapter: A,
+}
+
+impl<R: AsyncRead, A> CustomChunker<R, A> {
+    /// Consumes the [`CustomChunker`] and returns the underlying
+    /// [`ByteChunker`] and [`Adapter`].
+    pub fn into_innards(self) -> (ByteChunker<R>, A) {
+        (self.chunker, self.adapter)
+    }
+
+    /// Get a reference to the underlying [`Adapter`].
+    pub fn get_adapter(&self) -> &A {
+        &self.adapter
+    }
+
+    /// Get a mutable reference to the underlying [`Adapter`].
+    pub fn get_adapter_mut(&mut self) -> &mut A {
+        &mut self.adapter
+    }
+
+    /// Wrap the [`Adapter`] in a [`CatchPanics`]. See
+    /// [`crate::CustomChunker::isolate_panics`].
+    pub fn isolate_panics(self) -> CustomChunker<R, CatchPanics<A>> {
+        CustomChunker {
+            chunker: self.chunker,
+            adapter: CatchPanics::new(self.adapter),
+        }
+    }
+
+    /*
+    The builder-pattern methods below are forwarded to the wrapped
+    `ByteChunker`, so they can be called before or after `.with_adapter()`.
+    */
+
+    /// See [`ByteChunker::with_read_size`].
+    pub fn with_read_size(mut self, size: usize) -> Self {
+        self.chunker = self.chunker.with_read_size(size);
+        self
+    }
+
+    /// See [`ByteChunker::with_fragmented_reads`].
+    #[cfg(feature = "test-util")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+    pub fn with_fragmented_reads(mut self, seed: u64) -> Self {
+        self.chunker = self.chunker.with_fragmented_reads(seed);
+        self
+    }
+
+    /// See [`ByteChunker::with_poll_budget`].
+    pub fn with_poll_budget(mut self, bytes: usize) -> Self {
+        self.chunker = self.chunker.with_poll_budget(bytes);
+        self
+    }
+
+    /// See [`ByteChunker::with_incremental_search`].
+    pub fn with_incremental_search(mut self, incremental: bool) -> Self {
+        self.chunker = self.chunker.with_incremental_search(incremental);
+        self
+    }
+
+    /// See [`ByteChunker::with_buffer_policy`].
+    pub fn with_buffer_policy<P>(mut self, policy: P) -> Self
+    where
+        P: BufferPolicy + 'static,
+    {
+        self.chunker = self.chunker.with_buffer_policy(policy);
+        self
+    }
+
+    /// See [`ByteChunker::with_match`].
+    pub fn with_match(mut self, behavior: MatchDisposition) -> Self {
+        self.chunker = self.chunker.with_match(behavior);
+        self
+    }
+
+    /// See [`ByteChunker::with_overlap`].
+    pub fn with_overlap(mut self, policy: OverlapPolicy) -> Self {
+        self.chunker = self.chunker.with_overlap(policy);
+        self
+    }
+
+    /// See [`ByteChunker::with_min_repeats`].
+    pub fn with_min_repeats(mut self, k: usize) -> Self {
+        self.chunker = self.chunker.with_min_repeats(k);
+        self
+    }
+
+    /// See [`ByteChunker::with_min_gap`].
+    pub fn with_min_gap(mut self, n: usize) -> Self {
+        self.chunker = self.chunker.with_min_gap(n);
+        self
+    }
+
+    /// See [`ByteChunker::with_strict_termination`].
+    pub fn with_strict_termination(mut self, strict: bool) -> Self {
+        self.chunker = self.chunker.with_strict_termination(strict);
+        self
+    }
+
+    /// See [`ByteChunker::require_terminator`].
+    pub fn require_terminator(mut self) -> Self {
+        self.chunker = self.chunker.require_terminator();
+        self
+    }
+
+    /// See [`ByteChunker::with_anchor`].
+    pub fn with_anchor(mut self, pattern: &str) -> Result<Self, RcErr> {
+        self.chunker = self.chunker.with_anchor(pattern)?;
+        Ok(self)
+    }
+
+    /// See [`ByteChunker::set_match`].
+    pub fn set_match(&mut self, behavior: MatchDisposition) {
+        self.chunker.set_match(behavior);
+    }
+
+    /// See [`ByteChunker::with_match_fn`].
+    pub fn with_match_fn<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&[u8]) -> MatchDisposition + Send + 'static,
+    {
+        self.chunker = self.chunker.with_match_fn(f);
+        self
+    }
+
+    /// See [`ByteChunker::with_split_fn`].
+    pub fn with_split_fn<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&[u8], &[u8]) -> bool + Send + 'static,
+    {
+        self.chunker = self.chunker.with_split_fn(f);
+        self
+    }
+
+    /// See [`ByteChunker::with_max_chunk_len`].
+    pub fn with_max_chunk_len(mut self, max: usize) -> Self {
+        self.chunker = self.chunker.with_max_chunk_len(max);
+        self
+    }
+
+    /// See [`ByteChunker::trim_matches`].
+    pub fn trim_matches(mut self, pattern: &str) -> Result<Self, RcErr> {
+        self.chunker = self.chunker.trim_matches(pattern)?;
+        Ok(self)
+    }
+
+    /// See [`ByteChunker::with_grapheme_safe_splits`].
+    #[cfg(feature = "unicode")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "unicode")))]
+    pub fn with_grapheme_safe_splits(mut self, safe: bool) -> Self {
+        self.chunker = self.chunker.with_grapheme_safe_splits(safe);
+        self
+    }
+
+    /// See [`ByteChunker::with_bom_stripping`].
+    pub fn with_bom_stripping(mut self, strip: bool) -> Self {
+        self.chunker = self.chunker.with_bom_stripping(strip);
+        self
+    }
+
+    /// See [`ByteChunker::with_source_len`].
+    pub fn with_source_len(mut self, len: u64) -> Self {
+        self.chunker = self.chunker.with_source_len(len);
+        self
+    }
+
+    /// See [`ByteChunker::progress`].
+    pub fn progress(&mut self) -> watch::Receiver<Progress> {
+        self.chunker.progress()
+    }
+
+    /// See [`ByteChunker::stats`].
+    pub fn stats(&mut self) -> watch::Receiver<Stats> {
+        self.chunker.stats()
+    }
+
+    /// See [`ByteChunker::throttle`].
+    pub fn throttle(mut self, chunks_per_second: f64) -> Self {
+        self.chunker = self.chunker.throttle(chunks_per_second);
+        self
+    }
+
+    /// See [`ByteChunker::throttle_bytes`].
+    pub fn throttle_bytes(mut self, bytes_per_second: f64) -> Self {
+        self.chunker = self.chunker.throttle_bytes(bytes_per_second);
+        self
+    }
+
+    /// See [`ByteChunker::shutdown_handle`].
+    pub fn shutdown_handle(&mut self) -> ShutdownHandle {
+        self.chunker.shutdown_handle()
+    }
+
+    /// See [`ByteChunker::pause_handle`].
+    pub fn pause_handle(&mut self) -> PauseHandle {
+        self.chunker.pause_handle()
+    }
+
+    /// See [`ByteChunker::matched_pattern`].
+    pub fn matched_pattern(&self) -> Option<usize> {
+        self.chunker.matched_pattern()
+    }
+
+    /// See [`ByteChunker::buffered_len`].
+    pub fn buffered_len(&self) -> usize {
+        self.chunker.buffered_len()
+    }
+
+    /// See [`ByteChunker::peak_buffered_len`].
+    pub fn peak_buffered_len(&self) -> usize {
+        self.chunker.peak_buffered_len()
+    }
+}
+
+impl<R: AsyncRead, A> Unpin for CustomChunker<R, A> {}
+
+impl<R, A> Stream for CustomChunker<R, A>
+where
+    R: AsyncRead + Unpin,
+    A: Adapter
+{
+    type Item = A::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let p = Pin::new(&mut self.chunker).poll_next(cx);
+        match p {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(x) => Poll::Ready(self.adapter.adapt(x)),
+        }
+    }
+}
+
+/**
+Return a stream of the lines of `source`, as `String`s, with the line
+endings (`\n` or `\r\n`) removed. This is the async analog of
+[`string_lines`](crate::string_lines).
+
+```rust
+# use std::error::Error;
+# #[tokio::main(flavor = "current_thread")]
+# async fn main() -> Result<(), Box<dyn Error>> {
+    use regex_chunker::stream::string_lines;
+    use tokio_stream::StreamExt;
+    use std::io::Cursor;
+
+    let lines: Vec<String> = string_lines(Cursor::new(b"one\r\ntwo\nthree"))
+        .map(|res| res.unwrap())
+        .collect().await;
+    assert_eq!(&lines, &["one", "two", "three"]);
+#   Ok(()) }
+```
+*/
+pub fn string_lines<R: AsyncRead>(source: R) -> CustomChunker<R, StringAdapter> {
+    ByteChunker::lines(source).with_adapter(StringAdapter::default())
+}
+
+/**
+A plain [`Iterator`] over the items of one of this module's chunkers (or
+any other [`Stream`]), driven by a Tokio runtime. This lets sync code
+consume an async source (a child process's stdout, say) without writing
+its own `block_on` loop.
+
+Each call to `.next()` blocks the current thread on the runtime until the
+stream produces its next item, so, as with
+[`Handle::block_on`](https://docs.rs/tokio/latest/tokio/runtime/struct.Handle.html#method.block_on),
+it must not be used from within an async context.
+
+```rust
+# use std::error::Error;
+# fn main() -> Result<(), Box<dyn Error>> {
+    use regex_chunker::stream::ByteChunker;
+    use std::io::Cursor;
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    let chunker = ByteChunker::new(Cursor::new(b"one two three"), " ")?
+        .into_blocking(rt.handle().clone());
+    let chunks: Vec<Vec<u8>> = chunker.map(|res| res.unwrap()).collect();
+    assert_eq!(chunks, [b"one".to_vec(), b"two".to_vec(), b"three".to_vec()]);
+#   Ok(()) }
+```
+*/
+#[derive(Debug)]
+pub struct BlockingChunker<S> {
+    stream: S,
+    handle: Handle,
+}
+
+impl<S> BlockingChunker<S> {
+    /// Return a new `BlockingChunker` that will drive `stream` with the
+    /// runtime referred to by `handle`.
+    pub fn new(stream: S, handle: Handle) -> Self {
+        Self { stream, handle }
+    }
+
+    /// Consume the `BlockingChunker` and return the wrapped stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S: Stream + Unpin> Iterator for BlockingChunker<S> {
+    type Item = S::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.handle.block_on(self.stream.next())
+    }
+}
+
+/* Where the async `RevChunker` is in the process of getting a block. */
+enum RevState {
+    Start,
+    SeekingEnd,
+    Stepping,
+    Seeking(u64, usize),
+    Reading(u64, Vec<u8>, usize),
+    Done,
+}
+
+/**
+The async analog to the base crate's [`RevChunker`](crate::RevChunker).
+It wraps a source that implements both
+[`AsyncRead`](https://docs.rs/tokio/latest/tokio/io/trait.AsyncRead.html)
+and [`AsyncSeek`](https://docs.rs/tokio/latest/tokio/io/trait.AsyncSeek.html),
+reads it in blocks backward from the end, and yields its chunks last
+chunk first.
+
+```rust
+# use std::error::Error;
+# #[tokio::main(flavor = "current_thread")]
+# async fn main() -> Result<(), Box<dyn Error>> {
+    use regex_chunker::stream::RevChunker;
+    use tokio_stream::StreamExt;
+    use std::io::Cursor;
+
+    let text = b"first line\nsecond line\nthird line\n";
+
+    let last_two: Vec<Vec<u8>> = RevChunker::new(Cursor::new(text), r"\n")?
+        .map(|res| res.unwrap())
+        .collect().await;
+
+    assert_eq!(&last_two[..2], &[b"third line".to_vec(), b"second line".to_vec()]);
+#   Ok(()) }
+```
+*/
+pub struct RevChunker<R> {
+    source: R,
+    core: RevCore,
+    state: RevState,
+}
+
+impl<R> RevChunker<R> {
+    /// Return a new [`RevChunker`] wrapping the given async reader that
+    /// will chunk its contents by delimiting it with the given regular
+    /// expression pattern.
+    pub fn new(source: R, pattern: &str) -> Result<Self, RcErr> {
+        let fence = Regex::new(pattern)?;
+        Ok(Self::from_boundary(source, fence))
+    }
+
+    /// Return a new [`RevChunker`] wrapping the given async reader that
+    /// will chunk its contents at the delimiters found by the supplied
+    /// [`Boundary`].
+    pub fn from_boundary<B>(source: R, boundary: B) -> Self
+    where
+        B: Boundary + Send + 'static,
+    {
+        Self {
+            source,
+            core: RevCore::new(Box::new(boundary)),
+            state: RevState::Start,
+        }
+    }
+
+    /// Builder-pattern method for setting the size of the blocks read
+    /// from the source. Default size is 1024 bytes.
+    pub fn with_buffer_size(mut self, size: usize) -> Self {
+        self.core.set_block_size(size);
+        self
+    }
+
+    /// Builder-pattern method for controlling what the chunker does with
+    /// the matched text; default value is [`MatchDisposition::Drop`].
+    pub fn with_match(mut self, behavior: MatchDisposition) -> Self {
+        self.core.set_match(behavior);
+        self
+    }
+
+    /// Builder-pattern method for deciding what to do with each match
+    /// individually. See
+    /// [`ByteChunker::with_match_fn`](crate::ByteChunker::with_match_fn).
+    pub fn with_match_fn<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&[u8]) -> MatchDisposition + Send + 'static,
+    {
+        self.core.set_match_fn(Box::new(f));
+        self
+    }
+
+    /// Consumes the [`RevChunker`] and returns its wrapped reader.
+    pub fn into_inner(self) -> R {
+        self.source
+    }
+
+    fn fail(&mut self, e: std::io::Error) -> Poll<Option<Result<Vec<u8>, RcErr>>> {
+        self.core.finish();
+        self.state = RevState::Done;
+        Poll::Ready(Some(Err(e.into())))
+    }
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> Stream for RevChunker<R> {
+    type Item = Result<Vec<u8>, RcErr>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            match &mut this.state {
+                RevState::Start => {
+                    if let Err(e) = Pin::new(&mut this.source).start_seek(SeekFrom::End(0)) {
+                        return this.fail(e);
+                    }
+                    this.state = RevState::SeekingEnd;
+                }
+                RevState::SeekingEnd => match Pin::new(&mut this.source).poll_complete(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(Err(e)) => return this.fail(e),
+                    Poll::Ready(Ok(len)) => {
+                        this.core.set_len(len);
+                        this.state = RevState::Stepping;
+                    }
+                },
+                RevState::Stepping => match this.core.step() {
+                    RevStep::Chunk(v) => return Poll::Ready(Some(Ok(v))),
+                    RevStep::Done => {
+                        this.state = RevState::Done;
+                        return Poll::Ready(None);
+                    }
+                    RevStep::NeedMore(offset, len) => {
+                        let seek = SeekFrom::Start(offset);
+                        if let Err(e) = Pin::new(&mut this.source).start_seek(seek) {
+                            return this.fail(e);
+                        }
+                        this.state = RevState::Seeking(offset, len);
+                    }
+                },
+                RevState::Seeking(offset, len) => {
+                    let (offset, len) = (*offset, *len);
+                    match Pin::new(&mut this.source).poll_complete(cx) {
+                        Poll::Pending => return Poll::Pending,
+                        Poll::Ready(Err(e)) => return this.fail(e),
+                        Poll::Ready(Ok(_)) => {
+                            this.state = RevState::Reading(offset, vec![0u8; len], 0);
+                        }
+                    }
+                }
+                RevState::Reading(offset, block, filled) => {
+                    let mut read_buff = ReadBuf::new(&mut block[*filled..]);
+                    match Pin::new(&mut this.source).poll_read(cx, &mut read_buff) {
+                        Poll::Pending => return Poll::Pending,
+                        Poll::Ready(Err(e)) => return this.fail(e),
+                        Poll::Ready(Ok(())) => {
+                            let n = read_buff.filled().len();
+                            if n == 0 {
+                                let e = std::io::Error::from(std::io::ErrorKind::UnexpectedEof);
+                                return this.fail(e);
+                            }
+                            *filled += n;
+                            if *filled == block.len() {
+                                let (offset, block) = (*offset, std::mem::take(block));
+                                this.core.prepend(offset, block);
+                                this.state = RevState::Stepping;
+                            }
+                        }
+                    }
+                }
+                RevState::Done => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::tests::{
+        chunk_vec, ref_slice_cmp, HTTP_PATT, HTTP_URL, PASSWD_PATH, PASSWD_PATT, TEST_PATH,
+        TEST_PATT,
+    };
+
+    use std::process::Stdio;
+
+    use tokio::{fs::File, process::Command};
+    use tokio_stream::StreamExt;
+
+    static SOURCE: &str = "target/debug/slowsource";
+    static SOURCE_ARGS: &[&str] = &[TEST_PATH, "0.0", "0.1"];
+
+    #[tokio::test]
+    async fn basic_async() {
+        let byte_vec = std::fs::read(TEST_PATH).unwrap();
+        let re = Regex::new(TEST_PATT).unwrap();
+        let slice_vec = chunk_vec(&re, &byte_vec, MatchDisposition::Drop);
+
+        let f = File::open(TEST_PATH).await.unwrap();
+        let chunker = ByteChunker::new(f, TEST_PATT).unwrap();
+        let vec_vec: Vec<Vec<u8>> = chunker.map(|res| res.unwrap()).collect().await;
+
+        ref_slice_cmp(&vec_vec, &slice_vec);
+    }
+
+    #[tokio::test]
+    async fn small_reads_async() {
+        let byte_vec = std::fs::read(TEST_PATH).unwrap();
+        let re = Regex::new(TEST_PATT).unwrap();
+        let slice_vec = chunk_vec(&re, &byte_vec, MatchDisposition::Drop);
+
+        let f = File::open(TEST_PATH).await.unwrap();
+        let chunker = ByteChunker::new(f, TEST_PATT).unwrap().with_read_size(3);
+        let vec_vec: Vec<Vec<u8>> = chunker.map(|res| res.unwrap()).collect().await;
+
+        assert_eq!(vec_vec.len(), slice_vec.len());
+        ref_slice_cmp(&vec_vec, &slice_vec);
+    }
+
+    #[tokio::test]
+    async fn incremental_search_async() {
+        let text: Vec<u8> = std::iter::repeat_n(&b"abc"[..], 20_001)
+            .chain([&b"=+="[..], b"tail", b"=+=", b"end"])
+            .flatten()
+            .copied()
+            .collect();
+        let expected = vec![text[..60_003].to_vec(), b"tail".to_vec(), b"end".to_vec()];
+
+        let chunker = ByteChunker::new(std::io::Cursor::new(text), "=[+]=")
+            .unwrap()
+            .with_read_size(7)
+            .with_incremental_search(true);
+        let vec_vec: Vec<Vec<u8>> = chunker.map(|res| res.unwrap()).collect().await;
+        assert_eq!(vec_vec, expected);
+    }
+
+    #[tokio::test]
+    async fn incremental_search_differential() {
+        async fn chunks<F: Boundary + Send + 'static>(
+            text: &'static [u8],
+            fence: F,
+            read_size: usize,
+            incremental: bool,
+        ) -> Vec<Vec<u8>> {
+            let mut chunker = ByteChunker::from_boundary(std::io::Cursor::new(text), fence)
+                .with_read_size(read_size)
+                .with_match(MatchDisposition::Prepend)
+                .with_incremental_search(incremental);
+            let mut chunks = Vec::new();
+            while let Some(chunk) = chunker.next().await {
+                chunks.push(chunk.unwrap());
+            }
+            chunks
+        }
+
+        let text: &[u8] = b"one <a-long-tag-spanning-reads> two END three ENDING four \
+            ab abcdefgh x,,,,y <> END<q>END five";
+        // Matches longer than any single read, ones that depend on the
+        // byte after them, and ones of unbounded length.
+        let patterns = ["<[a-z-]{0,40}>", r"\bEND\b", "ab|abcdefgh", ",+", "(?i)end"];
+
+        for read_size in 1..9 {
+            for pattern in patterns {
+                let re = Regex::new(pattern).unwrap();
+                assert_eq!(
+                    chunks(text, re.clone(), read_size, true).await,
+                    chunks(text, re, read_size, false).await,
+                    "{pattern:?}, read size {read_size}"
+                );
+            }
+            let literals = || LiteralBoundary::new(["<>", "abcdefgh", " END "]).unwrap();
+            assert_eq!(
+                chunks(text, literals(), read_size, true).await,
+                chunks(text, literals(), read_size, false).await,
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn blocking_source_async() {
+        let byte_vec = std::fs::read(TEST_PATH).unwrap();
+        let re = Regex::new(TEST_PATT).unwrap();
+        let slice_vec = chunk_vec(&re, &byte_vec, MatchDisposition::Drop);
+
+        let f = std::fs::File::open(TEST_PATH).unwrap();
+        let chunker = ByteChunker::from_blocking(f, TEST_PATT)
+            .unwrap()
+            .with_read_size(5);
+        let vec_vec: Vec<Vec<u8>> = chunker.map(|res| res.unwrap()).collect().await;
+
+        ref_slice_cmp(&vec_vec, &slice_vec);
+    }
+
+    #[tokio::test]
+    async fn into_inner_async() {
+        let text = b"one two three";
+        let mut chunker = ByteChunker::new(std::io::Cursor::new(text), " ")
+            .unwrap()
+            .with_read_size(4);
+        assert_eq!(chunker.get_ref().position(), 0);
+        assert_eq!(chunker.next().await.unwrap().unwrap(), b"one");
+
+        let pos = chunker.get_ref().position();
+        assert!(pos >= 4);
+        let cursor = chunker.into_inner();
+        assert_eq!(cursor.position(), pos);
+    }
+
+    #[tokio::test]
+    async fn with_capacity_async() {
+        let f = File::open(TEST_PATH).await.unwrap();
+        let chunker = ByteChunker::new_with_capacity(f, TEST_PATT, 64 * 1024).unwrap();
+        assert!(chunker.freader.read_buffer().capacity() >= 64 * 1024);
+
+        let byte_vec = std::fs::read(TEST_PATH).unwrap();
+        let re = Regex::new(TEST_PATT).unwrap();
+        let slice_vec = chunk_vec(&re, &byte_vec, MatchDisposition::Drop);
+        let vec_vec: Vec<Vec<u8>> = chunker.map(|res| res.unwrap()).collect().await;
+        ref_slice_cmp(&vec_vec, &slice_vec);
+    }
+
+    #[tokio::test]
+    async fn replace_async() {
+        let text = b"one, two;three\r\nfour\n";
+        let vec_vec: Vec<Vec<u8>> = ByteChunker::new(&text[..], r"[,;]\s*|\r?\n")
+            .unwrap()
+            .with_match(MatchDisposition::Replace(b"\n".to_vec()))
+            .map(|res| res.unwrap())
+            .collect()
+            .await;
+        assert_eq!(vec_vec, [&b"one\n"[..], b"two\n", b"three\n", b"four\n"]);
+    }
+
+    #[tokio::test]
+    async fn trim_matches_async() {
+        let text = b"[a] , [ b ],[[c]]  ,, [] ,d";
+        let chunks: Vec<Chunk> = ByteChunker::new(&text[..], ",")
+            .unwrap()
+            .with_read_size(3)
+            .trim_matches(r"[\[\] ]+")
+            .unwrap()
+            .with_metadata()
+            .map(|res| res.unwrap())
+            .collect()
+            .await;
+        let bodies: Vec<&[u8]> = chunks.iter().map(|c| &c.body[..]).collect();
+        assert_eq!(bodies, [&b"a"[..], b"b", b"c", b"", b"", b"d"]);
+        assert_eq!(chunks[2].span, 14..15);
+    }
+
+    #[tokio::test]
+    async fn char_offsets_async() {
+        let text = "ça | va |très| bien, «merci» | 日本語";
+        let chunks: Vec<Chunk> = ByteChunker::new(text.as_bytes(), r"\|")
+            .unwrap()
+            .with_read_size(3)
+            .trim_matches(" ")
+            .unwrap()
+            .with_metadata()
+            .with_char_offsets()
+            .map(|res| res.unwrap())
+            .collect()
+            .await;
+        assert_eq!(chunks.len(), 5);
+        for c in chunks.iter() {
+            let (start, end) = (c.span.start as usize, c.span.end as usize);
+            let chars = text[..start].chars().count() as u64;
+            let len = text[start..end].chars().count() as u64;
+            assert_eq!(c.char_span, Some(chars..(chars + len)));
+        }
+    }
+
+    #[tokio::test]
+    async fn bom_stripping_async() {
+        let text = b"\xef\xbb\xbfone\ntwo";
+        for size in [1, 1024] {
+            let chunks: Vec<Vec<u8>> = ByteChunker::new(&text[..], r"\n")
+                .unwrap()
+                .with_read_size(size)
+                .with_bom_stripping(true)
+                .map(|res| res.unwrap())
+                .collect()
+                .await;
+            assert_eq!(chunks, [&b"one"[..], b"two"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn multipart() {
+        let body = b"--b\r\nA: 1\r\n\r\nx\r\n--b \r\n\r\n--bad\r\n--b--";
+        for size in [1, 4, 1024] {
+            let parts: Vec<Part> = MultipartChunker::new(&body[..], "b")
+                .unwrap()
+                .with_read_size(size)
+                .map(|res| Part::from(res.unwrap()))
+                .collect()
+                .await;
+            assert_eq!(parts.len(), 2);
+            assert_eq!(
+                (parts[0].header("a"), &parts[0].body[..]),
+                (Some(&b"1"[..]), &b"x"[..])
+            );
+            assert_eq!(
+                (&parts[1].headers[..], &parts[1].body[..]),
+                (&b""[..], &b"--bad"[..])
+            );
+        }
+
+        let truncated = b"--b\r\n\r\none\r\n--b\r\n\r\ntwo";
+        let parts: Vec<Result<Vec<u8>, RcErr>> = MultipartChunker::new(&truncated[..], "b")
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].as_ref().unwrap(), b"\r\none");
+        let eof = std::io::ErrorKind::UnexpectedEof;
+        assert!(matches!(&parts[1], Err(RcErr::Read(e)) if e.kind() == eof));
+
+        let empty = b"--b--\r\n";
+        let parts: Vec<Result<Vec<u8>, RcErr>> = MultipartChunker::new(&empty[..], "b")
+            .unwrap()
+            .collect()
+            .await;
+        assert!(parts.is_empty());
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "test-util")]
+    async fn misbehaving_readers_async() {
+        use crate::{ChaosReader, SlowReader};
+
+        let byte_vec = std::fs::read(TEST_PATH).unwrap();
+        let re = Regex::new(TEST_PATT).unwrap();
+        let slice_vec = chunk_vec(&re, &byte_vec, MatchDisposition::Drop);
+
+        let slow = SlowReader::new(std::io::Cursor::new(byte_vec.clone()))
+            .with_random_reads(1, 7, 1)
+            .with_delay(std::time::Duration::ZERO);
+        let chaos = ChaosReader::new(slow, 1)
+            .with_would_block(0.2)
+            .with_interrupted(0.2);
+        let vec_vec: Vec<Vec<u8>> = ByteChunker::new(chaos, TEST_PATT)
+            .unwrap()
+            .map(|res| res.unwrap())
+            .collect()
+            .await;
+        ref_slice_cmp(&vec_vec, &slice_vec);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "debug-internals")]
+    async fn trace_async() {
+        use crate::Decision;
+        use std::sync::{Arc, Mutex};
+
+        let decisions = Arc::new(Mutex::new(Vec::new()));
+        let log = decisions.clone();
+        let chunks: Vec<Vec<u8>> = ByteChunker::new(&b"ab;cd;;e"[..], ";")
+            .unwrap()
+            .with_read_size(3)
+            .with_match(MatchDisposition::Prepend)
+            .with_trace(move |event| log.lock().unwrap().push(event.decision))
+            .map(|res| res.unwrap())
+            .collect()
+            .await;
+        assert_eq!(chunks, [&b"ab"[..], b";cd", b";", b";e"]);
+
+        let decisions = decisions.lock().unwrap();
+        let splits: Vec<Decision> = decisions
+            .iter()
+            .copied()
+            .filter(|d| *d != Decision::Wait)
+            .collect();
+        assert_eq!(
+            splits,
+            [
+                Decision::Split {
+                    len: 2,
+                    consumed: 2
+                },
+                Decision::Split {
+                    len: 3,
+                    consumed: 3
+                },
+                Decision::Split {
+                    len: 1,
+                    consumed: 1
+                },
+                Decision::Tail { len: 2 },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn progress_async() {
+        let text = b"alpha\nbeta\ngamma\n";
+        let mut chunker = ByteChunker::new(&text[..], r"\n")
+            .unwrap()
+            .with_read_size(4)
+            .with_adapter(StringAdapter::default());
+        let progress = chunker.progress();
+        assert_eq!(*progress.borrow(), Progress::default());
+
+        assert_eq!(chunker.next().await.unwrap().unwrap(), "alpha");
+        let p = progress.borrow().clone();
+        assert_eq!(p.chunks, 1);
+        assert_eq!(p.bytes_read, 8);
+        assert_eq!(p.fraction(), None);
+
+        // A length supplied late still shows up, as do later receivers.
+        let mut chunker = chunker.with_source_len(text.len() as u64);
+        let late = chunker.progress();
+        while let Some(res) = chunker.next().await {
+            res.unwrap();
+        }
+        for rx in [progress, late] {
+            let p = rx.borrow().clone();
+            assert_eq!(p.bytes_read, text.len() as u64);
+            assert_eq!(p.chunks, 3);
+            assert!(p.finished);
+            assert_eq!(p.fraction(), Some(1.0));
+        }
+    }
+
+    #[tokio::test]
+    async fn inspector_async() {
+        let mut seen = Vec::new();
+        let mut chunks = Vec::new();
+        let mut inspector = ByteChunker::new(&b"ab;cd;;e"[..], ";")
+            .unwrap()
+            .with_read_size(3)
+            .with_inspector(|c: &Chunk| seen.push((c.span.clone(), c.delimiter.is_some())));
+        while let Some(res) = inspector.next().await {
+            chunks.push(res.unwrap());
+        }
+        drop(inspector);
+        assert_eq!(chunks, [&b"ab"[..], b"cd", b"", b"e"]);
+        assert_eq!(
+            seen,
+            [(0..2, true), (3..5, true), (6..6, true), (7..8, false)]
+        );
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "test-util")]
+    async fn fragmented_reads_async() {
+        let text = "one\r\ntwo\r\nthree, four\r\n";
+        for seed in 0..20 {
+            let lines: Vec<String> = ByteChunker::lines(text.as_bytes())
+                .with_fragmented_reads(seed)
+                .with_adapter(StringAdapter::default())
+                .map(|res| res.unwrap())
+                .collect()
+                .await;
+            assert_eq!(lines, ["one", "two", "three, four"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn merged_streams() {
+        let mut merged = merge([
+            ByteChunker::new(&b"a1;a2;a3"[..], ";").unwrap(),
+            ByteChunker::new(&b"b1;b2"[..], ";").unwrap(),
+        ]);
+        let late = merged.push(ByteChunker::new(&b"c1"[..], ";").unwrap());
+        assert_eq!(late, SourceId(2));
+
+        let mut items = Vec::new();
+        while let Some((id, res)) = merged.next().await {
+            items.push((id.0, res.unwrap()));
+        }
+        assert_eq!(merged.live(), 0);
+        let from = |src: usize| -> Vec<&[u8]> {
+            items
+                .iter()
+                .filter(|(id, _)| *id == src)
+                .map(|(_, v)| &v[..])
+                .collect()
+        };
+        assert_eq!(from(0), [&b"a1"[..], b"a2", b"a3"]);
+        assert_eq!(from(1), [&b"b1"[..], b"b2"]);
+        assert_eq!(from(2), [&b"c1"[..]]);
+        // No source gets two turns in a row while the others have items.
+        assert_ne!(items[0].0, items[1].0);
+    }
+
+    #[tokio::test]
+    async fn rechunked_stream() {
+        // Lines split at every other byte, with a `\r\n` broken in two.
+        let lines = ByteChunker::new(&b"ab\r\ncd\r\n\r\nef"[..], "@")
+            .unwrap()
+            .with_max_chunk_len(2)
+            .with_match(MatchDisposition::Append);
+        let rechunked: Vec<Vec<u8>> = Rechunker::new(lines, r"\r\n")
+            .unwrap()
+            .map(|res| res.unwrap())
+            .collect()
+            .await;
+        assert_eq!(rechunked, [&b"ab"[..], b"cd", b"", b"ef"]);
+
+        let pieces = vec![
+            Ok(b"x,".to_vec()),
+            Err(RcErr::Read(std::io::ErrorKind::Other.into())),
+            Ok(b"y".to_vec()),
+        ];
+        let results: Vec<_> = Rechunker::new(tokio_stream::iter(pieces), ",")
+            .unwrap()
+            .with_match(MatchDisposition::Append)
+            .collect()
+            .await;
+        assert!(matches!(
+            &results[..],
+            [Ok(x), Err(RcErr::Read(_)), Ok(y)] if x == b"x," && y == b"y"
+        ));
+    }
+
+    #[tokio::test]
+    async fn poll_budget() {
+        let mut text = vec![b'x'; 1000];
+        text.extend_from_slice(b"\nend");
+        let mut chunker = ByteChunker::new(&text[..], r"\n")
+            .unwrap()
+            .with_poll_budget(256);
+
+        let mut yields = 0;
+        let first = std::future::poll_fn(|cx| {
+            let p = Pin::new(&mut chunker).poll_next(cx);
+            if p.is_pending() {
+                yields += 1;
+            }
+            p
+        })
+        .await;
+        assert_eq!(first.unwrap().unwrap().len(), 1000);
+        assert!(yields >= 3, "only yielded {} times", yields);
+        assert_eq!(chunker.next().await.unwrap().unwrap(), b"end");
+        assert!(chunker.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn shutdown_wakes_waiting_chunker() {
+        // Data that never ends, so only a shutdown can end the chunker.
+        let (mut tx, rx) = tokio::io::duplex(64);
+        tokio::io::AsyncWriteExt::write_all(&mut tx, b"a;b;c")
+            .await
+            .unwrap();
+        let mut chunker = ByteChunker::new(rx, ";").unwrap();
+        let handle = chunker.shutdown_handle();
+        assert!(!handle.is_shut_down());
+
+        let remote = handle.clone();
+        let stopper = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            remote.shutdown();
+        });
+        let mut chunks = Vec::new();
+        while let Some(res) = chunker.next().await {
+            chunks.push(res.unwrap());
+        }
+        stopper.join().unwrap();
+        assert_eq!(chunks, [&b"a"[..], b"b", b"c"]);
+        assert!(handle.is_shut_down());
+        drop(tx);
+    }
+
+    #[tokio::test]
+    async fn shutdown_overrides_pause() {
+        let mut chunker = ByteChunker::new(&b"a;b"[..], ";")
+            .unwrap()
+            .with_adapter(StringAdapter::default());
+        let pause = chunker.pause_handle();
+        let shutdown = chunker.shutdown_handle();
+        pause.pause();
+        shutdown.shutdown();
+        assert!(chunker.next().await.is_none());
+        assert!(pause.is_paused());
+    }
+
+    #[tokio::test]
+    async fn throttled_chunks() {
+        use std::time::{Duration, Instant};
+
+        // A burst of 10, then the other two at a tenth of a second apiece.
+        let text = "a\n".repeat(12);
+        let mut chunker = ByteChunker::new(text.as_bytes(), r"\n")
+            .unwrap()
+            .throttle(10.0);
+        let start = Instant::now();
+        let mut n = 0;
+        while let Some(res) = chunker.next().await {
+            assert_eq!(res.unwrap(), b"a");
+            n += 1;
+        }
+        assert_eq!(n, 12);
+        assert!(start.elapsed() >= Duration::from_millis(150));
+
+        // The 30-byte chunk goes once the bucket is full, leaving it 10
+        // bytes in debt, so the next has to wait for 12.
+        let text = format!("{}\nbb\n", "x".repeat(30));
+        let mut chunker = ByteChunker::new(text.as_bytes(), r"\n")
+            .unwrap()
+            .throttle_bytes(20.0);
+        let start = Instant::now();
+        assert_eq!(chunker.next().await.unwrap().unwrap().len(), 30);
+        assert!(start.elapsed() < Duration::from_millis(100));
+        assert_eq!(chunker.next().await.unwrap().unwrap(), b"bb");
+        assert!(start.elapsed() >= Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "test-util")]
+    async fn stats_async() {
+        use crate::ChaosReader;
+        use std::io::ErrorKind;
+
+        let text = b"one;two;three";
+        let source = ChaosReader::new(&text[..], 0).with_error_after(6, ErrorKind::Other);
+        let mut chunker = ByteChunker::new(source, ";").unwrap();
+        assert_eq!(chunker.next().await.unwrap().unwrap(), b"one");
+        let stats = chunker.stats();
+        assert_eq!(
+            *stats.borrow(),
+            Stats {
+                bytes_in: 6,
+                chunks_out: 1,
+                errors: 0,
+                buffered: 2,
+            }
+        );
+
+        assert!(chunker.next().await.unwrap().is_err());
+        assert_eq!(stats.borrow().errors, 1);
+        // A receiver asked for later still sees the error.
+        assert_eq!(chunker.stats().borrow().errors, 1);
+    }
+
+    #[tokio::test]
+    async fn copy_chunked_pipeline() {
+        let input = "keep 1;drop 2;keep 3;".as_bytes();
+        let mut output = Vec::new();
+        let n = copy_chunked(
+            input,
+            ";",
+            |chunk| match chunk.starts_with(b"keep") {
+                true => [&chunk[..], b"|"].concat(),
+                false => Vec::new(),
+            },
+            &mut output,
+        )
+        .await
+        .unwrap();
+        assert_eq!(n, 3);
+        assert_eq!(output, b"keep 1|keep 3|");
+
+        let res = copy_chunked(input, "(", |c| c, Vec::new()).await;
+        assert!(matches!(res, Err(RcErr::Regex(_))));
+    }
+
+    #[tokio::test]
+    async fn pipeline_sinks() {
+        use std::sync::{Arc, Mutex};
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let summary = Pipeline::new(&b"a,bb,,ccc"[..], ",")
+            .unwrap()
+            .filter(|c| !c.is_empty())
+            .adapt(|c| Ok(c.len()))
+            .for_each(move |n| {
+                sink.lock().unwrap().push(n);
+                Ok(())
+            })
+            .run()
+            .await
+            .unwrap();
+        assert_eq!(*seen.lock().unwrap(), [1, 2, 3]);
+        assert_eq!(summary.filtered, 1);
+
+        // Errors halt the run by default.
+        let res = Pipeline::new(&b"a,b"[..], ",")
+            .unwrap()
+            .adapt(|_| Err::<(), _>(RcErr::Panic("nope".into())))
+            .for_each(|_| Ok(()))
+            .run()
+            .await;
+        assert!(matches!(res, Err(RcErr::Panic(_))));
+
+        // Delivering to a closed channel always does.
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        drop(rx);
+        let res = Pipeline::new(&b"a,b"[..], ",")
+            .unwrap()
+            .on_error(crate::ErrorResponse::Ignore)
+            .into_channel(tx)
+            .run()
+            .await;
+        assert!(matches!(res, Err(RcErr::Read(_))));
+    }
+
+    /* Collect everything `stream` yields, dropping each `next()` future as
+    soon as it returns `Pending` and starting a new one. Also returns how
+    many futures were dropped. */
+    async fn collect_cancelling<S: Stream + Unpin>(mut stream: S) -> (Vec<S::Item>, usize) {
+        use std::{future::poll_fn, pin::pin};
+
+        let (mut items, mut cancelled) = (Vec::new(), 0);
+        loop {
+            match poll_fn(|cx| Poll::Ready(pin!(stream.next()).poll(cx))).await {
+                Poll::Ready(Some(item)) => items.push(item),
+                Poll::Ready(None) => return (items, cancelled),
+                Poll::Pending => {
+                    cancelled += 1;
+                    tokio::task::yield_now().await;
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "test-util")]
+    async fn cancelled_next() {
+        use crate::{SlowReader, StringAdapter};
+        use std::time::Duration;
+
+        let text = "\u{feff}one,twö,three,,four,";
+        // Returns `Pending` before every read of 1 to 3 bytes.
+        let slow = |seed| {
+            SlowReader::new(text.as_bytes())
+                .with_random_reads(1, 3, seed)
+                .with_delay(Duration::ZERO)
+        };
+
+        for seed in 0..8 {
+            let expected: Vec<Chunk> = ByteChunker::new(slow(seed), ",")
+                .unwrap()
+                .with_bom_stripping(true)
+                .with_match(MatchDisposition::Prepend)
+                .with_metadata()
+                .with_char_offsets()
+                .map(Result::unwrap)
+                .collect()
+                .await;
+            let chunker = ByteChunker::new(slow(seed), ",")
+                .unwrap()
+                .with_bom_stripping(true)
+                .with_match(MatchDisposition::Prepend)
+                .with_metadata()
+                .with_char_offsets();
+            let (chunks, cancelled) = collect_cancelling(chunker).await;
+            assert!(cancelled > text.len() / 3);
+            let chunks: Vec<Chunk> = chunks.into_iter().map(Result::unwrap).collect();
+            assert_eq!(chunks, expected);
+
+            // A multi-byte character split across cancelled reads.
+            let chunker = ByteChunker::new(slow(seed), ",")
+                .unwrap()
+                .with_bom_stripping(true)
+                .with_adapter(StringAdapter::default());
+            let (strings, _) = collect_cancelling(chunker).await;
+            let strings: Vec<String> = strings.into_iter().map(Result::unwrap).collect();
+            assert_eq!(strings, ["one", "twö", "three", "", "four"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn cancelled_next_each_poll() {
+        use crate::StringAdapter;
+
+        /* Yields 1 to 3 bytes per read, returning `Pending` before each. */
+        struct Stutter {
+            data: &'static [u8],
+            reads: usize,
+            ready: bool,
+        }
+
+        impl AsyncRead for Stutter {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut ReadBuf<'_>,
+            ) -> Poll<std::io::Result<()>> {
+                self.ready = !self.ready;
+                if !self.ready {
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+                self.reads += 1;
+                let n = (1 + self.reads % 3)
+                    .min(self.data.len())
+                    .min(buf.remaining());
+                let (head, tail) = self.data.split_at(n);
+                buf.put_slice(head);
+                self.data = tail;
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        async fn drain<S: Stream + Unpin>(mut stream: S) -> Vec<S::Item> {
+            let mut items = Vec::new();
+            while let Some(item) = stream.next().await {
+                items.push(item);
+            }
+            items
+        }
+
+        const TEXT: &str = "\u{feff}one,,twö,three,,,four,tail";
+        let stutter = || Stutter {
+            data: TEXT.as_bytes(),
+            reads: 0,
+            ready: false,
+        };
+        let cursor = || std::io::Cursor::new(TEXT.as_bytes());
+
+        // Each configuration carries some state from one chunk to the next:
+        // a pending prefix, a repeat count or an anchor.
+        macro_rules! check {
+            ($c:ident => $config:expr) => {{
+                let (chunks, cancelled) = collect_cancelling({
+                    let $c = ByteChunker::new(stutter(), ",").unwrap();
+                    $config
+                })
+                .await;
+                assert!(cancelled >= TEXT.len() / 3);
+                let expected = drain({
+                    let $c = ByteChunker::new(cursor(), ",").unwrap();
+                    $config
+                })
+                .await;
+                let chunks: Vec<Vec<u8>> = chunks.into_iter().map(Result::unwrap).collect();
+                let expected: Vec<Vec<u8>> = expected.into_iter().map(Result::unwrap).collect();
+                assert_eq!(chunks, expected);
+
+                let (chunks, _) = collect_cancelling({
+                    let $c = ByteChunker::new(stutter(), ",").unwrap();
+                    $config.with_metadata().with_char_offsets()
+                })
+                .await;
+                let expected = drain({
+                    let $c = ByteChunker::new(cursor(), ",").unwrap();
+                    $config.with_metadata().with_char_offsets()
+                })
+                .await;
+                let chunks: Vec<Chunk> = chunks.into_iter().map(Result::unwrap).collect();
+                let expected: Vec<Chunk> = expected.into_iter().map(Result::unwrap).collect();
+                assert_eq!(chunks, expected);
+
+                let (strings, _) = collect_cancelling({
+                    let $c = ByteChunker::new(stutter(), ",").unwrap();
+                    $config.with_adapter(StringAdapter::default())
+                })
+                .await;
+                let expected = drain({
+                    let $c = ByteChunker::new(cursor(), ",").unwrap();
+                    $config.with_adapter(StringAdapter::default())
+                })
+                .await;
+                let strings: Vec<String> = strings.into_iter().map(Result::unwrap).collect();
+                let expected: Vec<String> = expected.into_iter().map(Result::unwrap).collect();
+                assert_eq!(strings, expected);
+            }};
+        }
+
+        check!(c => c);
+        check!(c => c.with_match(MatchDisposition::Append));
+        check!(c => c.with_match(MatchDisposition::Prepend));
+        check!(c => c.with_match(MatchDisposition::Wrap));
+        check!(c => c.with_min_repeats(2));
+        check!(c => c.with_bom_stripping(true).with_anchor("^t").unwrap());
+    }
+
+    #[tokio::test]
+    async fn cancelled_writes() {
+        use tokio::io::AsyncWrite;
+
+        /* Accepts a byte at a time, returning `Pending` before each. */
+        #[derive(Default)]
+        struct Trickle {
+            written: Vec<u8>,
+            ready: bool,
+        }
+
+        impl AsyncWrite for Trickle {
+            fn poll_write(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &[u8],
+            ) -> Poll<std::io::Result<usize>> {
+                self.ready = !self.ready;
+                if !self.ready {
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+                self.written.push(buf[0]);
+                Poll::Ready(Ok(1))
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn poll_shutdown(
+                self: Pin<&mut Self>,
+                _: &mut Context<'_>,
+            ) -> Poll<std::io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        let mut joiner = ChunkJoiner::new(Trickle::default(), ", ");
+        let mut chunks = ["one", "two", "three"].into_iter();
+        let mut chunk = chunks.next();
+        while let Some(c) = chunk {
+            // Drop the write whenever it's pending, and retry the chunk
+            // only if it wasn't accepted.
+            let before = joiner.count();
+            let mut write = Box::pin(joiner.write_chunk(c.as_bytes()));
+            let res = std::future::poll_fn(|cx| Poll::Ready(write.as_mut().poll(cx))).await;
+            drop(write);
+            if res.is_ready() || joiner.count() > before {
+                chunk = chunks.next();
+            }
+        }
+        assert_eq!(joiner.finish().await.unwrap().written, b"one, two, three");
+
+        let mut sink = JsonLinesSink::new(Trickle::default());
+        let mut values = ["a", "b\"c"].into_iter();
+        let mut value = values.next();
+        while let Some(v) = value {
+            let before = sink.count();
+            let mut write = Box::pin(sink.write_chunk(v));
+            let res = std::future::poll_fn(|cx| Poll::Ready(write.as_mut().poll(cx))).await;
+            drop(write);
+            if res.is_ready() || sink.count() > before {
+                value = values.next();
+            }
+        }
+        sink.flush().await.unwrap();
+        assert_eq!(sink.into_inner().written, b"\"a\"\n\"b\\\"c\"\n");
+    }
+
+    #[tokio::test]
+    async fn set_pattern_async() {
+        let text = "HELLO\r\nUPGRADE\r\none;two;three";
+        let mut chunker = ByteChunker::new(text.as_bytes(), r"\r\n")
+            .unwrap()
+            .with_read_size(4);
+        assert_eq!(chunker.next().await.unwrap().unwrap(), b"HELLO");
+        assert_eq!(chunker.next().await.unwrap().unwrap(), b"UPGRADE");
+
+        assert!(matches!(chunker.set_pattern("("), Err(RcErr::Regex(_))));
+        chunker.set_pattern(";").unwrap();
+        let rest = chunker.collect_strings().await.unwrap();
+        assert_eq!(rest, ["one", "two", "three"]);
+    }
+
+    #[tokio::test]
+    async fn set_match_async() {
+        let mut chunker = ByteChunker::new(&b"a\nb\nc\nd"[..], r"\n")
+            .unwrap()
+            .with_match(MatchDisposition::Prepend)
+            .with_read_size(2);
+        assert_eq!(chunker.next().await.unwrap().unwrap(), b"a");
+
+        // The newline kept for the next chunk mustn't be matched again.
+        chunker.set_match(MatchDisposition::Drop);
+        let rest = chunker.collect_strings().await.unwrap();
+        assert_eq!(rest, ["\nb", "c", "d"]);
+    }
+
+    #[tokio::test]
+    async fn spans_under_dispositions_async() {
+        let text = b"one;;two; three;four";
+        let dispos = [
+            MatchDisposition::Drop,
+            MatchDisposition::Append,
+            MatchDisposition::Prepend,
+            MatchDisposition::Replace(b"|".to_vec()),
+        ];
+        for dispo in dispos {
+            for size in [1, 3, 1024] {
+                let mut chunker = ByteChunker::new(&text[..], "; ?")
+                    .unwrap()
+                    .with_match(dispo.clone())
+                    .with_read_size(size)
+                    .with_metadata();
+                let mut end = 0;
+                while let Some(chunk) = chunker.next().await {
+                    let chunk = chunk.unwrap();
+                    let span = chunk.span.start as usize..chunk.span.end as usize;
+                    assert!(span.start >= end, "{:?}: {:?}", dispo, chunk);
+                    end = span.end;
+                    let source = &text[span];
+                    match &dispo {
+                        MatchDisposition::Replace(r) if !chunk.eof => {
+                            assert_eq!([source, &r[..]].concat(), chunk.body)
+                        }
+                        _ => assert_eq!(source, &chunk.body[..], "{:?}", dispo),
+                    }
+                }
+                assert_eq!(end, text.len());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn overlap_policies_async() {
+        let text = b"xaaayaaaaz";
+        let cases: [(OverlapPolicy, &[&[u8]]); 2] = [
+            (OverlapPolicy::Skip, &[b"x", b"ay", b"", b"z"]),
+            (OverlapPolicy::Rescan, &[b"x", b"", b"y", b"", b"", b"z"]),
+        ];
+        for (policy, bodies) in cases {
+            for size in [1, 2, 1024] {
+                let mut chunker = ByteChunker::new(&text[..], "aa")
+                    .unwrap()
+                    .with_overlap(policy)
+                    .with_read_size(size);
+                let mut got = Vec::new();
+                while let Some(chunk) = chunker.next().await {
+                    got.push(chunk.unwrap());
+                }
+                assert_eq!(&got, bodies, "{:?}, {}", policy, size);
+            }
+        }
+
+        let mut chunker = ByteChunker::new(&b"baaa"[..], "aa")
+            .unwrap()
+            .with_overlap(OverlapPolicy::Rescan)
+            .with_metadata();
+        let mut spans = Vec::new();
+        while let Some(chunk) = chunker.next().await {
+            spans.push(chunk.unwrap().span);
+        }
+        assert_eq!(spans, [0..1, 2..2]);
+    }
+
+    #[tokio::test]
+    async fn wrap_disposition_async() {
+        let byte_vec = std::fs::read(PASSWD_PATH).unwrap();
+        let re = Regex::new(r"\n").unwrap();
+        let slice_vec = chunk_vec(&re, &byte_vec, MatchDisposition::Wrap);
+        for size in [1, 7, 1024] {
+            let mut chunker = ByteChunker::new(File::open(PASSWD_PATH).await.unwrap(), r"\n")
+                .unwrap()
+                .with_match(MatchDisposition::Wrap)
+                .with_read_size(size);
+            let mut vec_vec = Vec::new();
+            while let Some(chunk) = chunker.next().await {
+                vec_vec.push(chunk.unwrap());
+            }
+            assert_eq!(vec_vec.len(), slice_vec.len());
+            ref_slice_cmp(&vec_vec, &slice_vec);
+        }
+    }
+
+    #[tokio::test]
+    async fn min_repeats_async() {
+        let text = b"a--b----c---d-";
+        for size in [1, 2, 1024] {
+            let mut chunker = ByteChunker::new(&text[..], "-")
+                .unwrap()
+                .with_min_repeats(3)
+                .with_read_size(size);
+            let mut chunks = Vec::new();
+            while let Some(chunk) = chunker.next().await {
+                chunks.push(chunk.unwrap());
+            }
+            assert_eq!(chunks, [&b"a--b"[..], b"c", b"d-"], "{}", size);
+        }
+    }
+
+    #[tokio::test]
+    async fn split_fn_vetoes_async() {
+        let text = br#"a,"b,c",d,"e,,f""#;
+        for size in [1, 3, 1024] {
+            let mut chunker = ByteChunker::new(&text[..], ",")
+                .unwrap()
+                .with_read_size(size)
+                .with_split_fn(|chunk, _| chunk.iter().filter(|&&b| b == b'"').count() % 2 == 0);
+            let mut chunks = Vec::new();
+            while let Some(chunk) = chunker.next().await {
+                chunks.push(chunk.unwrap());
+            }
+            assert_eq!(
+                chunks,
+                [&b"a"[..], br#""b,c""#, b"d", br#""e,,f""#],
+                "{}",
+                size
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn min_gap_async() {
+        let text = b";A;B first;C;D second;;EFG";
+        for size in [1, 3, 1024] {
+            let mut chunker = ByteChunker::new(&text[..], ";")
+                .unwrap()
+                .with_min_gap(4)
+                .with_read_size(size);
+            let mut chunks = Vec::new();
+            while let Some(chunk) = chunker.next().await {
+                chunks.push(chunk.unwrap());
+            }
+            assert_eq!(
+                chunks,
+                [&b";A;B first"[..], b"C;D second", b";EFG"],
+                "{}",
+                size
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn chunk_seq_async() {
+        /* Yields its data, then fails every read. */
+        struct Broken(&'static [u8]);
+
+        impl AsyncRead for Broken {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                _: &mut Context<'_>,
+                buf: &mut ReadBuf<'_>,
+            ) -> Poll<std::io::Result<()>> {
+                if self.0.is_empty() {
+                    return Poll::Ready(Err(std::io::ErrorKind::Other.into()));
+                }
+                let n = self.0.len().min(buf.remaining());
+                buf.put_slice(&self.0[..n]);
+                self.0 = &self.0[n..];
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        let src = Broken(b"a;b;c;");
+        let mut chunker = ByteChunker::new(src, ";").unwrap().with_metadata();
+        for (n, body) in [b"a", b"b", b"c"].into_iter().enumerate() {
+            let chunk = chunker.next().await.unwrap().unwrap();
+            assert_eq!(
+                (&chunk.body[..], chunk.seq, chunk.gap),
+                (&body[..], n as u64, false)
+            );
+        }
+        assert!(matches!(chunker.next().await, Some(Err(RcErr::Read(_)))));
+        // Unlike the sync chunker, the stream doesn't carry on past an
+        // error, so there's no gap to report.
+        assert!(chunker.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn anchored_splits_async() {
+        let text = b"a\n\n---b---c\n\n---d";
+        for size in [1, 3, 1024] {
+            let mut chunker = ByteChunker::new(&text[..], "---")
+                .unwrap()
+                .with_anchor(r"\n\n")
+                .unwrap()
+                .with_read_size(size);
+            let mut chunks = Vec::new();
+            while let Some(chunk) = chunker.next().await {
+                chunks.push(chunk.unwrap());
+            }
+            assert_eq!(chunks, [&b"a\n\n"[..], b"b---c\n\n", b"d"], "{}", size);
+        }
+    }
+
+    #[tokio::test]
+    async fn strict_termination_async() {
+        for size in [1, 4, 1024] {
+            let mut chunker = ByteChunker::new(&b"one;two;thr"[..], ";")
+                .unwrap()
+                .with_strict_termination(true)
+                .with_read_size(size);
+            let mut results = Vec::new();
+            while let Some(res) = chunker.next().await {
+                results.push(res);
+            }
+            assert_eq!(results.len(), 3, "{}", size);
+            assert_eq!(results[1].as_ref().unwrap(), b"two");
+            assert!(matches!(&results[2], Err(RcErr::Unterminated(rest)) if rest == b"thr"));
+        }
+
+        let mut chunker = ByteChunker::new(&b"one;two;"[..], ";")
+            .unwrap()
+            .with_strict_termination(true);
+        while let Some(res) = chunker.next().await {
+            res.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn buffered_len_async() {
+        let text = format!("a;{};b;", "x".repeat(20));
+        let mut chunker = ByteChunker::new(std::io::Cursor::new(text), ";")
+            .unwrap()
+            .with_read_size(4);
+        assert_eq!(
+            (chunker.buffered_len(), chunker.peak_buffered_len()),
+            (0, 0)
+        );
+
+        assert_eq!(chunker.next().await.unwrap().unwrap(), b"a");
+        assert_eq!(chunker.buffered_len(), 2);
+
+        while let Some(chunk) = chunker.next().await {
+            chunk.unwrap();
+        }
+        assert_eq!(chunker.buffered_len(), 0);
+        assert!((21..25).contains(&chunker.peak_buffered_len()));
+    }
+
+    #[test]
+    fn blocking_chunker() {
+        let byte_vec = std::fs::read(TEST_PATH).unwrap();
+        let re = Regex::new(TEST_PATT).unwrap();
+        let slice_vec = chunk_vec(&re, &byte_vec, MatchDisposition::Drop);
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let f = rt.block_on(File::open(TEST_PATH)).unwrap();
+        let vec_vec: Vec<Vec<u8>> = ByteChunker::new(f, TEST_PATT)
+            .unwrap()
+            .into_blocking(rt.handle().clone())
+            .map(|res| res.unwrap())
+            .collect();
+
+        ref_slice_cmp(&vec_vec, &slice_vec);
+    }
+
+    #[tokio::test]
+    async fn slow_async() {
+        let byte_vec = std::fs::read(TEST_PATH).unwrap();
+        let re = Regex::new(TEST_PATT).unwrap();
+        let slice_vec = chunk_vec(&re, &byte_vec, MatchDisposition::Drop);
+
+        let mut child = Command::new(SOURCE)
+            .args(SOURCE_ARGS)
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let chunker = ByteChunker::new(stdout, TEST_PATT).unwrap();
+        let vec_vec: Vec<Vec<u8>> = chunker.map(|res| res.unwrap()).collect().await;
+        child.wait().await.unwrap();
+
+        ref_slice_cmp(&vec_vec, &slice_vec);
+    }
+
+    #[tokio::test]
+    async fn rev_async() {
+        let byte_vec = std::fs::read(PASSWD_PATH).unwrap();
+        let re = Regex::new(PASSWD_PATT).unwrap();
+        let mut slice_vec = chunk_vec(&re, &byte_vec, MatchDisposition::Append);
+        if slice_vec.last().map(|v| v.is_empty()) == Some(true) {
+            slice_vec.pop();
+        }
+        slice_vec.reverse();
+
+        let f = File::open(PASSWD_PATH).await.unwrap();
+        let chunker = RevChunker::new(f, PASSWD_PATT)
+            .unwrap()
+            .with_buffer_size(100)
+            .with_match(MatchDisposition::Append);
+        let vec_vec: Vec<Vec<u8>> = chunker.map(|res| res.unwrap()).collect().await;
+
+        assert_eq!(vec_vec.len(), slice_vec.len());
+        ref_slice_cmp(&vec_vec, &slice_vec);
+    }
+
+    #[tokio::test]
+    async fn json_lines() {
+        let text = b"a\\b\n\xffc";
+        let chunker = ByteChunker::new(std::io::Cursor::new(text), r"\n")
+            .unwrap()
+            .with_metadata();
+        let mut sink = JsonLinesSink::new(Vec::new());
+        assert_eq!(sink.write_stream(chunker).await.unwrap(), 2);
+
+        let output = String::from_utf8(sink.into_inner()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines[0],
+            r#"{"body":"a\\b","delimiter":"\n","start":0,"end":3,"eof":false,"forced":false}"#
+        );
+        assert_eq!(
+            lines[1],
+            "{\"body\":\"\u{fffd}c\",\"delimiter\":null,\"start\":4,\"end\":6,\
+             \"eof\":true,\"forced\":false}"
+        );
+    }
+
+    #[tokio::test]
+    async fn collect_async() {
+        let f = File::open(PASSWD_PATH).await.unwrap();
+        let strings = ByteChunker::new(f, PASSWD_PATT)
+            .unwrap()
+            .collect_strings()
+            .await
+            .unwrap();
+        let byte_vec = std::fs::read(PASSWD_PATH).unwrap();
+        let re = Regex::new(PASSWD_PATT).unwrap();
+        ref_slice_cmp(&strings, &chunk_vec(&re, &byte_vec, MatchDisposition::Drop));
+
+        let text = b"\xff,ok,\xfe";
+        let chunker = ByteChunker::new(std::io::Cursor::new(text), ",").unwrap();
+        match chunker.collect_strings().await {
+            Err(RcErr::Collect(e)) => {
+                assert_eq!((e.chunks, e.errors, e.first_offset), (1, 2, 0));
+                assert!(matches!(*e.first, RcErr::Utf8(_)));
+            }
+            x => panic!("expected a collect error, got {:?}", x),
+        }
+    }
+
+    #[tokio::test]
+    async fn count_async() {
+        let f = File::open(PASSWD_PATH).await.unwrap();
+        let n = ByteChunker::new(f, PASSWD_PATT)
+            .unwrap()
+            .count_chunks()
+            .await
+            .unwrap();
+        let f = std::fs::File::open(PASSWD_PATH).unwrap();
+        assert_eq!(n, crate::ByteChunker::new(f, PASSWD_PATT).unwrap().count());
     }
 }
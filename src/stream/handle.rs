@@ -0,0 +1,159 @@
+/*!
+Handles for controlling a stream chunker from elsewhere.
+*/
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Waker},
+};
+
+/* A flag that can be raised from another task or thread, waking the task
+that last looked at it. */
+#[derive(Debug, Default)]
+pub(crate) struct Signal {
+    raised: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl Signal {
+    /* Arrange for the current task to be woken when the signal is raised
+    (or lowered), and return whether it's raised. Registering first means
+    a signal raised in between can't be missed. */
+    pub(crate) fn check(&self, cx: &Context<'_>) -> bool {
+        *self.waker.lock().unwrap() = Some(cx.waker().clone());
+        self.raised.load(Ordering::Acquire)
+    }
+
+    fn set(&self, raised: bool) {
+        self.raised.store(raised, Ordering::Release);
+        if let Some(waker) = self.waker.lock().unwrap().take() {
+            waker.wake();
+        }
+    }
+
+    fn is_raised(&self) -> bool {
+        self.raised.load(Ordering::Acquire)
+    }
+}
+
+/**
+A cloneable handle for shutting down a
+[`stream::ByteChunker`](crate::stream::ByteChunker) from another task or
+thread, returned by
+[`ByteChunker::shutdown_handle`](crate::stream::ByteChunker::shutdown_handle).
+
+After [`shutdown`](ShutdownHandle::shutdown) is called, the chunker stops
+reading from its source at the next opportunity (waking up if it's waiting
+on a read), treats the source as if it had ended, returns the data it has
+buffered as its remaining chunks, and ends. The last of these is
+terminated by the "end" of the source, so it carries the `eof` flag when
+read through a [`MetaChunker`](crate::stream::MetaChunker). This is how
+to drain a service cleanly on `SIGTERM`, without losing data the chunker
+has already read.
+
+```rust
+# use std::error::Error;
+# #[tokio::main(flavor = "current_thread")]
+# async fn main() -> Result<(), Box<dyn Error>> {
+    use regex_chunker::stream::ByteChunker;
+    use tokio_stream::StreamExt;
+
+    // A source that has only sent part of its data, and may never send
+    // the rest.
+    let (mut tx, rx) = tokio::io::duplex(64);
+    tokio::io::AsyncWriteExt::write_all(&mut tx, b"one\ntwo\nthr").await?;
+
+    let mut chunker = ByteChunker::new(rx, r"\n")?;
+    let handle = chunker.shutdown_handle();
+    let mut chunker = chunker.with_metadata();
+    assert_eq!(chunker.next().await.unwrap()?.body, b"one");
+
+    handle.shutdown();
+    assert_eq!(chunker.next().await.unwrap()?.body, b"two");
+    let last = chunker.next().await.unwrap()?;
+    assert_eq!(last.body, b"thr");
+    assert!(last.eof);
+    assert!(chunker.next().await.is_none());
+#   Ok(()) }
+```
+*/
+#[derive(Clone, Debug)]
+pub struct ShutdownHandle {
+    pub(crate) signal: Arc<Signal>,
+}
+
+impl ShutdownHandle {
+    /// Tell the chunker to stop reading and finish up.
+    pub fn shutdown(&self) {
+        self.signal.set(true);
+    }
+
+    /// Return whether [`shutdown`](ShutdownHandle::shutdown) has been
+    /// called.
+    pub fn is_shut_down(&self) -> bool {
+        self.signal.is_raised()
+    }
+}
+
+/**
+A cloneable handle for pausing a
+[`stream::ByteChunker`](crate::stream::ByteChunker) and resuming it later,
+from another task or thread, returned by
+[`ByteChunker::pause_handle`](crate::stream::ByteChunker::pause_handle).
+
+While paused, the chunker returns `Poll::Pending` without polling its
+source or returning any chunks, so backpressure from downstream (a full
+queue, a congested service) can throttle ingestion without dropping the
+chunker or the connection under it. Resuming wakes the chunker's task.
+A [`shutdown`](ShutdownHandle::shutdown) overrides a pause, so a paused
+chunker can still be drained.
+
+```rust
+# use std::error::Error;
+# #[tokio::main(flavor = "current_thread")]
+# async fn main() -> Result<(), Box<dyn Error>> {
+    use regex_chunker::stream::ByteChunker;
+    use std::{thread, time::Duration};
+    use tokio_stream::StreamExt;
+
+    let mut chunker = ByteChunker::new(&b"one\ntwo\n"[..], r"\n")?;
+    let handle = chunker.pause_handle();
+
+    assert_eq!(chunker.next().await.unwrap()?, b"one");
+    handle.pause();
+    assert!(handle.is_paused());
+
+    // Something else decides when to carry on.
+    let resumer = handle.clone();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(10));
+        resumer.resume();
+    });
+    assert_eq!(chunker.next().await.unwrap()?, b"two");
+    assert!(!handle.is_paused());
+#   Ok(()) }
+```
+*/
+#[derive(Clone, Debug)]
+pub struct PauseHandle {
+    pub(crate) signal: Arc<Signal>,
+}
+
+impl PauseHandle {
+    /// Pause the chunker.
+    pub fn pause(&self) {
+        self.signal.set(true);
+    }
+
+    /// Resume the chunker, waking its task if it's waiting.
+    pub fn resume(&self) {
+        self.signal.set(false);
+    }
+
+    /// Return whether the chunker is paused.
+    pub fn is_paused(&self) -> bool {
+        self.signal.is_raised()
+    }
+}
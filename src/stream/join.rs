@@ -0,0 +1,262 @@
+/*!
+Rejoining chunk streams with separators, and copying data through a
+per-chunk transform.
+*/
+use std::{
+    future::poll_fn,
+    pin::Pin,
+    task::{ready, Poll},
+};
+
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio_stream::{Stream, StreamExt};
+
+use super::ByteChunker;
+use crate::RcErr;
+
+/**
+Read from `reader`, split what's read into chunks at matches for the regex
+`pattern`, pass each chunk through `transform`, and write whatever it
+returns to `writer`, which is flushed at the end. Returns the number of
+chunks copied.
+
+Each chunk is written in full before any more is read, so a slow `writer`
+slows down the reading rather than letting data pile up in between. The
+first error, whether reading or writing, stops the copy and is returned.
+
+The delimiters are dropped, as usual; `transform` has to put back anything
+wanted between chunks. For more control, build the
+[`ByteChunker`] yourself and hand it to
+[`ChunkJoiner::write_stream`].
+
+```rust
+# use std::error::Error;
+# #[tokio::main(flavor = "current_thread")]
+# async fn main() -> Result<(), Box<dyn Error>> {
+    use regex_chunker::stream::copy_chunked;
+
+    let input = &b"alpha\nbeta\ngamma\n"[..];
+    let mut output = Vec::new();
+    let n = copy_chunked(
+        input,
+        r"\n",
+        |chunk| {
+            let mut line = chunk.to_ascii_uppercase();
+            line.push(b'\n');
+            line
+        },
+        &mut output,
+    )
+    .await?;
+
+    assert_eq!(n, 3);
+    assert_eq!(output, b"ALPHA\nBETA\nGAMMA\n");
+#   Ok(()) }
+```
+*/
+pub async fn copy_chunked<R, F, T, W>(
+    reader: R,
+    pattern: &str,
+    mut transform: F,
+    mut writer: W,
+) -> Result<usize, RcErr>
+where
+    R: AsyncRead + Unpin,
+    F: FnMut(Vec<u8>) -> T,
+    T: AsRef<[u8]>,
+    W: AsyncWrite + Unpin,
+{
+    let mut chunker = ByteChunker::new(reader, pattern)?;
+    let mut n = 0;
+    while let Some(res) = chunker.next().await {
+        writer.write_all(transform(res?).as_ref()).await?;
+        n += 1;
+    }
+    writer.flush().await?;
+    Ok(n)
+}
+
+/**
+The async analog to the base crate's [`ChunkJoiner`](crate::ChunkJoiner).
+It writes chunks to an
+[`AsyncWrite`](https://docs.rs/tokio/latest/tokio/io/trait.AsyncWrite.html)r
+with a separator between (or, optionally, after) them, completing a
+split/transform/rejoin pipeline.
+
+Writing is cancel-safe: each chunk (with its separator) is staged in an
+internal buffer before any of it is written, so if a
+[`write_chunk`](ChunkJoiner::write_chunk) future is dropped partway
+through (losing a `select!` race, say), the rest is written at the start
+of the next call to `write_chunk`, [`flush`](ChunkJoiner::flush), or
+[`finish`](ChunkJoiner::finish), and nothing is written twice or lost.
+A cancelled call may or may not have accepted its chunk;
+[`count`](ChunkJoiner::count) says which.
+
+```rust
+# use std::error::Error;
+# #[tokio::main(flavor = "current_thread")]
+# async fn main() -> Result<(), Box<dyn Error>> {
+    use regex_chunker::stream::{ByteChunker, ChunkJoiner};
+    use std::io::Cursor;
+
+    let text = b"b\na\nc";
+    let chunker = ByteChunker::new(Cursor::new(text), r"\n")?;
+
+    let mut joiner = ChunkJoiner::new(Vec::new(), "\n").with_trailing_separator(true);
+    assert_eq!(joiner.write_stream(chunker).await?, 3);
+    assert_eq!(joiner.finish().await?, b"b\na\nc\n");
+#   Ok(()) }
+```
+*/
+#[derive(Debug)]
+pub struct ChunkJoiner<W> {
+    writer: W,
+    separator: Vec<u8>,
+    trailing: bool,
+    flush_per_chunk: bool,
+    count: usize,
+    staged: Staged,
+}
+
+impl<W> ChunkJoiner<W> {
+    /// Return a new `ChunkJoiner` that writes chunks to `writer`, with
+    /// `separator` between them.
+    pub fn new<S: AsRef<[u8]>>(writer: W, separator: S) -> Self {
+        Self {
+            writer,
+            separator: separator.as_ref().to_vec(),
+            trailing: false,
+            flush_per_chunk: false,
+            count: 0,
+            staged: Staged::default(),
+        }
+    }
+
+    /// Builder-pattern method for writing the separator after every
+    /// chunk. See
+    /// [`ChunkJoiner::with_trailing_separator`](crate::ChunkJoiner::with_trailing_separator).
+    pub fn with_trailing_separator(mut self, trailing: bool) -> Self {
+        self.trailing = trailing;
+        self
+    }
+
+    /// Builder-pattern method for flushing the writer after every chunk.
+    /// Default is `false`.
+    pub fn with_flush_per_chunk(mut self, flush: bool) -> Self {
+        self.flush_per_chunk = flush;
+        self
+    }
+
+    /// Return the number of chunks written (or, if a write was cancelled,
+    /// accepted for writing) so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Get a reference to the wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Consume the `ChunkJoiner` and return the wrapped writer, without
+    /// flushing it or finishing a cancelled write.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: AsyncWrite + Unpin> ChunkJoiner<W> {
+    /// Write a single chunk, along with the separator that belongs before
+    /// or after it.
+    pub async fn write_chunk(&mut self, chunk: &[u8]) -> Result<(), RcErr> {
+        self.staged.drain(&mut self.writer).await?;
+
+        let buff = self.staged.start();
+        if !self.trailing && self.count > 0 {
+            buff.extend_from_slice(&self.separator);
+        }
+        buff.extend_from_slice(chunk);
+        if self.trailing {
+            buff.extend_from_slice(&self.separator);
+        }
+        self.count += 1;
+
+        self.staged.drain(&mut self.writer).await?;
+        if self.flush_per_chunk {
+            self.writer.flush().await?;
+        }
+        Ok(())
+    }
+
+    /**
+    Write every chunk yielded by `stream`, then flush the writer. Returns
+    the number of chunks written, or the first error encountered, either
+    from the stream or from writing.
+    */
+    pub async fn write_stream<S, T>(&mut self, mut stream: S) -> Result<usize, RcErr>
+    where
+        S: Stream<Item = Result<T, RcErr>> + Unpin,
+        T: AsRef<[u8]>,
+    {
+        let mut n = 0;
+        while let Some(res) = stream.next().await {
+            self.write_chunk(res?.as_ref()).await?;
+            n += 1;
+        }
+        self.writer.flush().await?;
+        Ok(n)
+    }
+
+    /// Finish any cancelled write, then flush the wrapped writer.
+    pub async fn flush(&mut self) -> Result<(), RcErr> {
+        self.staged.drain(&mut self.writer).await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+
+    /// Finish any cancelled write and flush the wrapped writer, then
+    /// consume the `ChunkJoiner` and return it.
+    pub async fn finish(mut self) -> Result<W, RcErr> {
+        self.flush().await?;
+        Ok(self.writer)
+    }
+}
+
+/* Bytes staged for writing, kept across calls so that a write cancelled
+partway through can be picked up where it left off. */
+#[derive(Debug, Default)]
+pub(crate) struct Staged {
+    buff: Vec<u8>,
+    written: usize,
+}
+
+impl Staged {
+    /* Return the (emptied) buffer, to stage the next write. Only to be
+    called once everything staged before has been drained. */
+    pub(crate) fn start(&mut self) -> &mut Vec<u8> {
+        debug_assert!(self.written == self.buff.len());
+        self.buff.clear();
+        self.written = 0;
+        &mut self.buff
+    }
+
+    /* Write whatever hasn't been written yet. Progress is recorded as it's
+    made, so this is safe to cancel and call again. */
+    pub(crate) async fn drain<W>(&mut self, writer: &mut W) -> std::io::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        poll_fn(|cx| {
+            while self.written < self.buff.len() {
+                let unwritten = &self.buff[self.written..];
+                let n = ready!(Pin::new(&mut *writer).poll_write(cx, unwritten))?;
+                if n == 0 {
+                    return Poll::Ready(Err(std::io::ErrorKind::WriteZero.into()));
+                }
+                self.written += n;
+            }
+            Poll::Ready(Ok(()))
+        })
+        .await
+    }
+}
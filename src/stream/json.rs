@@ -0,0 +1,191 @@
+/*!
+Writing chunk streams out as newline-delimited JSON.
+*/
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio_stream::{Stream, StreamExt};
+
+use super::join::Staged;
+use crate::{Chunk, RcErr};
+
+/**
+Trait for chunk types that can be written as a single JSON value by a
+[`JsonLinesSink`].
+
+Byte chunks are written as JSON strings; any invalid UTF-8 is replaced
+with `U+FFFD REPLACEMENT CHARACTER`. Newlines and other control characters
+are always escaped, so each value occupies exactly one line of output.
+*/
+pub trait ToJson {
+    /// Append the JSON representation of `self` to `buff`.
+    fn write_json(&self, buff: &mut Vec<u8>);
+}
+
+/* Append `s` to `buff` as a quoted and escaped JSON string. */
+fn write_json_str(s: &str, buff: &mut Vec<u8>) {
+    buff.push(b'"');
+    for c in s.chars() {
+        match c {
+            '"' => buff.extend_from_slice(b"\\\""),
+            '\\' => buff.extend_from_slice(b"\\\\"),
+            '\n' => buff.extend_from_slice(b"\\n"),
+            '\r' => buff.extend_from_slice(b"\\r"),
+            '\t' => buff.extend_from_slice(b"\\t"),
+            c if (c as u32) < 0x20 || c == '\u{2028}' || c == '\u{2029}' => {
+                buff.extend_from_slice(format!("\\u{:04x}", c as u32).as_bytes());
+            }
+            c => {
+                let mut utf8 = [0u8; 4];
+                buff.extend_from_slice(c.encode_utf8(&mut utf8).as_bytes());
+            }
+        }
+    }
+    buff.push(b'"');
+}
+
+impl ToJson for str {
+    fn write_json(&self, buff: &mut Vec<u8>) {
+        write_json_str(self, buff);
+    }
+}
+
+impl ToJson for String {
+    fn write_json(&self, buff: &mut Vec<u8>) {
+        write_json_str(self, buff);
+    }
+}
+
+impl ToJson for [u8] {
+    fn write_json(&self, buff: &mut Vec<u8>) {
+        write_json_str(&String::from_utf8_lossy(self), buff);
+    }
+}
+
+impl ToJson for Vec<u8> {
+    fn write_json(&self, buff: &mut Vec<u8>) {
+        self.as_slice().write_json(buff);
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn write_json(&self, buff: &mut Vec<u8>) {
+        match self {
+            Some(t) => t.write_json(buff),
+            None => buff.extend_from_slice(b"null"),
+        }
+    }
+}
+
+/**
+A [`Chunk`] is written as an object with `body`, `delimiter`, `start`,
+`end`, `eof`, and `forced` members.
+*/
+impl ToJson for Chunk {
+    fn write_json(&self, buff: &mut Vec<u8>) {
+        buff.extend_from_slice(b"{\"body\":");
+        self.body.write_json(buff);
+        buff.extend_from_slice(b",\"delimiter\":");
+        self.delimiter.write_json(buff);
+        let tail = format!(
+            ",\"start\":{},\"end\":{},\"eof\":{},\"forced\":{}}}",
+            self.span.start, self.span.end, self.eof, self.forced
+        );
+        buff.extend_from_slice(tail.as_bytes());
+    }
+}
+
+/**
+Writes chunks to an
+[`AsyncWrite`](https://docs.rs/tokio/latest/tokio/io/trait.AsyncWrite.html)r
+as newline-delimited JSON ("JSON lines"), one value per chunk. Combined
+with a chunker, this makes a complete "delimited text in, NDJSON out"
+converter.
+
+```rust
+# use std::error::Error;
+# #[tokio::main(flavor = "current_thread")]
+# async fn main() -> Result<(), Box<dyn Error>> {
+    use regex_chunker::{stream::{ByteChunker, JsonLinesSink}, StringAdapter};
+    use std::io::Cursor;
+
+    let text = b"one\t1\r\ntwo \"2\"\r\n";
+    let chunker = ByteChunker::new(Cursor::new(text), r"\r\n")?
+        .with_adapter(StringAdapter::default());
+
+    let mut sink = JsonLinesSink::new(Vec::new());
+    let n = sink.write_stream(chunker).await?;
+    assert_eq!(n, 2);
+    assert_eq!(
+        sink.into_inner(),
+        b"\"one\\t1\"\n\"two \\\"2\\\"\"\n"
+    );
+#   Ok(()) }
+```
+*/
+#[derive(Debug)]
+pub struct JsonLinesSink<W> {
+    writer: W,
+    staged: Staged,
+    count: usize,
+}
+
+impl<W> JsonLinesSink<W> {
+    /// Return a new `JsonLinesSink` that writes to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            staged: Staged::default(),
+            count: 0,
+        }
+    }
+
+    /// Return the number of values written so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Consume the `JsonLinesSink` and return the wrapped writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: AsyncWrite + Unpin> JsonLinesSink<W> {
+    /// Write a single chunk as a line of JSON. Like
+    /// [`ChunkJoiner::write_chunk`](super::ChunkJoiner::write_chunk),
+    /// this is cancel-safe.
+    pub async fn write_chunk<T: ToJson + ?Sized>(&mut self, chunk: &T) -> Result<(), RcErr> {
+        self.staged.drain(&mut self.writer).await?;
+        let buff = self.staged.start();
+        chunk.write_json(buff);
+        buff.push(b'\n');
+        self.count += 1;
+        self.staged.drain(&mut self.writer).await?;
+        Ok(())
+    }
+
+    /**
+    Write every chunk yielded by `stream`, then flush the writer. Returns
+    the number of chunks written, or the first error encountered, either
+    from the stream or from writing.
+    */
+    pub async fn write_stream<S, T>(&mut self, mut stream: S) -> Result<usize, RcErr>
+    where
+        S: Stream<Item = Result<T, RcErr>> + Unpin,
+        T: ToJson,
+    {
+        let mut n = 0;
+        while let Some(res) = stream.next().await {
+            self.write_chunk(&res?).await?;
+            n += 1;
+        }
+        self.writer.flush().await?;
+        Ok(n)
+    }
+
+    /// Finish any cancelled write, then flush the wrapped writer.
+    pub async fn flush(&mut self) -> Result<(), RcErr> {
+        self.staged.drain(&mut self.writer).await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+/*!
+Merging several chunk streams into one.
+*/
+use std::{
+    fmt::{Display, Formatter},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use tokio_stream::Stream;
+
+/// Identifies which of the streams merged by a [`Merge`] an item came
+/// from: the position of the stream in the order they were added, from 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SourceId(pub usize);
+
+impl Display for SourceId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "source {}", self.0)
+    }
+}
+
+/**
+Merge several streams of chunks (the stdout and stderr of a process, say,
+or a chunker for each of many log files) into one stream, tagging each
+item with the [`SourceId`] of the stream it came from. See [`Merge`].
+
+```rust
+# use std::error::Error;
+# #[tokio::main(flavor = "current_thread")]
+# async fn main() -> Result<(), Box<dyn Error>> {
+    use regex_chunker::stream::{merge, ByteChunker, SourceId};
+    use tokio_stream::StreamExt;
+
+    let out = ByteChunker::lines(&b"out 1\nout 2\nout 3\n"[..]);
+    let err = ByteChunker::lines(&b"err 1\n"[..]);
+
+    let mut items = Vec::new();
+    let mut merged = merge([out, err]);
+    while let Some((id, res)) = merged.next().await {
+        items.push((id, String::from_utf8(res?)?));
+    }
+
+    assert_eq!(
+        items,
+        [
+            (SourceId(0), "out 1".to_string()),
+            (SourceId(1), "err 1".to_string()),
+            (SourceId(0), "out 2".to_string()),
+            (SourceId(0), "out 3".to_string()),
+        ]
+    );
+#   Ok(()) }
+```
+*/
+pub fn merge<I, S>(streams: I) -> Merge<S>
+where
+    I: IntoIterator<Item = S>,
+    S: Stream + Unpin,
+{
+    let mut merged = Merge::new();
+    for stream in streams {
+        merged.push(stream);
+    }
+    merged
+}
+
+/**
+A stream of the items from several streams, each tagged with the
+[`SourceId`] of the stream it came from, returned by [`merge`].
+
+The streams are polled fairly: each poll starts with the stream after the
+one that produced the previous item, so a stream with a steady supply of
+items can't starve the others. A stream is dropped once it ends, and the
+`Merge` ends once they all have. More streams can be added (even to a
+`Merge` that has ended) with [`push`](Merge::push).
+*/
+#[derive(Debug)]
+pub struct Merge<S> {
+    // Streams are replaced with `None` as they end, so `SourceId`s stay put.
+    streams: Vec<Option<S>>,
+    // Where the next poll starts.
+    next: usize,
+}
+
+impl<S> Merge<S> {
+    /// Return a new `Merge` of no streams at all, to be added with
+    /// [`push`](Merge::push).
+    pub fn new() -> Self {
+        Self {
+            streams: Vec::new(),
+            next: 0,
+        }
+    }
+
+    /// Add a stream, returning the [`SourceId`] its items will be tagged
+    /// with.
+    pub fn push(&mut self, stream: S) -> SourceId {
+        self.streams.push(Some(stream));
+        SourceId(self.streams.len() - 1)
+    }
+
+    /// Return the number of streams that haven't yet ended.
+    pub fn live(&self) -> usize {
+        self.streams.iter().filter(|s| s.is_some()).count()
+    }
+
+    /// Get a mutable reference to the stream with the given `id`, if it
+    /// hasn't yet ended.
+    pub fn get_mut(&mut self, id: SourceId) -> Option<&mut S> {
+        self.streams.get_mut(id.0)?.as_mut()
+    }
+}
+
+impl<S> Default for Merge<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Stream + Unpin> Stream for Merge<S> {
+    type Item = (SourceId, S::Item);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let n = self.streams.len();
+        for i in (self.next..n).chain(0..self.next) {
+            let slot = &mut self.streams[i];
+            let stream = match slot.as_mut() {
+                Some(stream) => stream,
+                None => continue,
+            };
+            match Pin::new(stream).poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    self.next = (i + 1) % n;
+                    return Poll::Ready(Some((SourceId(i), item)));
+                }
+                Poll::Ready(None) => *slot = None,
+                Poll::Pending => {}
+            }
+        }
+
+        match self.live() {
+            0 => Poll::Ready(None),
+            _ => Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,208 @@
+/*!
+Splitting MIME multipart bodies into their parts.
+*/
+use std::{
+    io::{Error, ErrorKind},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use regex::bytes::Regex;
+use tokio::io::AsyncRead;
+use tokio_stream::Stream;
+
+use super::ByteChunker;
+use crate::{
+    mail::{find_header, split_headers},
+    MatchDisposition, RcErr,
+};
+
+// Index of the closing delimiter among the patterns given to the chunker.
+const CLOSE: usize = 0;
+
+/* Where a `MultipartChunker` is in the body. */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Preamble,
+    Parts,
+    Done,
+}
+
+/**
+A chunker that yields the parts of a MIME multipart body (like an HTTP
+`multipart/form-data` request body) read from an
+[`AsyncRead`](https://docs.rs/tokio/latest/tokio/io/trait.AsyncRead.html)er,
+given the boundary string from its `Content-Type` header.
+
+Each part is delimited by a line consisting of `--` and the boundary,
+and the last part by `--`, the boundary, and `--`. Any preamble before the
+first delimiter is skipped, and nothing after the closing delimiter is
+read. The line endings that belong to the delimiters are removed, so each
+part is just its headers, a blank line, and its body; use [`Part::from`]
+to separate them. If the source ends before the closing delimiter, an
+error of kind [`ErrorKind::UnexpectedEof`] is returned in place of the
+final part.
+
+```rust
+# use std::error::Error;
+# #[tokio::main(flavor = "current_thread")]
+# async fn main() -> Result<(), Box<dyn Error>> {
+    use regex_chunker::stream::{MultipartChunker, Part};
+    use tokio_stream::StreamExt;
+
+    let body = b"preamble\r\n\
+        --XyZ\r\n\
+        Content-Disposition: form-data; name=\"a\"\r\n\
+        \r\n\
+        one\r\n\
+        --XyZ\r\n\
+        Content-Disposition: form-data; name=\"b\"\r\n\
+        \r\n\
+        two\r\nlines\r\n\
+        --XyZ--\r\n\
+        epilogue";
+
+    let parts: Vec<Part> = MultipartChunker::new(&body[..], "XyZ")?
+        .map(|res| Part::from(res.unwrap()))
+        .collect()
+        .await;
+
+    assert_eq!(parts.len(), 2);
+    assert_eq!(parts[1].header("content-disposition"), Some(&b"form-data; name=\"b\""[..]));
+    assert_eq!(parts[1].body, b"two\r\nlines");
+#   Ok(()) }
+```
+*/
+pub struct MultipartChunker<R: AsyncRead> {
+    chunker: ByteChunker<R>,
+    opening: Regex,
+    state: State,
+}
+
+impl<R: AsyncRead> MultipartChunker<R> {
+    /// Return a new [`MultipartChunker`] that splits `source` into the
+    /// parts delimited by `boundary`.
+    pub fn new(source: R, boundary: &str) -> Result<Self, RcErr> {
+        let boundary = regex::escape(boundary);
+        let chunker = ByteChunker::from_patterns(
+            source,
+            [
+                (format!(r"\r?\n--{}--", boundary), MatchDisposition::Drop),
+                (
+                    format!(r"\r?\n--{}[ \t]*\r?\n", boundary),
+                    MatchDisposition::Drop,
+                ),
+            ],
+        )?;
+        // The source might start right off with a delimiter, in which case
+        // there's no line ending before it.
+        let opening = Regex::new(&format!(r"^--{}(?:--|[ \t]*\r?\n)", boundary))?;
+
+        Ok(Self {
+            chunker,
+            opening,
+            state: State::Preamble,
+        })
+    }
+
+    /// See [`ByteChunker::with_read_size`].
+    pub fn with_read_size(mut self, size: usize) -> Self {
+        self.chunker = self.chunker.with_read_size(size);
+        self
+    }
+
+    /// Consumes the [`MultipartChunker`] and returns the underlying
+    /// [`ByteChunker`].
+    pub fn into_inner(self) -> ByteChunker<R> {
+        self.chunker
+    }
+}
+
+impl<R: AsyncRead + Unpin> Stream for MultipartChunker<R> {
+    type Item = Result<Vec<u8>, RcErr>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if self.state == State::Done {
+                return Poll::Ready(None);
+            }
+
+            let mut part = match Pin::new(&mut self.chunker).poll_next(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Some(Ok(part))) => part,
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                Poll::Ready(None) => {
+                    self.state = State::Done;
+                    return Poll::Ready(Some(Err(unterminated())));
+                }
+            };
+            // `None` if the part was ended by the end of the source.
+            let closing = self.chunker.matched_pattern().map(|n| n == CLOSE);
+
+            let mut preamble = false;
+            if self.state == State::Preamble {
+                let opening = self.opening.find(&part).map(|m| m.as_bytes().to_vec());
+                match opening {
+                    // The body closed before it opened; there are no parts.
+                    Some(opening) if opening.ends_with(b"--") => {
+                        self.state = State::Done;
+                        continue;
+                    }
+                    Some(opening) => {
+                        part.drain(..opening.len());
+                    }
+                    None => preamble = true,
+                }
+            }
+
+            match closing {
+                Some(false) => self.state = State::Parts,
+                Some(true) => self.state = State::Done,
+                None => {
+                    self.state = State::Done;
+                    return Poll::Ready(Some(Err(unterminated())));
+                }
+            }
+            if !preamble {
+                return Poll::Ready(Some(Ok(part)));
+            }
+        }
+    }
+}
+
+/* The error returned when the source ends before the closing delimiter. */
+fn unterminated() -> RcErr {
+    Error::new(
+        ErrorKind::UnexpectedEof,
+        "multipart body has no closing delimiter",
+    )
+    .into()
+}
+
+/**
+A part of a multipart body, separated into its headers and its body.
+Converting a part yielded by a [`MultipartChunker`] into a `Part` splits
+it at the first blank line.
+*/
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Part {
+    /// The part's header lines, without the blank line that ends them.
+    pub headers: Vec<u8>,
+    /// The part's body.
+    pub body: Vec<u8>,
+}
+
+impl Part {
+    /// Return the value of the first header named `name` (compared
+    /// case-insensitively), with surrounding whitespace removed.
+    pub fn header(&self, name: &str) -> Option<&[u8]> {
+        find_header(&self.headers, name)
+    }
+}
+
+impl From<Vec<u8>> for Part {
+    fn from(part: Vec<u8>) -> Self {
+        let (headers, body) = split_headers(part);
+        Self { headers, body }
+    }
+}
@@ -0,0 +1,254 @@
+/*!
+Wiring a chunker, a chain of adapters and filters, and a sink together.
+*/
+use std::pin::Pin;
+
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::mpsc,
+};
+use tokio_stream::{Stream, StreamExt};
+
+use super::{ByteChunker, ChunkJoiner};
+use crate::{ErrorResponse, RcErr};
+
+type ChunkStream = Pin<Box<dyn Stream<Item = Result<Vec<u8>, RcErr>> + Send>>;
+type Stages<T> = Box<dyn FnMut(Vec<u8>) -> Result<Option<T>, RcErr> + Send>;
+
+/**
+A source, a chunker, and a chain of adapters and filters, wired together
+so they can be run to completion with a single `.await` once given a
+sink: an [`mpsc`](https://docs.rs/tokio/latest/tokio/sync/mpsc/index.html)
+channel, an `AsyncWrite`r, or a callback. This is the shape most
+applications of this crate end up with, without the loop around it.
+
+Each stage sees the output of the one before it. An error from the
+chunker or from an adapter is handled according to
+[`on_error`](Pipeline::on_error): by default the run stops and returns
+it, but with [`ErrorResponse::Continue`] or [`ErrorResponse::Ignore`] the
+chunk is skipped and counted. A failure to deliver to the sink (a closed
+channel, a failed write) always stops the run.
+
+```rust
+# use std::error::Error;
+# #[tokio::main(flavor = "current_thread")]
+# async fn main() -> Result<(), Box<dyn Error>> {
+    use regex_chunker::{stream::Pipeline, ErrorResponse, RcErr};
+    use std::io::{Error as IoError, ErrorKind};
+    use tokio::sync::mpsc;
+
+    let input = &b"3\n1\nfour\n1\n5\n"[..];
+    let (tx, mut rx) = mpsc::channel(8);
+
+    let summary = Pipeline::new(input, r"\n")?
+        .adapt(|chunk| String::from_utf8(chunk).map_err(RcErr::from))
+        .adapt(|s| {
+            s.parse::<u32>()
+                .map_err(|e| RcErr::Read(IoError::new(ErrorKind::InvalidData, e)))
+        })
+        .filter(|n| *n != 1)
+        .adapt(|n| Ok(n * 10))
+        .on_error(ErrorResponse::Continue)
+        .into_channel(tx)
+        .run()
+        .await?;
+
+    assert_eq!((rx.recv().await, rx.recv().await), (Some(30), Some(50)));
+    assert_eq!((summary.delivered, summary.filtered, summary.errors), (2, 2, 1));
+#   Ok(()) }
+```
+*/
+pub struct Pipeline<T> {
+    source: ChunkStream,
+    stages: Stages<T>,
+    on_error: ErrorResponse,
+}
+
+impl Pipeline<Vec<u8>> {
+    /// Start a pipeline that splits the data read from `source` at
+    /// matches for the regex `pattern`.
+    pub fn new<R>(source: R, pattern: &str) -> Result<Self, RcErr>
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+    {
+        Ok(Self::from_stream(ByteChunker::new(source, pattern)?))
+    }
+
+    /// Start a pipeline from an already-configured chunker, or any other
+    /// stream of chunks.
+    pub fn from_stream<S>(chunks: S) -> Self
+    where
+        S: Stream<Item = Result<Vec<u8>, RcErr>> + Send + 'static,
+    {
+        Self {
+            source: Box::pin(chunks),
+            stages: Box::new(|chunk| Ok(Some(chunk))),
+            on_error: ErrorResponse::Halt,
+        }
+    }
+}
+
+impl<T: 'static> Pipeline<T> {
+    /// Add a stage that transforms each item, or fails.
+    pub fn adapt<U, F>(self, mut f: F) -> Pipeline<U>
+    where
+        F: FnMut(T) -> Result<U, RcErr> + Send + 'static,
+    {
+        let mut stages = self.stages;
+        Pipeline {
+            source: self.source,
+            stages: Box::new(move |chunk| match stages(chunk)? {
+                Some(t) => f(t).map(Some),
+                None => Ok(None),
+            }),
+            on_error: self.on_error,
+        }
+    }
+
+    /// Add a stage that drops the items for which `keep` returns `false`.
+    pub fn filter<F>(self, mut keep: F) -> Self
+    where
+        F: FnMut(&T) -> bool + Send + 'static,
+    {
+        let mut stages = self.stages;
+        Pipeline {
+            source: self.source,
+            stages: Box::new(move |chunk| Ok(stages(chunk)?.filter(|t| keep(t)))),
+            on_error: self.on_error,
+        }
+    }
+
+    /// Set what to do when the chunker or an adapter returns an error.
+    /// [`ErrorResponse::Halt`] (the default) stops the run and returns
+    /// the error; the other two skip the chunk and carry on.
+    pub fn on_error(mut self, response: ErrorResponse) -> Self {
+        self.on_error = response;
+        self
+    }
+
+    /// Finish the pipeline by sending each item down a channel.
+    pub fn into_channel(self, tx: mpsc::Sender<T>) -> RunnablePipeline<T> {
+        self.into_sink(Sink::Channel(tx))
+    }
+
+    /// Finish the pipeline by passing each item to a callback, which can
+    /// stop the run by returning an error.
+    pub fn for_each<F>(self, f: F) -> RunnablePipeline<T>
+    where
+        F: FnMut(T) -> Result<(), RcErr> + Send + 'static,
+    {
+        self.into_sink(Sink::Callback(Box::new(f)))
+    }
+
+    /// Finish the pipeline by writing each item to `writer`, with
+    /// `separator` between them, through a [`ChunkJoiner`]. The writer
+    /// is flushed at the end of the run.
+    pub fn into_writer<W, P>(self, writer: W, separator: P) -> RunnablePipeline<T>
+    where
+        T: AsRef<[u8]>,
+        W: AsyncWrite + Send + Unpin + 'static,
+        P: AsRef<[u8]>,
+    {
+        let writer: Box<dyn AsyncWrite + Send + Unpin> = Box::new(writer);
+        let joiner = ChunkJoiner::new(writer, separator);
+        self.into_sink(Sink::Writer(joiner, <T as AsRef<[u8]>>::as_ref))
+    }
+
+    fn into_sink(self, sink: Sink<T>) -> RunnablePipeline<T> {
+        RunnablePipeline {
+            pipeline: self,
+            sink,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Pipeline<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("on_error", &self.on_error)
+            .finish_non_exhaustive()
+    }
+}
+
+enum Sink<T> {
+    Channel(mpsc::Sender<T>),
+    Callback(Box<dyn FnMut(T) -> Result<(), RcErr> + Send>),
+    Writer(
+        ChunkJoiner<Box<dyn AsyncWrite + Send + Unpin>>,
+        fn(&T) -> &[u8],
+    ),
+}
+
+/**
+A [`Pipeline`] with a sink attached, ready to [`run`](RunnablePipeline::run).
+*/
+pub struct RunnablePipeline<T> {
+    pipeline: Pipeline<T>,
+    sink: Sink<T>,
+}
+
+impl<T> RunnablePipeline<T> {
+    /**
+    Run the pipeline until the source is exhausted, returning a summary
+    of what happened to the chunks, or the error that stopped it.
+    */
+    pub async fn run(mut self) -> Result<PipelineSummary, RcErr> {
+        let mut summary = PipelineSummary::default();
+        while let Some(res) = self.pipeline.source.next().await {
+            match res.and_then(&mut self.pipeline.stages) {
+                Ok(Some(item)) => {
+                    self.deliver(item).await?;
+                    summary.delivered += 1;
+                }
+                Ok(None) => summary.filtered += 1,
+                Err(e) => match self.pipeline.on_error {
+                    ErrorResponse::Halt => return Err(e),
+                    ErrorResponse::Continue | ErrorResponse::Ignore => summary.errors += 1,
+                },
+            }
+        }
+        if let Sink::Writer(joiner, _) = &mut self.sink {
+            joiner.flush().await?;
+        }
+        Ok(summary)
+    }
+
+    async fn deliver(&mut self, item: T) -> Result<(), RcErr> {
+        match &mut self.sink {
+            Sink::Channel(tx) => tx.send(item).await.map_err(|_| {
+                let closed = std::io::ErrorKind::BrokenPipe;
+                RcErr::Read(std::io::Error::new(closed, "pipeline channel closed"))
+            }),
+            Sink::Callback(f) => f(item),
+            Sink::Writer(joiner, as_bytes) => joiner.write_chunk(as_bytes(&item)).await,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for RunnablePipeline<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sink = match self.sink {
+            Sink::Channel(_) => "channel",
+            Sink::Callback(_) => "callback",
+            Sink::Writer(..) => "writer",
+        };
+        f.debug_struct("RunnablePipeline")
+            .field("pipeline", &self.pipeline)
+            .field("sink", &sink)
+            .finish()
+    }
+}
+
+/// What happened to the chunks that went through a [`Pipeline`], as
+/// returned by [`RunnablePipeline::run`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PipelineSummary {
+    /// The number of items delivered to the sink.
+    pub delivered: u64,
+    /// The number of items dropped by filters.
+    pub filtered: u64,
+    /// The number of chunks skipped because of errors, under
+    /// [`ErrorResponse::Continue`] or [`ErrorResponse::Ignore`].
+    pub errors: u64,
+}
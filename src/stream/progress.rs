@@ -0,0 +1,108 @@
+/*!
+Reporting how far a chunker has got through its source.
+*/
+use tokio::sync::watch;
+
+/**
+A snapshot of how far a [`ByteChunker`](crate::stream::ByteChunker) has
+got through its source, as published on the channel returned by
+[`ByteChunker::progress`](crate::stream::ByteChunker::progress).
+
+```rust
+# use std::error::Error;
+# #[tokio::main(flavor = "current_thread")]
+# async fn main() -> Result<(), Box<dyn Error>> {
+use regex_chunker::stream::ByteChunker;
+use tokio_stream::StreamExt;
+use std::io::Cursor;
+
+let text = b"one\ntwo\nthree\nfour";
+let mut chunker = ByteChunker::new(Cursor::new(text), r"\n")?
+    .with_source_len(text.len() as u64);
+let progress = chunker.progress();
+
+let first = chunker.next().await.unwrap()?;
+assert_eq!(first, b"one");
+assert_eq!(progress.borrow().chunks, 1);
+
+while let Some(res) = chunker.next().await {
+    res?;
+}
+let done = progress.borrow().clone();
+assert_eq!(done.bytes_read, 18);
+assert_eq!(done.chunks, 4);
+assert!(done.finished);
+assert_eq!(done.fraction(), Some(1.0));
+# Ok(()) }
+```
+*/
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Progress {
+    /// The number of bytes read from the source so far, including any
+    /// that are buffered but not yet part of a returned chunk.
+    pub bytes_read: u64,
+    /// The number of chunks returned so far.
+    pub chunks: u64,
+    /// The length of the source, if it was supplied with
+    /// [`ByteChunker::with_source_len`](crate::stream::ByteChunker::with_source_len).
+    pub source_len: Option<u64>,
+    /// Whether the source has been exhausted and every chunk returned.
+    pub finished: bool,
+}
+
+impl Progress {
+    /// The fraction of the source read so far, from 0.0 to 1.0, if its
+    /// length is known. An empty source counts as entirely read.
+    pub fn fraction(&self) -> Option<f64> {
+        self.source_len.map(|len| match len {
+            0 => 1.0,
+            len => (self.bytes_read as f64 / len as f64).min(1.0),
+        })
+    }
+}
+
+/* The sending half of a chunker's progress channel, created the first time
+someone asks for a receiver. */
+#[derive(Debug, Default)]
+pub(crate) struct ProgressTx {
+    source_len: Option<u64>,
+    sender: Option<watch::Sender<Progress>>,
+}
+
+impl ProgressTx {
+    pub(crate) fn set_source_len(&mut self, len: u64) {
+        self.source_len = Some(len);
+        if let Some(sender) = self.sender.as_ref() {
+            sender.send_modify(|p| p.source_len = Some(len));
+        }
+    }
+
+    /* Return a new receiver, starting the channel off at `current` if there
+    isn't one yet. */
+    pub(crate) fn subscribe(&mut self, current: Progress) -> watch::Receiver<Progress> {
+        match self.sender.as_ref() {
+            Some(sender) => sender.subscribe(),
+            None => {
+                let current = Progress {
+                    source_len: self.source_len,
+                    ..current
+                };
+                let (sender, receiver) = watch::channel(current);
+                self.sender = Some(sender);
+                receiver
+            }
+        }
+    }
+
+    /* Publish the latest figures, if anyone is listening. */
+    pub(crate) fn update(&self, bytes_read: u64, chunks: u64, finished: bool) {
+        if let Some(sender) = self.sender.as_ref() {
+            sender.send_modify(|p| {
+                p.bytes_read = bytes_read;
+                p.chunks = chunks;
+                p.finished = finished;
+            });
+        }
+    }
+}
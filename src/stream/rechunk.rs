@@ -0,0 +1,133 @@
+/*!
+Re-splitting a stream of chunks on a different pattern.
+*/
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::BytesMut;
+use regex::bytes::Regex;
+use tokio_stream::Stream;
+use tokio_util::codec::Decoder;
+
+use super::ByteDecoder;
+use crate::{Boundary, MatchDisposition, RcErr};
+
+/**
+Takes a stream of chunks, from a chunker or anywhere else, and splits the
+data in it again at matches for a different pattern, as if the chunks had
+been joined back together and read through a new chunker, but without
+going back through an `AsyncRead`. The original chunk boundaries make no
+difference: a chunk may be split into several, several may be merged into
+one, and a delimiter that spans two of them is still found.
+
+An error from the wrapped stream is passed along as soon as it arrives;
+whatever data has been buffered stays put, to be continued by the chunks
+after the error.
+
+```rust
+# use std::error::Error;
+# #[tokio::main(flavor = "current_thread")]
+# async fn main() -> Result<(), Box<dyn Error>> {
+    use regex_chunker::{stream::Rechunker, RcErr};
+    use tokio_stream::StreamExt;
+
+    // Pieces of records, split without regard for the delimiters.
+    let pieces: Vec<Result<Vec<u8>, RcErr>> = ["id=1", ";i", "d=2;id", "=3"]
+        .iter()
+        .map(|s| Ok(s.as_bytes().to_vec()))
+        .collect();
+
+    let records: Vec<Vec<u8>> = Rechunker::new(tokio_stream::iter(pieces), ";")?
+        .map(|res| res.unwrap())
+        .collect()
+        .await;
+    assert_eq!(records, [&b"id=1"[..], b"id=2", b"id=3"]);
+#   Ok(()) }
+```
+*/
+pub struct Rechunker<S> {
+    source: S,
+    decoder: ByteDecoder,
+    buff: BytesMut,
+    source_done: bool,
+}
+
+impl<S> Rechunker<S> {
+    /// Return a new `Rechunker` that splits the data in the `source`
+    /// stream's chunks at matches for the regex `pattern`.
+    pub fn new(source: S, pattern: &str) -> Result<Self, RcErr> {
+        let fence = Regex::new(pattern)?;
+        Ok(Self::from_boundary(source, fence))
+    }
+
+    /// Return a new `Rechunker` that splits the data in the `source`
+    /// stream's chunks at the delimiters found by the supplied
+    /// [`Boundary`].
+    pub fn from_boundary<B>(source: S, boundary: B) -> Self
+    where
+        B: Boundary + Send + 'static,
+    {
+        Self {
+            source,
+            decoder: ByteDecoder::new(Box::new(boundary)),
+            buff: BytesMut::new(),
+            source_done: false,
+        }
+    }
+
+    /// Builder-pattern method for controlling what the `Rechunker` does
+    /// with the matched text. See
+    /// [`ByteChunker::with_match`](crate::ByteChunker::with_match).
+    pub fn with_match(mut self, behavior: MatchDisposition) -> Self {
+        self.decoder.engine.match_dispo = behavior;
+        self
+    }
+
+    /// Builder-pattern method for limiting the length of the chunks
+    /// returned. See
+    /// [`ByteChunker::with_max_chunk_len`](crate::ByteChunker::with_max_chunk_len).
+    pub fn with_max_chunk_len(mut self, max: usize) -> Self {
+        self.decoder.engine.max_chunk_len = Some(max.max(1));
+        self
+    }
+
+    /// Consume the `Rechunker` and return the wrapped stream, along with
+    /// any buffered data that hasn't yet been returned as a chunk.
+    pub fn into_innards(self) -> (S, Vec<u8>) {
+        (self.source, self.buff.to_vec())
+    }
+}
+
+impl<S> Unpin for Rechunker<S> {}
+
+impl<S, T> Stream for Rechunker<S>
+where
+    S: Stream<Item = Result<T, RcErr>> + Unpin,
+    T: AsRef<[u8]>,
+{
+    type Item = Result<Vec<u8>, RcErr>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if this.source_done {
+                let res = this.decoder.decode_eof(&mut this.buff);
+                return Poll::Ready(res.transpose().map(|res| res.map(|c| c.body)));
+            }
+            match this.decoder.decode(&mut this.buff) {
+                Ok(Some(chunk)) => return Poll::Ready(Some(Ok(chunk.body))),
+                Ok(None) => {}
+                Err(e) => return Poll::Ready(Some(Err(e))),
+            }
+
+            match Pin::new(&mut this.source).poll_next(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(None) => this.source_done = true,
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                Poll::Ready(Some(Ok(piece))) => this.buff.extend_from_slice(piece.as_ref()),
+            }
+        }
+    }
+}
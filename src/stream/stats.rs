@@ -0,0 +1,98 @@
+/*!
+Live counters for long-lived chunkers.
+*/
+use tokio::sync::watch;
+
+/**
+Running totals for a [`ByteChunker`](crate::stream::ByteChunker), as
+published on the channel returned by
+[`ByteChunker::stats`](crate::stream::ByteChunker::stats). Unlike
+[`Progress`](crate::stream::Progress), which is about getting through a
+source of (usually) known size, these are meant for scraping into metrics
+for chunkers that run indefinitely.
+
+```rust
+# use std::error::Error;
+# #[tokio::main(flavor = "current_thread")]
+# async fn main() -> Result<(), Box<dyn Error>> {
+use regex_chunker::stream::ByteChunker;
+use tokio_stream::StreamExt;
+
+let mut chunker = ByteChunker::new(&b"one\ntwo\nthree"[..], r"\n")?;
+let stats = chunker.stats();
+
+assert_eq!(chunker.next().await.unwrap()?, b"one");
+{
+    let now = stats.borrow();
+    assert_eq!(now.chunks_out, 1);
+    assert_eq!(now.bytes_in, 13);
+    assert_eq!(now.buffered, 9);
+}
+
+while let Some(res) = chunker.next().await {
+    res?;
+}
+let done = stats.borrow().clone();
+assert_eq!(done.chunks_out, 3);
+assert_eq!(done.errors, 0);
+assert_eq!(done.buffered, 0);
+# Ok(()) }
+```
+*/
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stats {
+    /// The number of bytes read from the source so far.
+    pub bytes_in: u64,
+    /// The number of chunks produced so far.
+    pub chunks_out: u64,
+    /// The number of errors the chunker has returned.
+    pub errors: u64,
+    /// The number of bytes read but not yet returned as part of a chunk.
+    pub buffered: usize,
+}
+
+/* The sending half of a chunker's stats channel, created the first time
+someone asks for a receiver. */
+#[derive(Debug, Default)]
+pub(crate) struct StatsTx {
+    errors: u64,
+    sender: Option<watch::Sender<Stats>>,
+}
+
+impl StatsTx {
+    /* Return a new receiver, starting the channel off at `current` if there
+    isn't one yet. */
+    pub(crate) fn subscribe(&mut self, current: Stats) -> watch::Receiver<Stats> {
+        match self.sender.as_ref() {
+            Some(sender) => sender.subscribe(),
+            None => {
+                let current = Stats {
+                    errors: self.errors,
+                    ..current
+                };
+                let (sender, receiver) = watch::channel(current);
+                self.sender = Some(sender);
+                receiver
+            }
+        }
+    }
+
+    /* Publish the latest figures, if anyone is listening. */
+    pub(crate) fn update(&self, bytes_in: u64, chunks_out: u64, buffered: usize) {
+        if let Some(sender) = self.sender.as_ref() {
+            sender.send_modify(|s| {
+                s.bytes_in = bytes_in;
+                s.chunks_out = chunks_out;
+                s.buffered = buffered;
+            });
+        }
+    }
+
+    pub(crate) fn record_error(&mut self) {
+        self.errors += 1;
+        if let Some(sender) = self.sender.as_ref() {
+            sender.send_modify(|s| s.errors = self.errors);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+/*!
+Limiting the rate at which a stream chunker returns chunks.
+*/
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use tokio::time::{sleep, Instant, Sleep};
+
+use crate::Chunk;
+
+/* A token bucket holding up to a second's worth of tokens, refilled
+continuously at `rate` tokens per second. */
+#[derive(Debug)]
+struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64) -> Self {
+        let capacity = rate.max(1.0);
+        Self {
+            rate,
+            capacity,
+            tokens: capacity,
+            last: Instant::now(),
+        }
+    }
+
+    /* How long until `cost` tokens can be taken. A cost larger than the
+    bucket only has to wait for a full bucket, and then leaves it in debt,
+    so a huge chunk delays the ones after it rather than never going. */
+    fn wait(&mut self, cost: f64) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last = now;
+
+        let need = cost.min(self.capacity);
+        match self.tokens >= need {
+            true => Duration::ZERO,
+            false => Duration::from_secs_f64((need - self.tokens) / self.rate),
+        }
+    }
+
+    fn take(&mut self, cost: f64) {
+        self.tokens -= cost;
+    }
+}
+
+/* Holds each chunk back until the chunk and byte rate limits (whichever
+are set) allow it to be returned. */
+#[derive(Debug, Default)]
+pub(crate) struct Throttle {
+    chunks: Option<TokenBucket>,
+    bytes: Option<TokenBucket>,
+    held: Option<Chunk>,
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl Throttle {
+    pub(crate) fn set_chunk_rate(&mut self, per_second: f64) {
+        assert!(per_second > 0.0, "chunk rate must be positive");
+        self.chunks = Some(TokenBucket::new(per_second));
+    }
+
+    pub(crate) fn set_byte_rate(&mut self, per_second: f64) {
+        assert!(per_second > 0.0, "byte rate must be positive");
+        self.bytes = Some(TokenBucket::new(per_second));
+    }
+
+    /* Whether a chunk is being held back. */
+    pub(crate) fn is_holding(&self) -> bool {
+        self.held.is_some()
+    }
+
+    /* Hold `chunk` back until it can be released by `poll_release`. */
+    pub(crate) fn hold(&mut self, chunk: Chunk) {
+        self.held = Some(chunk);
+    }
+
+    /* Return the held chunk once the rate limits allow it. */
+    pub(crate) fn poll_release(&mut self, cx: &mut Context<'_>) -> Poll<Chunk> {
+        let len = self.held.as_ref().map_or(0, |c| c.body.len()) as f64;
+        loop {
+            let wait = [(&mut self.chunks, 1.0), (&mut self.bytes, len)]
+                .into_iter()
+                .filter_map(|(bucket, cost)| bucket.as_mut().map(|b| b.wait(cost)))
+                .max()
+                .unwrap_or(Duration::ZERO);
+
+            if wait.is_zero() {
+                if let Some(bucket) = self.chunks.as_mut() {
+                    bucket.take(1.0);
+                }
+                if let Some(bucket) = self.bytes.as_mut() {
+                    bucket.take(len);
+                }
+                self.sleep = None;
+                // `poll_release` is only called while holding a chunk.
+                return Poll::Ready(self.held.take().unwrap());
+            }
+
+            let deadline = Instant::now() + wait;
+            let timer = self.sleep.get_or_insert_with(|| Box::pin(sleep(wait)));
+            timer.as_mut().reset(deadline);
+            if timer.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+/*!
+Instrumentation reporting where a chunker spends its time.
+*/
+use std::{
+    fmt::{Debug, Formatter},
+    time::{Duration, Instant},
+};
+
+/**
+How long a [`ByteChunker`](crate::ByteChunker) spent producing a single
+chunk, broken down by activity. These are passed to the hook set with
+[`ByteChunker::with_timing`](crate::ByteChunker::with_timing) as each
+chunk is returned.
+
+Comparing `read` with `search` over a run shows whether a pipeline is
+limited by its source or by its delimiter pattern.
+
+```rust
+use regex_chunker::{ByteChunker, RcErr};
+use std::{io::Cursor, sync::{Arc, Mutex}, time::Duration};
+
+let totals = Arc::new(Mutex::new((Duration::ZERO, Duration::ZERO)));
+let t = totals.clone();
+
+let n = ByteChunker::new(Cursor::new(b"a few short words"), " ")?
+    .with_timing(move |timing| {
+        let mut t = t.lock().unwrap();
+        t.0 += timing.read;
+        t.1 += timing.search;
+    })
+    .count();
+assert_eq!(n, 4);
+
+let (read, search) = *totals.lock().unwrap();
+println!("read: {:?}, search: {:?}", read, search);
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChunkTiming {
+    /// Time spent waiting on reads from the source.
+    pub read: Duration,
+    /// The number of reads made from the source.
+    pub reads: usize,
+    /// Time spent searching the buffered data for a delimiter.
+    pub search: Duration,
+    /// Time spent copying the chunk out of the chunker's buffer.
+    pub copy: Duration,
+}
+
+/* Accumulates a `ChunkTiming`, and passes it to the hook when the chunk
+is done. */
+pub(crate) struct Timer {
+    current: ChunkTiming,
+    hook: Box<dyn FnMut(&ChunkTiming) + Send>,
+}
+
+impl Timer {
+    pub(crate) fn new<F>(hook: F) -> Self
+    where
+        F: FnMut(&ChunkTiming) + Send + 'static,
+    {
+        Self {
+            current: ChunkTiming::default(),
+            hook: Box::new(hook),
+        }
+    }
+
+    pub(crate) fn add_read(&mut self, since: Instant) {
+        self.current.read += since.elapsed();
+        self.current.reads += 1;
+    }
+
+    pub(crate) fn add_search(&mut self, since: Instant) {
+        self.current.search += since.elapsed();
+    }
+
+    pub(crate) fn add_copy(&mut self, since: Instant) {
+        self.current.copy += since.elapsed();
+    }
+
+    /// Report the current chunk's timing and start afresh.
+    pub(crate) fn report(&mut self) {
+        (self.hook)(&self.current);
+        self.current = ChunkTiming::default();
+    }
+}
+
+impl Debug for Timer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Timer")
+            .field("current", &self.current)
+            .finish()
+    }
+}
@@ -0,0 +1,107 @@
+/*!
+Tracing the decisions a chunker makes about where its chunks end.
+*/
+use std::{
+    fmt::{Display, Formatter},
+    ops::Range,
+};
+
+use crate::MatchDisposition;
+
+/* A hook receiving each `DecodeEvent`; see `ByteChunker::with_trace`. */
+pub(crate) type TraceFn = Box<dyn FnMut(&DecodeEvent) + Send>;
+
+/// What a chunker did after searching its buffer for a delimiter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Decision {
+    /// No delimiter was found, so the chunker went back for more data.
+    Wait,
+    /// A chunk of the first `len` bytes of the buffer was returned, and
+    /// the first `consumed` bytes (which include any dropped delimiter)
+    /// were removed from the buffer.
+    Split { len: usize, consumed: usize },
+    /// The chunk reached the maximum chunk length, so the first `len`
+    /// bytes of the buffer were returned as a chunk without a delimiter.
+    Forced { len: usize },
+    /// The source ended, so the `len` bytes remaining in the buffer were
+    /// returned as the last chunk.
+    Tail { len: usize },
+}
+
+/**
+A record of one of a chunker's decisions about where to end a chunk,
+passed to the hook set with
+[`ByteChunker::with_trace`](crate::ByteChunker::with_trace) (or
+[`stream::ByteChunker::with_trace`](crate::stream::ByteChunker::with_trace)).
+Positions within the buffer are relative to its start, which is the start
+of the chunk being looked for.
+
+The [`Display`] implementation renders an event as a single line, for
+logging.
+
+```rust
+use regex_chunker::{ByteChunker, Decision, MatchDisposition, RcErr};
+use std::{io::Cursor, sync::{Arc, Mutex}};
+
+let events = Arc::new(Mutex::new(Vec::new()));
+let log = events.clone();
+
+let n = ByteChunker::new(Cursor::new(b"one, two"), ", ")?
+    .with_buffer_size(4)
+    .with_match(MatchDisposition::Append)
+    .with_trace(move |event| log.lock().unwrap().push(event.clone()))
+    .count();
+assert_eq!(n, 2);
+
+let events = events.lock().unwrap();
+let split = events.iter().find(|e| e.found.is_some()).unwrap();
+assert_eq!(split.found, Some(3..5));
+assert_eq!(split.decision, Decision::Split { len: 5, consumed: 5 });
+assert_eq!(events.last().unwrap().decision, Decision::Tail { len: 3 });
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodeEvent {
+    /// The position in the source of the start of the buffer.
+    pub offset: u64,
+    /// The number of bytes in the buffer.
+    pub buffered: usize,
+    /// Where in the buffer the search for a delimiter started.
+    pub scan_offset: usize,
+    /// Whether the source had ended, so no more data was coming.
+    pub at_end: bool,
+    /// Where in the buffer the delimiter was found, if it was.
+    pub found: Option<Range<usize>>,
+    /// What was to be done with the delimiter, if one was found.
+    pub disposition: Option<MatchDisposition>,
+    /// What the chunker did as a result.
+    pub decision: Decision,
+}
+
+impl Display for DecodeEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "@{}: {} bytes buffered, scanned from {}",
+            self.offset, self.buffered, self.scan_offset
+        )?;
+        if self.at_end {
+            write!(f, " at end")?;
+        }
+        if let Some(found) = &self.found {
+            write!(f, ", matched {}..{}", found.start, found.end)?;
+        }
+        if let Some(dispo) = &self.disposition {
+            write!(f, " ({:?})", dispo)?;
+        }
+        match self.decision {
+            Decision::Wait => write!(f, "; waiting for more data"),
+            Decision::Split { len, consumed } => {
+                write!(f, "; split off {} bytes, consumed {}", len, consumed)
+            }
+            Decision::Forced { len } => write!(f, "; forced a split at {} bytes", len),
+            Decision::Tail { len } => write!(f, "; returned the last {} bytes", len),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+/*!
+Trimming unwanted text from the ends of chunks.
+*/
+use std::ops::Range;
+
+use regex::bytes::Regex;
+
+use crate::RcErr;
+
+/* Finds the part of a chunk left after trimming matches of a pattern from
+either end; see `ByteChunker::trim_matches`. */
+#[derive(Clone, Debug)]
+pub(crate) struct Trimmer {
+    leading: Regex,
+    trailing: Regex,
+}
+
+impl Trimmer {
+    pub(crate) fn new(pattern: &str) -> Result<Self, RcErr> {
+        Ok(Self {
+            leading: Regex::new(&format!("^(?:{})", pattern))?,
+            trailing: Regex::new(&format!("(?:{})$", pattern))?,
+        })
+    }
+
+    /// The range of `chunk` that remains after trimming.
+    pub(crate) fn range(&self, chunk: &[u8]) -> Range<usize> {
+        let start = self.leading.find(chunk).map_or(0, |m| m.end());
+        let end = self
+            .trailing
+            .find_at(chunk, start)
+            .map_or(chunk.len(), |m| m.start());
+        start..end
+    }
+}
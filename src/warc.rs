@@ -0,0 +1,125 @@
+/*!
+Chunking WARC (Web ARChive) files into records.
+*/
+use crate::{
+    mail::{blank_line, find_header, split_headers},
+    Boundary,
+};
+
+/* The most line-ending bytes that end a record: `\r\n\r\n`. */
+const RECORD_END_LEN: usize = 4;
+
+/**
+A [`Boundary`] that splits a WARC file into its records. Rather than
+searching for a delimiter, which could just as well turn up inside an
+archived payload, it reads the `Content-Length` header of each record and
+skips that many bytes of content block; the delimiter is the blank line
+(`\r\n\r\n`) that follows. Each chunk is then a record's header block, the
+blank line that ends it, and its content block.
+
+A record with no readable `Content-Length` is taken to have an empty
+content block. This is most easily used through
+[`ByteChunker::warc_records`](crate::ByteChunker::warc_records); see
+[`WarcRecord`] for splitting the records up.
+*/
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WarcBoundary;
+
+impl WarcBoundary {
+    /// Return a new [`WarcBoundary`].
+    pub fn new() -> Self {
+        Self
+    }
+
+    /* Find the end of the record that starts at `start`. */
+    fn find_record(&self, haystack: &[u8], start: usize, at_end: bool) -> Option<(usize, usize)> {
+        let record = &haystack[start..];
+        let (headers_end, block_start) = blank_line(record)?;
+        let len = find_header(&record[..headers_end], "content-length")
+            .and_then(|len| std::str::from_utf8(len).ok())
+            .and_then(|len| len.parse::<usize>().ok())
+            .unwrap_or(0);
+
+        let block_end = start + block_start.checked_add(len)?;
+        let after = haystack.get(block_end..)?;
+        if after.len() < RECORD_END_LEN && !at_end {
+            return None;
+        }
+        let record_end = after
+            .iter()
+            .take(RECORD_END_LEN)
+            .take_while(|&&b| b == b'\r' || b == b'\n')
+            .count();
+        Some((block_end, block_end + record_end))
+    }
+}
+
+impl Boundary for WarcBoundary {
+    fn find_at(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        self.find_record(haystack, start, false)
+    }
+
+    fn find_at_end(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        self.find_record(haystack, start, true)
+    }
+}
+
+/**
+A WARC record, separated into its header block and its content block.
+Converting a chunk yielded by
+[`ByteChunker::warc_records`](crate::ByteChunker::warc_records) into a
+`WarcRecord` splits it at the blank line that ends the header block.
+
+```rust
+use regex_chunker::{ByteChunker, RcErr, WarcRecord};
+use std::io::Cursor;
+
+// The content block of the first record contains a blank line and what
+// looks like the start of another record.
+let mut warc = Vec::new();
+for (kind, block) in [("resource", "a\r\n\r\nWARC/1.0\r\n"), ("metadata", "b")] {
+    warc.extend_from_slice(
+        format!(
+            "WARC/1.0\r\nWARC-Type: {}\r\nContent-Length: {}\r\n\r\n{}\r\n\r\n",
+            kind,
+            block.len(),
+            block
+        )
+        .as_bytes(),
+    );
+}
+
+let records: Vec<WarcRecord> = ByteChunker::warc_records(Cursor::new(warc))
+    .map(|res| res.map(WarcRecord::from))
+    .collect::<Result<_, RcErr>>()?;
+
+assert_eq!(records.len(), 2);
+assert_eq!(records[0].header("warc-type"), Some(&b"resource"[..]));
+assert_eq!(records[0].block, b"a\r\n\r\nWARC/1.0\r\n");
+assert_eq!(records[1].block, b"b");
+# Ok::<(), RcErr>(())
+```
+*/
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WarcRecord {
+    /// The record's header lines, starting with its version line
+    /// (like `WARC/1.1`), without the blank line that ends them.
+    pub headers: Vec<u8>,
+    /// The record's content block.
+    pub block: Vec<u8>,
+}
+
+impl WarcRecord {
+    /// Return the value of the first header named `name` (compared
+    /// case-insensitively), with surrounding whitespace removed.
+    pub fn header(&self, name: &str) -> Option<&[u8]> {
+        find_header(&self.headers, name)
+    }
+}
+
+impl From<Vec<u8>> for WarcRecord {
+    fn from(record: Vec<u8>) -> Self {
+        let (headers, block) = split_headers(record);
+        Self { headers, block }
+    }
+}
@@ -0,0 +1,149 @@
+/*!
+Chunking text into words, as defined by the Unicode word segmentation
+rules.
+*/
+use std::io::Read;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{Boundary, ByteChunker, CustomChunker, RcErr, StringAdapter};
+
+/* Whether a word segment is a word, rather than space or punctuation. */
+fn is_word(segment: &str) -> bool {
+    segment.chars().any(char::is_alphanumeric)
+}
+
+/**
+A [`Boundary`] that finds the spaces between words, as determined by the
+word boundary rules of [Unicode Standard Annex #29](https://www.unicode.org/reports/tr29/),
+using the [`unicode-segmentation`](https://docs.rs/unicode-segmentation/latest/unicode_segmentation/)
+crate. Unlike a regex matching whitespace and punctuation, this also
+separates the words of scripts that don't put spaces between them.
+
+Each delimiter is the run of spaces and punctuation between two words,
+or, where two words are directly adjacent, a zero-width delimiter between
+them. Spaces and punctuation at the very start of the source produce an
+empty first chunk, which the [`StringChunker::unicode_words`] chunker
+skips.
+
+The buffered data should be UTF-8; searching stops at the first invalid
+byte sequence.
+*/
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WordBoundary;
+
+impl WordBoundary {
+    /// Return a new [`WordBoundary`].
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Boundary for WordBoundary {
+    fn find_at(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        find_word_gap(haystack, start, false)
+    }
+
+    fn find_at_end(&mut self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        find_word_gap(haystack, start, true)
+    }
+}
+
+/*
+Find the next gap between words in `haystack`. Unless `at_end` is set,
+the text might continue past the end of `haystack`.
+*/
+fn find_word_gap(haystack: &[u8], start: usize, at_end: bool) -> Option<(usize, usize)> {
+    let (text, complete) = match std::str::from_utf8(haystack) {
+        Ok(text) => (text, at_end),
+        Err(e) => {
+            // This can't fail; we've just been told it's valid.
+            let text = std::str::from_utf8(&haystack[..e.valid_up_to()]).unwrap();
+            // An invalid sequence ends the text for good, but more data
+            // could finish an incomplete one.
+            (text, at_end || e.error_len().is_some())
+        }
+    };
+    let text = text.get(start..)?;
+
+    let segments: Vec<(usize, bool)> = text
+        .split_word_bound_indices()
+        .map(|(n, segment)| (start + n, is_word(segment)))
+        .collect();
+
+    for (n, &(offset, word)) in segments.iter().enumerate() {
+        if word {
+            // Two words with nothing between them.
+            if let Some(&(next, true)) = segments.get(n + 1) {
+                return Some((next, next));
+            }
+            continue;
+        }
+
+        return match segments[n + 1..].iter().find(|(_, word)| *word) {
+            Some(&(next, _)) => Some((offset, next)),
+            None if complete => Some((offset, start + text.len())),
+            None => None,
+        };
+    }
+    None
+}
+
+/**
+A chunker that yields `String`s. It's built by one of the functions that
+preconfigure it for a particular kind of text.
+*/
+pub struct StringChunker<R> {
+    chunker: CustomChunker<R, StringAdapter>,
+}
+
+impl<R> StringChunker<R> {
+    /**
+    Return a [`StringChunker`] that yields the words of `source`, as
+    determined by a [`WordBoundary`]; the spaces and punctuation between
+    them are dropped. A chunk that isn't valid UTF-8 is yielded as an error,
+    and ends the iteration.
+
+    ```rust
+    use regex_chunker::{RcErr, StringChunker};
+    use std::io::Cursor;
+
+    let text = "The quick (\"brown\") fox; 日本語.";
+    let words: Vec<String> = StringChunker::unicode_words(Cursor::new(text))
+        .collect::<Result<_, RcErr>>()?;
+
+    assert_eq!(words, ["The", "quick", "brown", "fox", "日", "本", "語"]);
+    # Ok::<(), RcErr>(())
+    ```
+    */
+    pub fn unicode_words(source: R) -> Self {
+        let chunker = ByteChunker::from_boundary(source, WordBoundary::new())
+            .with_adapter(StringAdapter::default());
+        Self { chunker }
+    }
+
+    /// Builder-pattern method for setting the read buffer size.
+    /// Default size is 1024 bytes.
+    pub fn with_buffer_size(mut self, size: usize) -> Self {
+        self.chunker = self.chunker.with_buffer_size(size);
+        self
+    }
+
+    /// Consumes the [`StringChunker`] and returns its wrapped reader.
+    pub fn into_inner(self) -> R {
+        self.chunker.into_innards().0.into_inner()
+    }
+}
+
+impl<R: Read> Iterator for StringChunker<R> {
+    type Item = Result<String, RcErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.chunker.next()? {
+                Ok(s) if s.is_empty() => continue,
+                res => return Some(res),
+            }
+        }
+    }
+}